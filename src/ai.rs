@@ -0,0 +1,307 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_xpbd_3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    alert::{AlertKind, RaiseAlert},
+    building::Building,
+    cooldown::{Ability, Cooldowns},
+    health::{Damage, DamageType, Health},
+    hitbox::MeleeSwing,
+    nav::NavAgent,
+    perception::{Alertness, Awareness},
+    projectile::{lead, LaunchProjectile, ProjectileKind},
+    shaman::EnemyCasting,
+    stagger::Staggered,
+    state::GameplaySet,
+};
+
+// close enough to a waypoint to pick the next one
+const ARRIVE_DISTANCE: f32 = 0.5;
+// seconds spent standing around between patrols
+const IDLE_TIME: f32 = 3.0;
+// degrees a melee swing sweeps across
+const MELEE_ARC: f32 = 90.0;
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Archetypes::starter())
+            .add_systems(Update, (think, act).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Archetype {
+    Construct,
+    Shaman,
+    // corrupted wildlife, quick and skittish
+    Crawler,
+    Slinger,
+}
+
+/// What an enemy goes after once it notices it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Prey {
+    Buildings,
+    Player,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Attack {
+    // hits whatever is in reach
+    Melee(Damage),
+    // shoots from afar and backs off from prey closer than `keep_distance`
+    Ranged { kind: ProjectileKind, keep_distance: f32 },
+}
+
+/// How an archetype behaves, kept as plain data like the spells.
+#[derive(Clone, Copy, Debug)]
+pub struct Behavior {
+    pub speed: f32,
+    pub prey: Prey,
+    // how far away prey gets noticed
+    pub sight_range: f32,
+    // degrees across the vision cone
+    pub fov: f32,
+    // how far noises carry for it, relative to everyone else
+    pub hearing: f32,
+    pub attack_range: f32,
+    // `None` for enemies that fight some other way, like shamans casting
+    pub attack: Option<Attack>,
+    // seconds between attacks
+    pub attack_interval: f32,
+    // fraction of the health below which it runs, zero never does
+    pub flee_below: f32,
+    // how far from home it wanders, zero just stands around
+    pub patrol_radius: f32,
+    // how much of the spawn budget one of these takes up
+    pub cost: u32,
+}
+
+#[derive(Resource, Default)]
+pub struct Archetypes {
+    behaviors: HashMap<Archetype, Behavior>,
+}
+
+impl Archetypes {
+    fn starter() -> Self {
+        let mut archetypes = Archetypes::default();
+        // walks straight for the nearest building and bashes it
+        archetypes.add(Archetype::Construct, Behavior {
+            speed: 1.5,
+            prey: Prey::Buildings,
+            sight_range: 30.0,
+            fov: 360.0,
+            hearing: 0.5,
+            attack_range: 1.2,
+            attack: Some(Attack::Melee(Damage::new(8.0, DamageType::Physical))),
+            attack_interval: 1.0,
+            flee_below: 0.0,
+            patrol_radius: 0.0,
+            cost: 2,
+        });
+        // stays back and casts, see shaman.rs
+        archetypes.add(Archetype::Shaman, Behavior {
+            speed: 1.2,
+            prey: Prey::Player,
+            sight_range: 14.0,
+            fov: 140.0,
+            hearing: 1.0,
+            attack_range: 6.0,
+            attack: None,
+            attack_interval: 0.0,
+            flee_below: 0.25,
+            patrol_radius: 3.0,
+            cost: 3,
+        });
+        archetypes.add(Archetype::Crawler, Behavior {
+            speed: 2.8,
+            prey: Prey::Player,
+            sight_range: 8.0,
+            fov: 100.0,
+            hearing: 1.5,
+            attack_range: 0.9,
+            attack: Some(Attack::Melee(Damage::new(6.0, DamageType::Physical))),
+            attack_interval: 0.8,
+            flee_below: 0.3,
+            patrol_radius: 5.0,
+            cost: 1,
+        });
+        // keeps its distance and leads its shots
+        archetypes.add(Archetype::Slinger, Behavior {
+            speed: 1.8,
+            prey: Prey::Player,
+            sight_range: 16.0,
+            fov: 120.0,
+            hearing: 1.0,
+            attack_range: 11.0,
+            attack: Some(Attack::Ranged { kind: ProjectileKind::Shard, keep_distance: 6.0 }),
+            attack_interval: 1.6,
+            flee_below: 0.2,
+            patrol_radius: 4.0,
+            cost: 2,
+        });
+        archetypes
+    }
+
+    pub fn add(&mut self, archetype: Archetype, behavior: Behavior) {
+        self.behaviors.insert(archetype, behavior);
+    }
+
+    pub fn get(&self, archetype: Archetype) -> &Behavior {
+        self.behaviors.get(&archetype).expect("archetype without a behavior")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AiState {
+    #[default]
+    Idle,
+    Patrol,
+    // heading for wherever something was last noticed
+    Investigate,
+    Chase,
+    Attack,
+    Flee,
+}
+
+#[derive(Component)]
+pub struct Brain {
+    pub archetype: Archetype,
+    pub state: AiState,
+    // where it wanders around
+    pub home: Vec3,
+    waypoint: Option<Vec3>,
+    // seconds left standing around
+    idle: f32,
+}
+
+impl Brain {
+    pub fn new(archetype: Archetype, home: Vec3) -> Self {
+        Brain {
+            archetype,
+            state: AiState::Idle,
+            home,
+            waypoint: None,
+            idle: IDLE_TIME,
+        }
+    }
+}
+
+fn think(
+    archetypes: Res<Archetypes>,
+    mut brain_query: Query<(&mut Brain, &Awareness, &Transform, &Health)>,
+    target_query: Query<&Transform>,
+    time: Res<Time>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for (mut brain, awareness, transform, health) in &mut brain_query {
+        let behavior = archetypes.get(brain.archetype);
+        let pos = transform.translation;
+        let target = awareness.target.and_then(|t| target_query.get(t).ok());
+        let suspicious = awareness.alertness() >= Alertness::Suspicious && awareness.last_known.is_some();
+
+        brain.state = match (brain.state, target) {
+            (_, Some(_)) if health.fraction() < behavior.flee_below => AiState::Flee,
+            (_, Some(t)) if t.translation.distance(pos) <= behavior.attack_range => AiState::Attack,
+            (_, Some(_)) => AiState::Chase,
+            (_, None) if suspicious => AiState::Investigate,
+            (AiState::Idle, None) => {
+                brain.idle -= time.delta_seconds();
+                if brain.idle > 0.0 || behavior.patrol_radius <= 0.0 {
+                    AiState::Idle
+                } else {
+                    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                    let distance = rng.gen_range(0.0..behavior.patrol_radius);
+                    brain.waypoint = Some(brain.home + Vec3::new(angle.cos(), 0.0, angle.sin()) * distance);
+                    AiState::Patrol
+                }
+            }
+            (AiState::Patrol, None) => {
+                let arrived = brain.waypoint.is_none_or(|w| w.xz().distance(pos.xz()) <= ARRIVE_DISTANCE);
+                if arrived {
+                    brain.idle = IDLE_TIME;
+                    AiState::Idle
+                } else {
+                    AiState::Patrol
+                }
+            }
+            // lost track of it
+            (_, None) => {
+                brain.idle = IDLE_TIME;
+                AiState::Idle
+            }
+        };
+    }
+}
+
+fn act(
+    archetypes: Res<Archetypes>,
+    mut cooldowns: ResMut<Cooldowns>,
+    // staggered enemies are in no state to do anything
+    mut brain_query: Query<(Entity, &Brain, &Awareness, &Transform, &mut NavAgent, Has<EnemyCasting>), Without<Staggered>>,
+    target_query: Query<(&Transform, Option<&LinearVelocity>, Has<Building>)>,
+    mut swing_events: EventWriter<MeleeSwing>,
+    mut alert_events: EventWriter<RaiseAlert>,
+    mut launch_events: EventWriter<LaunchProjectile>,
+) {
+    for (entity, brain, awareness, transform, mut agent, is_casting) in &mut brain_query {
+        let behavior = archetypes.get(brain.archetype);
+        let pos = transform.translation;
+        let target = awareness.target.and_then(|t| Some((t, target_query.get(t).ok()?)));
+
+        let goal = match (brain.state, target) {
+            (AiState::Patrol, _) => brain.waypoint,
+            (AiState::Investigate, _) => awareness.last_known,
+            (AiState::Chase, Some((_, (t, ..)))) => Some(t.translation),
+            // anywhere away from the threat
+            (AiState::Flee, Some((_, (t, ..)))) => Some(pos * 2.0 - t.translation),
+            (AiState::Attack, Some((target, (target_transform, target_velocity, is_building)))) => {
+                let target_pos = target_transform.translation;
+                let ready = behavior.attack.is_some() && cooldowns.is_ready(entity, Ability::EnemyAttack);
+                if ready {
+                    cooldowns.start(entity, Ability::EnemyAttack, behavior.attack_interval);
+                }
+
+                match behavior.attack {
+                    Some(Attack::Melee(damage)) => {
+                        if ready {
+                            swing_events.send(MeleeSwing {
+                                attacker: entity,
+                                origin: pos,
+                                facing: target_pos - pos,
+                                arc: MELEE_ARC,
+                                range: behavior.attack_range,
+                                damage,
+                            });
+                            if is_building {
+                                alert_events.send(RaiseAlert { entity: target, kind: AlertKind::UnderAttack });
+                            }
+                        }
+                        None
+                    }
+                    Some(Attack::Ranged { kind, keep_distance }) => {
+                        if ready {
+                            let origin = pos + Vec3::Y * 0.5;
+                            let velocity = target_velocity.map_or(Vec3::ZERO, |v| v.0);
+                            let direction = lead(origin, target_pos, velocity, kind.speed());
+                            launch_events.send(LaunchProjectile { owner: entity, kind, origin, direction });
+                        }
+                        // too close for comfort, back off while shooting
+                        (target_pos.distance(pos) < keep_distance).then(|| pos * 2.0 - target_pos)
+                    }
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+
+        // casters hold still until the spell goes off
+        agent.goal = goal.filter(|_| !is_casting);
+        agent.speed = behavior.speed;
+    }
+}
@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+
+use crate::{building::Building, camera::Camera, state::GameplaySet};
+
+// seconds an alert stays up after it was last raised
+const ALERT_TIMEOUT: f32 = 2.0;
+const MAX_SHOWN: usize = 6;
+
+pub struct AlertPlugin;
+
+impl Plugin for AlertPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Alerts>()
+            .add_event::<RaiseAlert>()
+            .add_systems(Startup, setup_alert_ui)
+            .add_systems(Update, (
+                collect_alerts,
+                focus_alerts,
+                update_alert_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertKind {
+    NoPower,
+    OutputFull,
+    MissingIngredient,
+    UnderAttack,
+    Broken,
+}
+
+impl AlertKind {
+    pub fn message(&self) -> &'static str {
+        match self {
+            AlertKind::NoPower => "No power",
+            AlertKind::OutputFull => "Output full",
+            AlertKind::MissingIngredient => "Missing ingredient",
+            AlertKind::UnderAttack => "Under attack",
+            AlertKind::Broken => "Needs repair",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            AlertKind::UnderAttack => Color::RED,
+            AlertKind::NoPower => Color::ORANGE,
+            _ => Color::YELLOW,
+        }
+    }
+}
+
+/// Raised every frame the problem persists, repeats are merged.
+#[derive(Event)]
+pub struct RaiseAlert {
+    pub entity: Entity,
+    pub kind: AlertKind,
+}
+
+pub struct Alert {
+    pub entity: Entity,
+    pub kind: AlertKind,
+    // seconds since it was last raised
+    pub age: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct Alerts(pub Vec<Alert>);
+
+#[derive(Component)]
+struct AlertRow(usize);
+
+fn collect_alerts(
+    time: Res<Time>,
+    mut alerts: ResMut<Alerts>,
+    mut alert_events: EventReader<RaiseAlert>,
+) {
+    for alert in &mut alerts.0 {
+        alert.age += time.delta_seconds();
+    }
+
+    for event in alert_events.read() {
+        match alerts.0.iter_mut().find(|a| a.entity == event.entity && a.kind == event.kind) {
+            Some(alert) => alert.age = 0.0,
+            None => alerts.0.push(Alert { entity: event.entity, kind: event.kind, age: 0.0 }),
+        }
+    }
+
+    alerts.0.retain(|a| a.age < ALERT_TIMEOUT);
+}
+
+fn focus_alerts(
+    alerts: Res<Alerts>,
+    row_query: Query<(&AlertRow, &Interaction), Changed<Interaction>>,
+    mut cam_query: Query<&mut Camera>,
+) {
+    for (row, interaction) in &row_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if let Some(alert) = alerts.0.get(row.0) {
+            cam_query.get_single_mut().expect("not one camera").focus_on(alert.entity);
+        }
+    }
+}
+
+fn setup_alert_ui(mut commands: Commands) {
+    commands.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            top: Val::Percent(40.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        ..default()
+    }).with_children(|parent| {
+        for i in 0..MAX_SHOWN {
+            parent.spawn((
+                AlertRow(i),
+                ButtonBundle {
+                    style: Style {
+                        display: Display::None,
+                        padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                    ..default()
+                },
+            )).with_children(|row| {
+                row.spawn(TextBundle::from_section("", TextStyle {
+                    font_size: 16.0,
+                    color: Color::WHITE,
+                    ..default()
+                }));
+            });
+        }
+    });
+}
+
+fn update_alert_ui(
+    alerts: Res<Alerts>,
+    building_query: Query<&Building>,
+    mut row_query: Query<(&AlertRow, &mut Style, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (row, mut style, children) in &mut row_query {
+        let Some(alert) = alerts.0.get(row.0) else {
+            style.display = Display::None;
+            continue;
+        };
+        style.display = Display::Flex;
+
+        let message = match building_query.get(alert.entity) {
+            Ok(building) => format!("{}: {}", building.kind.name(), alert.kind.message()),
+            Err(_) => alert.kind.message().to_string(),
+        };
+
+        for child in children {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = message.clone();
+                text.sections[0].style.color = alert.kind.color();
+            }
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use bevy::{audio::Volume, prelude::*};
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    audio::{Bus, Mixer},
+    building::{Building, BuildingKind},
+    environment::Wind,
+    loading::Preload,
+    machine::Machine,
+    state::GameState,
+    Player,
+};
+
+// how far around the player counts as where they are
+const REGION_RANGE: f32 = 20.0;
+// this many working machines nearby and it's a factory floor
+const FACTORY_MACHINES: usize = 4;
+// anything solid this close overhead is a roof
+const CEILING_HEIGHT: f32 = 6.0;
+// seconds between looking around, regions don't change that fast
+const SURVEY_INTERVAL: f32 = 0.5;
+// seconds for a bed to fade all the way in or out
+const FADE_TIME: f32 = 3.0;
+
+pub struct AmbiencePlugin;
+
+impl Plugin for AmbiencePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Surroundings>()
+            .add_systems(Startup, setup_ambience)
+            // fades out in menus too, so not gameplay only
+            .add_systems(Update, (survey_surroundings, fade_beds).chain());
+    }
+}
+
+/// The kind of place the player is in, by what's around them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Region {
+    #[default]
+    Wilds,
+    // close to wells, batteries and pylons
+    Ley,
+    Factory,
+}
+
+/// A looping background sound, faded in and out to suit the region.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bed {
+    Wind,
+    Mana,
+    Machinery,
+}
+
+impl Bed {
+    const ALL: [Bed; 3] = [Bed::Wind, Bed::Mana, Bed::Machinery];
+
+    fn path(&self) -> &'static str {
+        match self {
+            Bed::Wind => "ambience/wind.ogg",
+            Bed::Mana => "ambience/mana.ogg",
+            Bed::Machinery => "ambience/machinery.ogg",
+        }
+    }
+
+    fn level(&self, region: Region, indoors: bool) -> f32 {
+        let level = match (self, region) {
+            (Bed::Wind, Region::Wilds) => 0.8,
+            (Bed::Wind, _) => 0.4,
+            (Bed::Mana, Region::Ley) => 0.7,
+            (Bed::Mana, _) => 0.0,
+            (Bed::Machinery, Region::Factory) => 0.6,
+            // always some far off clatter away from the wilds
+            (Bed::Machinery, Region::Ley) => 0.15,
+            (Bed::Machinery, Region::Wilds) => 0.0,
+        };
+        // walls keep the wind out and the rest a little muffled
+        match (self, indoors) {
+            (_, false) => level,
+            (Bed::Wind, true) => level * 0.15,
+            (_, true) => level * 0.7,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Surroundings {
+    pub region: Region,
+    pub indoors: bool,
+    until_survey: f32,
+}
+
+#[derive(Component)]
+struct AmbientBed {
+    bed: Bed,
+    volume: f32,
+}
+
+fn setup_ambience(mut commands: Commands, asset_server: Res<AssetServer>, mut preload: ResMut<Preload>) {
+    for bed in Bed::ALL {
+        let source = asset_server.load(bed.path());
+        preload.add(source.clone());
+        commands.spawn((
+            AmbientBed { bed, volume: 0.0 },
+            AudioBundle {
+                source,
+                settings: PlaybackSettings::LOOP.with_volume(Volume::new_relative(0.0)),
+            },
+        ));
+    }
+}
+
+fn survey_surroundings(
+    time: Res<Time<Real>>,
+    state: Res<State<GameState>>,
+    spatial_query: SpatialQuery,
+    mut surroundings: ResMut<Surroundings>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    building_query: Query<(&Building, &Transform, Option<&Machine>)>,
+) {
+    if *state.get() != GameState::InGame {
+        return;
+    }
+    surroundings.until_survey -= time.delta_seconds();
+    if surroundings.until_survey > 0.0 {
+        return;
+    }
+    surroundings.until_survey = SURVEY_INTERVAL;
+
+    let Ok((player, player_transform)) = player_query.get_single() else {
+        return;
+    };
+    let position = player_transform.translation;
+
+    let nearby = building_query.iter()
+        .filter(|(_, transform, _)| transform.translation.distance(position) <= REGION_RANGE);
+    let mut ley = false;
+    let mut machines = 0;
+    for (building, _, machine) in nearby {
+        ley |= matches!(building.kind, BuildingKind::ManaWell | BuildingKind::ManaBattery | BuildingKind::Pylon);
+        machines += machine.map_or(0, |m| (m.working && m.enabled) as usize);
+    }
+
+    // the factory drowns out the ley lines when there's both
+    surroundings.region = if machines >= FACTORY_MACHINES {
+        Region::Factory
+    } else if ley {
+        Region::Ley
+    } else {
+        Region::Wilds
+    };
+    surroundings.indoors = spatial_query.cast_ray(
+        position,
+        Vec3::Y,
+        CEILING_HEIGHT,
+        true,
+        SpatialQueryFilter::new().without_entities([player]),
+    ).is_some();
+}
+
+fn fade_beds(
+    time: Res<Time<Real>>,
+    state: Res<State<GameState>>,
+    mixer: Res<Mixer>,
+    wind: Res<Wind>,
+    surroundings: Res<Surroundings>,
+    mut bed_query: Query<(&mut AmbientBed, Option<&AudioSink>)>,
+) {
+    let in_game = matches!(state.get(), GameState::InGame | GameState::Paused);
+    let step = time.delta_seconds() / FADE_TIME;
+
+    for (mut ambient, sink) in &mut bed_query {
+        let mut target = if in_game { ambient.bed.level(surroundings.region, surroundings.indoors) } else { 0.0 };
+        // louder in a storm, barely there when it's calm
+        if ambient.bed == Bed::Wind {
+            target *= 0.3 + 0.7 * wind.strength;
+        }
+        ambient.volume = if ambient.volume < target {
+            (ambient.volume + step).min(target)
+        } else {
+            (ambient.volume - step).max(target)
+        };
+
+        // the sink only shows up once the bed has loaded and started
+        if let Some(sink) = sink {
+            sink.set_volume(ambient.volume * mixer.volume(Bus::Effects));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    dialogue::Dialogue,
+    enemy::Hostile,
+    perception::{Alertness, Awareness},
+    loading::Preload,
+    settings::Settings,
+    state::GameState,
+    Player,
+};
+
+// seconds to fade a track all the way in or out
+const FADE_TIME: f32 = 2.0;
+// combat music keeps going this long after the last enemy loses interest
+const COMBAT_LINGER: f32 = 8.0;
+const COMBAT_RANGE: f32 = 30.0;
+// the pause menu plays the same track, just quieter
+const PAUSED_VOLUME: f32 = 0.4;
+// music sits this far down under a conversation
+const DUCKED_VOLUME: f32 = 0.35;
+// seconds to duck all the way down or come back up
+const DUCK_TIME: f32 = 0.5;
+
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Music>()
+            .init_resource::<Mixer>()
+            .add_systems(Startup, preload_music)
+            // music keeps playing in menus, so none of this is gameplay only
+            .add_systems(Update, (mix_buses, detect_combat, choose_track, fade_music).chain());
+    }
+}
+
+/// A group of sounds sharing one volume, each under the master volume.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bus {
+    Music,
+    Effects,
+    Ui,
+}
+
+/// How loud each bus plays right now, from the settings and whatever is
+/// ducking it. Everything that plays a sound takes its volume from here.
+#[derive(Resource)]
+pub struct Mixer {
+    music: f32,
+    effects: f32,
+    ui: f32,
+    // one with nothing going on, down to DUCKED_VOLUME under dialogue
+    duck: f32,
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Mixer { music: 0.0, effects: 0.0, ui: 0.0, duck: 1.0 }
+    }
+}
+
+impl Mixer {
+    pub fn volume(&self, bus: Bus) -> f32 {
+        match bus {
+            Bus::Music => self.music,
+            Bus::Effects => self.effects,
+            Bus::Ui => self.ui,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Track {
+    Menu,
+    Exploration,
+    Combat,
+}
+
+impl Track {
+    const ALL: [Track; 3] = [Track::Menu, Track::Exploration, Track::Combat];
+
+    fn path(&self) -> &'static str {
+        match self {
+            Track::Menu => "music/menu.ogg",
+            Track::Exploration => "music/exploration.ogg",
+            Track::Combat => "music/combat.ogg",
+        }
+    }
+}
+
+/// The background music, one track at a time, crossfading when it changes.
+#[derive(Resource, Default)]
+pub struct Music {
+    pub track: Option<Track>,
+    // seconds of combat music left once nothing is after the player
+    combat: f32,
+}
+
+impl Music {
+    pub fn in_combat(&self) -> bool {
+        self.combat > 0.0
+    }
+}
+
+#[derive(Component)]
+struct MusicPlayer {
+    // on its way out, despawned once silent
+    fading_out: bool,
+    volume: f32,
+}
+
+fn preload_music(asset_server: Res<AssetServer>, mut preload: ResMut<Preload>) {
+    for track in Track::ALL {
+        preload.add(asset_server.load::<AudioSource>(track.path()));
+    }
+}
+
+fn mix_buses(
+    time: Res<Time<Real>>,
+    settings: Res<Settings>,
+    state: Res<State<GameState>>,
+    dialogue: Res<Dialogue>,
+    mut mixer: ResMut<Mixer>,
+) {
+    let target = if dialogue.is_open() { DUCKED_VOLUME } else { 1.0 };
+    let step = time.delta_seconds() / DUCK_TIME * (1.0 - DUCKED_VOLUME);
+    mixer.duck = if mixer.duck < target {
+        (mixer.duck + step).min(target)
+    } else {
+        (mixer.duck - step).max(target)
+    };
+
+    let paused = if *state.get() == GameState::Paused { PAUSED_VOLUME } else { 1.0 };
+    mixer.music = settings.master_volume * settings.music_volume * paused * mixer.duck;
+    mixer.effects = settings.master_volume * settings.effects_volume;
+    mixer.ui = settings.master_volume * settings.ui_volume;
+}
+
+fn detect_combat(
+    time: Res<Time<Real>>,
+    state: Res<State<GameState>>,
+    mut music: ResMut<Music>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    enemy_query: Query<(&Transform, &Awareness), With<Hostile>>,
+) {
+    if *state.get() != GameState::InGame {
+        return;
+    }
+    let Ok((player, player_transform)) = player_query.get_single() else {
+        return;
+    };
+
+    let hunted = enemy_query.iter().any(|(transform, awareness)| {
+        awareness.target == Some(player)
+            && awareness.alertness() == Alertness::Alert
+            && transform.translation.distance(player_transform.translation) <= COMBAT_RANGE
+    });
+    music.combat = if hunted { COMBAT_LINGER } else { (music.combat - time.delta_seconds()).max(0.0) };
+}
+
+fn choose_track(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Res<State<GameState>>,
+    mut music: ResMut<Music>,
+    mut player_query: Query<&mut MusicPlayer>,
+) {
+    let track = match state.get() {
+        GameState::MainMenu | GameState::Loading => Track::Menu,
+        GameState::InGame | GameState::Paused if music.in_combat() => Track::Combat,
+        GameState::InGame | GameState::Paused => Track::Exploration,
+    };
+    if music.track == Some(track) {
+        return;
+    }
+    music.track = Some(track);
+
+    // whatever was playing fades out under the new track fading in
+    for mut player in &mut player_query {
+        player.fading_out = true;
+    }
+    commands.spawn((
+        MusicPlayer { fading_out: false, volume: 0.0 },
+        AudioBundle {
+            source: asset_server.load(track.path()),
+            settings: PlaybackSettings::LOOP.with_volume(Volume::new_relative(0.0)),
+        },
+    ));
+}
+
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mixer: Res<Mixer>,
+    mut player_query: Query<(Entity, &mut MusicPlayer, Option<&AudioSink>)>,
+) {
+    let full = mixer.volume(Bus::Music);
+    let step = time.delta_seconds() / FADE_TIME;
+
+    for (entity, mut player, sink) in &mut player_query {
+        let target = if player.fading_out { 0.0 } else { full };
+        player.volume = if player.volume < target {
+            (player.volume + step).min(target)
+        } else {
+            (player.volume - step).max(target)
+        };
+
+        if player.fading_out && player.volume <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // the sink only shows up once the track has loaded and started
+        if let Some(sink) = sink {
+            sink.set_volume(player.volume);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+use rand::Rng;
+
+use crate::{layer::Layer, projectile::ProjectileBlocked, state::GameplaySet};
+
+const CRACKS: usize = 12;
+const BARRIER_COLOR: Color = Color::rgba(0.5, 0.7, 1.0, 0.25);
+
+pub struct BarrierPlugin;
+
+impl Plugin for BarrierPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<SpawnBarrier>()
+            .add_systems(Update, (
+                spawn_barriers,
+                absorb_projectiles,
+                update_barriers,
+                draw_cracks,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Event)]
+pub struct SpawnBarrier {
+    pub pos: Vec3,
+    pub radius: f32,
+    // damage it can take before it breaks
+    pub budget: f32,
+    pub duration: f32,
+}
+
+/// A dome that catches enemy projectiles until its budget runs out.
+#[derive(Component)]
+pub struct Barrier {
+    pub budget: f32,
+    pub max_budget: f32,
+    pub ttl: f32,
+    // zig-zag lines on the surface, relative to the center
+    cracks: Vec<[Vec3; 4]>,
+}
+
+impl Barrier {
+    pub fn fraction(&self) -> f32 {
+        self.budget / self.max_budget
+    }
+}
+
+fn random_crack(radius: f32) -> [Vec3; 4] {
+    let mut rng = rand::thread_rng();
+    let on_dome = |v: Vec3| {
+        let mut v = v.normalize_or_zero();
+        v.y = v.y.abs();
+        v * radius
+    };
+
+    let start = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(0.0..1.0), rng.gen_range(-1.0..1.0));
+    let mut crack = [on_dome(start); 4];
+    for i in 1..4 {
+        let jitter = Vec3::new(rng.gen_range(-0.3..0.3), rng.gen_range(-0.3..0.3), rng.gen_range(-0.3..0.3));
+        crack[i] = on_dome(crack[i - 1] / radius + jitter);
+    }
+    crack
+}
+
+fn spawn_barriers(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut spawn_events: EventReader<SpawnBarrier>,
+) {
+    for event in spawn_events.read() {
+        commands.spawn((
+            Barrier {
+                budget: event.budget,
+                max_budget: event.budget,
+                ttl: event.duration,
+                cracks: (0..CRACKS).map(|_| random_crack(event.radius)).collect(),
+            },
+            PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::UVSphere { radius: event.radius, ..default() })),
+                // every barrier fades on its own
+                material: materials.add(StandardMaterial {
+                    base_color: BARRIER_COLOR,
+                    alpha_mode: AlphaMode::Blend,
+                    ..default()
+                }),
+                transform: Transform::from_translation(event.pos),
+                ..default()
+            },
+            RigidBody::Static,
+            Collider::ball(event.radius),
+            Sensor,
+            Layer::barrier(),
+        ));
+    }
+}
+
+fn absorb_projectiles(
+    mut barrier_query: Query<&mut Barrier>,
+    mut blocked_events: EventReader<ProjectileBlocked>,
+) {
+    for event in blocked_events.read() {
+        if let Ok(mut barrier) = barrier_query.get_mut(event.barrier) {
+            barrier.budget -= event.kind.damage().amount;
+        }
+    }
+}
+
+fn update_barriers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut barrier_query: Query<(Entity, &mut Barrier, &Handle<StandardMaterial>)>,
+) {
+    for (entity, mut barrier, material) in &mut barrier_query {
+        barrier.ttl -= time.delta_seconds();
+        if barrier.ttl <= 0.0 || barrier.budget <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // it gets redder and more see-through the weaker it is
+        if let Some(material) = materials.get_mut(material) {
+            let fraction = barrier.fraction();
+            material.base_color = Color::rgba(
+                1.0 - 0.5 * fraction,
+                0.7 * fraction,
+                fraction,
+                0.1 + 0.15 * fraction,
+            );
+        }
+    }
+}
+
+fn draw_cracks(
+    barrier_query: Query<(&Barrier, &Transform)>,
+    mut gizmos: Gizmos,
+) {
+    for (barrier, transform) in &barrier_query {
+        let shown = ((1.0 - barrier.fraction()) * barrier.cracks.len() as f32).ceil() as usize;
+        for crack in barrier.cracks.iter().take(shown) {
+            gizmos.linestrip(crack.iter().map(|p| transform.translation + *p), Color::WHITE);
+        }
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    building::{BuildingGrid, BuildingKind, BuildingPlaced, Facing, GridPos},
+    camera::Camera,
+    inventory::Inventory,
+    item::{Item, ItemStack},
+    splitter::Splitter,
+    state::GameplaySet,
+    storage::Chest,
+    streaming::Dormant,
+    underground::UndergroundBelt,
+};
+
+// tiles per second
+pub const BELT_SPEED: f32 = 1.5;
+// minimum distance between two items on the same belt
+pub const ITEM_SPACING: f32 = 0.25;
+
+// only belts this close to the camera get item meshes
+const RENDER_DISTANCE: f32 = 25.0;
+
+pub struct BeltPlugin;
+
+impl Plugin for BeltPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_belt_item_assets)
+            .add_systems(Update, (
+                init_belts,
+                advance_belts,
+                unload_chests,
+                transfer_belt_items,
+                render_belt_items,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+pub struct LaneItem {
+    pub item: Item,
+    // distance to the item in front, or to the end of the belt for the first one
+    pub gap: f32,
+}
+
+/// Items on a belt stored as gaps between them instead of entities, so a
+/// compressed belt only has to touch the items up to the first real gap.
+#[derive(Component, Default)]
+pub struct Belt {
+    // the item furthest along comes first
+    pub items: VecDeque<LaneItem>,
+}
+
+impl Belt {
+    /// Progress of every item along the belt, 0.0 at its start and 1.0 at its end.
+    pub fn positions(&self) -> impl Iterator<Item = (Item, f32)> + '_ {
+        self.items.iter().scan(1.0, |end, slot| {
+            *end -= slot.gap;
+            Some((slot.item, *end))
+        })
+    }
+
+    pub fn has_room(&self) -> bool {
+        let used: f32 = self.items.iter().map(|s| s.gap).sum();
+        self.items.is_empty() || 1.0 - used >= ITEM_SPACING
+    }
+
+    /// Puts an item at the start of the belt.
+    pub fn push(&mut self, item: Item) {
+        let used: f32 = self.items.iter().map(|s| s.gap).sum();
+        self.items.push_back(LaneItem { item, gap: 1.0 - used });
+    }
+
+    /// The first item, if it reached the end of the belt.
+    pub fn front_ready(&self) -> Option<Item> {
+        self.items.front().filter(|s| s.gap <= 0.0).map(|s| s.item)
+    }
+
+    pub fn pop_front(&mut self) -> Option<Item> {
+        let front = self.items.pop_front()?;
+        if let Some(next) = self.items.front_mut() {
+            next.gap += front.gap;
+        }
+        Some(front.item)
+    }
+
+    pub fn advance(&mut self, step: f32) {
+        // the end of the belt never moves
+        let mut moved_in_front = 0.0;
+
+        for (i, slot) in self.items.iter_mut().enumerate() {
+            // everything behind an item that moved freely moves with it
+            if moved_in_front >= step {
+                break;
+            }
+
+            let min_gap = if i == 0 { 0.0 } else { ITEM_SPACING };
+            let moved = step.min(slot.gap + moved_in_front - min_gap).max(0.0);
+            slot.gap += moved_in_front - moved;
+            moved_in_front = moved;
+        }
+    }
+}
+
+// marks a pooled mesh used to draw belt items
+#[derive(Component)]
+struct BeltItemVisual;
+
+/// One mesh and one material per item, shared by everything that draws
+/// loose items so they batch into a handful of draw calls.
+#[derive(Resource)]
+pub struct BeltItemAssets {
+    pub mesh: Handle<Mesh>,
+    pub materials: HashMap<Item, Handle<StandardMaterial>>,
+}
+
+fn setup_belt_item_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(BeltItemAssets {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 0.2 })),
+        materials: Item::ALL.iter()
+            .map(|item| (*item, materials.add(item.color().into())))
+            .collect(),
+    });
+}
+
+fn init_belts(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read() {
+        match event.kind {
+            BuildingKind::Belt
+            | BuildingKind::UndergroundEntrance
+            | BuildingKind::UndergroundExit => {
+                commands.entity(event.entity).insert(Belt::default());
+            }
+            BuildingKind::Splitter => {
+                commands.entity(event.entity).insert(Splitter::default());
+            }
+            _ => {}
+        }
+    }
+}
+
+fn advance_belts(
+    time: Res<Time>,
+    // belts in unloaded chunks hold still
+    mut belt_query: Query<&mut Belt, Without<Dormant>>,
+) {
+    let step = BELT_SPEED * time.delta_seconds();
+
+    for mut belt in &mut belt_query {
+        if !belt.items.is_empty() {
+            belt.advance(step);
+        }
+    }
+}
+
+// a belt leading away from a chest carries off whatever is in it, one item
+// at a time as there's room
+fn unload_chests(
+    grid: Res<BuildingGrid>,
+    mut belt_query: Query<(&mut Belt, &GridPos, &Facing), Without<Dormant>>,
+    mut chest_query: Query<&mut Inventory, With<Chest>>,
+) {
+    for (mut belt, pos, facing) in &mut belt_query {
+        if !belt.has_room() {
+            continue;
+        }
+        let Some(mut inventory) = grid.get(pos.step(facing.opposite()))
+            .and_then(|e| chest_query.get_mut(e).ok()) else {
+            continue;
+        };
+
+        if let Some(stack) = inventory.extract(None, 1) {
+            belt.push(stack.item);
+        }
+    }
+}
+
+/// Whether `belt` can take an item coming from a belt facing `incoming`.
+pub fn belt_accepts(
+    belt_query: &Query<(Entity, &mut Belt, &GridPos, &Facing)>,
+    belt: Entity,
+    incoming: Facing,
+) -> bool {
+    let Ok((_, belt, _, facing)) = belt_query.get(belt) else {
+        return false;
+    };
+
+    // belts facing each other don't connect
+    *facing != incoming.opposite() && belt.has_room()
+}
+
+fn transfer_belt_items(
+    grid: Res<BuildingGrid>,
+    mut belt_query: Query<(Entity, &mut Belt, &GridPos, &Facing)>,
+    mut inventory_query: Query<&mut Inventory>,
+    mut splitter_query: Query<(&mut Splitter, &GridPos, &Facing)>,
+    underground_query: Query<&UndergroundBelt>,
+) {
+    // belts whose front item reached the end
+    let mut ready: Vec<(Entity, Item, GridPos, Facing)> = belt_query.iter()
+        .filter_map(|(entity, belt, pos, facing)| {
+            Some((entity, belt.front_ready()?, *pos, *facing))
+        })
+        .collect();
+
+    // entrances hand their items to the paired exit instead of the next tile
+    let target_of = |source: Entity, pos: GridPos, facing: Facing| {
+        match underground_query.get(source) {
+            Ok(UndergroundBelt { exit: false, partner }) => *partner,
+            _ => grid.get(pos.step(facing)),
+        }
+    };
+
+    // let belts feeding a splitter from its priority side go first
+    ready.sort_by_key(|(_, _, pos, facing)| {
+        let target = pos.step(*facing);
+        let prioritized = grid.get(target)
+            .and_then(|e| splitter_query.get(e).ok())
+            .is_some_and(|(splitter, spos, sfacing)| {
+                splitter.input_priority
+                    .is_some_and(|side| splitter.tile(*spos, *sfacing, side) == target)
+            });
+        !prioritized
+    });
+
+    for (source, item, pos, facing) in ready {
+        let Some(target) = target_of(source, pos, facing) else {
+            continue;
+        };
+
+        // exits only take items coming through their tunnel
+        let tunnel_only = underground_query.get(target)
+            .is_ok_and(|u| u.exit && u.partner != Some(source));
+        if tunnel_only {
+            continue;
+        }
+
+        let delivered = if let Ok(mut inventory) = inventory_query.get_mut(target) {
+            inventory.try_insert(ItemStack::new(item, 1))
+        } else if let Ok((mut splitter, spos, sfacing)) = splitter_query.get_mut(target) {
+            // splitters only take items from behind
+            if facing != *sfacing {
+                continue;
+            }
+
+            let output = splitter.output_order(item).into_iter()
+                .map(|side| (side, grid.get(splitter.tile(*spos, *sfacing, side).step(*sfacing))))
+                .find(|(_, belt)| belt.is_some_and(|b| belt_accepts(&belt_query, b, *sfacing)));
+
+            if let Some((side, Some(belt))) = output {
+                splitter.routed(side);
+                belt_query.get_mut(belt).expect("output belt vanished").1.push(item);
+                true
+            } else {
+                false
+            }
+        } else if belt_accepts(&belt_query, target, facing) {
+            belt_query.get_mut(target).expect("target belt vanished").1.push(item);
+            true
+        } else {
+            false
+        };
+
+        if delivered {
+            let (_, mut belt, _, _) = belt_query.get_mut(source).expect("source belt vanished");
+            belt.pop_front();
+        }
+    }
+}
+
+fn render_belt_items(
+    mut commands: Commands,
+    assets: Res<BeltItemAssets>,
+    cam_query: Query<&Transform, With<Camera>>,
+    belt_query: Query<(&Belt, &GridPos, &Facing)>,
+    mut visual_query: Query<
+        (&mut Transform, &mut Handle<StandardMaterial>, &mut Visibility),
+        (With<BeltItemVisual>, Without<Camera>),
+    >,
+) {
+    let camera = cam_query.get_single().expect("not one camera").translation;
+
+    // only items on belts near the camera get a mesh
+    let shown = belt_query.iter()
+        .filter(|(_, pos, _)| pos.to_world().distance(camera) <= RENDER_DISTANCE)
+        .flat_map(|(belt, pos, facing)| {
+            belt.positions().map(move |(item, progress)| (
+                item,
+                pos.to_world() + facing.direction() * (progress - 0.5) + Vec3::Y * 0.2,
+            ))
+        });
+
+    // reuse a pool of meshes, hiding whatever is left over
+    let mut visuals = visual_query.iter_mut();
+    for (item, translation) in shown {
+        match visuals.next() {
+            Some((mut transform, mut material, mut visibility)) => {
+                transform.translation = translation;
+                *material = assets.materials[&item].clone();
+                *visibility = Visibility::Visible;
+            }
+            None => {
+                commands.spawn((
+                    BeltItemVisual,
+                    PbrBundle {
+                        mesh: assets.mesh.clone(),
+                        material: assets.materials[&item].clone(),
+                        transform: Transform::from_translation(translation),
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+
+    for (_, _, mut visibility) in visuals {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn positions(belt: &Belt) -> Vec<f32> {
+        belt.positions().map(|(_, p)| p).collect()
+    }
+
+    #[test]
+    fn pushed_items_start_at_the_beginning() {
+        let mut belt = Belt::default();
+        belt.push(Item::Stone);
+
+        assert_eq!(positions(&belt), vec![0.0]);
+        assert!(!belt.has_room());
+    }
+
+    #[test]
+    fn items_keep_their_spacing() {
+        let mut belt = Belt::default();
+        belt.push(Item::Stone);
+        belt.advance(0.5);
+        belt.push(Item::Wood);
+        belt.advance(1.0);
+
+        // the first one stops at the end, the second one spacing behind it
+        assert_eq!(positions(&belt), vec![1.0, 1.0 - ITEM_SPACING]);
+        assert_eq!(belt.front_ready(), Some(Item::Stone));
+    }
+
+    #[test]
+    fn room_opens_up_once_the_last_item_moved_far_enough() {
+        let mut belt = Belt::default();
+        belt.push(Item::Stone);
+        belt.advance(ITEM_SPACING / 2.0);
+        assert!(!belt.has_room());
+
+        belt.advance(ITEM_SPACING);
+        assert!(belt.has_room());
+    }
+
+    #[test]
+    fn popping_the_front_keeps_everything_else_in_place() {
+        let mut belt = Belt::default();
+        belt.push(Item::Stone);
+        belt.advance(0.6);
+        belt.push(Item::Wood);
+        belt.advance(0.1);
+
+        let before = positions(&belt)[1];
+        assert_eq!(belt.pop_front(), Some(Item::Stone));
+        assert_eq!(belt.items.len(), 1);
+        assert!((positions(&belt)[0] - before).abs() < 1e-5);
+        assert_eq!(belt.front_ready(), None);
+    }
+}
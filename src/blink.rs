@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    camera::Camera,
+    health::Invulnerable,
+    magic::{SpellBook, SpellCast, SpellEffect},
+    state::GameplaySet,
+};
+
+// keeps the caster from ending up flush against a wall
+const WALL_MARGIN: f32 = 0.2;
+// seconds the trail stays visible
+const TRAIL_TIME: f32 = 0.3;
+
+pub struct BlinkPlugin;
+
+impl Plugin for BlinkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (blink, draw_trails).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Component)]
+struct BlinkTrail {
+    from: Vec3,
+    to: Vec3,
+    ttl: f32,
+}
+
+fn blink(
+    mut commands: Commands,
+    book: Res<SpellBook>,
+    spatial_query: SpatialQuery,
+    camera_query: Query<&Transform, With<Camera>>,
+    mut caster_query: Query<(&mut Transform, &mut LinearVelocity, &Collider), Without<Camera>>,
+    mut cast_events: EventReader<SpellCast>,
+) {
+    for event in cast_events.read() {
+        let Some(SpellEffect::Blink { distance, keep_momentum, invulnerability }) =
+            book.get(event.spell).map(|s| s.effect)
+        else {
+            continue;
+        };
+        let Ok((mut transform, mut velocity, collider)) = caster_query.get_mut(event.caster) else {
+            continue;
+        };
+
+        let direction = camera_query.get_single().map_or(transform.forward(), |c| c.forward());
+        let from = transform.translation;
+
+        // sweep the caster's own shape so it never lands inside something
+        let hit = spatial_query.cast_shape(
+            collider,
+            from,
+            Quat::IDENTITY,
+            direction,
+            distance,
+            true,
+            SpatialQueryFilter::new().without_entities([event.caster]),
+        );
+        let travel = hit.map_or(distance, |h| (h.time_of_impact - WALL_MARGIN).max(0.0));
+        let to = from + direction * travel;
+
+        transform.translation = to;
+        if !keep_momentum {
+            velocity.0 = Vec3::ZERO;
+        }
+
+        commands.entity(event.caster).insert(Invulnerable { remaining: invulnerability });
+        commands.spawn(BlinkTrail { from, to, ttl: TRAIL_TIME });
+    }
+}
+
+fn draw_trails(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut trail_query: Query<(Entity, &mut BlinkTrail)>,
+    mut gizmos: Gizmos,
+) {
+    for (entity, mut trail) in &mut trail_query {
+        let fade = trail.ttl / TRAIL_TIME;
+        let color = Color::rgba(0.6, 0.5, 1.0, fade);
+        gizmos.line(trail.from, trail.to, color);
+        gizmos.circle(trail.from, Vec3::Y, 0.6, color);
+        gizmos.circle(trail.to, Vec3::Y, 0.6 * (1.0 - fade) + 0.3, color);
+
+        trail.ttl -= time.delta_seconds();
+        if trail.ttl <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::{
+        AimPoint, Building, BuildingGrid, BuildingKind, BuildMode, Facing, GridPos,
+        building_transform, rotate_offset,
+    },
+    machine::{Machine, MachineConfig},
+    state::GameplaySet,
+};
+
+pub struct BlueprintPlugin;
+
+impl Plugin for BlueprintPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<BlueprintTool>()
+            .add_event::<StampBlueprint>()
+            .add_systems(Update, (
+                blueprint_input,
+                stamp_blueprints,
+                draw_blueprint_preview,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BlueprintEntry {
+    pub kind: BuildingKind,
+    // relative to the blueprint origin
+    pub offset: IVec2,
+    pub facing: Facing,
+    // recipe and settings of captured machines
+    pub machine: Option<MachineConfig>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Blueprint {
+    pub entries: Vec<BlueprintEntry>,
+}
+
+impl Blueprint {
+    /// Where every entry ends up when stamped at `origin` rotated by `rotation`.
+    pub fn placements(&self, origin: GridPos, rotation: Facing) -> impl Iterator<Item = (BuildingKind, GridPos, Facing)> + '_ {
+        self.entries.iter().map(move |e| (
+            e.kind,
+            GridPos(origin.0 + rotate_offset(e.offset, rotation)),
+            e.facing.rotated(rotation),
+        ))
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum ToolState {
+    #[default]
+    Off,
+    Selecting { start: Option<GridPos> },
+    Pasting,
+}
+
+#[derive(Resource, Default)]
+pub struct BlueprintTool {
+    pub state: ToolState,
+    pub blueprint: Option<Blueprint>,
+    pub rotation: Facing,
+}
+
+#[derive(Event)]
+pub struct StampBlueprint {
+    pub origin: GridPos,
+    pub rotation: Facing,
+}
+
+// a planned building waiting to be constructed
+#[derive(Component)]
+pub struct Ghost {
+    pub kind: BuildingKind,
+    pub machine: Option<MachineConfig>,
+}
+
+fn capture(
+    start: GridPos,
+    end: GridPos,
+    building_query: &Query<(Entity, &Building, &GridPos, &Facing, Option<&Machine>)>,
+) -> Blueprint {
+    let min = start.0.min(end.0);
+    let max = start.0.max(end.0);
+
+    let entries = building_query.iter()
+        .filter(|(_, _, pos, _, _)| pos.0.cmpge(min).all() && pos.0.cmple(max).all())
+        .map(|(_, building, pos, facing, machine)| BlueprintEntry {
+            kind: building.kind,
+            offset: pos.0 - min,
+            facing: *facing,
+            machine: machine.map(|m| m.config()),
+        })
+        .collect();
+
+    Blueprint { entries }
+}
+
+fn blueprint_input(
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    aim: Res<AimPoint>,
+    mut tool: ResMut<BlueprintTool>,
+    mut build_mode: ResMut<BuildMode>,
+    building_query: Query<(Entity, &Building, &GridPos, &Facing, Option<&Machine>)>,
+    mut stamp_events: EventWriter<StampBlueprint>,
+) {
+    if keys.just_pressed(KeyCode::C) {
+        tool.state = match tool.state {
+            ToolState::Off => {
+                build_mode.active = false;
+                ToolState::Selecting { start: None }
+            }
+            _ => ToolState::Off,
+        };
+    }
+
+    if tool.state == ToolState::Off {
+        return;
+    }
+
+    // the two tools share the mouse, so entering build mode closes this one
+    if build_mode.active {
+        tool.state = ToolState::Off;
+        return;
+    }
+
+    let Some(aimed) = aim.0.map(GridPos::from_world) else {
+        return;
+    };
+
+    let state = tool.state;
+    match state {
+        ToolState::Selecting { start } => {
+            if mouse.just_pressed(MouseButton::Left) {
+                tool.state = ToolState::Selecting { start: Some(aimed) };
+            }
+
+            if let (true, Some(start)) = (mouse.just_released(MouseButton::Left), start) {
+                let blueprint = capture(start, aimed, &building_query);
+                if !blueprint.entries.is_empty() {
+                    tool.blueprint = Some(blueprint);
+                    tool.rotation = Facing::North;
+                    tool.state = ToolState::Pasting;
+                } else {
+                    tool.state = ToolState::Selecting { start: None };
+                }
+            }
+        }
+        ToolState::Pasting => {
+            if keys.just_pressed(KeyCode::R) {
+                tool.rotation = tool.rotation.clockwise();
+            }
+
+            if mouse.just_pressed(MouseButton::Left) {
+                stamp_events.send(StampBlueprint {
+                    origin: aimed,
+                    rotation: tool.rotation,
+                });
+            }
+
+            // go back to selecting a new region
+            if mouse.just_pressed(MouseButton::Right) {
+                tool.state = ToolState::Selecting { start: None };
+            }
+        }
+        ToolState::Off => {}
+    }
+}
+
+fn stamp_blueprints(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    tool: Res<BlueprintTool>,
+    grid: Res<BuildingGrid>,
+    ghost_query: Query<&GridPos, With<Ghost>>,
+    mut stamp_events: EventReader<StampBlueprint>,
+) {
+    let Some(blueprint) = &tool.blueprint else {
+        return;
+    };
+
+    for event in stamp_events.read() {
+        let placements = blueprint.entries.iter()
+            .zip(blueprint.placements(event.origin, event.rotation));
+
+        for (entry, (kind, pos, facing)) in placements {
+            // skip anything that is already built or planned
+            let blocked = kind.tiles(pos, facing).iter().any(|t| !grid.is_free(*t))
+                || ghost_query.iter().any(|g| *g == pos);
+            if blocked {
+                continue;
+            }
+
+            spawn_ghost(&mut commands, &mut meshes, &mut materials, kind, pos, facing, entry.machine);
+        }
+    }
+}
+
+pub fn spawn_ghost(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    kind: BuildingKind,
+    pos: GridPos,
+    facing: Facing,
+    machine: Option<MachineConfig>,
+) -> Entity {
+    let size = kind.size();
+    commands.spawn((
+        Ghost { kind, machine },
+        pos,
+        facing,
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z))),
+            material: materials.add(StandardMaterial {
+                base_color: kind.color().with_a(0.35),
+                alpha_mode: AlphaMode::Blend,
+                ..default()
+            }),
+            transform: building_transform(kind, pos, facing),
+            ..default()
+        },
+    )).id()
+}
+
+fn draw_blueprint_preview(
+    tool: Res<BlueprintTool>,
+    aim: Res<AimPoint>,
+    mut gizmos: Gizmos,
+) {
+    let Some(aimed) = aim.0.map(GridPos::from_world) else {
+        return;
+    };
+
+    match (&tool.state, &tool.blueprint) {
+        (ToolState::Selecting { start: Some(start) }, _) => {
+            let min = start.0.min(aimed.0).as_vec2() - 0.5;
+            let max = start.0.max(aimed.0).as_vec2() + 0.5;
+            let center = (min + max) / 2.0;
+
+            gizmos.rect(
+                Vec3::new(center.x, 0.05, center.y),
+                Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
+                max - min,
+                Color::CYAN,
+            );
+        }
+        (ToolState::Pasting, Some(blueprint)) => {
+            for (kind, pos, facing) in blueprint.placements(aimed, tool.rotation) {
+                let transform = building_transform(kind, pos, facing);
+                gizmos.cuboid(transform.with_scale(kind.size()), Color::CYAN);
+            }
+        }
+        _ => {}
+    }
+}
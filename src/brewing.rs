@@ -0,0 +1,298 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::{
+    building::{BuildingKind, BuildingPlaced},
+    input::{Action, InputMap},
+    interaction::{interact, Interactable, Interacted, INTERACT_RANGE},
+    inventory::Inventory,
+    item::{Item, ItemStack},
+    locale::Locale,
+    state::GameplaySet,
+    stats::{ItemConsumed, ItemProduced},
+    status::{ApplyStatus, Status, StatusKind},
+    Player,
+};
+
+pub struct BrewingPlugin;
+
+impl Plugin for BrewingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<OpenStand>()
+            .add_event::<DrinkPotion>()
+            .add_systems(Startup, setup_brewing_ui)
+            .add_systems(Update, (
+                init_stands,
+                toggle_stand.after(interact),
+                brewing_input,
+                run_brews,
+                drink_potions,
+                update_brewing_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Potion {
+    Healing,
+    Haste,
+    Resistance,
+}
+
+impl Potion {
+    pub const ALL: [Potion; 3] = [Potion::Healing, Potion::Haste, Potion::Resistance];
+
+    pub fn item(&self) -> Item {
+        match self {
+            Potion::Healing => Item::HealingPotion,
+            Potion::Haste => Item::HastePotion,
+            Potion::Resistance => Item::ResistancePotion,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.item().name()
+    }
+
+    pub fn reagents(&self) -> &'static [(Item, u32)] {
+        match self {
+            Potion::Healing => &[(Item::ManaCrystal, 1), (Item::Wood, 2)],
+            Potion::Haste => &[(Item::ManaCrystal, 1), (Item::CopperOre, 2)],
+            Potion::Resistance => &[(Item::Essence, 1), (Item::IronOre, 2)],
+        }
+    }
+
+    // seconds at the stand
+    pub fn brew_time(&self) -> f32 {
+        match self {
+            Potion::Healing => 5.0,
+            Potion::Haste | Potion::Resistance => 8.0,
+        }
+    }
+
+    pub fn status(&self) -> Status {
+        let (kind, duration, strength) = match self {
+            Potion::Healing => (StatusKind::Regeneration, 5.0, 10.0),
+            Potion::Haste => (StatusKind::Haste, 20.0, 0.5),
+            Potion::Resistance => (StatusKind::Fortified, 30.0, 0.3),
+        };
+        Status::new(kind, duration, strength)
+    }
+}
+
+/// Asks `drinker` to drink one `potion` out of its inventory.
+#[derive(Event)]
+pub struct DrinkPotion {
+    pub drinker: Entity,
+    pub potion: Potion,
+}
+
+struct Brew {
+    potion: Potion,
+    progress: f32,
+}
+
+#[derive(Component, Default)]
+pub struct BrewingStand {
+    brewing: Option<Brew>,
+}
+
+#[derive(Resource, Default)]
+struct OpenStand {
+    stand: Option<Entity>,
+    selected: usize,
+}
+
+#[derive(Component)]
+struct BrewingUi;
+
+#[derive(Component)]
+struct BrewingText;
+
+fn init_stands(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read().filter(|e| e.kind == BuildingKind::BrewingStand) {
+        commands.entity(event.entity).insert((BrewingStand::default(), Interactable::new("Brew")));
+    }
+}
+
+fn toggle_stand(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut open: ResMut<OpenStand>,
+    player_query: Query<&Transform, With<Player>>,
+    stand_query: Query<(Entity, &Transform), With<BrewingStand>>,
+    mut interacted_events: EventReader<Interacted>,
+) {
+    let player = player_query.get_single().expect("not one player");
+
+    // close once the player walks away or the stand is gone
+    if let Some(entity) = open.stand {
+        let in_range = stand_query.get(entity)
+            .is_ok_and(|(_, t)| t.translation.distance(player.translation) <= INTERACT_RANGE);
+
+        if !in_range {
+            open.stand = None;
+        }
+    }
+
+    let used = interacted_events.read().map(|e| e.entity).find(|e| stand_query.contains(*e));
+    if !input_map.just_pressed(Action::Interact, &keys) {
+        return;
+    }
+
+    if open.stand.is_some() {
+        open.stand = None;
+        return;
+    }
+
+    open.stand = used;
+}
+
+fn brewing_input(
+    keys: Res<Input<KeyCode>>,
+    mut open: ResMut<OpenStand>,
+    mut stand_query: Query<&mut BrewingStand>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+    mut consumed_events: EventWriter<ItemConsumed>,
+) {
+    let Some(mut stand) = open.stand.and_then(|e| stand_query.get_mut(e).ok()) else {
+        return;
+    };
+
+    let count = Potion::ALL.len();
+    if keys.just_pressed(KeyCode::Down) {
+        open.selected = (open.selected + 1) % count;
+    }
+    if keys.just_pressed(KeyCode::Up) {
+        open.selected = (open.selected + count - 1) % count;
+    }
+
+    // one brew at a time per stand
+    if !keys.just_pressed(KeyCode::Return) || stand.brewing.is_some() {
+        return;
+    }
+
+    let mut inventory = player_query.get_single_mut().expect("not one player");
+    let potion = Potion::ALL[open.selected];
+    if !inventory.contains_all(potion.reagents()) {
+        return;
+    }
+
+    for (item, n) in potion.reagents() {
+        inventory.remove(*item, *n);
+        consumed_events.send(ItemConsumed { item: *item, count: *n });
+    }
+    stand.brewing = Some(Brew { potion, progress: 0.0 });
+}
+
+fn run_brews(
+    time: Res<Time>,
+    mut stand_query: Query<&mut BrewingStand>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+    mut produced_events: EventWriter<ItemProduced>,
+) {
+    let mut inventory = player_query.get_single_mut().expect("not one player");
+
+    for mut stand in &mut stand_query {
+        let Some(brew) = stand.brewing.as_mut() else {
+            continue;
+        };
+
+        let potion = brew.potion;
+        brew.progress = (brew.progress + time.delta_seconds()).min(potion.brew_time());
+
+        // a finished potion waits at the stand until there's room for it
+        let stack = ItemStack::new(potion.item(), 1);
+        if brew.progress < potion.brew_time() || !inventory.can_insert(stack) {
+            continue;
+        }
+
+        inventory.insert(stack);
+        stand.brewing = None;
+        produced_events.send(ItemProduced { item: potion.item(), count: 1 });
+    }
+}
+
+fn drink_potions(
+    mut drinker_query: Query<&mut Inventory>,
+    mut drink_events: EventReader<DrinkPotion>,
+    mut status_events: EventWriter<ApplyStatus>,
+) {
+    for event in drink_events.read() {
+        let Ok(mut inventory) = drinker_query.get_mut(event.drinker) else {
+            continue;
+        };
+
+        if inventory.remove(event.potion.item(), 1) {
+            status_events.send(ApplyStatus { target: event.drinker, status: event.potion.status() });
+        }
+    }
+}
+
+fn setup_brewing_ui(mut commands: Commands) {
+    commands.spawn((
+        BrewingUi,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(20.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            BrewingText,
+            TextBundle::from_section("", TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            }),
+        ));
+    });
+}
+
+fn update_brewing_ui(
+    open: Res<OpenStand>,
+    locale: Res<Locale>,
+    stand_query: Query<&BrewingStand>,
+    player_query: Query<&Inventory, With<Player>>,
+    mut ui_query: Query<&mut Visibility, With<BrewingUi>>,
+    mut text_query: Query<&mut Text, With<BrewingText>>,
+) {
+    let mut visibility = ui_query.get_single_mut().expect("not one brewing ui");
+    let Some(stand) = open.stand.and_then(|e| stand_query.get(e).ok()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Visible;
+
+    let inventory = player_query.get_single().expect("not one player");
+
+    let mut content = String::from(locale.get("Brewing Stand"));
+    for (i, potion) in Potion::ALL.iter().enumerate() {
+        let marker = if i == open.selected { '>' } else { ' ' };
+        let reagents = potion.reagents().iter()
+            .map(|(item, n)| format!("{} {}/{}", locale.get(item.name()), inventory.count(*item), n))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = write!(content, "\n{} {} ({})", marker, locale.get(potion.name()), reagents);
+    }
+
+    if let Some(brew) = &stand.brewing {
+        let fraction = brew.progress / brew.potion.brew_time();
+        let percent = format!("{:.0}", fraction * 100.0);
+        let _ = write!(content, "\n{}", locale.fill("Brewing {} {}%", &[&locale.get(brew.potion.name()), &percent]));
+    }
+
+    text_query.get_single_mut().expect("not one brewing text").sections[0].value = content;
+}
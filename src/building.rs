@@ -0,0 +1,783 @@
+use std::f32::consts::PI;
+
+use bevy::{prelude::*, utils::{HashMap, HashSet}};
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    camera::Camera,
+    controls::SurfaceMaterial,
+    health::{Died, Health},
+    inventory::Inventory,
+    item::Item,
+    research::Research,
+    state::GameplaySet,
+    ui_sound::{PlayUiSound, UiSound},
+    underground,
+    Player,
+};
+
+// what enemies have to chew through before a building falls
+const BUILDING_HEALTH: f32 = 100.0;
+
+pub struct BuildingPlugin;
+
+impl Plugin for BuildingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<BuildingGrid>()
+            .init_resource::<AimPoint>()
+            .init_resource::<BuildMode>()
+            .init_resource::<BuildingAssets>()
+            .add_event::<PlaceBuilding>()
+            .add_event::<BuildingPlaced>()
+            .add_event::<RemoveBuilding>()
+            .add_event::<BuildingRemoved>()
+            .add_systems(Update, (
+                update_aim_point,
+                build_mode_input,
+                place_buildings,
+                remove_dead_buildings,
+                remove_buildings,
+                draw_build_preview,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GridPos(pub IVec2);
+
+impl GridPos {
+    pub fn from_world(pos: Vec3) -> Self {
+        GridPos(IVec2::new(pos.x.round() as i32, pos.z.round() as i32))
+    }
+
+    pub fn to_world(self) -> Vec3 {
+        Vec3::new(self.0.x as f32, 0.0, self.0.y as f32)
+    }
+
+    pub fn step(self, facing: Facing) -> Self {
+        GridPos(self.0 + facing.delta())
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Facing {
+    #[default]
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Facing {
+    // north is bevy's forward (-Z)
+    pub fn delta(self) -> IVec2 {
+        match self {
+            Facing::North => IVec2::new(0, -1),
+            Facing::East => IVec2::new(1, 0),
+            Facing::South => IVec2::new(0, 1),
+            Facing::West => IVec2::new(-1, 0),
+        }
+    }
+
+    pub fn direction(self) -> Vec3 {
+        Vec3::new(self.delta().x as f32, 0.0, self.delta().y as f32)
+    }
+
+    pub fn rotation(self) -> Quat {
+        match self {
+            Facing::North => Quat::IDENTITY,
+            Facing::East => Quat::from_rotation_y(-PI / 2.0),
+            Facing::South => Quat::from_rotation_y(PI),
+            Facing::West => Quat::from_rotation_y(PI / 2.0),
+        }
+    }
+
+    pub fn clockwise(self) -> Self {
+        match self {
+            Facing::North => Facing::East,
+            Facing::East => Facing::South,
+            Facing::South => Facing::West,
+            Facing::West => Facing::North,
+        }
+    }
+
+    pub fn counter_clockwise(self) -> Self {
+        self.clockwise().clockwise().clockwise()
+    }
+
+    pub fn opposite(self) -> Self {
+        self.clockwise().clockwise()
+    }
+
+    // treats `by` as a rotation away from north
+    pub fn rotated(self, by: Facing) -> Self {
+        match by {
+            Facing::North => self,
+            Facing::East => self.clockwise(),
+            Facing::South => self.opposite(),
+            Facing::West => self.counter_clockwise(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BuildingKind {
+    Chest,
+    Belt,
+    Splitter,
+    UndergroundEntrance,
+    UndergroundExit,
+    ManaWell,
+    ManaBattery,
+    SolarCollector,
+    Assembler,
+    Purifier,
+    HeatSink,
+    WaterPipe,
+    Rail,
+    RailSignal,
+    TrainStation,
+    BeamTurret,
+    BoltTurret,
+    Lab,
+    Collector,
+    Wire,
+    ConstantCombinator,
+    ArithmeticCombinator,
+    DeciderCombinator,
+    Lamp,
+    DroneHub,
+    WaterWheel,
+    WindTurbine,
+    EnchantingTable,
+    Pylon,
+    Candle,
+    BrewingStand,
+    GolemForge,
+    Decoy,
+}
+
+impl BuildingKind {
+    pub const ALL: &'static [BuildingKind] = &[
+        BuildingKind::Chest,
+        BuildingKind::Belt,
+        BuildingKind::Splitter,
+        BuildingKind::UndergroundEntrance,
+        BuildingKind::UndergroundExit,
+        BuildingKind::ManaWell,
+        BuildingKind::ManaBattery,
+        BuildingKind::SolarCollector,
+        BuildingKind::Assembler,
+        BuildingKind::Purifier,
+        BuildingKind::HeatSink,
+        BuildingKind::WaterPipe,
+        BuildingKind::Rail,
+        BuildingKind::RailSignal,
+        BuildingKind::TrainStation,
+        BuildingKind::BeamTurret,
+        BuildingKind::BoltTurret,
+        BuildingKind::Lab,
+        BuildingKind::Collector,
+        BuildingKind::Wire,
+        BuildingKind::ConstantCombinator,
+        BuildingKind::ArithmeticCombinator,
+        BuildingKind::DeciderCombinator,
+        BuildingKind::Lamp,
+        BuildingKind::DroneHub,
+        BuildingKind::WaterWheel,
+        BuildingKind::WindTurbine,
+        BuildingKind::EnchantingTable,
+        BuildingKind::Pylon,
+        BuildingKind::Candle,
+        BuildingKind::BrewingStand,
+        BuildingKind::GolemForge,
+        BuildingKind::Decoy,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildingKind::Chest => "Chest",
+            BuildingKind::Belt => "Belt",
+            BuildingKind::Splitter => "Splitter",
+            BuildingKind::UndergroundEntrance => "Underground Entrance",
+            BuildingKind::UndergroundExit => "Underground Exit",
+            BuildingKind::ManaWell => "Mana Well",
+            BuildingKind::ManaBattery => "Mana Battery",
+            BuildingKind::SolarCollector => "Solar Collector",
+            BuildingKind::Assembler => "Assembler",
+            BuildingKind::Purifier => "Purifier",
+            BuildingKind::HeatSink => "Heat Sink",
+            BuildingKind::WaterPipe => "Water Pipe",
+            BuildingKind::Rail => "Rail",
+            BuildingKind::RailSignal => "Rail Signal",
+            BuildingKind::TrainStation => "Train Station",
+            BuildingKind::BeamTurret => "Beam Turret",
+            BuildingKind::BoltTurret => "Bolt Turret",
+            BuildingKind::Lab => "Lab",
+            BuildingKind::Collector => "Collector",
+            BuildingKind::Wire => "Wire",
+            BuildingKind::ConstantCombinator => "Constant Combinator",
+            BuildingKind::ArithmeticCombinator => "Arithmetic Combinator",
+            BuildingKind::DeciderCombinator => "Decider Combinator",
+            BuildingKind::Lamp => "Lamp",
+            BuildingKind::DroneHub => "Drone Hub",
+            BuildingKind::WaterWheel => "Water Wheel",
+            BuildingKind::WindTurbine => "Wind Turbine",
+            BuildingKind::EnchantingTable => "Enchanting Table",
+            BuildingKind::Pylon => "Pylon",
+            BuildingKind::Candle => "Candle",
+            BuildingKind::BrewingStand => "Brewing Stand",
+            BuildingKind::GolemForge => "Golem Forge",
+            BuildingKind::Decoy => "Decoy",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            BuildingKind::Chest => Color::rgb_u8(150, 111, 51),
+            BuildingKind::Belt => Color::rgb_u8(60, 60, 60),
+            BuildingKind::Splitter => Color::rgb_u8(200, 170, 40),
+            BuildingKind::UndergroundEntrance => Color::rgb_u8(180, 90, 30),
+            BuildingKind::UndergroundExit => Color::rgb_u8(120, 60, 20),
+            BuildingKind::ManaWell => Color::rgb_u8(70, 90, 200),
+            BuildingKind::ManaBattery => Color::rgb_u8(40, 160, 170),
+            BuildingKind::SolarCollector => Color::rgb_u8(230, 200, 80),
+            BuildingKind::Assembler => Color::rgb_u8(110, 110, 130),
+            BuildingKind::Purifier => Color::rgb_u8(220, 240, 255),
+            BuildingKind::HeatSink => Color::rgb_u8(160, 160, 170),
+            BuildingKind::WaterPipe => Color::rgb_u8(60, 110, 200),
+            BuildingKind::Rail => Color::rgb_u8(120, 100, 90),
+            BuildingKind::RailSignal => Color::rgb_u8(200, 60, 60),
+            BuildingKind::TrainStation => Color::rgb_u8(90, 140, 90),
+            BuildingKind::BeamTurret => Color::rgb_u8(80, 200, 220),
+            BuildingKind::BoltTurret => Color::rgb_u8(220, 110, 60),
+            BuildingKind::Lab => Color::rgb_u8(170, 120, 220),
+            BuildingKind::Collector => Color::rgb_u8(120, 90, 50),
+            BuildingKind::Wire => Color::rgb_u8(200, 40, 40),
+            BuildingKind::ConstantCombinator => Color::rgb_u8(60, 70, 60),
+            BuildingKind::ArithmeticCombinator => Color::rgb_u8(70, 60, 80),
+            BuildingKind::DeciderCombinator => Color::rgb_u8(80, 70, 50),
+            BuildingKind::Lamp => Color::rgb_u8(255, 240, 180),
+            BuildingKind::DroneHub => Color::rgb_u8(90, 130, 160),
+            BuildingKind::WaterWheel => Color::rgb_u8(110, 80, 50),
+            BuildingKind::WindTurbine => Color::rgb_u8(230, 230, 235),
+            BuildingKind::EnchantingTable => Color::rgb_u8(120, 40, 140),
+            BuildingKind::Pylon => Color::rgb_u8(90, 80, 110),
+            BuildingKind::Candle => Color::rgb_u8(250, 240, 210),
+            BuildingKind::BrewingStand => Color::rgb_u8(60, 120, 90),
+            BuildingKind::GolemForge => Color::rgb_u8(110, 105, 95),
+            BuildingKind::Decoy => Color::rgb_u8(200, 170, 120),
+        }
+    }
+
+    // size of the mesh, the footprint is what actually blocks tiles
+    pub fn size(&self) -> Vec3 {
+        match self {
+            BuildingKind::Chest => Vec3::new(0.8, 0.7, 0.8),
+            BuildingKind::Belt => Vec3::new(0.9, 0.1, 1.0),
+            BuildingKind::Splitter => Vec3::new(1.9, 0.4, 0.5),
+            BuildingKind::UndergroundEntrance
+            | BuildingKind::UndergroundExit => Vec3::new(0.9, 0.4, 0.9),
+            BuildingKind::ManaWell => Vec3::new(0.9, 1.2, 0.9),
+            BuildingKind::ManaBattery => Vec3::new(0.7, 1.0, 0.7),
+            BuildingKind::SolarCollector => Vec3::new(1.9, 0.2, 1.9),
+            BuildingKind::Assembler => Vec3::new(0.9, 0.9, 0.9),
+            BuildingKind::Purifier => Vec3::new(0.6, 1.4, 0.6),
+            BuildingKind::HeatSink => Vec3::new(0.8, 0.6, 0.8),
+            BuildingKind::WaterPipe => Vec3::new(0.3, 0.3, 1.0),
+            BuildingKind::Rail | BuildingKind::RailSignal => Vec3::new(1.0, 0.05, 1.0),
+            BuildingKind::TrainStation => Vec3::new(1.0, 0.1, 1.0),
+            BuildingKind::BeamTurret | BuildingKind::BoltTurret => Vec3::new(0.7, 1.2, 0.7),
+            BuildingKind::Lab => Vec3::new(0.9, 1.0, 0.9),
+            BuildingKind::Collector => Vec3::new(0.8, 0.5, 0.8),
+            BuildingKind::Wire => Vec3::new(0.15, 0.05, 1.0),
+            BuildingKind::ConstantCombinator
+            | BuildingKind::ArithmeticCombinator
+            | BuildingKind::DeciderCombinator => Vec3::new(0.6, 0.4, 0.6),
+            BuildingKind::Lamp => Vec3::new(0.4, 0.6, 0.4),
+            BuildingKind::DroneHub => Vec3::new(0.9, 0.5, 0.9),
+            BuildingKind::WaterWheel => Vec3::new(0.3, 0.9, 0.9),
+            BuildingKind::WindTurbine => Vec3::new(0.4, 2.5, 0.4),
+            BuildingKind::EnchantingTable => Vec3::new(0.9, 0.8, 0.9),
+            BuildingKind::Pylon => Vec3::new(0.4, 1.8, 0.4),
+            BuildingKind::Candle => Vec3::new(0.15, 0.3, 0.15),
+            BuildingKind::BrewingStand => Vec3::new(0.6, 0.9, 0.6),
+            BuildingKind::GolemForge => Vec3::new(0.9, 0.7, 0.9),
+            BuildingKind::Decoy => Vec3::new(0.5, 1.6, 0.5),
+        }
+    }
+
+    // items it takes to build, handed back when it's taken apart
+    pub fn cost(&self) -> &'static [(Item, u32)] {
+        match self {
+            BuildingKind::Chest => &[(Item::Wood, 4)],
+            BuildingKind::Belt => &[(Item::IronPlate, 1)],
+            BuildingKind::Splitter => &[(Item::IronPlate, 4), (Item::Gear, 2)],
+            BuildingKind::UndergroundEntrance
+            | BuildingKind::UndergroundExit => &[(Item::IronPlate, 3), (Item::Stone, 2)],
+            BuildingKind::ManaWell => &[(Item::Stone, 8), (Item::IronPlate, 4)],
+            BuildingKind::ManaBattery => &[(Item::CopperPlate, 5), (Item::IronPlate, 2)],
+            BuildingKind::SolarCollector => &[(Item::CopperPlate, 4), (Item::Stone, 4)],
+            BuildingKind::Assembler => &[(Item::IronPlate, 6), (Item::Gear, 3)],
+            BuildingKind::Purifier => &[(Item::Stone, 6), (Item::CopperPlate, 3)],
+            BuildingKind::HeatSink => &[(Item::CopperPlate, 4)],
+            BuildingKind::WaterPipe => &[(Item::IronPlate, 1)],
+            BuildingKind::Rail => &[(Item::Stone, 1), (Item::IronPlate, 1)],
+            BuildingKind::RailSignal => &[(Item::IronPlate, 2), (Item::CopperPlate, 1)],
+            BuildingKind::TrainStation => &[(Item::Stone, 5), (Item::IronPlate, 5)],
+            BuildingKind::BeamTurret => &[(Item::IronPlate, 6), (Item::CopperPlate, 6)],
+            BuildingKind::BoltTurret => &[(Item::IronPlate, 8), (Item::Gear, 4)],
+            BuildingKind::Lab => &[(Item::Wood, 5), (Item::IronPlate, 5), (Item::Gear, 2)],
+            BuildingKind::Collector => &[(Item::Wood, 4), (Item::IronPlate, 2)],
+            BuildingKind::Wire => &[(Item::CopperPlate, 1)],
+            BuildingKind::ConstantCombinator
+            | BuildingKind::ArithmeticCombinator
+            | BuildingKind::DeciderCombinator => &[(Item::CopperPlate, 3), (Item::IronPlate, 2)],
+            BuildingKind::Lamp => &[(Item::CopperPlate, 2), (Item::Stone, 1)],
+            BuildingKind::DroneHub => &[(Item::IronPlate, 10), (Item::Gear, 6), (Item::CopperPlate, 4)],
+            BuildingKind::WaterWheel => &[(Item::Wood, 10), (Item::IronPlate, 2)],
+            BuildingKind::WindTurbine => &[(Item::IronPlate, 6), (Item::CopperPlate, 3)],
+            BuildingKind::EnchantingTable => &[(Item::Wood, 6), (Item::Stone, 4), (Item::ManaCrystal, 4)],
+            BuildingKind::Pylon => &[(Item::Stone, 6), (Item::ManaCrystal, 1)],
+            BuildingKind::Candle => &[(Item::Wood, 1)],
+            BuildingKind::BrewingStand => &[(Item::Stone, 4), (Item::CopperPlate, 3)],
+            BuildingKind::GolemForge => &[(Item::Stone, 20), (Item::Gear, 4), (Item::ManaCrystal, 3)],
+            BuildingKind::Decoy => &[(Item::Wood, 6), (Item::ManaCrystal, 1)],
+        }
+    }
+
+    // flat buildings can be walked over
+    pub fn is_solid(&self) -> bool {
+        !matches!(
+            self,
+            BuildingKind::Belt
+                | BuildingKind::UndergroundEntrance
+                | BuildingKind::UndergroundExit
+                | BuildingKind::SolarCollector
+                | BuildingKind::Rail
+                | BuildingKind::RailSignal
+                | BuildingKind::TrainStation
+                | BuildingKind::Wire
+        )
+    }
+
+    // what the top of a solid building sounds like underfoot
+    pub fn surface(&self) -> SurfaceMaterial {
+        match self {
+            BuildingKind::ManaWell | BuildingKind::ManaBattery | BuildingKind::Pylon => SurfaceMaterial::Crystal,
+            _ => SurfaceMaterial::Metal,
+        }
+    }
+
+    // these get their material tinted at runtime, so they can't share one
+    pub fn tinted(&self) -> bool {
+        matches!(self, BuildingKind::Assembler | BuildingKind::Lamp)
+    }
+
+    /// Tiles occupied relative to the origin tile when facing north.
+    pub fn footprint(&self) -> &'static [IVec2] {
+        match self {
+            BuildingKind::Chest
+            | BuildingKind::Belt
+            | BuildingKind::UndergroundEntrance
+            | BuildingKind::UndergroundExit
+            | BuildingKind::ManaWell
+            | BuildingKind::ManaBattery
+            | BuildingKind::Assembler
+            | BuildingKind::Purifier
+            | BuildingKind::HeatSink
+            | BuildingKind::WaterPipe
+            | BuildingKind::Rail
+            | BuildingKind::RailSignal
+            | BuildingKind::TrainStation
+            | BuildingKind::BeamTurret
+            | BuildingKind::BoltTurret
+            | BuildingKind::Lab
+            | BuildingKind::Collector
+            | BuildingKind::Wire
+            | BuildingKind::ConstantCombinator
+            | BuildingKind::ArithmeticCombinator
+            | BuildingKind::DeciderCombinator
+            | BuildingKind::Lamp
+            | BuildingKind::DroneHub
+            | BuildingKind::WaterWheel
+            | BuildingKind::WindTurbine
+            | BuildingKind::EnchantingTable
+            | BuildingKind::Pylon
+            | BuildingKind::Candle
+            | BuildingKind::BrewingStand
+            | BuildingKind::GolemForge
+            | BuildingKind::Decoy => &[IVec2::ZERO],
+            // extends to the right of the origin tile
+            BuildingKind::Splitter => &[IVec2::ZERO, IVec2::X],
+            BuildingKind::SolarCollector => &[
+                IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE,
+            ],
+        }
+    }
+
+    pub fn tiles(&self, pos: GridPos, facing: Facing) -> Vec<GridPos> {
+        self.footprint().iter()
+            .map(|offset| GridPos(pos.0 + rotate_offset(*offset, facing)))
+            .collect()
+    }
+}
+
+pub fn rotate_offset(offset: IVec2, facing: Facing) -> IVec2 {
+    match facing {
+        Facing::North => offset,
+        Facing::East => IVec2::new(-offset.y, offset.x),
+        Facing::South => -offset,
+        Facing::West => IVec2::new(offset.y, -offset.x),
+    }
+}
+
+#[derive(Component)]
+pub struct Building {
+    pub kind: BuildingKind,
+}
+
+#[derive(Resource, Default)]
+pub struct BuildingGrid {
+    tiles: HashMap<IVec2, Entity>,
+    // nothing can be built on water
+    water: HashSet<IVec2>,
+}
+
+impl BuildingGrid {
+    pub fn get(&self, pos: GridPos) -> Option<Entity> {
+        self.tiles.get(&pos.0).copied()
+    }
+
+    pub fn is_free(&self, pos: GridPos) -> bool {
+        !self.tiles.contains_key(&pos.0) && !self.is_water(pos)
+    }
+
+    pub fn is_water(&self, pos: GridPos) -> bool {
+        self.water.contains(&pos.0)
+    }
+
+    pub fn add_water(&mut self, pos: GridPos) {
+        self.water.insert(pos.0);
+    }
+
+    pub fn occupy(&mut self, pos: GridPos, entity: Entity) {
+        self.tiles.insert(pos.0, entity);
+    }
+
+    pub fn free(&mut self, entity: Entity) {
+        self.tiles.retain(|_, e| *e != entity);
+    }
+}
+
+/// Meshes and materials shared between buildings of the same kind. Entities
+/// with the same handles get drawn as a single instanced batch, which keeps
+/// long belt lines and pipe runs cheap.
+#[derive(Resource, Default)]
+pub struct BuildingAssets {
+    meshes: HashMap<BuildingKind, Handle<Mesh>>,
+    materials: HashMap<BuildingKind, Handle<StandardMaterial>>,
+}
+
+impl BuildingAssets {
+    pub fn mesh(&mut self, kind: BuildingKind, meshes: &mut Assets<Mesh>) -> Handle<Mesh> {
+        self.meshes.entry(kind)
+            .or_insert_with(|| {
+                let size = kind.size();
+                meshes.add(Mesh::from(shape::Box::new(size.x, size.y, size.z)))
+            })
+            .clone()
+    }
+
+    pub fn material(&mut self, kind: BuildingKind, materials: &mut Assets<StandardMaterial>) -> Handle<StandardMaterial> {
+        if kind.tinted() {
+            return materials.add(kind.color().into());
+        }
+
+        self.materials.entry(kind)
+            .or_insert_with(|| materials.add(kind.color().into()))
+            .clone()
+    }
+}
+
+// where the center of the screen hits the ground
+#[derive(Resource, Default)]
+pub struct AimPoint(pub Option<Vec3>);
+
+#[derive(Resource, Default)]
+pub struct BuildMode {
+    pub active: bool,
+    pub selected: usize,
+    pub facing: Facing,
+}
+
+impl BuildMode {
+    pub fn kind(&self) -> BuildingKind {
+        BuildingKind::ALL[self.selected % BuildingKind::ALL.len()]
+    }
+}
+
+#[derive(Event)]
+pub struct PlaceBuilding {
+    pub kind: BuildingKind,
+    pub pos: GridPos,
+    pub facing: Facing,
+    // the sender already brought the materials, otherwise
+    // they come out of the player's inventory
+    pub supplied: bool,
+}
+
+// sent after the base entity has been spawned so other
+// modules can attach their components
+#[derive(Event)]
+pub struct BuildingPlaced {
+    pub entity: Entity,
+    pub kind: BuildingKind,
+    pub pos: GridPos,
+    pub facing: Facing,
+}
+
+// anything that wants a building gone sends this, contents
+// have to be taken care of beforehand
+#[derive(Event)]
+pub struct RemoveBuilding(pub Entity);
+
+#[derive(Event)]
+pub struct BuildingRemoved {
+    pub entity: Entity,
+    pub kind: BuildingKind,
+    pub pos: GridPos,
+    pub facing: Facing,
+    // knocked down by enemies rather than taken apart
+    pub destroyed: bool,
+}
+
+fn update_aim_point(
+    cam_query: Query<&Transform, With<Camera>>,
+    mut aim: ResMut<AimPoint>,
+) {
+    let Ok(cam) = cam_query.get_single() else {
+        return;
+    };
+
+    // intersect the view ray with the ground plane (y = 0)
+    let dir = cam.forward();
+    aim.0 = (dir.y < -0.01).then(|| cam.translation + dir * (-cam.translation.y / dir.y));
+}
+
+fn build_mode_input(
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    aim: Res<AimPoint>,
+    research: Res<Research>,
+    mut mode: ResMut<BuildMode>,
+    mut place_events: EventWriter<PlaceBuilding>,
+) {
+    if keys.just_pressed(KeyCode::B) {
+        mode.active = !mode.active;
+    }
+
+    if !mode.active {
+        return;
+    }
+
+    // locked buildings are skipped when cycling
+    let count = BuildingKind::ALL.len();
+    let step = if keys.just_pressed(KeyCode::BracketRight) {
+        1
+    } else if keys.just_pressed(KeyCode::BracketLeft) {
+        count - 1
+    } else {
+        0
+    };
+    if step != 0 {
+        let mut selected = mode.selected;
+        for _ in 0..count {
+            selected = (selected + step) % count;
+            if research.building_unlocked(BuildingKind::ALL[selected]) {
+                break;
+            }
+        }
+        mode.selected = selected;
+    }
+    if keys.just_pressed(KeyCode::R) {
+        mode.facing = mode.facing.clockwise();
+    }
+
+    if let (true, Some(aim)) = (mouse.just_pressed(MouseButton::Left), aim.0) {
+        place_events.send(PlaceBuilding {
+            kind: mode.kind(),
+            pos: GridPos::from_world(aim),
+            facing: mode.facing,
+            supplied: false,
+        });
+    }
+}
+
+pub fn building_transform(kind: BuildingKind, pos: GridPos, facing: Facing) -> Transform {
+    let tiles = kind.tiles(pos, facing);
+    let center = tiles.iter().map(|t| t.to_world()).sum::<Vec3>() / tiles.len() as f32;
+
+    Transform::from_translation(center + Vec3::Y * kind.size().y / 2.0)
+        .with_rotation(facing.rotation())
+}
+
+pub fn can_place(
+    kind: BuildingKind,
+    pos: GridPos,
+    facing: Facing,
+    grid: &BuildingGrid,
+    building_query: &Query<(&Building, &Facing)>,
+) -> bool {
+    if !kind.tiles(pos, facing).iter().all(|t| grid.is_free(*t)) {
+        return false;
+    }
+
+    match kind {
+        BuildingKind::UndergroundExit => {
+            underground::find_entrance(pos, facing, grid, building_query).is_some()
+        }
+        // has to dip into the river next to it
+        BuildingKind::WaterWheel => {
+            [Facing::North, Facing::East, Facing::South, Facing::West].into_iter()
+                .any(|f| grid.is_water(pos.step(f)))
+        }
+        _ => true,
+    }
+}
+
+fn place_buildings(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut assets: ResMut<BuildingAssets>,
+    mut grid: ResMut<BuildingGrid>,
+    research: Res<Research>,
+    building_query: Query<(&Building, &Facing)>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+    mut place_events: EventReader<PlaceBuilding>,
+    mut placed_events: EventWriter<BuildingPlaced>,
+    mut sound_events: EventWriter<PlayUiSound>,
+) {
+    let mut inventory = player_query.get_single_mut().expect("not one player");
+
+    for event in place_events.read() {
+        let placeable = research.building_unlocked(event.kind)
+            && can_place(event.kind, event.pos, event.facing, &grid, &building_query)
+            && (event.supplied || inventory.contains_all(event.kind.cost()));
+        if !placeable {
+            // drones just try again later, only the player's own clicks complain
+            if !event.supplied {
+                sound_events.send(PlayUiSound(UiSound::Error));
+            }
+            continue;
+        }
+
+        if !event.supplied {
+            for (item, n) in event.kind.cost() {
+                inventory.remove(*item, *n);
+            }
+        }
+
+        let tiles = event.kind.tiles(event.pos, event.facing);
+
+        let size = event.kind.size();
+        let entity = commands.spawn((
+            Building { kind: event.kind },
+            Health::new(BUILDING_HEALTH),
+            event.pos,
+            event.facing,
+            PbrBundle {
+                mesh: assets.mesh(event.kind, &mut meshes),
+                material: assets.material(event.kind, &mut materials),
+                transform: building_transform(event.kind, event.pos, event.facing),
+                ..default()
+            },
+        )).id();
+
+        if event.kind.is_solid() {
+            commands.entity(entity).insert((
+                RigidBody::Static,
+                Collider::cuboid(size.x, size.y, size.z),
+                event.kind.surface(),
+            ));
+        }
+
+        for tile in tiles {
+            grid.occupy(tile, entity);
+        }
+
+        placed_events.send(BuildingPlaced {
+            entity,
+            kind: event.kind,
+            pos: event.pos,
+            facing: event.facing,
+        });
+    }
+}
+
+fn remove_dead_buildings(
+    building_query: Query<(), With<Building>>,
+    mut died_events: EventReader<Died>,
+    mut remove_events: EventWriter<RemoveBuilding>,
+) {
+    for event in died_events.read() {
+        if building_query.contains(event.entity) {
+            remove_events.send(RemoveBuilding(event.entity));
+        }
+    }
+}
+
+fn remove_buildings(
+    mut commands: Commands,
+    mut grid: ResMut<BuildingGrid>,
+    building_query: Query<(&Building, &GridPos, &Facing, &Health)>,
+    mut remove_events: EventReader<RemoveBuilding>,
+    mut removed_events: EventWriter<BuildingRemoved>,
+) {
+    for RemoveBuilding(entity) in remove_events.read() {
+        let Ok((building, pos, facing, health)) = building_query.get(*entity) else {
+            continue;
+        };
+
+        grid.free(*entity);
+        commands.entity(*entity).despawn_recursive();
+        removed_events.send(BuildingRemoved {
+            entity: *entity,
+            kind: building.kind,
+            pos: *pos,
+            facing: *facing,
+            destroyed: health.is_dead(),
+        });
+    }
+}
+
+fn draw_build_preview(
+    mode: Res<BuildMode>,
+    aim: Res<AimPoint>,
+    grid: Res<BuildingGrid>,
+    building_query: Query<(&Building, &Facing)>,
+    player_query: Query<&Inventory, With<Player>>,
+    mut gizmos: Gizmos,
+) {
+    let (true, Some(aim)) = (mode.active, aim.0) else {
+        return;
+    };
+
+    let kind = mode.kind();
+    let pos = GridPos::from_world(aim);
+    let affordable = player_query.get_single().expect("not one player").contains_all(kind.cost());
+    let color = if affordable && can_place(kind, pos, mode.facing, &grid, &building_query) {
+        Color::GREEN
+    } else {
+        Color::RED
+    };
+
+    let transform = building_transform(kind, pos, mode.facing);
+    gizmos.cuboid(transform.with_scale(kind.size()), color);
+
+    // show which way the building faces
+    let start = transform.translation;
+    gizmos.line(start, start + mode.facing.rotation() * Vec3::NEG_Z, color);
+}
@@ -1,16 +1,29 @@
 use std::f32::consts::PI;
 
-use bevy::{prelude::*, input::mouse::{MouseMotion, MouseWheel}, window::{PrimaryWindow, CursorGrabMode}};
+use bevy::{prelude::*, input::mouse::{MouseMotion, MouseWheel}, utils::HashSet, window::{PrimaryWindow, CursorGrabMode}};
 
-use crate::Player;
+use crate::{
+    settings::Settings,
+    state::{GameState, GameplaySet},
+    Player,
+};
+
+// seconds the camera stays on a focused entity before returning to the player
+const FOCUS_TIME: f32 = 4.0;
+// where the camera sits when framing a conversation, off to the side of the two
+const FRAMING_DISTANCE: f32 = 4.0;
+const FRAMING_HEIGHT: f32 = 1.5;
+const FRAMING_SPEED: f32 = 4.0;
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Startup, setup_cursor)
-            .add_systems(Update, (orbit_camera, apply_zoom));
+            .init_resource::<CursorUsers>()
+            .add_systems(OnEnter(GameState::InGame), grab_cursor)
+            .add_systems(Update, (release_cursor, orbit_camera, apply_zoom).in_set(GameplaySet))
+            .add_systems(PostUpdate, grab_cursor.run_if(resource_changed::<CursorUsers>()));
     }
 }
 
@@ -18,6 +31,19 @@ impl Plugin for CameraPlugin {
 pub struct Camera {
     pub distance: f32,
     pub mouse_sensitivity: f32,
+    pub invert_y: bool,
+    // orbit this instead of the player for a while
+    pub focus: Option<Entity>,
+    focus_time: f32,
+    /// Frames the player and this from the side, while talking to it.
+    pub framing: Option<Entity>,
+}
+
+impl Camera {
+    pub fn focus_on(&mut self, entity: Entity) {
+        self.focus = Some(entity);
+        self.focus_time = FOCUS_TIME;
+    }
 }
 
 impl Default for Camera {
@@ -25,40 +51,138 @@ impl Default for Camera {
         Camera {
             distance: 10.0,
             mouse_sensitivity: 0.5,
+            invert_y: false,
+            focus: None,
+            focus_time: 0.0,
+            framing: None,
+        }
+    }
+}
+
+/// Screens that need a mouse pointer while they're open.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CursorUser {
+    Inventory,
+    Crafting,
+    Journal,
+    Map,
+    TechTree,
+    Dialogue,
+    Radial,
+    Machine,
+}
+
+/// Every screen currently using the pointer, the cursor is only grabbed
+/// again once the last of them closes.
+#[derive(Resource, Default)]
+pub struct CursorUsers(HashSet<CursorUser>);
+
+impl CursorUsers {
+    pub fn set(&mut self, user: CursorUser, using: bool) {
+        if using {
+            self.0.insert(user);
+        } else {
+            self.0.remove(&user);
         }
     }
+
+    pub fn any(&self) -> bool {
+        !self.0.is_empty()
+    }
 }
 
-fn setup_cursor(
+pub fn set_cursor_free(window: &mut Window, free: bool) {
+    window.cursor.visible = free;
+    window.cursor.grab_mode = if free { CursorGrabMode::None } else { CursorGrabMode::Locked };
+}
+
+// back in game, or the last screen closed
+fn grab_cursor(
+    users: Res<CursorUsers>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    set_cursor_free(&mut window_query.get_single_mut().expect("not one window"), users.any());
+}
+
+// holding alt frees the cursor so the ui can be clicked
+fn release_cursor(
+    keys: Res<Input<KeyCode>>,
+    settings: Res<Settings>,
+    users: Res<CursorUsers>,
     mut window_query: Query<&mut Window, With<PrimaryWindow>>,
 ) {
     let mut window = window_query.get_single_mut().expect("not one window");
 
-    window.cursor.visible = false;
-    window.cursor.grab_mode = CursorGrabMode::Locked;
+    // or pressing it once, then again to grab it back
+    let grabbed = window.cursor.grab_mode == CursorGrabMode::Locked;
+    let release = keys.just_pressed(KeyCode::AltLeft) && (grabbed || !settings.toggle_cursor);
+    let grab = if settings.toggle_cursor {
+        keys.just_pressed(KeyCode::AltLeft) && !grabbed
+    } else {
+        keys.just_released(KeyCode::AltLeft)
+    };
+
+    if release {
+        set_cursor_free(&mut window, true);
+    }
+    // an open screen keeps the pointer
+    if grab && !users.any() {
+        set_cursor_free(&mut window, false);
+    }
 }
 
 fn orbit_camera(
+    time: Res<Time>,
     window_query: Query<&Window, With<PrimaryWindow>>,
-    mut cam_query: Query<(&mut Transform, &Camera)>,
+    mut cam_query: Query<(&mut Transform, &mut Camera)>,
     player_query: Query<&Transform, (With<Player>, Without<Camera>)>,
+    focus_query: Query<&GlobalTransform>,
     mut mouse_event_reader: EventReader<MouseMotion>
 ) {
-    let (mut cam_transform, cam) = cam_query.get_single_mut().expect("");
+    let (mut cam_transform, mut cam) = cam_query.get_single_mut().expect("");
     let player_transform = player_query.get_single().expect("not one player");
 
+    cam.focus_time -= time.delta_seconds();
+    if cam.focus_time <= 0.0 {
+        cam.focus = None;
+    }
+
+    if let Some(Ok(other)) = cam.framing.map(|e| focus_query.get(e)) {
+        let middle = (player_transform.translation + other.translation()) / 2.0;
+        let across = (other.translation() - player_transform.translation).cross(Vec3::Y).normalize_or_zero();
+        let framed = Transform::from_translation(middle + across * FRAMING_DISTANCE + Vec3::Y * FRAMING_HEIGHT)
+            .looking_at(middle, Vec3::Y);
+
+        let t = (FRAMING_SPEED * time.delta_seconds()).min(1.0);
+        cam_transform.translation = cam_transform.translation.lerp(framed.translation, t);
+        cam_transform.rotation = cam_transform.rotation.slerp(framed.rotation, t);
+        mouse_event_reader.clear();
+        return;
+    }
+
+    // fall back to the player if the focused entity is gone
+    let target = cam.focus
+        .and_then(|e| focus_query.get(e).ok())
+        .map_or(player_transform.translation, |t| t.translation());
+
     // sum all mouse motions since the last frame
     let mut mouse_delta = mouse_event_reader.read()
         .fold(Vec2::ZERO, |sum, i| sum + i.delta);
 
     // make sure the camera can't go inside the player
-    if cam_transform.translation == player_transform.translation {
+    if cam_transform.translation == target {
         cam_transform.translation.x += cam.distance;
     }
 
     // normalize mouse movements since they are relative to the 
     // screen size (in pixels)
     let window = window_query.get_single().expect("not one window");
+
+    // the cursor is being used for the ui
+    if window.cursor.visible {
+        mouse_delta = Vec2::ZERO;
+    }
+
     mouse_delta.x /= window.width();
     mouse_delta.y /= window.height();
 
@@ -66,6 +190,9 @@ fn orbit_camera(
     // and convert to radians
     mouse_delta.x *= cam.mouse_sensitivity * 2.0 * PI;
     mouse_delta.y *= cam.mouse_sensitivity * 2.0 * PI;
+    if cam.invert_y {
+        mouse_delta.y = -mouse_delta.y;
+    }
 
     // if the mouse goes up rotate the cam down
     let pitch = Quat::from_rotation_x(-mouse_delta.y);
@@ -77,24 +204,27 @@ fn orbit_camera(
 
     // apply pitch only if the camera doesn't too far
     if (cam_transform.rotation * pitch * Vec3::Y).y > 0.0 {
-        cam_transform.rotation = cam_transform.rotation * pitch;
+        cam_transform.rotation *= pitch;
     }
 
-    // rotate the cam around the player
+    // rotate the cam around the player or focus
     let rotation_matrix = Mat3::from_quat(cam_transform.rotation);
-    cam_transform.translation = player_transform.translation 
+    cam_transform.translation = target
         + rotation_matrix.mul_vec3(Vec3::new(0.0, 0.0, cam.distance));
 
 }
 
 fn apply_zoom(
+    window_query: Query<&Window, With<PrimaryWindow>>,
     mut scroll_event_reader: EventReader<MouseWheel>,
     mut cam_query: Query<&mut Camera>,
 ) {
     // sum the scrolling events since last frame
     let delta = scroll_event_reader.read().fold(0.0, |sum, i| sum + i.y);
 
-    if delta != 0.0 {
+    // the cursor is being used for the ui, which may scroll on its own
+    let window = window_query.get_single().expect("not one window");
+    if delta != 0.0 && !window.cursor.visible {
         let mut cam = cam_query.get_single_mut().expect("not one camera");
         cam.distance = f32::max(cam.distance - delta, 1.0);
     }
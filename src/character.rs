@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+
+use crate::{inventory::Inventory, item::Item, state::GameplaySet, Player};
+
+// seconds after spending mana before it starts coming back
+const REGEN_DELAY: f32 = 2.0;
+// mana per second
+const BASE_MANA_REGEN: f32 = 5.0;
+const CRYSTAL_MANA: f32 = 40.0;
+const MAX_STAMINA: f32 = 100.0;
+// stamina per second, after a shorter pause than mana
+const STAMINA_REGEN: f32 = 30.0;
+const STAMINA_DELAY: f32 = 0.8;
+
+pub struct CharacterPlugin;
+
+impl Plugin for CharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (regenerate_mana, regenerate_stamina, consume_crystals).in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Stat {
+    MaxMana,
+    ManaRegen,
+    // fraction on top of the base speed
+    Speed,
+    // on top of every damage type
+    Resistance,
+    // fraction added to how fast spells are cast
+    CastSpeed,
+    // fraction taken off every spell's mana cost
+    ManaEfficiency,
+    ExtraProjectiles,
+    // flux that can be held before casting risks a backlash
+    Stability,
+}
+
+/// Where a modifier came from, so it can be swapped out as a whole.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ModifierSource {
+    Gear,
+    Status,
+}
+
+/// A flat amount added to one stat.
+#[derive(Clone, Copy, Debug)]
+pub struct Modifier {
+    pub stat: Stat,
+    pub amount: f32,
+}
+
+/// Resources the player spends on abilities.
+#[derive(Component)]
+pub struct Stats {
+    pub mana: f32,
+    pub max_mana: f32,
+    pub mana_regen: f32,
+    // seconds until regeneration kicks in again
+    pub regen_delay: f32,
+    // spent on dodging
+    pub stamina: f32,
+    pub max_stamina: f32,
+    stamina_delay: f32,
+    // before any modifiers
+    base_max_mana: f32,
+    base_mana_regen: f32,
+    modifiers: Vec<(ModifierSource, Modifier)>,
+}
+
+impl Stats {
+    pub fn new(max_mana: f32) -> Self {
+        Stats {
+            mana: max_mana,
+            max_mana,
+            mana_regen: BASE_MANA_REGEN,
+            regen_delay: 0.0,
+            stamina: MAX_STAMINA,
+            max_stamina: MAX_STAMINA,
+            stamina_delay: 0.0,
+            base_max_mana: max_mana,
+            base_mana_regen: BASE_MANA_REGEN,
+            modifiers: Vec::new(),
+        }
+    }
+
+    /// Everything the modifiers add to `stat`.
+    pub fn modifier(&self, stat: Stat) -> f32 {
+        self.modifiers.iter()
+            .filter(|(_, m)| m.stat == stat)
+            .map(|(_, m)| m.amount)
+            .sum()
+    }
+
+    /// What a spell costing `cost` actually takes out of the mana pool.
+    pub fn spell_cost(&self, cost: f32) -> f32 {
+        // never entirely free
+        cost * (1.0 - self.modifier(Stat::ManaEfficiency)).max(0.2)
+    }
+
+    pub fn cast_speed(&self) -> f32 {
+        (1.0 + self.modifier(Stat::CastSpeed)).max(0.1)
+    }
+
+    pub fn extra_projectiles(&self) -> u32 {
+        self.modifier(Stat::ExtraProjectiles).max(0.0) as u32
+    }
+
+    /// Replaces all modifiers from `source` with `modifiers`.
+    pub fn set_modifiers(&mut self, source: ModifierSource, modifiers: impl IntoIterator<Item = Modifier>) {
+        self.modifiers.retain(|(s, _)| *s != source);
+        self.modifiers.extend(modifiers.into_iter().map(|m| (source, m)));
+
+        self.max_mana = (self.base_max_mana + self.modifier(Stat::MaxMana)).max(0.0);
+        self.mana_regen = (self.base_mana_regen + self.modifier(Stat::ManaRegen)).max(0.0);
+        self.mana = self.mana.min(self.max_mana);
+    }
+
+    pub fn restore_mana(&mut self, amount: f32) {
+        self.mana = (self.mana + amount).min(self.max_mana);
+    }
+
+    /// Takes `amount` mana if there is enough, otherwise nothing.
+    pub fn spend_mana(&mut self, amount: f32) -> bool {
+        if self.mana < amount {
+            return false;
+        }
+
+        self.mana -= amount;
+        self.regen_delay = REGEN_DELAY;
+        true
+    }
+
+    /// Takes `amount` stamina if there is enough, otherwise nothing.
+    pub fn spend_stamina(&mut self, amount: f32) -> bool {
+        if self.stamina < amount {
+            return false;
+        }
+
+        self.stamina -= amount;
+        self.stamina_delay = STAMINA_DELAY;
+        true
+    }
+}
+
+fn regenerate_mana(
+    time: Res<Time>,
+    mut stats_query: Query<&mut Stats>,
+) {
+    let dt = time.delta_seconds();
+
+    for mut stats in &mut stats_query {
+        if stats.regen_delay > 0.0 {
+            stats.regen_delay -= dt;
+            continue;
+        }
+
+        let regen = stats.mana_regen * dt;
+        stats.restore_mana(regen);
+    }
+}
+
+fn regenerate_stamina(
+    time: Res<Time>,
+    mut stats_query: Query<&mut Stats>,
+) {
+    let dt = time.delta_seconds();
+
+    for mut stats in &mut stats_query {
+        if stats.stamina_delay > 0.0 {
+            stats.stamina_delay -= dt;
+            continue;
+        }
+
+        stats.stamina = (stats.stamina + STAMINA_REGEN * dt).min(stats.max_stamina);
+    }
+}
+
+// crystals can be cracked open for an instant refill
+fn consume_crystals(
+    keys: Res<Input<KeyCode>>,
+    mut player_query: Query<(&mut Stats, &mut Inventory), With<Player>>,
+) {
+    if !keys.just_pressed(KeyCode::K) {
+        return;
+    }
+
+    let (mut stats, mut inventory) = player_query.get_single_mut().expect("not one player");
+    if stats.mana < stats.max_mana && inventory.remove(Item::ManaCrystal, 1) {
+        stats.restore_mana(CRYSTAL_MANA);
+    }
+}
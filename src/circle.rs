@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::AimPoint,
+    character::Stats,
+    enemy::Hostile,
+    health::Health,
+    magic::{SpellBook, SpellCast, SpellEffect},
+    power::{ManaConsumer, ManaProducer},
+    state::GameplaySet,
+};
+
+// a circle can only be drawn this far from the caster
+const INSCRIBE_RANGE: f32 = 8.0;
+// per second for everything friendly standing in a vigor circle
+const VIGOR_MANA: f32 = 4.0;
+const VIGOR_HEALING: f32 = 3.0;
+// the most a conduit hands to the machines inside it, per second
+const CONDUIT_OUTPUT: f32 = 12.0;
+const MAX_CIRCLES: usize = 6;
+
+pub struct CirclePlugin;
+
+impl Plugin for CirclePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Inscription>()
+            .add_systems(Update, (
+                inscription_input,
+                inscribe_circles,
+                vigor_circles,
+                conduit_circles,
+                draw_circles,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CircleKind {
+    // restores mana and health
+    Vigor,
+    // powers machines inside it without wires
+    Conduit,
+    // rituals can be channeled from anywhere inside
+    Anchor,
+}
+
+impl CircleKind {
+    pub const ALL: [CircleKind; 3] = [CircleKind::Vigor, CircleKind::Conduit, CircleKind::Anchor];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CircleKind::Vigor => "Vigor",
+            CircleKind::Conduit => "Conduit",
+            CircleKind::Anchor => "Anchor",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            CircleKind::Vigor => Color::rgb(0.3, 1.0, 0.5),
+            CircleKind::Conduit => Color::rgb(0.3, 0.6, 1.0),
+            CircleKind::Anchor => Color::rgb(0.9, 0.4, 1.0),
+        }
+    }
+}
+
+/// Which circle the next inscription draws.
+#[derive(Resource, Default)]
+pub struct Inscription {
+    pub selected: usize,
+}
+
+impl Inscription {
+    pub fn kind(&self) -> CircleKind {
+        CircleKind::ALL[self.selected]
+    }
+}
+
+/// A circle inscribed on the ground, it stays until it's drawn over.
+#[derive(Component)]
+pub struct MagicCircle {
+    pub kind: CircleKind,
+    pub radius: f32,
+    // seconds since it was drawn, the oldest one goes first
+    age: f32,
+}
+
+impl MagicCircle {
+    pub fn contains(&self, center: Vec3, pos: Vec3) -> bool {
+        center.xz().distance(pos.xz()) <= self.radius
+    }
+}
+
+fn inscription_input(
+    keys: Res<Input<KeyCode>>,
+    mut inscription: ResMut<Inscription>,
+) {
+    if keys.just_pressed(KeyCode::I) {
+        inscription.selected = (inscription.selected + 1) % CircleKind::ALL.len();
+    }
+}
+
+fn inscribe_circles(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    book: Res<SpellBook>,
+    aim: Res<AimPoint>,
+    inscription: Res<Inscription>,
+    caster_query: Query<&Transform>,
+    circle_query: Query<(Entity, &MagicCircle, &Transform)>,
+    mut cast_events: EventReader<SpellCast>,
+) {
+    for event in cast_events.read() {
+        let Some(SpellEffect::Inscribe { radius }) = book.get(event.spell).map(|s| s.effect) else {
+            continue;
+        };
+        let Ok(caster) = caster_query.get(event.caster) else {
+            continue;
+        };
+
+        // on the aim point if it's close enough, otherwise right at the caster's feet
+        let feet = caster.translation * Vec3::new(1.0, 0.0, 1.0);
+        let center = aim.0.filter(|a| a.distance(feet) <= INSCRIBE_RANGE).unwrap_or(feet);
+
+        // drawing into an existing circle wipes it out instead
+        let overlapping = circle_query.iter().find(|(_, c, t)| c.contains(t.translation, center));
+        if let Some((entity, _, _)) = overlapping {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        if circle_query.iter().len() >= MAX_CIRCLES {
+            let oldest = circle_query.iter().max_by(|a, b| a.1.age.total_cmp(&b.1.age));
+            if let Some((entity, _, _)) = oldest {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        let kind = inscription.kind();
+        let mut circle = commands.spawn((
+            MagicCircle { kind, radius, age: 0.0 },
+            PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Cylinder { radius, height: 0.01, ..default() })),
+                material: materials.add(StandardMaterial {
+                    base_color: kind.color().with_a(0.2),
+                    emissive: kind.color() * 0.3,
+                    alpha_mode: AlphaMode::Blend,
+                    unlit: true,
+                    ..default()
+                }),
+                // just above the ground so it doesn't flicker
+                transform: Transform::from_translation(center + Vec3::Y * 0.02),
+                ..default()
+            },
+        ));
+        if kind == CircleKind::Conduit {
+            circle.insert(ManaProducer { output: 0.0 });
+        }
+    }
+}
+
+fn vigor_circles(
+    time: Res<Time>,
+    circle_query: Query<(&MagicCircle, &Transform)>,
+    mut stats_query: Query<(&Transform, &mut Stats)>,
+    mut health_query: Query<(&Transform, &mut Health), Without<Hostile>>,
+) {
+    let dt = time.delta_seconds();
+
+    for (circle, center) in circle_query.iter().filter(|(c, _)| c.kind == CircleKind::Vigor) {
+        for (transform, mut stats) in &mut stats_query {
+            if circle.contains(center.translation, transform.translation) {
+                stats.restore_mana(VIGOR_MANA * dt);
+            }
+        }
+        for (transform, mut health) in &mut health_query {
+            if circle.contains(center.translation, transform.translation) {
+                health.heal(VIGOR_HEALING * dt);
+            }
+        }
+    }
+}
+
+fn conduit_circles(
+    mut circle_query: Query<(&MagicCircle, &Transform, &mut ManaProducer)>,
+    consumer_query: Query<(&ManaConsumer, &Transform)>,
+) {
+    // covers exactly what the machines inside want, so it never feeds the rest of the grid
+    for (circle, center, mut producer) in &mut circle_query {
+        let demand: f32 = consumer_query.iter()
+            .filter(|(_, t)| circle.contains(center.translation, t.translation))
+            .map(|(c, _)| c.demand)
+            .sum();
+        producer.output = demand.min(CONDUIT_OUTPUT);
+    }
+}
+
+fn draw_circles(
+    time: Res<Time>,
+    mut circle_query: Query<(&mut MagicCircle, &Transform)>,
+    mut gizmos: Gizmos,
+) {
+    for (mut circle, transform) in &mut circle_query {
+        circle.age += time.delta_seconds();
+
+        // an outer ring, an inner ring and a slowly turning star between them
+        let center = transform.translation;
+        let color = circle.kind.color();
+        gizmos.circle(center, Vec3::Y, circle.radius, color);
+        gizmos.circle(center, Vec3::Y, circle.radius * 0.8, color);
+
+        let turn = circle.age * 0.2;
+        let points = (0..=5).map(|i| {
+            let angle = turn + i as f32 * std::f32::consts::TAU * 2.0 / 5.0;
+            center + Vec3::new(angle.cos(), 0.0, angle.sin()) * circle.radius * 0.8
+        });
+        gizmos.linestrip(points, color);
+    }
+}
@@ -0,0 +1,495 @@
+use std::{collections::VecDeque, fmt::Write};
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    building::{AimPoint, Building, BuildingGrid, BuildingKind, BuildingPlaced, Facing, GridPos},
+    inventory::Inventory,
+    item::Item,
+    power::ManaBattery,
+    state::GameplaySet,
+};
+
+// networks update ten times a second
+const TICK: f32 = 0.1;
+
+pub struct CircuitPlugin;
+
+impl Plugin for CircuitPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<CircuitNetworks>()
+            .add_systems(Startup, setup_circuit_ui)
+            .add_systems(Update, (
+                init_circuit_parts,
+                circuit_input,
+                update_circuits,
+                update_lamps,
+                update_circuit_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Signal {
+    Mana,
+    A,
+    B,
+    C,
+    Item(Item),
+}
+
+impl Signal {
+    pub fn all() -> Vec<Signal> {
+        let mut all = vec![Signal::Mana, Signal::A, Signal::B, Signal::C];
+        all.extend(Item::ALL.iter().map(|i| Signal::Item(*i)));
+        all
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Signal::Mana => "Mana",
+            Signal::A => "A",
+            Signal::B => "B",
+            Signal::C => "C",
+            Signal::Item(item) => item.name(),
+        }
+    }
+
+    fn cycled(self, forward: bool) -> Self {
+        let all = Signal::all();
+        let i = all.iter().position(|s| *s == self).unwrap_or(0);
+        let step = if forward { 1 } else { all.len() - 1 };
+        all[(i + step) % all.len()]
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Comparator {
+    Less,
+    Equal,
+    #[default]
+    Greater,
+}
+
+impl Comparator {
+    pub fn compare(&self, a: i32, b: i32) -> bool {
+        match self {
+            Comparator::Less => a < b,
+            Comparator::Equal => a == b,
+            Comparator::Greater => a > b,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Comparator::Less => "<",
+            Comparator::Equal => "=",
+            Comparator::Greater => ">",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Comparator::Less => Comparator::Equal,
+            Comparator::Equal => Comparator::Greater,
+            Comparator::Greater => Comparator::Less,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operation {
+    pub fn apply(&self, a: i32, b: i32) -> i32 {
+        match self {
+            Operation::Add => a.saturating_add(b),
+            Operation::Subtract => a.saturating_sub(b),
+            Operation::Multiply => a.saturating_mul(b),
+            Operation::Divide => a.checked_div(b).unwrap_or(0),
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Operation::Add => "+",
+            Operation::Subtract => "-",
+            Operation::Multiply => "*",
+            Operation::Divide => "/",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Operation::Add => Operation::Subtract,
+            Operation::Subtract => Operation::Multiply,
+            Operation::Multiply => Operation::Divide,
+            Operation::Divide => Operation::Add,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CombinatorKind {
+    // always outputs `value`
+    Constant,
+    // outputs `input <op> value`
+    Arithmetic(Operation),
+    // outputs 1 while `input <cmp> value` holds
+    Decider(Comparator),
+}
+
+/// Combinators read their network and write back to it on the next tick.
+#[derive(Component)]
+pub struct Combinator {
+    pub kind: CombinatorKind,
+    pub input: Signal,
+    pub output: Signal,
+    pub value: i32,
+}
+
+/// Lets a machine, lamp or station react to its network.
+#[derive(Component, Default)]
+pub struct CircuitCondition {
+    // unset conditions never block anything
+    pub signal: Option<Signal>,
+    pub comparator: Comparator,
+    pub value: i32,
+    pub satisfied: bool,
+}
+
+impl CircuitCondition {
+    pub fn allows(&self) -> bool {
+        self.signal.is_none() || self.satisfied
+    }
+}
+
+#[derive(Component)]
+pub struct Wire;
+
+#[derive(Component)]
+pub struct Lamp;
+
+#[derive(Default)]
+pub struct Signals(pub HashMap<Signal, i32>);
+
+impl Signals {
+    pub fn get(&self, signal: Signal) -> i32 {
+        self.0.get(&signal).copied().unwrap_or(0)
+    }
+
+    fn add(&mut self, signal: Signal, amount: i32) {
+        *self.0.entry(signal).or_default() += amount;
+    }
+}
+
+#[derive(Resource)]
+pub struct CircuitNetworks {
+    pub networks: Vec<Signals>,
+    // which network every connected building belongs to
+    pub membership: HashMap<Entity, usize>,
+    pub wires: HashMap<IVec2, usize>,
+    timer: Timer,
+}
+
+impl Default for CircuitNetworks {
+    fn default() -> Self {
+        CircuitNetworks {
+            networks: Vec::new(),
+            membership: HashMap::default(),
+            wires: HashMap::default(),
+            timer: Timer::from_seconds(TICK, TimerMode::Repeating),
+        }
+    }
+}
+
+impl CircuitNetworks {
+    pub fn signals(&self, entity: Entity) -> Option<&Signals> {
+        self.membership.get(&entity).map(|n| &self.networks[*n])
+    }
+}
+
+#[derive(Component)]
+struct CircuitText;
+
+fn init_circuit_parts(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read() {
+        let mut entity = commands.entity(event.entity);
+        let combinator = |kind| Combinator { kind, input: Signal::A, output: Signal::A, value: 0 };
+
+        match event.kind {
+            BuildingKind::Wire => {
+                entity.insert(Wire);
+            }
+            BuildingKind::ConstantCombinator => {
+                entity.insert(combinator(CombinatorKind::Constant));
+            }
+            BuildingKind::ArithmeticCombinator => {
+                entity.insert(combinator(CombinatorKind::Arithmetic(Operation::Add)));
+            }
+            BuildingKind::DeciderCombinator => {
+                entity.insert(combinator(CombinatorKind::Decider(Comparator::Greater)));
+            }
+            BuildingKind::Lamp => {
+                entity.insert((Lamp, CircuitCondition::default()));
+            }
+            BuildingKind::Assembler | BuildingKind::TrainStation => {
+                entity.insert(CircuitCondition::default());
+            }
+            _ => {}
+        }
+    }
+}
+
+fn circuit_input(
+    keys: Res<Input<KeyCode>>,
+    aim: Res<AimPoint>,
+    grid: Res<BuildingGrid>,
+    mut combinator_query: Query<&mut Combinator>,
+    mut condition_query: Query<&mut CircuitCondition>,
+) {
+    let Some(entity) = aim.0.and_then(|a| grid.get(GridPos::from_world(a))) else {
+        return;
+    };
+
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let step = if shift { 10 } else { 1 };
+    let delta = if keys.just_pressed(KeyCode::Equals) {
+        step
+    } else if keys.just_pressed(KeyCode::Minus) {
+        -step
+    } else {
+        0
+    };
+    let cycle = if keys.just_pressed(KeyCode::Period) {
+        Some(true)
+    } else if keys.just_pressed(KeyCode::Comma) {
+        Some(false)
+    } else {
+        None
+    };
+
+    if let Ok(mut combinator) = combinator_query.get_mut(entity) {
+        combinator.value += delta;
+
+        // shift picks the output signal, constants only have an output
+        if let Some(forward) = cycle {
+            if shift || combinator.kind == CombinatorKind::Constant {
+                combinator.output = combinator.output.cycled(forward);
+            } else {
+                combinator.input = combinator.input.cycled(forward);
+            }
+        }
+
+        if keys.just_pressed(KeyCode::Slash) {
+            combinator.kind = match combinator.kind {
+                CombinatorKind::Constant => CombinatorKind::Constant,
+                CombinatorKind::Arithmetic(op) => CombinatorKind::Arithmetic(op.next()),
+                CombinatorKind::Decider(cmp) => CombinatorKind::Decider(cmp.next()),
+            };
+        }
+    } else if let Ok(mut condition) = condition_query.get_mut(entity) {
+        condition.value += delta;
+
+        // cycling past the last signal clears the condition again
+        if let Some(forward) = cycle {
+            let all = Signal::all();
+            condition.signal = match condition.signal {
+                None if forward => all.first().copied(),
+                None => all.last().copied(),
+                Some(signal) if forward && Some(&signal) == all.last() => None,
+                Some(signal) if !forward && Some(&signal) == all.first() => None,
+                Some(signal) => Some(signal.cycled(forward)),
+            };
+        }
+
+        if keys.just_pressed(KeyCode::Slash) {
+            condition.comparator = condition.comparator.next();
+        }
+    }
+}
+
+fn update_circuits(
+    time: Res<Time>,
+    mut circuits: ResMut<CircuitNetworks>,
+    wire_query: Query<&GridPos, With<Wire>>,
+    building_query: Query<(Entity, &Building, &GridPos, &Facing), Without<Wire>>,
+    inventory_query: Query<(Entity, &Inventory)>,
+    battery_query: Query<(Entity, &ManaBattery)>,
+    combinator_query: Query<(Entity, &Combinator)>,
+    mut condition_query: Query<(Entity, &mut CircuitCondition)>,
+) {
+    if !circuits.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    // flood fill connected wires into networks
+    let mut network_of: HashMap<IVec2, usize> = wire_query.iter().map(|p| (p.0, usize::MAX)).collect();
+    let mut count = 0;
+    let tiles: Vec<IVec2> = network_of.keys().copied().collect();
+
+    for start in tiles {
+        if network_of[&start] != usize::MAX {
+            continue;
+        }
+
+        let mut queue = VecDeque::from([start]);
+        network_of.insert(start, count);
+        while let Some(tile) = queue.pop_front() {
+            for neighbour in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y].map(|d| tile + d) {
+                if network_of.get(&neighbour) == Some(&usize::MAX) {
+                    network_of.insert(neighbour, count);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        count += 1;
+    }
+
+    // anything next to a wire joins its network
+    let membership: HashMap<Entity, usize> = building_query.iter()
+        .filter_map(|(entity, building, pos, facing)| {
+            let network = building.kind.tiles(*pos, *facing).iter()
+                .flat_map(|t| [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y].map(|d| t.0 + d))
+                .find_map(|n| network_of.get(&n).copied())?;
+            Some((entity, network))
+        })
+        .collect();
+
+    let mut networks: Vec<Signals> = (0..count).map(|_| Signals::default()).collect();
+
+    for (entity, inventory) in &inventory_query {
+        let Some(network) = membership.get(&entity) else {
+            continue;
+        };
+        for stack in inventory.slots().iter().filter_map(|s| s.stack) {
+            networks[*network].add(Signal::Item(stack.item), stack.count as i32);
+        }
+    }
+
+    for (entity, battery) in &battery_query {
+        if let Some(network) = membership.get(&entity) {
+            networks[*network].add(Signal::Mana, battery.charge as i32);
+        }
+    }
+
+    // combinators see what the network carried on the previous tick
+    for (entity, combinator) in &combinator_query {
+        let Some(network) = membership.get(&entity) else {
+            continue;
+        };
+        let input = circuits.signals(entity).map_or(0, |s| s.get(combinator.input));
+
+        let output = match combinator.kind {
+            CombinatorKind::Constant => combinator.value,
+            CombinatorKind::Arithmetic(op) => op.apply(input, combinator.value),
+            CombinatorKind::Decider(cmp) => cmp.compare(input, combinator.value) as i32,
+        };
+        networks[*network].add(combinator.output, output);
+    }
+
+    for (entity, mut condition) in &mut condition_query {
+        condition.satisfied = match (condition.signal, membership.get(&entity)) {
+            (Some(signal), Some(network)) => {
+                condition.comparator.compare(networks[*network].get(signal), condition.value)
+            }
+            _ => false,
+        };
+    }
+
+    circuits.networks = networks;
+    circuits.membership = membership;
+    circuits.wires = network_of;
+}
+
+fn update_lamps(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    lamp_query: Query<(&CircuitCondition, &Handle<StandardMaterial>), (With<Lamp>, Changed<CircuitCondition>)>,
+) {
+    for (condition, handle) in &lamp_query {
+        if let Some(material) = materials.get_mut(handle) {
+            material.emissive = if condition.satisfied {
+                BuildingKind::Lamp.color() * 3.0
+            } else {
+                Color::BLACK
+            };
+        }
+    }
+}
+
+fn setup_circuit_ui(mut commands: Commands) {
+    commands.spawn((
+        CircuitText,
+        TextBundle::from_section("", TextStyle {
+            font_size: 16.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(40.0),
+            bottom: Val::Px(44.0),
+            ..default()
+        }),
+    ));
+}
+
+fn update_circuit_ui(
+    aim: Res<AimPoint>,
+    grid: Res<BuildingGrid>,
+    circuits: Res<CircuitNetworks>,
+    combinator_query: Query<&Combinator>,
+    condition_query: Query<&CircuitCondition>,
+    wire_query: Query<&GridPos, With<Wire>>,
+    mut text_query: Query<&mut Text, With<CircuitText>>,
+) {
+    let mut text = text_query.get_single_mut().expect("not one circuit text");
+    text.sections[0].value.clear();
+
+    let Some(entity) = aim.0.and_then(|a| grid.get(GridPos::from_world(a))) else {
+        return;
+    };
+
+    if let Ok(combinator) = combinator_query.get(entity) {
+        text.sections[0].value = match combinator.kind {
+            CombinatorKind::Constant => {
+                format!("{} = {}", combinator.output.name(), combinator.value)
+            }
+            CombinatorKind::Arithmetic(op) => format!(
+                "{} {} {} -> {}",
+                combinator.input.name(), op.symbol(), combinator.value, combinator.output.name(),
+            ),
+            CombinatorKind::Decider(cmp) => format!(
+                "{} {} {} -> {}",
+                combinator.input.name(), cmp.symbol(), combinator.value, combinator.output.name(),
+            ),
+        };
+    } else if let Ok(condition) = condition_query.get(entity) {
+        if let Some(signal) = condition.signal {
+            text.sections[0].value = format!(
+                "Circuit: {} {} {} ({})",
+                signal.name(),
+                condition.comparator.symbol(),
+                condition.value,
+                if condition.satisfied { "true" } else { "false" },
+            );
+        }
+    } else if let Ok(pos) = wire_query.get(entity) {
+        let content = &mut text.sections[0].value;
+        content.push_str("Network:");
+
+        let signals = circuits.wires.get(&pos.0).and_then(|n| circuits.networks.get(*n));
+        for (signal, value) in signals.iter().flat_map(|s| s.0.iter()).filter(|(_, v)| **v != 0) {
+            let _ = write!(content, " {} {}", signal.name(), value);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    Player, Speed,
+    camera::Camera,
+    character::{Stat, Stats},
+    dilation::TimeDilation,
+    dodge::Rolling,
+    input::{Action, InputMap},
+    levitation::Flying,
+    melee::Lunging,
+    minecart::Riding,
+    state::GameplaySet,
+};
+
+pub const JUMP_SPEED: f32 = 5.0;
+// distance from the player's center to the bottom of its collider
+pub const PLAYER_HALF_HEIGHT: f32 = 1.0;
+// seconds between footsteps at walking pace
+const FOOTSTEP_INTERVAL: f32 = 0.4;
+// slower than this is sneaking, and makes no footsteps
+const FOOTSTEP_SPEED: f32 = 1.0;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<Footstep>()
+            .add_event::<Landed>()
+            .add_systems(Update, (
+                update_grounded,
+                player_movement,
+                jump,
+                footsteps,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+/// What a floor is made of, for how it sounds underfoot. Anything without
+/// one counts as grass. Ordered from the softest to the one that rings out
+/// the most when something hits it.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SurfaceMaterial {
+    #[default]
+    Grass,
+    Metal,
+    Crystal,
+}
+
+impl SurfaceMaterial {
+    pub const ALL: [SurfaceMaterial; 3] = [SurfaceMaterial::Grass, SurfaceMaterial::Metal, SurfaceMaterial::Crystal];
+}
+
+#[derive(Component)]
+pub struct Grounded {
+    pub surface: SurfaceMaterial,
+}
+
+/// The player put a foot down while walking.
+#[derive(Event)]
+pub struct Footstep {
+    pub at: Vec3,
+    pub surface: SurfaceMaterial,
+}
+
+/// The player came down after being in the air.
+#[derive(Event)]
+pub struct Landed {
+    pub at: Vec3,
+    // how fast the player was falling just before touching down
+    pub fall_speed: f32,
+    pub surface: SurfaceMaterial,
+}
+
+fn update_grounded(
+    mut commands: Commands,
+    spatial_query: SpatialQuery,
+    surface_query: Query<&SurfaceMaterial>,
+    mut player_query: Query<(Entity, &Transform, &LinearVelocity, Option<&mut Grounded>), With<Player>>,
+    mut landed_events: EventWriter<Landed>,
+    // the physics step has already stopped the fall by the time the ray hits
+    mut fall_speed: Local<f32>,
+) {
+    let (player, transform, velocity, grounded) = player_query.get_single_mut().expect("not one player");
+
+    let hit = spatial_query.cast_ray(
+        transform.translation,
+        Vec3::NEG_Y,
+        PLAYER_HALF_HEIGHT + 0.1,
+        true,
+        SpatialQueryFilter::new().without_entities([player]),
+    );
+
+    let surface = hit.map(|hit| surface_query.get(hit.entity).copied().unwrap_or_default());
+
+    match (surface, grounded) {
+        (Some(surface), None) => {
+            commands.entity(player).insert(Grounded { surface });
+            landed_events.send(Landed { at: transform.translation, fall_speed: *fall_speed, surface });
+        }
+        (Some(surface), Some(mut grounded)) => {
+            if grounded.surface != surface {
+                grounded.surface = surface;
+            }
+        }
+        (None, Some(_)) => {
+            commands.entity(player).remove::<Grounded>();
+            *fall_speed = (-velocity.y).max(0.0);
+        }
+        (None, None) => {
+            *fall_speed = (-velocity.y).max(0.0);
+        }
+    }
+}
+
+fn player_movement(
+    // lunges and rolls carry the player along on their own
+    mut player_query: Query<
+        (&mut LinearVelocity, &Speed, &Stats),
+        (With<Player>, Without<Riding>, Without<Lunging>, Without<Rolling>),
+    >,
+    camera_query: Query<&Transform, (With<Camera>, Without<Player>)>,
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    dilation: Res<TimeDilation>,
+) {
+    // nothing to steer while sitting in a vehicle
+    let Ok((mut velocity, player_speed, stats)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    let camera = camera_query.get_single().expect("couldn't fetch camera");
+
+    let mut direction = Vec3::ZERO;
+
+    if input_map.pressed(Action::MoveForward, &keys) {
+        direction += camera.forward();
+    }
+
+    if input_map.pressed(Action::MoveLeft, &keys) {
+        direction += camera.left();
+    }
+
+    if input_map.pressed(Action::MoveBack, &keys) {
+        direction += camera.back();
+    }
+
+    if input_map.pressed(Action::MoveRight, &keys) {
+        direction += camera.right();
+    }
+
+    direction.y = 0.0;
+
+    // keep the vertical velocity so gravity and jumps still work, and
+    // make up for slowed down physics so the player walks at normal speed
+    let speed = player_speed.value * (1.0 + stats.modifier(Stat::Speed)).max(0.0);
+    let movement = direction.normalize_or_zero() * speed / dilation.factor;
+    velocity.x = movement.x;
+    velocity.z = movement.z;
+}
+
+fn jump(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    // space climbs instead while flying
+    mut player_query: Query<&mut LinearVelocity, (With<Player>, With<Grounded>, Without<Riding>, Without<Flying>)>,
+) {
+    if !input_map.just_pressed(Action::Jump, &keys) {
+        return;
+    }
+
+    if let Ok(mut velocity) = player_query.get_single_mut() {
+        velocity.y = JUMP_SPEED;
+    }
+}
+
+fn footsteps(
+    time: Res<Time>,
+    player_query: Query<(&Transform, &LinearVelocity, &Grounded), With<Player>>,
+    mut footstep_events: EventWriter<Footstep>,
+    mut until_next: Local<f32>,
+) {
+    let Ok((transform, velocity, grounded)) = player_query.get_single() else {
+        return;
+    };
+    if velocity.xz().length() < FOOTSTEP_SPEED {
+        return;
+    }
+
+    *until_next -= time.delta_seconds();
+    if *until_next <= 0.0 {
+        *until_next = FOOTSTEP_INTERVAL;
+        footstep_events.send(Footstep { at: transform.translation, surface: grounded.surface });
+    }
+}
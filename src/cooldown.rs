@@ -0,0 +1,178 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{dilation::TimeDilation, magic::SpellId, shaman::EnemySpell, Player};
+
+pub struct CooldownPlugin;
+
+impl Plugin for CooldownPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Cooldowns>()
+            .add_event::<CooldownExpired>()
+            .add_systems(PreUpdate, tick_cooldowns);
+    }
+}
+
+/// Anything that has to wait before it can be used again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Ability {
+    Spell(SpellId),
+    TurretShot,
+    MinionAttack,
+    GolemAttack,
+    EnemyAttack,
+    EnemySpell(EnemySpell),
+}
+
+struct Cooldown {
+    // seconds
+    remaining: f32,
+    duration: f32,
+    // how fast it runs down, e.g. slower for a turret on a weak grid
+    rate: f32,
+}
+
+/// Every running cooldown in the game, keyed by whoever used the ability.
+#[derive(Resource, Default)]
+pub struct Cooldowns {
+    active: HashMap<(Entity, Ability), Cooldown>,
+}
+
+impl Cooldowns {
+    pub fn start(&mut self, entity: Entity, ability: Ability, duration: f32) {
+        if duration <= 0.0 {
+            return;
+        }
+
+        let rate = self.active.get(&(entity, ability)).map_or(1.0, |c| c.rate);
+        self.active.insert((entity, ability), Cooldown { remaining: duration, duration, rate });
+    }
+
+    /// Seconds left, zero once it's ready.
+    pub fn remaining(&self, entity: Entity, ability: Ability) -> f32 {
+        self.active.get(&(entity, ability)).map_or(0.0, |c| c.remaining)
+    }
+
+    // 1.0 right after starting, down to 0.0 once it's ready
+    pub fn fraction(&self, entity: Entity, ability: Ability) -> f32 {
+        self.active.get(&(entity, ability)).map_or(0.0, |c| c.remaining / c.duration)
+    }
+
+    pub fn is_ready(&self, entity: Entity, ability: Ability) -> bool {
+        self.remaining(entity, ability) <= 0.0
+    }
+
+    /// Takes `seconds` off a running cooldown, it expires on the next tick if
+    /// that's more than was left.
+    // nothing shortens cooldowns yet, but effects that do should go through here
+    #[allow(dead_code)]
+    pub fn reduce(&mut self, entity: Entity, ability: Ability, seconds: f32) {
+        if let Some(cooldown) = self.active.get_mut(&(entity, ability)) {
+            cooldown.remaining = (cooldown.remaining - seconds).max(0.0);
+        }
+    }
+
+    // everything it was waiting on, once it's gone for good
+    pub fn forget(&mut self, entity: Entity) {
+        self.active.retain(|(e, _), _| *e != entity);
+    }
+
+    pub fn set_rate(&mut self, entity: Entity, ability: Ability, rate: f32) {
+        if let Some(cooldown) = self.active.get_mut(&(entity, ability)) {
+            cooldown.rate = rate;
+        }
+    }
+
+    // runs everything down by each user's `dt` and hands back what expired
+    fn tick(&mut self, dt: impl Fn(Entity) -> f32) -> Vec<(Entity, Ability)> {
+        let mut expired = Vec::new();
+        self.active.retain(|(entity, ability), cooldown| {
+            cooldown.remaining -= dt(*entity) * cooldown.rate;
+            if cooldown.remaining > 0.0 {
+                return true;
+            }
+
+            expired.push((*entity, *ability));
+            false
+        });
+        expired
+    }
+}
+
+#[derive(Event)]
+pub struct CooldownExpired {
+    pub entity: Entity,
+    pub ability: Ability,
+}
+
+fn tick_cooldowns(
+    time: Res<Time>,
+    dilation: Res<TimeDilation>,
+    mut cooldowns: ResMut<Cooldowns>,
+    player_query: Query<(), With<Player>>,
+    mut expired_events: EventWriter<CooldownExpired>,
+) {
+    // the player's abilities keep their pace while time is dilated
+    let scaled = time.delta_seconds();
+    let unscaled = dilation.unscaled(&time);
+
+    for (entity, ability) in cooldowns.tick(|entity| if player_query.contains(entity) { unscaled } else { scaled }) {
+        expired_events.send(CooldownExpired { entity, ability });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_scales_how_fast_it_runs_down() {
+        let turret = Entity::from_raw(0);
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.start(turret, Ability::TurretShot, 2.0);
+        cooldowns.set_rate(turret, Ability::TurretShot, 0.5);
+
+        assert!(cooldowns.tick(|_| 1.0).is_empty());
+        assert_eq!(cooldowns.remaining(turret, Ability::TurretShot), 1.5);
+        assert_eq!(cooldowns.fraction(turret, Ability::TurretShot), 0.75);
+    }
+
+    #[test]
+    fn restarting_keeps_the_rate() {
+        let turret = Entity::from_raw(0);
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.start(turret, Ability::TurretShot, 1.0);
+        cooldowns.set_rate(turret, Ability::TurretShot, 0.5);
+        cooldowns.start(turret, Ability::TurretShot, 1.0);
+
+        cooldowns.tick(|_| 1.0);
+        assert_eq!(cooldowns.remaining(turret, Ability::TurretShot), 0.5);
+    }
+
+    #[test]
+    fn expiry_is_reported_once() {
+        let player = Entity::from_raw(0);
+        let minion = Entity::from_raw(1);
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.start(player, Ability::MinionAttack, 1.0);
+        cooldowns.start(minion, Ability::MinionAttack, 1.0);
+
+        // only the player's clock moves
+        let expired = cooldowns.tick(|entity| if entity == player { 1.0 } else { 0.25 });
+        assert_eq!(expired, vec![(player, Ability::MinionAttack)]);
+        assert!(cooldowns.is_ready(player, Ability::MinionAttack));
+        assert!(!cooldowns.is_ready(minion, Ability::MinionAttack));
+        assert!(cooldowns.tick(|_| 0.0).is_empty());
+    }
+
+    #[test]
+    fn reducing_past_zero_expires_on_the_next_tick() {
+        let turret = Entity::from_raw(0);
+        let mut cooldowns = Cooldowns::default();
+        cooldowns.start(turret, Ability::TurretShot, 3.0);
+        cooldowns.reduce(turret, Ability::TurretShot, 5.0);
+
+        assert!(cooldowns.is_ready(turret, Ability::TurretShot));
+        assert_eq!(cooldowns.tick(|_| 0.0), vec![(turret, Ability::TurretShot)]);
+    }
+}
@@ -0,0 +1,175 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    building::{BuildingKind, BuildingPlaced},
+    power::ManaProducer,
+    state::GameplaySet,
+};
+
+// in tiles
+pub const CHUNK_SIZE: f32 = 16.0;
+// how much corruption the vegetation can take before it is fully withered
+pub const WITHER_LEVEL: f32 = 100.0;
+
+pub struct CorruptionPlugin;
+
+impl Plugin for CorruptionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<CorruptionMap>()
+            .insert_resource(SpreadTimer(Timer::from_seconds(1.0, TimerMode::Repeating)))
+            .add_systems(Update, (
+                init_corruption_buildings,
+                emit_corruption,
+                purify_corruption,
+                spread_corruption,
+                wither_vegetation,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Component)]
+pub struct CorruptionEmitter {
+    pub per_second: f32,
+}
+
+#[derive(Component)]
+pub struct Purifier {
+    pub per_second: f32,
+}
+
+// anything green that should look sick on corrupted ground
+#[derive(Component)]
+pub struct Vegetation {
+    pub healthy: Color,
+}
+
+#[derive(Resource, Default)]
+pub struct CorruptionMap {
+    chunks: HashMap<IVec2, f32>,
+}
+
+impl CorruptionMap {
+    pub fn chunk_of(pos: Vec3) -> IVec2 {
+        IVec2::new(
+            (pos.x / CHUNK_SIZE).floor() as i32,
+            (pos.z / CHUNK_SIZE).floor() as i32,
+        )
+    }
+
+    pub fn get(&self, pos: Vec3) -> f32 {
+        self.chunks.get(&Self::chunk_of(pos)).copied().unwrap_or(0.0)
+    }
+
+    pub fn total(&self) -> f32 {
+        self.chunks.values().sum()
+    }
+
+    pub fn add(&mut self, pos: Vec3, amount: f32) {
+        let level = self.chunks.entry(Self::chunk_of(pos)).or_insert(0.0);
+        *level = f32::max(*level + amount, 0.0);
+    }
+
+    /// How much more often hostiles should spawn at `pos`.
+    pub fn spawn_rate_multiplier(&self, pos: Vec3) -> f32 {
+        1.0 + self.get(pos) / WITHER_LEVEL
+    }
+}
+
+#[derive(Resource)]
+struct SpreadTimer(Timer);
+
+fn init_corruption_buildings(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read() {
+        match event.kind {
+            // wells pull raw mana out of the ground and leak some of it
+            BuildingKind::ManaWell => {
+                commands.entity(event.entity).insert(CorruptionEmitter { per_second: 0.5 });
+            }
+            BuildingKind::Purifier => {
+                commands.entity(event.entity).insert(Purifier { per_second: 2.0 });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn emit_corruption(
+    time: Res<Time>,
+    mut map: ResMut<CorruptionMap>,
+    emitter_query: Query<(&CorruptionEmitter, &Transform, Option<&ManaProducer>)>,
+) {
+    for (emitter, transform, producer) in &emitter_query {
+        // idle generators don't leak anything
+        if producer.is_some_and(|p| p.output <= 0.0) {
+            continue;
+        }
+
+        map.add(transform.translation, emitter.per_second * time.delta_seconds());
+    }
+}
+
+fn purify_corruption(
+    time: Res<Time>,
+    mut map: ResMut<CorruptionMap>,
+    purifier_query: Query<(&Purifier, &Transform)>,
+) {
+    for (purifier, transform) in &purifier_query {
+        map.add(transform.translation, -purifier.per_second * time.delta_seconds());
+    }
+}
+
+fn spread_corruption(
+    time: Res<Time>,
+    mut timer: ResMut<SpreadTimer>,
+    mut map: ResMut<CorruptionMap>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    // every chunk leaks a bit into its neighbours and the ground absorbs a little
+    let mut next = HashMap::default();
+    for (chunk, level) in &map.chunks {
+        let leaked = level * 0.02;
+        *next.entry(*chunk).or_insert(0.0) += level - leaked * 4.0 - level * 0.005;
+
+        for offset in [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y] {
+            *next.entry(*chunk + offset).or_insert(0.0) += leaked;
+        }
+    }
+
+    next.retain(|_, level| *level > 0.01);
+    map.chunks = next;
+}
+
+fn wither_vegetation(
+    map: Res<CorruptionMap>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    vegetation_query: Query<(&Vegetation, &Transform, &Handle<StandardMaterial>)>,
+) {
+    if !map.is_changed() {
+        return;
+    }
+
+    let withered = Color::rgb_u8(90, 60, 100);
+
+    for (vegetation, transform, handle) in &vegetation_query {
+        let t = (map.get(transform.translation) / WITHER_LEVEL).min(1.0);
+
+        if let Some(material) = materials.get_mut(handle) {
+            material.base_color = lerp_color(vegetation.healthy, withered, t);
+        }
+    }
+}
+
+pub fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let a = Vec4::from(a.as_rgba_f32());
+    let b = Vec4::from(b.as_rgba_f32());
+    let c = a.lerp(b, t);
+
+    Color::rgba(c.x, c.y, c.z, c.w)
+}
@@ -0,0 +1,425 @@
+use std::{collections::VecDeque, fmt::Write};
+
+use bevy::prelude::*;
+
+use crate::{
+    camera::{CursorUser, CursorUsers},
+    implement::with_rolled_rarity,
+    input::{Action, InputMap},
+    inventory::Inventory,
+    item::ItemStack,
+    locale::{Locale, Localized},
+    recipe::{Category, Recipe},
+    research::Research,
+    settings::Settings,
+    state::GameplaySet,
+    stats::{ItemConsumed, ItemProduced},
+    tooltip::ItemTooltip,
+    ui_sound::{PlayUiSound, UiSound},
+    Player,
+};
+
+// crafting by hand is slower than in an assembler
+const HAND_TIME_FACTOR: f32 = 1.5;
+const QUEUE_LIMIT: usize = 10;
+
+const BAR_WIDTH: f32 = 200.0;
+const BUTTON_COLOR: Color = Color::rgba(0.3, 0.3, 0.3, 0.9);
+const DISABLED_COLOR: Color = Color::rgba(0.15, 0.15, 0.15, 0.9);
+
+pub struct CraftingPlugin;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<CraftingPanel>()
+            .add_systems(Startup, setup_crafting_ui)
+            .add_systems(Update, (
+                toggle_crafting,
+                crafting_input,
+                run_craft_queue,
+                update_crafting_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+pub struct CraftJob {
+    pub recipe: Recipe,
+    pub progress: f32,
+}
+
+/// Hand crafting jobs, the ingredients are taken out of the
+/// inventory when a job is queued and refunded when it's cancelled.
+#[derive(Component, Default)]
+pub struct CraftQueue {
+    pub jobs: VecDeque<CraftJob>,
+}
+
+#[derive(Resource, Default)]
+pub struct CraftingPanel {
+    pub open: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Amount {
+    One,
+    Five,
+    // as many as the ingredients and the queue allow
+    Max,
+}
+
+impl Amount {
+    const ALL: [Amount; 3] = [Amount::One, Amount::Five, Amount::Max];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Amount::One => "1",
+            Amount::Five => "5",
+            Amount::Max => "Max",
+        }
+    }
+
+    fn count(&self) -> u32 {
+        match self {
+            Amount::One => 1,
+            Amount::Five => 5,
+            Amount::Max => u32::MAX,
+        }
+    }
+}
+
+#[derive(Component)]
+struct CraftingUi;
+
+#[derive(Component)]
+struct CategoryHeader(Category);
+
+#[derive(Component)]
+struct RecipeRow(Recipe);
+
+#[derive(Component)]
+struct RecipeName(Recipe);
+
+#[derive(Component)]
+struct Ingredients(Recipe);
+
+#[derive(Component)]
+struct CraftButton {
+    recipe: Recipe,
+    amount: Amount,
+}
+
+#[derive(Component)]
+struct CancelButton;
+
+#[derive(Component)]
+struct QueueText;
+
+#[derive(Component)]
+struct QueueFill;
+
+fn hand_recipes(research: &Research) -> Vec<Recipe> {
+    Recipe::ALL.iter()
+        .copied()
+        .filter(|r| r.handcraftable() && research.recipe_unlocked(*r))
+        .collect()
+}
+
+// how many times over the inventory holds the ingredients
+fn affordable(recipe: Recipe, inventory: &Inventory) -> u32 {
+    recipe.inputs().iter()
+        .map(|(item, n)| inventory.count(*item) / n)
+        .min()
+        .unwrap_or(0)
+}
+
+// reserves the ingredients right away so nothing else can use them
+// returns how many were queued, which may be none
+fn queue_crafts(recipe: Recipe, count: u32, inventory: &mut Inventory, queue: &mut CraftQueue) -> u32 {
+    let room = QUEUE_LIMIT.saturating_sub(queue.jobs.len()) as u32;
+    let queued = count.min(room).min(affordable(recipe, inventory));
+    for _ in 0..queued {
+        for (item, n) in recipe.inputs() {
+            inventory.remove(*item, *n);
+        }
+        queue.jobs.push_back(CraftJob { recipe, progress: 0.0 });
+    }
+    queued
+}
+
+// cancels the most recently queued job, as long as the refund fits
+fn cancel_last(inventory: &mut Inventory, queue: &mut CraftQueue) {
+    let Some(job) = queue.jobs.back() else {
+        return;
+    };
+
+    // the inputs may compete for the same free slots, so try the whole
+    // refund on a copy first
+    let mut refunded = inventory.clone();
+    let fits = job.recipe.inputs().iter()
+        .all(|(item, n)| refunded.insert(ItemStack::new(*item, *n)) == 0);
+    if fits {
+        queue.jobs.pop_back();
+        *inventory = refunded;
+    }
+}
+
+fn toggle_crafting(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut panel: ResMut<CraftingPanel>,
+    mut cursor_users: ResMut<CursorUsers>,
+) {
+    if !input_map.just_pressed(Action::Crafting, &keys) {
+        return;
+    }
+
+    panel.open = !panel.open;
+    cursor_users.set(CursorUser::Crafting, panel.open);
+}
+
+fn crafting_input(
+    keys: Res<Input<KeyCode>>,
+    panel: Res<CraftingPanel>,
+    research: Res<Research>,
+    craft_query: Query<(&CraftButton, &Interaction), Changed<Interaction>>,
+    cancel_query: Query<&Interaction, (With<CancelButton>, Changed<Interaction>)>,
+    mut player_query: Query<(&mut Inventory, &mut CraftQueue), With<Player>>,
+    mut sound_events: EventWriter<PlayUiSound>,
+) {
+    if !panel.open {
+        return;
+    }
+
+    let (mut inventory, mut queue) = player_query.get_single_mut().expect("not one player");
+
+    for (button, interaction) in &craft_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let queued = research.recipe_unlocked(button.recipe)
+            && queue_crafts(button.recipe, button.amount.count(), &mut inventory, &mut queue) > 0;
+        if !queued {
+            sound_events.send(PlayUiSound(UiSound::Error));
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) || cancel_query.iter().any(|i| *i == Interaction::Pressed) {
+        cancel_last(&mut inventory, &mut queue);
+    }
+}
+
+fn run_craft_queue(
+    time: Res<Time>,
+    mut player_query: Query<(&mut Inventory, &mut CraftQueue), With<Player>>,
+    mut produced_events: EventWriter<ItemProduced>,
+    mut consumed_events: EventWriter<ItemConsumed>,
+) {
+    let (mut inventory, mut queue) = player_query.get_single_mut().expect("not one player");
+
+    let Some(job) = queue.jobs.front_mut() else {
+        return;
+    };
+
+    let recipe = job.recipe;
+    let duration = recipe.time() * HAND_TIME_FACTOR;
+    job.progress = (job.progress + time.delta_seconds()).min(duration);
+
+    // wait with a finished job until there's room for it
+    if job.progress < duration || !inventory.can_insert(recipe.output()) {
+        return;
+    }
+
+    // hand made implements come out better or worse each time
+    inventory.insert(with_rolled_rarity(recipe.output()));
+    queue.jobs.pop_front();
+
+    for (item, n) in recipe.inputs() {
+        consumed_events.send(ItemConsumed { item: *item, count: *n });
+    }
+    produced_events.send(ItemProduced { item: recipe.output().item, count: recipe.output().count });
+}
+
+fn setup_crafting_ui(mut commands: Commands) {
+    let text = |value: &str, size: f32| TextBundle::from_section(value, TextStyle {
+        font_size: size,
+        color: Color::WHITE,
+        ..default()
+    });
+    let row = || NodeBundle {
+        style: Style {
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(6.0),
+            ..default()
+        },
+        ..default()
+    };
+    let button = || ButtonBundle {
+        style: Style {
+            min_width: Val::Px(28.0),
+            padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        background_color: BUTTON_COLOR.into(),
+        ..default()
+    };
+
+    commands.spawn((
+        CraftingUi,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(20.0),
+                top: Val::Px(20.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((Localized("Crafting"), text("Crafting", 20.0)));
+
+        for category in Category::ALL {
+            let recipes: Vec<Recipe> = Recipe::ALL.into_iter()
+                .filter(|r| r.handcraftable() && r.category() == category)
+                .collect();
+            if recipes.is_empty() {
+                continue;
+            }
+
+            parent.spawn((
+                CategoryHeader(category),
+                Localized(category.name()),
+                text(category.name(), 16.0).with_style(Style {
+                    margin: UiRect::top(Val::Px(6.0)),
+                    ..default()
+                }),
+            ));
+            for recipe in recipes {
+                parent.spawn((RecipeRow(recipe), ItemTooltip(Some(recipe.output())), row())).with_children(|row| {
+                    row.spawn((RecipeName(recipe), text("", 16.0).with_style(Style {
+                        width: Val::Px(140.0),
+                        ..default()
+                    })));
+                    row.spawn((Ingredients(recipe), text("", 14.0).with_style(Style {
+                        width: Val::Px(240.0),
+                        ..default()
+                    })));
+                    for amount in Amount::ALL {
+                        row.spawn((CraftButton { recipe, amount }, button())).with_children(|b| {
+                            b.spawn((Localized(amount.name()), text(amount.name(), 14.0)));
+                        });
+                    }
+                });
+            }
+        }
+
+        parent.spawn((Localized("Queue"), text("Queue", 16.0).with_style(Style {
+            margin: UiRect::top(Val::Px(6.0)),
+            ..default()
+        })));
+        parent.spawn((QueueText, text("", 14.0)));
+        parent.spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(6.0),
+                ..default()
+            },
+            background_color: Color::DARK_GRAY.into(),
+            ..default()
+        }).with_children(|track| {
+            track.spawn((QueueFill, NodeBundle {
+                style: Style {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                background_color: Color::WHITE.into(),
+                ..default()
+            }));
+        });
+        parent.spawn((CancelButton, button())).with_children(|b| {
+            b.spawn((Localized("Cancel last"), text("Cancel last", 14.0)));
+        });
+    });
+}
+
+fn update_crafting_ui(
+    panel: Res<CraftingPanel>,
+    research: Res<Research>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    player_query: Query<(&Inventory, &CraftQueue), With<Player>>,
+    mut ui_query: Query<&mut Visibility, With<CraftingUi>>,
+    mut row_query: Query<(&RecipeRow, &mut Style), Without<CategoryHeader>>,
+    mut header_query: Query<(&CategoryHeader, &mut Style), Without<RecipeRow>>,
+    mut button_query: Query<(&CraftButton, &mut BackgroundColor)>,
+    mut name_query: Query<(&RecipeName, &mut Text), (Without<Ingredients>, Without<QueueText>)>,
+    mut ingredient_query: Query<(&Ingredients, &mut Text), Without<QueueText>>,
+    mut queue_query: Query<&mut Text, With<QueueText>>,
+    mut fill_query: Query<&mut Style, (With<QueueFill>, Without<RecipeRow>, Without<CategoryHeader>)>,
+) {
+    let mut visibility = ui_query.get_single_mut().expect("not one crafting ui");
+    if !panel.open {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Visible;
+
+    let (inventory, queue) = player_query.get_single().expect("not one player");
+    let unlocked = hand_recipes(&research);
+    let queue_full = queue.jobs.len() >= QUEUE_LIMIT;
+
+    for (RecipeRow(recipe), mut style) in &mut row_query {
+        style.display = if unlocked.contains(recipe) { Display::Flex } else { Display::None };
+    }
+    for (CategoryHeader(category), mut style) in &mut header_query {
+        let any = unlocked.iter().any(|r| r.category() == *category);
+        style.display = if any { Display::Flex } else { Display::None };
+    }
+
+    for (button, mut background) in &mut button_query {
+        let craftable = !queue_full && affordable(button.recipe, inventory) > 0;
+        *background = if craftable { BUTTON_COLOR } else { DISABLED_COLOR }.into();
+    }
+    for (RecipeName(recipe), mut text) in &mut name_query {
+        let section = &mut text.sections[0];
+        section.value = locale.get(recipe.name()).to_string();
+        section.style.color = if affordable(*recipe, inventory) > 0 { Color::WHITE } else { Color::GRAY };
+    }
+
+    // each ingredient in its own section, so it can be colored by whether there's enough
+    for (Ingredients(recipe), mut text) in &mut ingredient_query {
+        text.sections = recipe.inputs().iter().enumerate()
+            .map(|(i, (item, n))| {
+                let have = inventory.count(*item);
+                let separator = if i == 0 { "" } else { ", " };
+                TextSection::new(
+                    format!("{}{} {}/{}", separator, locale.get(item.name()), have, n),
+                    TextStyle {
+                        font_size: 14.0,
+                        color: if have >= *n { settings.palette().good } else { settings.palette().bad },
+                        ..default()
+                    },
+                )
+            })
+            .collect();
+    }
+
+    let mut content = String::new();
+    for (i, job) in queue.jobs.iter().enumerate() {
+        let marker = if i == 0 { '>' } else { ' ' };
+        let _ = writeln!(content, "{} {}", marker, locale.get(job.recipe.name()));
+    }
+    content.pop();
+    queue_query.get_single_mut().expect("not one queue text").sections[0].value = content;
+
+    let fraction = queue.jobs.front()
+        .map_or(0.0, |job| job.progress / (job.recipe.time() * HAND_TIME_FACTOR));
+    fill_query.get_single_mut().expect("not one queue bar").width = Val::Percent(fraction * 100.0);
+}
@@ -0,0 +1,196 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    camera::Camera,
+    enemy::Hostile,
+    health::{DamageDealt, Health},
+    hitbox::{resolve_hit, Hurtbox},
+    interaction::Focus,
+    magic::Caster,
+    settings::Settings,
+    state::GameplaySet,
+    Player,
+};
+
+const GAP: f32 = 4.0;
+const TICK_LENGTH: f32 = 8.0;
+const TICK_WIDTH: f32 = 2.0;
+// pixels the ticks move apart per unit of speed, and while casting
+const MOVING_SPREAD: f32 = 2.0;
+const CASTING_SPREAD: f32 = 6.0;
+const MAX_SPREAD: f32 = 16.0;
+// how quickly the ticks settle towards their spread, per second
+const SPREAD_RATE: f32 = 10.0;
+// how far a spell target can be to light it up
+const TARGET_RANGE: f32 = 30.0;
+// seconds the hit marker stays up
+const HIT_TIME: f32 = 0.2;
+const HIT_SIZE: f32 = 4.0;
+
+pub struct CrosshairPlugin;
+
+impl Plugin for CrosshairPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_crosshair)
+            .add_systems(Update, (update_crosshair, confirm_hits).in_set(GameplaySet));
+    }
+}
+
+#[derive(Component)]
+struct CrosshairRoot;
+
+/// One of the four lines, pointing out along `0`.
+#[derive(Component)]
+struct Tick(Vec2);
+
+#[derive(Component)]
+struct Center;
+
+/// One of the corners that flash when damage lands.
+#[derive(Component)]
+struct HitTick(Vec2);
+
+fn setup_crosshair(mut commands: Commands) {
+    let square = |size: Vec2, color: Color| NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Px(size.x),
+            height: Val::Px(size.y),
+            ..default()
+        },
+        background_color: color.into(),
+        ..default()
+    };
+
+    commands.spawn((
+        CrosshairRoot,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(50.0),
+                top: Val::Percent(50.0),
+                ..default()
+            },
+            ..default()
+        },
+    )).with_children(|parent| {
+        for direction in [Vec2::Y, Vec2::NEG_Y, Vec2::X, Vec2::NEG_X] {
+            let size = if direction.x == 0.0 {
+                Vec2::new(TICK_WIDTH, TICK_LENGTH)
+            } else {
+                Vec2::new(TICK_LENGTH, TICK_WIDTH)
+            };
+            parent.spawn((Tick(direction), square(size, Color::WHITE)));
+        }
+        for direction in [Vec2::ONE, Vec2::new(1.0, -1.0), Vec2::new(-1.0, 1.0), Vec2::NEG_ONE] {
+            let mut hit = square(Vec2::splat(HIT_SIZE), Color::WHITE);
+            hit.visibility = Visibility::Hidden;
+            parent.spawn((HitTick(direction.normalize()), hit));
+        }
+        parent.spawn((Center, square(Vec2::splat(TICK_WIDTH), Color::WHITE)));
+    });
+}
+
+fn update_crosshair(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    focus: Res<Focus>,
+    spatial_query: SpatialQuery,
+    player_query: Query<(Entity, &LinearVelocity, &Caster), With<Player>>,
+    camera_query: Query<&Transform, (With<Camera>, Without<Player>)>,
+    hostile_query: Query<(), (With<Hostile>, With<Health>)>,
+    hurtbox_query: Query<&Hurtbox>,
+    mut root_query: Query<&mut Visibility, With<CrosshairRoot>>,
+    mut tick_query: Query<(&Tick, &mut Style, &mut BackgroundColor), Without<Center>>,
+    mut center_query: Query<(&mut Style, &mut BackgroundColor, &mut BorderColor), With<Center>>,
+    mut spread: Local<f32>,
+) {
+    let mut root = root_query.get_single_mut().expect("not one crosshair");
+    *root = if settings.crosshair { Visibility::Inherited } else { Visibility::Hidden };
+    if !settings.crosshair {
+        return;
+    }
+
+    let (player, velocity, caster) = player_query.get_single().expect("not one player");
+    let camera = camera_query.get_single().expect("not one camera");
+
+    // walking around and casting both throw the aim off
+    let speed = Vec2::new(velocity.x, velocity.z).length();
+    let busy = caster.casting.is_some() || caster.channeling.is_some();
+    let target_spread = if settings.dynamic_crosshair {
+        (speed * MOVING_SPREAD + if busy { CASTING_SPREAD } else { 0.0 }).min(MAX_SPREAD)
+    } else {
+        0.0
+    };
+    *spread += (target_spread - *spread) * (SPREAD_RATE * time.delta_seconds()).min(1.0);
+
+    let hit = spatial_query.cast_ray(
+        camera.translation,
+        camera.forward(),
+        TARGET_RANGE,
+        true,
+        SpatialQueryFilter::new().without_entities([player]),
+    );
+    let target = hit.is_some_and(|h| hostile_query.contains(resolve_hit(&hurtbox_query, h.entity).0));
+    let interactable = focus.0.is_some();
+
+    let color = if target { Color::RED } else { Color::WHITE };
+    for (Tick(direction), mut style, mut background) in &mut tick_query {
+        let size = if direction.x == 0.0 {
+            Vec2::new(TICK_WIDTH, TICK_LENGTH)
+        } else {
+            Vec2::new(TICK_LENGTH, TICK_WIDTH)
+        };
+        // screen y points down
+        let center = Vec2::new(direction.x, -direction.y) * (GAP + *spread + TICK_LENGTH / 2.0);
+        style.left = Val::Px(center.x - size.x / 2.0);
+        style.top = Val::Px(center.y - size.y / 2.0);
+        *background = color.into();
+    }
+
+    // a box around the middle for things that can be used, a dot otherwise
+    let (mut style, mut background, mut border) =
+        center_query.get_single_mut().expect("not one crosshair center");
+    let size = if interactable { TICK_LENGTH } else { TICK_WIDTH };
+    style.width = Val::Px(size);
+    style.height = Val::Px(size);
+    style.left = Val::Px(-size / 2.0);
+    style.top = Val::Px(-size / 2.0);
+    style.border = UiRect::all(Val::Px(if interactable { TICK_WIDTH / 2.0 } else { 0.0 }));
+    *background = if interactable { Color::NONE } else { color }.into();
+    *border = Color::CYAN.into();
+}
+
+fn confirm_hits(
+    time: Res<Time>,
+    player_query: Query<Entity, With<Player>>,
+    mut hit_query: Query<(&HitTick, &mut Style, &mut Visibility, &mut BackgroundColor)>,
+    mut dealt_events: EventReader<DamageDealt>,
+    // seconds left showing, and whether it was a critical
+    mut shown: Local<(f32, bool)>,
+) {
+    let player = player_query.get_single().expect("not one player");
+
+    for event in dealt_events.read() {
+        if event.source == Some(player) && event.target != player && event.amount > 0.0 {
+            *shown = (HIT_TIME, (shown.1 && shown.0 > 0.0) || event.critical);
+        }
+    }
+    shown.0 -= time.delta_seconds();
+
+    let (remaining, critical) = *shown;
+    for (HitTick(direction), mut style, mut visibility, mut background) in &mut hit_query {
+        if remaining <= 0.0 {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        *visibility = Visibility::Inherited;
+        let center = Vec2::new(direction.x, -direction.y) * (GAP + TICK_LENGTH);
+        style.left = Val::Px(center.x - HIT_SIZE / 2.0);
+        style.top = Val::Px(center.y - HIT_SIZE / 2.0);
+        *background = if critical { Color::YELLOW } else { Color::WHITE }.into();
+    }
+}
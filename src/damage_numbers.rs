@@ -0,0 +1,158 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::{
+    health::{DamageDealt, DamageType},
+    state::GameplaySet,
+};
+
+// numbers on screen at once, the oldest gets reused when they run out
+const POOL_SIZE: usize = 32;
+// seconds a number stays up
+const LIFETIME: f32 = 0.9;
+// how fast it floats up, in world units per second
+const RISE_SPEED: f32 = 1.2;
+// hits on the same thing this soon after add up instead, so beams and
+// burns don't spray a number every frame
+const MERGE_TIME: f32 = 0.25;
+// how far above the last one a new number on the same target starts
+const STACK_OFFSET: f32 = 0.35;
+const HEIGHT: f32 = 1.0;
+const FONT_SIZE: f32 = 16.0;
+const CRITICAL_SCALE: f32 = 1.5;
+
+pub struct DamageNumberPlugin;
+
+impl Plugin for DamageNumberPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_damage_numbers)
+            .add_systems(Update, (show_damage_numbers, float_damage_numbers).chain().in_set(GameplaySet));
+    }
+}
+
+/// One of the pooled texts, free while it has no target.
+#[derive(Component)]
+struct DamageNumber {
+    target: Option<Entity>,
+    kind: DamageType,
+    amount: f32,
+    critical: bool,
+    // where in the world it hangs
+    anchor: Vec3,
+    age: f32,
+}
+
+fn setup_damage_numbers(mut commands: Commands) {
+    for _ in 0..POOL_SIZE {
+        commands.spawn((
+            DamageNumber {
+                target: None,
+                kind: DamageType::Physical,
+                amount: 0.0,
+                critical: false,
+                anchor: Vec3::ZERO,
+                age: 0.0,
+            },
+            TextBundle {
+                text: Text::from_section("", TextStyle {
+                    font_size: FONT_SIZE,
+                    color: Color::WHITE,
+                    ..default()
+                }),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+    }
+}
+
+fn show_damage_numbers(
+    target_query: Query<&Transform>,
+    mut number_query: Query<(Entity, &mut DamageNumber, &mut Text)>,
+    mut dealt_events: EventReader<DamageDealt>,
+) {
+    for event in dealt_events.read() {
+        let Ok(target) = target_query.get(event.target) else {
+            continue;
+        };
+        if event.amount <= 0.0 {
+            continue;
+        }
+
+        let merge = number_query.iter()
+            .find(|(_, n, _)| n.target == Some(event.target) && n.kind == event.kind && n.age < MERGE_TIME)
+            .map(|(e, ..)| e);
+        let (entity, amount, critical, anchor) = match merge {
+            Some(entity) => {
+                let (_, number, _) = number_query.get(entity).expect("merging into a missing number");
+                (entity, number.amount + event.amount, number.critical || event.critical, number.anchor)
+            }
+            None => {
+                let stacked = number_query.iter()
+                    .filter(|(_, n, _)| n.target == Some(event.target))
+                    .count();
+                // a free one if there is any, otherwise whichever is about to fade
+                let entity = number_query.iter()
+                    .max_by(|a, b| {
+                        a.1.target.is_none().cmp(&b.1.target.is_none()).then(a.1.age.total_cmp(&b.1.age))
+                    })
+                    .map(|(e, ..)| e)
+                    .expect("empty damage number pool");
+                let anchor = target.translation + Vec3::Y * (HEIGHT + stacked as f32 * STACK_OFFSET);
+                (entity, event.amount, event.critical, anchor)
+            }
+        };
+
+        let (_, mut number, mut text) = number_query.get_mut(entity).expect("lost a damage number");
+        *number = DamageNumber { target: Some(event.target), kind: event.kind, amount, critical, anchor, age: 0.0 };
+
+        // the text only changes here, floating it around doesn't touch it
+        let section = &mut text.sections[0];
+        section.value.clear();
+        let _ = write!(section.value, "{:.0}", amount);
+        if critical {
+            section.value.push('!');
+        }
+        section.style.font_size = if critical { FONT_SIZE * CRITICAL_SCALE } else { FONT_SIZE };
+        section.style.color = event.kind.color();
+    }
+}
+
+fn float_damage_numbers(
+    time: Res<Time>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut number_query: Query<(&mut DamageNumber, &mut Text, &mut Style, &mut Visibility)>,
+) {
+    let (camera, camera_transform) = camera_query.get_single().expect("not one camera");
+    let dt = time.delta_seconds();
+
+    for (mut number, mut text, mut style, mut visibility) in &mut number_query {
+        if number.target.is_none() {
+            continue;
+        }
+
+        number.age += dt;
+        number.anchor.y += RISE_SPEED * dt;
+        if number.age >= LIFETIME {
+            number.target = None;
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+
+        // behind the camera
+        let Some(screen) = camera.world_to_viewport(camera_transform, number.anchor) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Visible;
+        style.left = Val::Px(screen.x);
+        style.top = Val::Px(screen.y);
+        text.sections[0].style.color.set_a(1.0 - number.age / LIFETIME);
+    }
+}
@@ -0,0 +1,75 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::state::GameplaySet;
+
+// real seconds for one in-game day
+pub const DAY_LENGTH: f32 = 600.0;
+
+pub struct DayNightPlugin;
+
+impl Plugin for DayNightPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(TimeOfDay { hours: 8.0 })
+            .add_systems(Startup, setup_sun)
+            .add_systems(Update, (advance_time_of_day, update_sun).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Resource)]
+pub struct TimeOfDay {
+    // 0.0 to 24.0
+    pub hours: f32,
+}
+
+impl TimeOfDay {
+    /// How strong the sun is, 0.0 at night and 1.0 at noon.
+    pub fn sunlight(&self) -> f32 {
+        f32::max(((self.hours - 6.0) / 12.0 * PI).sin(), 0.0)
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.sunlight() <= 0.0
+    }
+}
+
+#[derive(Component)]
+struct Sun;
+
+fn setup_sun(mut commands: Commands) {
+    commands.spawn((
+        Sun,
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                shadows_enabled: true,
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+fn advance_time_of_day(
+    time: Res<Time>,
+    mut time_of_day: ResMut<TimeOfDay>,
+) {
+    time_of_day.hours = (time_of_day.hours + time.delta_seconds() / DAY_LENGTH * 24.0) % 24.0;
+}
+
+fn update_sun(
+    time_of_day: Res<TimeOfDay>,
+    mut ambient: ResMut<AmbientLight>,
+    mut sun_query: Query<(&mut Transform, &mut DirectionalLight), With<Sun>>,
+) {
+    let (mut transform, mut light) = sun_query.get_single_mut().expect("not one sun");
+
+    // the sun rises in the east at 6:00 and sets in the west at 18:00
+    let angle = (time_of_day.hours - 6.0) / 24.0 * 2.0 * PI;
+    *transform = Transform::from_rotation(Quat::from_rotation_z(angle) * Quat::from_rotation_y(PI / 2.0));
+
+    let sunlight = time_of_day.sunlight();
+    light.illuminance = 10_000.0 * sunlight;
+    ambient.brightness = 0.02 + 0.08 * sunlight;
+}
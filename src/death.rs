@@ -0,0 +1,190 @@
+use bevy::prelude::*;
+
+use crate::{
+    cooldown::Cooldowns,
+    health::{apply_damage, despawn_dead, Died},
+    perception::Awareness,
+    state::GameplaySet,
+    threat::ThreatTable,
+    Player,
+};
+
+// seconds a corpse spends sinking into the ground before it's gone
+const SINK_TIME: f32 = 1.0;
+const SINK_DEPTH: f32 = 0.5;
+const EXPLOSION_RADIUS: f32 = 1.5;
+
+pub struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, (animate_death_effects, rot_corpses).in_set(GameplaySet))
+            // the dead still have their looks until they're despawned
+            .add_systems(PostUpdate, (play_death_effects, release_references)
+                .after(apply_damage)
+                .before(despawn_dead));
+    }
+}
+
+/// How something goes when it dies.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeathEffect {
+    // shrinks and fades into the ground
+    Dissolve,
+    // bursts apart in a flash
+    Explode,
+}
+
+impl DeathEffect {
+    // seconds
+    pub fn duration(&self) -> f32 {
+        match self {
+            DeathEffect::Dissolve => 1.2,
+            DeathEffect::Explode => 0.4,
+        }
+    }
+}
+
+/// Leaves a body behind for this many seconds.
+#[derive(Component)]
+pub struct LeavesCorpse(pub f32);
+
+#[derive(Component)]
+struct Dying {
+    effect: DeathEffect,
+    age: f32,
+    // the size it died at
+    scale: Vec3,
+}
+
+#[derive(Component)]
+struct Corpse {
+    remaining: f32,
+    // where it lay down, before sinking
+    rest: Vec3,
+}
+
+fn play_death_effects(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    dead_query: Query<(
+        &Transform,
+        Option<&Handle<Mesh>>,
+        Option<&Handle<StandardMaterial>>,
+        Option<&DeathEffect>,
+        Option<&LeavesCorpse>,
+    )>,
+    mut died_events: EventReader<Died>,
+) {
+    for event in died_events.read() {
+        let Ok((transform, mesh, material, effect, corpse)) = dead_query.get(event.entity) else {
+            continue;
+        };
+        let (Some(mesh), Some(material)) = (mesh, material.and_then(|m| materials.get(m)).cloned()) else {
+            continue;
+        };
+
+        if let Some(effect) = effect {
+            // a copy of the body to fade out, the real one is despawned right after
+            let fading = materials.add(StandardMaterial { alpha_mode: AlphaMode::Blend, ..material.clone() });
+            commands.spawn((
+                Dying { effect: *effect, age: 0.0, scale: transform.scale },
+                PbrBundle {
+                    mesh: mesh.clone(),
+                    material: fading,
+                    transform: *transform,
+                    ..default()
+                },
+            ));
+        }
+
+        if let Some(LeavesCorpse(seconds)) = corpse {
+            let darker = material.base_color * 0.4;
+            let rest = transform.translation;
+            commands.spawn((
+                Corpse { remaining: *seconds, rest },
+                PbrBundle {
+                    mesh: mesh.clone(),
+                    material: materials.add(StandardMaterial { base_color: darker.with_a(1.0), ..material }),
+                    // lying on its side
+                    transform: transform.with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2)),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+// nothing should keep chasing, hating or waiting on something that's gone
+fn release_references(
+    mut cooldowns: ResMut<Cooldowns>,
+    mut threat_query: Query<&mut ThreatTable>,
+    mut awareness_query: Query<&mut Awareness>,
+    player_query: Query<(), With<Player>>,
+    mut died_events: EventReader<Died>,
+) {
+    for event in died_events.read() {
+        // the player keeps their cooldowns through a respawn
+        if !player_query.contains(event.entity) {
+            cooldowns.forget(event.entity);
+        }
+        for mut table in &mut threat_query {
+            table.forget(event.entity);
+        }
+        for mut awareness in &mut awareness_query {
+            if awareness.target == Some(event.entity) {
+                awareness.target = None;
+            }
+        }
+    }
+}
+
+fn animate_death_effects(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut dying_query: Query<(Entity, &mut Dying, &mut Transform, &Handle<StandardMaterial>)>,
+    mut gizmos: Gizmos,
+) {
+    for (entity, mut dying, mut transform, material) in &mut dying_query {
+        dying.age += time.delta_seconds();
+        let progress = (dying.age / dying.effect.duration()).min(1.0);
+        if progress >= 1.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        match dying.effect {
+            DeathEffect::Dissolve => {
+                transform.scale = dying.scale * (1.0 - progress);
+                transform.translation.y -= time.delta_seconds() * 0.3;
+            }
+            DeathEffect::Explode => {
+                transform.scale = dying.scale * (1.0 + progress);
+                gizmos.circle(transform.translation, Vec3::Y, EXPLOSION_RADIUS * progress, Color::ORANGE);
+            }
+        }
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color.set_a(1.0 - progress);
+        }
+    }
+}
+
+fn rot_corpses(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut corpse_query: Query<(Entity, &mut Corpse, &mut Transform)>,
+) {
+    for (entity, mut corpse, mut transform) in &mut corpse_query {
+        corpse.remaining -= time.delta_seconds();
+        if corpse.remaining <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // sinks out of sight over its last second
+        let sunk = (1.0 - corpse.remaining / SINK_TIME).max(0.0);
+        transform.translation = corpse.rest - Vec3::Y * SINK_DEPTH * sunk;
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::entity::Entities,
+    prelude::*,
+};
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    controls::Grounded,
+    input::{Action, InputMap},
+    state::GameplaySet,
+    streaming::FactoryChunks,
+    Player,
+};
+
+// how many frames the graph goes back
+const SAMPLES: usize = 120;
+const BAR_WIDTH: f32 = 2.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+// milliseconds at the top of the graph
+const GRAPH_MAX: f32 = 50.0;
+// 60 and 30 fps
+const SMOOTH_FRAME: f32 = 1000.0 / 60.0;
+const SLOW_FRAME: f32 = 1000.0 / 30.0;
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins(FrameTimeDiagnosticsPlugin)
+            .init_resource::<DebugOverlay>()
+            .add_systems(Startup, setup_overlay)
+            .add_systems(Update, (toggle_overlay, update_overlay).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub shown: bool,
+    // frame times in milliseconds, newest last
+    frames: VecDeque<f32>,
+}
+
+#[derive(Component)]
+struct OverlayRoot;
+
+#[derive(Component)]
+struct OverlayText;
+
+/// One column of the frame time graph, `0` frames back from the newest.
+#[derive(Component)]
+struct FrameBar(usize);
+
+fn setup_overlay(mut commands: Commands) {
+    commands.spawn((
+        OverlayRoot,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(20.0),
+                top: Val::Px(20.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(9),
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            OverlayText,
+            TextBundle::from_section("", TextStyle {
+                font_size: 14.0,
+                color: Color::WHITE,
+                ..default()
+            }),
+        ));
+        parent.spawn(NodeBundle {
+            style: Style {
+                height: Val::Px(GRAPH_HEIGHT),
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+            background_color: Color::rgba(1.0, 1.0, 1.0, 0.05).into(),
+            ..default()
+        }).with_children(|graph| {
+            for i in (0..SAMPLES).rev() {
+                graph.spawn((
+                    FrameBar(i),
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(BAR_WIDTH),
+                            height: Val::Px(0.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ));
+            }
+        });
+    });
+}
+
+fn toggle_overlay(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut overlay: ResMut<DebugOverlay>,
+) {
+    if input_map.just_pressed(Action::DebugOverlay, &keys) {
+        overlay.shown = !overlay.shown;
+    }
+}
+
+fn update_overlay(
+    time: Res<Time<Real>>,
+    diagnostics: Res<DiagnosticsStore>,
+    entities: &Entities,
+    collisions: Res<Collisions>,
+    chunks: Res<FactoryChunks>,
+    mut overlay: ResMut<DebugOverlay>,
+    body_query: Query<&RigidBody>,
+    player_query: Query<(&Transform, &LinearVelocity, Has<Grounded>), With<Player>>,
+    mut root_query: Query<&mut Visibility, With<OverlayRoot>>,
+    mut text_query: Query<&mut Text, With<OverlayText>>,
+    mut bar_query: Query<(&FrameBar, &mut Style, &mut BackgroundColor)>,
+) {
+    // keep sampling while hidden so the graph is full when it's opened
+    overlay.frames.push_back(time.delta_seconds() * 1000.0);
+    if overlay.frames.len() > SAMPLES {
+        overlay.frames.pop_front();
+    }
+
+    let mut root = root_query.get_single_mut().expect("not one debug overlay");
+    *root = if overlay.shown { Visibility::Visible } else { Visibility::Hidden };
+    if !overlay.shown {
+        return;
+    }
+
+    let fps = diagnostics.get(FrameTimeDiagnosticsPlugin::FPS).and_then(|d| d.smoothed()).unwrap_or(0.0);
+    let frame_time = diagnostics.get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|d| d.smoothed())
+        .unwrap_or(0.0);
+
+    let count = |kind: RigidBody| body_query.iter().filter(|b| **b == kind).count();
+    let dormant = chunks.chunk_count(true);
+    let (transform, velocity, grounded) = player_query.get_single().expect("not one player");
+    let pos = transform.translation;
+
+    let mut text = text_query.get_single_mut().expect("not one debug text");
+    text.sections[0].value = format!(
+        "{fps:.0} fps ({frame_time:.1} ms)\n\
+        entities {}\n\
+        bodies {} dynamic, {} kinematic, {} static\n\
+        contacts {}\n\
+        chunks {} active, {dormant} dormant\n\
+        position {:.1} {:.1} {:.1}\n\
+        velocity {:.1} ({:.1} {:.1} {:.1})\n\
+        {}",
+        entities.len(),
+        count(RigidBody::Dynamic),
+        count(RigidBody::Kinematic),
+        count(RigidBody::Static),
+        collisions.iter().count(),
+        chunks.chunk_count(false),
+        pos.x, pos.y, pos.z,
+        velocity.length(), velocity.x, velocity.y, velocity.z,
+        if grounded { "grounded" } else { "airborne" },
+    );
+
+    for (FrameBar(age), mut style, mut background) in &mut bar_query {
+        let ms = overlay.frames.iter().rev().nth(*age).copied().unwrap_or(0.0);
+        style.height = Val::Px((ms / GRAPH_MAX).min(1.0) * GRAPH_HEIGHT);
+        *background = if ms > SLOW_FRAME {
+            Color::RED
+        } else if ms > SMOOTH_FRAME {
+            Color::YELLOW
+        } else {
+            Color::GREEN
+        }.into();
+    }
+}
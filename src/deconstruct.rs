@@ -0,0 +1,309 @@
+use bevy::prelude::*;
+
+use crate::{
+    belt::Belt,
+    blueprint::{BlueprintTool, Ghost, ToolState},
+    building::{AimPoint, Building, BuildingKind, BuildMode, GridPos, RemoveBuilding},
+    drone::{Drone, DroneTask},
+    ground::DropItem,
+    inventory::Inventory,
+    item::ItemStack,
+    machine::Machine,
+    module::ModuleSlots,
+    state::GameplaySet,
+    Player,
+};
+
+// the player only takes apart what's within reach, drones handle the rest
+const PLAYER_REACH: f32 = 10.0;
+// seconds per building taken apart by the player
+const DECONSTRUCT_TIME: f32 = 0.25;
+
+pub struct DeconstructPlugin;
+
+impl Plugin for DeconstructPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<DeconstructTool>()
+            .add_systems(Startup, setup_deconstruct_ui)
+            .add_systems(Update, (
+                deconstruct_input,
+                deconstruct_marked,
+                draw_deconstruction,
+                update_deconstruct_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Resource)]
+pub struct DeconstructTool {
+    pub active: bool,
+    pub start: Option<GridPos>,
+    // only buildings of this kind get marked, everything if unset
+    pub filter: Option<BuildingKind>,
+    cooldown: Timer,
+}
+
+impl Default for DeconstructTool {
+    fn default() -> Self {
+        DeconstructTool {
+            active: false,
+            start: None,
+            filter: None,
+            cooldown: Timer::from_seconds(DECONSTRUCT_TIME, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct MarkedForDeconstruction;
+
+#[derive(Component)]
+struct DeconstructText;
+
+fn deconstruct_input(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mouse: Res<Input<MouseButton>>,
+    aim: Res<AimPoint>,
+    mut tool: ResMut<DeconstructTool>,
+    mut build_mode: ResMut<BuildMode>,
+    mut blueprint_tool: ResMut<BlueprintTool>,
+    building_query: Query<(Entity, &Building, &GridPos, Has<MarkedForDeconstruction>)>,
+    ghost_query: Query<(Entity, &Ghost, &GridPos)>,
+) {
+    if keys.just_pressed(KeyCode::Z) {
+        tool.active = !tool.active;
+        tool.start = None;
+
+        if tool.active {
+            build_mode.active = false;
+            blueprint_tool.state = ToolState::Off;
+        }
+    }
+
+    if !tool.active {
+        return;
+    }
+
+    // the tools share the mouse, so opening another one closes this one
+    if build_mode.active || blueprint_tool.state != ToolState::Off {
+        tool.active = false;
+        return;
+    }
+
+    // cycle the filter through every kind and back to everything
+    let kinds = BuildingKind::ALL;
+    let index = tool.filter.and_then(|f| kinds.iter().position(|k| *k == f));
+    if keys.just_pressed(KeyCode::BracketRight) {
+        tool.filter = match index {
+            None => kinds.first().copied(),
+            Some(i) => kinds.get(i + 1).copied(),
+        };
+    }
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        tool.filter = match index {
+            None => kinds.last().copied(),
+            Some(0) => None,
+            Some(i) => kinds.get(i - 1).copied(),
+        };
+    }
+
+    let Some(aimed) = aim.0.map(GridPos::from_world) else {
+        return;
+    };
+
+    if mouse.just_pressed(MouseButton::Left) {
+        tool.start = Some(aimed);
+    }
+
+    let (true, Some(start)) = (mouse.just_released(MouseButton::Left), tool.start) else {
+        return;
+    };
+    tool.start = None;
+
+    let min = start.0.min(aimed.0);
+    let max = start.0.max(aimed.0);
+    let inside = |pos: &GridPos| pos.0.cmpge(min).all() && pos.0.cmple(max).all();
+    let wanted = |kind: BuildingKind| tool.filter.is_none_or(|f| f == kind);
+
+    // holding shift takes the marks off again
+    let unmark = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    for (entity, building, pos, marked) in &building_query {
+        if !inside(pos) || !wanted(building.kind) {
+            continue;
+        }
+
+        match (unmark, marked) {
+            (false, false) => {
+                commands.entity(entity).insert(MarkedForDeconstruction);
+            }
+            (true, true) => {
+                commands.entity(entity).remove::<MarkedForDeconstruction>();
+            }
+            _ => {}
+        }
+    }
+
+    // planned buildings are simply cancelled
+    if !unmark {
+        for (entity, ghost, pos) in &ghost_query {
+            if inside(pos) && wanted(ghost.kind) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Everything a building holds plus what it cost, handed back when it's taken apart.
+pub fn building_contents(
+    kind: BuildingKind,
+    inventory: Option<&Inventory>,
+    belt: Option<&Belt>,
+    machine: Option<&Machine>,
+    modules: Option<&ModuleSlots>,
+) -> Vec<ItemStack> {
+    let mut contents: Vec<ItemStack> = kind.cost().iter()
+        .map(|(item, n)| ItemStack::new(*item, *n))
+        .collect();
+
+    contents.extend(inventory.iter().flat_map(|i| i.slots()).filter_map(|s| s.stack));
+
+    contents.extend(belt.iter().flat_map(|b| b.items.iter()).map(|s| ItemStack::new(s.item, 1)));
+
+    if let Some((Some(recipe), output)) = machine.map(|m| (m.recipe, m.output)) {
+        if output > 0 {
+            contents.push(ItemStack::new(recipe.output().item, output));
+        }
+    }
+
+    contents.extend(modules.iter()
+        .flat_map(|m| m.slots.iter())
+        .flatten()
+        .map(|m| ItemStack::new(m.item(), 1)));
+
+    contents
+}
+
+fn deconstruct_marked(
+    time: Res<Time>,
+    mut tool: ResMut<DeconstructTool>,
+    mut player_query: Query<(&mut Inventory, &Transform), With<Player>>,
+    marked_query: Query<
+        (
+            Entity,
+            &Building,
+            &Transform,
+            Option<&Inventory>,
+            Option<&Belt>,
+            Option<&Machine>,
+            Option<&ModuleSlots>,
+        ),
+        (With<MarkedForDeconstruction>, Without<Player>),
+    >,
+    drone_query: Query<&Drone>,
+    mut remove_events: EventWriter<RemoveBuilding>,
+    mut drop_events: EventWriter<DropItem>,
+) {
+    if !tool.cooldown.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let (mut player_inventory, player_transform) = player_query.get_single_mut().expect("not one player");
+    let player = player_transform.translation;
+
+    // leave alone whatever a drone is already on its way to
+    let claimed: Vec<Entity> = drone_query.iter()
+        .filter_map(|d| match d.task {
+            DroneTask::Deconstruct { building } => Some(building),
+            _ => None,
+        })
+        .collect();
+
+    // take apart the closest marked building in reach
+    let closest = marked_query.iter()
+        .filter(|(e, _, t, ..)| !claimed.contains(e) && t.translation.distance(player) <= PLAYER_REACH)
+        .min_by(|a, b| a.2.translation.distance(player).total_cmp(&b.2.translation.distance(player)));
+
+    let Some((entity, building, transform, inventory, belt, machine, modules)) = closest else {
+        return;
+    };
+
+    for stack in building_contents(building.kind, inventory, belt, machine, modules) {
+        let leftover = player_inventory.insert(stack);
+        if leftover > 0 {
+            drop_events.send(DropItem {
+                stack: ItemStack::new(stack.item, leftover),
+                pos: transform.translation,
+                magnetic: false,
+            });
+        }
+    }
+
+    remove_events.send(RemoveBuilding(entity));
+}
+
+fn draw_deconstruction(
+    tool: Res<DeconstructTool>,
+    aim: Res<AimPoint>,
+    marked_query: Query<(&Building, &Transform), With<MarkedForDeconstruction>>,
+    mut gizmos: Gizmos,
+) {
+    for (building, transform) in &marked_query {
+        gizmos.cuboid(transform.with_scale(building.kind.size() * 1.05), Color::RED);
+    }
+
+    let (true, Some(start), Some(aimed)) = (tool.active, tool.start, aim.0.map(GridPos::from_world)) else {
+        return;
+    };
+
+    let min = start.0.min(aimed.0).as_vec2() - 0.5;
+    let max = start.0.max(aimed.0).as_vec2() + 0.5;
+    let center = (min + max) / 2.0;
+
+    gizmos.rect(
+        Vec3::new(center.x, 0.05, center.y),
+        Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
+        max - min,
+        Color::RED,
+    );
+}
+
+fn setup_deconstruct_ui(mut commands: Commands) {
+    commands.spawn((
+        DeconstructText,
+        TextBundle::from_section("", TextStyle {
+            font_size: 16.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(40.0),
+            top: Val::Px(20.0),
+            ..default()
+        }),
+    ));
+}
+
+fn update_deconstruct_ui(
+    tool: Res<DeconstructTool>,
+    marked_query: Query<(), With<MarkedForDeconstruction>>,
+    mut text_query: Query<&mut Text, With<DeconstructText>>,
+) {
+    let mut text = text_query.get_single_mut().expect("not one deconstruct text");
+
+    let marked = marked_query.iter().count();
+    if !tool.active && marked == 0 {
+        text.sections[0].value.clear();
+        return;
+    }
+
+    let filter = tool.filter.map_or("everything", |k| k.name());
+    text.sections[0].value = if tool.active {
+        format!("Deconstruct: {filter} ([ ]) | {marked} marked")
+    } else {
+        format!("{marked} marked for deconstruction")
+    };
+}
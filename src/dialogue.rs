@@ -0,0 +1,398 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera::{Camera, CursorUser, CursorUsers},
+    faction::{toggle_trader, Faction, Factions, OpenTrade, OpenTrader, Stance},
+    interaction::{interact, Interacted, INTERACT_RANGE},
+    locale::Locale,
+    quest::{Quest, QuestLog, QuestState},
+    state::GameplaySet,
+    toast::{Notify, Priority},
+    Player,
+};
+
+const MAX_CHOICES: usize = 4;
+const PANEL_WIDTH: f32 = 640.0;
+const CHOICE_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.0);
+const CHOICE_HOVER: Color = Color::rgba(1.0, 1.0, 1.0, 0.1);
+const SPEAKER_COLOR: Color = Color::GOLD;
+
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Dialogue>()
+            .add_systems(Startup, setup_dialogue_ui)
+            .add_systems(Update, (
+                start_dialogue.after(interact).before(toggle_trader),
+                dialogue_input,
+                update_dialogue_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+/// Everything someone can talk about, each a tree of lines starting at the first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Conversation {
+    Trader,
+}
+
+impl Conversation {
+    pub fn speaker(&self) -> &'static str {
+        match self {
+            Conversation::Trader => "Trader",
+        }
+    }
+
+    pub fn lines(&self) -> &'static [Line] {
+        match self {
+            Conversation::Trader => &TRADER,
+        }
+    }
+}
+
+/// What the speaker says, and what the player can answer.
+pub struct Line {
+    pub text: &'static str,
+    pub choices: &'static [Choice],
+}
+
+pub struct Choice {
+    pub text: &'static str,
+    // hidden unless this holds
+    pub condition: Condition,
+    pub consequence: Consequence,
+    // the line this leads to, or the end of the conversation
+    pub next: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Condition {
+    Always,
+    Quest(Quest, QuestState),
+    Attitude(Faction, Stance),
+    Not(&'static Condition),
+}
+
+impl Condition {
+    fn holds(&self, log: &QuestLog, factions: &Factions) -> bool {
+        match self {
+            Condition::Always => true,
+            Condition::Quest(quest, state) => log.state(*quest) == *state,
+            Condition::Attitude(faction, stance) => factions.attitude(*faction) == *stance,
+            Condition::Not(condition) => !condition.holds(log, factions),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Consequence {
+    Nothing,
+    StartQuest(Quest),
+    OpenTrade,
+}
+
+const TRADER: [Line; 5] = [
+    Line {
+        text: "Welcome, traveller. What brings you out this far?",
+        choices: &[
+            Choice {
+                text: "Show me your wares.",
+                condition: Condition::Not(&Condition::Attitude(Faction::Traders, Stance::Hostile)),
+                consequence: Consequence::OpenTrade,
+                next: None,
+            },
+            Choice {
+                text: "Why won't you trade with me?",
+                condition: Condition::Attitude(Faction::Traders, Stance::Hostile),
+                consequence: Consequence::Nothing,
+                next: Some(4),
+            },
+            Choice {
+                text: "Heard of any work?",
+                condition: Condition::Always,
+                consequence: Consequence::Nothing,
+                next: Some(1),
+            },
+            Choice {
+                text: "Goodbye.",
+                condition: Condition::Always,
+                consequence: Consequence::Nothing,
+                next: None,
+            },
+        ],
+    },
+    Line {
+        text: "Old caches lie scattered about, and the constructs keep coming for us. Take your pick.",
+        choices: &[
+            Choice {
+                text: "I'll find the caches.",
+                condition: Condition::Quest(Quest::TreasureHunt, QuestState::Locked),
+                consequence: Consequence::StartQuest(Quest::TreasureHunt),
+                next: Some(2),
+            },
+            Choice {
+                text: "I'll deal with the constructs.",
+                condition: Condition::Quest(Quest::Defender, QuestState::Locked),
+                consequence: Consequence::StartQuest(Quest::Defender),
+                next: Some(2),
+            },
+            Choice {
+                text: "Anything else?",
+                condition: Condition::Always,
+                consequence: Consequence::Nothing,
+                next: Some(3),
+            },
+            Choice {
+                text: "Back.",
+                condition: Condition::Always,
+                consequence: Consequence::Nothing,
+                next: Some(0),
+            },
+        ],
+    },
+    Line {
+        text: "Good. Come back once it's done.",
+        choices: &[
+            Choice {
+                text: "Goodbye.",
+                condition: Condition::Always,
+                consequence: Consequence::Nothing,
+                next: None,
+            },
+        ],
+    },
+    Line {
+        text: "The mage guild pays well for research. Build a lab and they'll take notice, for better or worse.",
+        choices: &[
+            Choice {
+                text: "Back.",
+                condition: Condition::Always,
+                consequence: Consequence::Nothing,
+                next: Some(0),
+            },
+        ],
+    },
+    Line {
+        text: "You've spilled enough of our blood. Come back when you've made it right.",
+        choices: &[
+            Choice {
+                text: "Goodbye.",
+                condition: Condition::Always,
+                consequence: Consequence::Nothing,
+                next: None,
+            },
+        ],
+    },
+];
+
+/// Makes this talk when interacted with.
+#[derive(Component)]
+pub struct Speaker(pub Conversation);
+
+/// The conversation the player is in, if any.
+#[derive(Resource, Default)]
+pub struct Dialogue {
+    speaker: Option<(Entity, Conversation)>,
+    line: usize,
+}
+
+impl Dialogue {
+    pub fn is_open(&self) -> bool {
+        self.speaker.is_some()
+    }
+}
+
+#[derive(Component)]
+struct DialogueUi;
+
+#[derive(Component)]
+struct SpeakerText;
+
+#[derive(Component)]
+struct LineText;
+
+// the nth choice whose condition holds
+#[derive(Component)]
+struct ChoiceButton(usize);
+
+fn visible_choices<'a>(line: &'a Line, log: &QuestLog, factions: &Factions) -> Vec<&'a Choice> {
+    line.choices.iter()
+        .filter(|c| c.condition.holds(log, factions))
+        .take(MAX_CHOICES)
+        .collect()
+}
+
+fn end_dialogue(dialogue: &mut Dialogue, camera: &mut Camera, cursor_users: &mut CursorUsers) {
+    dialogue.speaker = None;
+    camera.framing = None;
+    cursor_users.set(CursorUser::Dialogue, false);
+}
+
+fn setup_dialogue_ui(mut commands: Commands) {
+    let text = |size: f32, color: Color| TextBundle::from_section("", TextStyle {
+        font_size: size,
+        color,
+        ..default()
+    });
+
+    commands.spawn((
+        DialogueUi,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                bottom: Val::Px(40.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(PANEL_WIDTH),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(12.0)),
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+            ..default()
+        }).with_children(|panel| {
+            panel.spawn((SpeakerText, text(20.0, SPEAKER_COLOR)));
+            panel.spawn((LineText, text(18.0, Color::WHITE)));
+            for i in 0..MAX_CHOICES {
+                panel.spawn((
+                    ChoiceButton(i),
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                            ..default()
+                        },
+                        background_color: CHOICE_COLOR.into(),
+                        ..default()
+                    },
+                )).with_children(|button| {
+                    button.spawn(text(16.0, Color::WHITE));
+                });
+            }
+        });
+    });
+}
+
+fn start_dialogue(
+    mut dialogue: ResMut<Dialogue>,
+    open_trader: Res<OpenTrader>,
+    speaker_query: Query<&Speaker>,
+    mut camera_query: Query<&mut Camera>,
+    mut cursor_users: ResMut<CursorUsers>,
+    mut interacted_events: EventReader<Interacted>,
+) {
+    for event in interacted_events.read() {
+        let Ok(Speaker(conversation)) = speaker_query.get(event.entity) else {
+            continue;
+        };
+        // interacting while trading puts the offers away instead
+        if dialogue.is_open() || open_trader.trader() == Some(event.entity) {
+            continue;
+        }
+
+        dialogue.speaker = Some((event.entity, *conversation));
+        dialogue.line = 0;
+        camera_query.get_single_mut().expect("not one camera").framing = Some(event.entity);
+        cursor_users.set(CursorUser::Dialogue, true);
+    }
+}
+
+fn dialogue_input(
+    mut dialogue: ResMut<Dialogue>,
+    mut log: ResMut<QuestLog>,
+    factions: Res<Factions>,
+    locale: Res<Locale>,
+    player_query: Query<&Transform, With<Player>>,
+    speaker_query: Query<&Transform, With<Speaker>>,
+    choice_query: Query<(&ChoiceButton, &Interaction), Changed<Interaction>>,
+    mut camera_query: Query<&mut Camera>,
+    mut cursor_users: ResMut<CursorUsers>,
+    mut trade_events: EventWriter<OpenTrade>,
+    mut notify_events: EventWriter<Notify>,
+) {
+    let Some((speaker, conversation)) = dialogue.speaker else {
+        return;
+    };
+    let mut camera = camera_query.get_single_mut().expect("not one camera");
+
+    // walking off, or the speaker being gone, ends it
+    let player = player_query.get_single().expect("not one player").translation;
+    let in_range = speaker_query.get(speaker)
+        .is_ok_and(|t| t.translation.distance(player) <= INTERACT_RANGE);
+    if !in_range {
+        end_dialogue(&mut dialogue, &mut camera, &mut cursor_users);
+        return;
+    }
+
+    let line = &conversation.lines()[dialogue.line];
+    let choices = visible_choices(line, &log, &factions);
+    let picked = choice_query.iter()
+        .find(|(_, interaction)| **interaction == Interaction::Pressed)
+        .and_then(|(ChoiceButton(i), _)| choices.get(*i).copied());
+    let Some(choice) = picked else {
+        return;
+    };
+
+    match choice.consequence {
+        Consequence::Nothing => {}
+        Consequence::StartQuest(quest) => {
+            log.start(quest);
+            let message = locale.fill("Quest started: {}", &[&locale.get(quest.name())]);
+            notify_events.send(Notify::new(message, Priority::Normal));
+        }
+        Consequence::OpenTrade => trade_events.send(OpenTrade { trader: speaker }),
+    }
+
+    match choice.next {
+        Some(next) => dialogue.line = next,
+        None => end_dialogue(&mut dialogue, &mut camera, &mut cursor_users),
+    }
+}
+
+fn update_dialogue_ui(
+    dialogue: Res<Dialogue>,
+    log: Res<QuestLog>,
+    factions: Res<Factions>,
+    locale: Res<Locale>,
+    mut ui_query: Query<&mut Visibility, With<DialogueUi>>,
+    mut speaker_query: Query<&mut Text, (With<SpeakerText>, Without<LineText>)>,
+    mut line_query: Query<&mut Text, (With<LineText>, Without<SpeakerText>)>,
+    mut choice_query: Query<(&ChoiceButton, &Interaction, &mut Style, &mut BackgroundColor, &Children)>,
+    mut text_query: Query<&mut Text, (Without<SpeakerText>, Without<LineText>)>,
+) {
+    let mut visibility = ui_query.get_single_mut().expect("not one dialogue ui");
+    let Some((_, conversation)) = dialogue.speaker else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Visible;
+
+    let line = &conversation.lines()[dialogue.line];
+    speaker_query.get_single_mut().expect("not one speaker text").sections[0].value =
+        locale.get(conversation.speaker()).to_string();
+    line_query.get_single_mut().expect("not one line text").sections[0].value = locale.get(line.text).to_string();
+
+    let choices = visible_choices(line, &log, &factions);
+    for (ChoiceButton(i), interaction, mut style, mut background, children) in &mut choice_query {
+        let Some(choice) = choices.get(*i) else {
+            style.display = Display::None;
+            continue;
+        };
+        style.display = Display::Flex;
+        *background = if *interaction == Interaction::None { CHOICE_COLOR } else { CHOICE_HOVER }.into();
+
+        if let Some(mut text) = children.first().and_then(|c| text_query.get_mut(*c).ok()) {
+            text.sections[0].value = format!("{}. {}", i + 1, locale.get(choice.text));
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+use crate::{
+    character::Stats,
+    magic::{SpellBook, SpellCast, SpellEffect},
+    state::GameplaySet,
+};
+
+pub struct TimeDilationPlugin;
+
+impl Plugin for TimeDilationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TimeDilation>()
+            .add_systems(Update, (start_dilation, sustain_dilation).chain().in_set(GameplaySet));
+    }
+}
+
+struct Dilation {
+    caster: Entity,
+    factor: f32,
+    // in unscaled seconds
+    remaining: f32,
+    mana_per_second: f32,
+}
+
+/// Slows down the world, physics included, while the caster keeps
+/// moving and casting at normal speed.
+#[derive(Resource)]
+pub struct TimeDilation {
+    // 1.0 is normal speed
+    pub factor: f32,
+    active: Option<Dilation>,
+    /// Slows the world on top of any spell for as long as it's set, for
+    /// menus that shouldn't quite pause the game.
+    pub menu_factor: Option<f32>,
+}
+
+impl Default for TimeDilation {
+    fn default() -> Self {
+        TimeDilation { factor: 1.0, active: None, menu_factor: None }
+    }
+}
+
+impl TimeDilation {
+    /// Seconds that passed this frame for someone the dilation doesn't affect.
+    pub fn unscaled(&self, time: &Time) -> f32 {
+        time.delta_seconds() / self.factor
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+}
+
+fn start_dilation(
+    book: Res<SpellBook>,
+    mut dilation: ResMut<TimeDilation>,
+    mut cast_events: EventReader<SpellCast>,
+) {
+    for event in cast_events.read() {
+        let Some(SpellEffect::TimeDilation { factor, duration, mana_per_second }) =
+            book.get(event.spell).map(|s| s.effect)
+        else {
+            continue;
+        };
+
+        // casting it again while it runs ends it early
+        if dilation.active.as_ref().is_some_and(|d| d.caster == event.caster) {
+            dilation.active = None;
+            continue;
+        }
+
+        dilation.active = Some(Dilation { caster: event.caster, factor, remaining: duration, mana_per_second });
+    }
+}
+
+fn sustain_dilation(
+    time: Res<Time>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut dilation: ResMut<TimeDilation>,
+    mut stats_query: Query<&mut Stats>,
+) {
+    let dt = dilation.unscaled(&time);
+
+    let keep_going = match dilation.active.as_mut() {
+        Some(active) => {
+            active.remaining -= dt;
+            let paid = stats_query.get_mut(active.caster)
+                .is_ok_and(|mut stats| stats.spend_mana(active.mana_per_second * dt));
+            paid && active.remaining > 0.0
+        }
+        None => false,
+    };
+    if !keep_going {
+        dilation.active = None;
+    }
+
+    // always put the clock back once it's over
+    let factor = dilation.active.as_ref().map_or(1.0, |d| d.factor) * dilation.menu_factor.unwrap_or(1.0);
+    if dilation.factor != factor {
+        dilation.factor = factor;
+        virtual_time.set_relative_speed(factor);
+    }
+}
@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+use crate::{
+    enemy::Hostile,
+    magic::{SpellBook, SpellCast, SpellEffect},
+    shaman::{EnemyCasting, InterruptEnemyCast},
+    state::GameplaySet,
+    status::{RemoveStatus, StatusEffects},
+};
+
+pub struct DispelPlugin;
+
+impl Plugin for DispelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, dispel.in_set(GameplaySet));
+    }
+}
+
+// strips the caster of curses and everything hostile around it of its
+// blessings, and breaks any enemy spell still winding up
+fn dispel(
+    book: Res<SpellBook>,
+    caster_query: Query<(&Transform, Option<&StatusEffects>)>,
+    hostile_query: Query<(Entity, &Transform, Option<&StatusEffects>, Has<EnemyCasting>), With<Hostile>>,
+    mut cast_events: EventReader<SpellCast>,
+    mut remove_events: EventWriter<RemoveStatus>,
+    mut interrupt_events: EventWriter<InterruptEnemyCast>,
+) {
+    for event in cast_events.read() {
+        let Some(SpellEffect::Dispel { radius }) = book.get(event.spell).map(|s| s.effect) else {
+            continue;
+        };
+        let Ok((caster, effects)) = caster_query.get(event.caster) else {
+            continue;
+        };
+
+        for status in effects.into_iter().flat_map(|e| &e.active).filter(|s| s.kind.is_harmful()) {
+            remove_events.send(RemoveStatus { target: event.caster, kind: status.kind });
+        }
+
+        for (target, transform, effects, is_casting) in &hostile_query {
+            if transform.translation.distance(caster.translation) > radius {
+                continue;
+            }
+
+            for status in effects.into_iter().flat_map(|e| &e.active).filter(|s| !s.kind.is_harmful()) {
+                remove_events.send(RemoveStatus { target, kind: status.kind });
+            }
+            if is_casting {
+                interrupt_events.send(InterruptEnemyCast { caster: target });
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    camera::Camera,
+    character::Stats,
+    controls::Grounded,
+    health::Invulnerable,
+    input::{Action, InputMap},
+    levitation::Flying,
+    melee::{Combo, Lunging},
+    minecart::Riding,
+    state::GameplaySet,
+    Player,
+};
+
+const ROLL_SPEED: f32 = 9.0;
+const ROLL_TIME: f32 = 0.35;
+// the start of the roll can't be hit, the tail end can
+const IFRAME_TIME: f32 = 0.25;
+const STAMINA_COST: f32 = 25.0;
+
+pub struct DodgePlugin;
+
+impl Plugin for DodgePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (dodge_input, roll).chain().in_set(GameplaySet));
+    }
+}
+
+/// Rolling along `velocity`, movement input is ignored meanwhile.
+#[derive(Component)]
+pub struct Rolling {
+    velocity: Vec3,
+    remaining: f32,
+}
+
+fn dodge_input(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut player_query: Query<
+        (Entity, &mut Stats, &mut Combo, &Transform),
+        (With<Player>, With<Grounded>, Without<Rolling>, Without<Flying>, Without<Riding>),
+    >,
+    camera_query: Query<&Transform, (With<Camera>, Without<Player>)>,
+) {
+    if !input_map.just_pressed(Action::Dodge, &keys) {
+        return;
+    }
+    let Ok((player, mut stats, mut combo, transform)) = player_query.get_single_mut() else {
+        return;
+    };
+    // only out of the tail end of a swipe
+    if combo.is_attacking() && !combo.cancellable() {
+        return;
+    }
+    if !stats.spend_stamina(STAMINA_COST) {
+        return;
+    }
+
+    let camera = camera_query.get_single().unwrap_or(transform);
+    let mut direction = Vec3::ZERO;
+    if input_map.pressed(Action::MoveForward, &keys) {
+        direction += camera.forward();
+    }
+    if input_map.pressed(Action::MoveLeft, &keys) {
+        direction += camera.left();
+    }
+    if input_map.pressed(Action::MoveBack, &keys) {
+        direction += camera.back();
+    }
+    if input_map.pressed(Action::MoveRight, &keys) {
+        direction += camera.right();
+    }
+    // standing still rolls forward
+    if direction.x == 0.0 && direction.z == 0.0 {
+        direction = camera.forward();
+    }
+    let direction = Vec3::new(direction.x, 0.0, direction.z).normalize_or_zero();
+
+    combo.cancel();
+    commands.entity(player)
+        .remove::<Lunging>()
+        .insert((
+            Rolling { velocity: direction * ROLL_SPEED, remaining: ROLL_TIME },
+            Invulnerable { remaining: IFRAME_TIME },
+        ));
+}
+
+// set every frame so friction and damping don't eat into the roll
+fn roll(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut rolling_query: Query<(Entity, &mut Rolling, &mut LinearVelocity)>,
+) {
+    for (entity, mut rolling, mut velocity) in &mut rolling_query {
+        rolling.remaining -= time.delta_seconds();
+        if rolling.remaining <= 0.0 {
+            commands.entity(entity).remove::<Rolling>();
+            continue;
+        }
+        velocity.x = rolling.velocity.x;
+        velocity.z = rolling.velocity.z;
+    }
+}
@@ -0,0 +1,409 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    belt::Belt,
+    blueprint::{spawn_ghost, Ghost},
+    building::{
+        can_place, Building, BuildingGrid, BuildingKind, BuildingPlaced, BuildingRemoved, Facing,
+        GridPos, PlaceBuilding, RemoveBuilding,
+    },
+    deconstruct::{building_contents, MarkedForDeconstruction},
+    ground::DropItem,
+    health::Health,
+    inventory::Inventory,
+    item::{Item, ItemStack},
+    machine::{ConfigureMachine, Machine, MachineConfig},
+    module::ModuleSlots,
+    research::Research,
+    state::GameplaySet,
+    storage::Chest,
+    wear::{Wear, SERVICE_DURABILITY},
+};
+
+const DRONES_PER_HUB: usize = 2;
+// drones only work on things this close to their hub
+const HUB_RANGE: f32 = 15.0;
+const DRONE_SPEED: f32 = 4.0;
+const FLIGHT_HEIGHT: f32 = 2.0;
+// close enough to grab or drop something
+const ARRIVE_DISTANCE: f32 = 0.3;
+
+pub struct DronePlugin;
+
+impl Plugin for DronePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RebuildConfigs>()
+            .add_systems(Update, (
+                init_drone_hubs,
+                remember_damaged_machines,
+                ghost_destroyed_buildings,
+                assign_drone_tasks,
+                move_drones,
+                finish_drone_tasks,
+                configure_built_machines,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DroneTask {
+    Idle,
+    // pick up building materials for a ghost
+    Fetch { ghost: Entity, chest: Entity },
+    Build { ghost: Entity },
+    Deconstruct { building: Entity },
+    // grab a repair kit for a worn machine
+    FetchKit { machine: Entity, chest: Entity },
+    Repair { machine: Entity },
+    // bring carried items to a chest
+    Deliver { chest: Entity },
+}
+
+#[derive(Component)]
+pub struct Drone {
+    pub hub: Entity,
+    pub task: DroneTask,
+    pub carrying: Vec<ItemStack>,
+}
+
+#[derive(Component)]
+pub struct DroneHub;
+
+// machine settings of damaged buildings, so a rebuild picks up where it left off
+#[derive(Resource, Default)]
+struct RebuildConfigs(HashMap<GridPos, MachineConfig>);
+
+fn init_drone_hubs(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut placed_events: EventReader<BuildingPlaced>,
+    mut removed_events: EventReader<BuildingRemoved>,
+    drone_query: Query<(Entity, &Drone, &Transform)>,
+    mut drop_events: EventWriter<DropItem>,
+) {
+    for event in placed_events.read().filter(|e| e.kind == BuildingKind::DroneHub) {
+        commands.entity(event.entity).insert(DroneHub);
+
+        for _ in 0..DRONES_PER_HUB {
+            commands.spawn((
+                Drone { hub: event.entity, task: DroneTask::Idle, carrying: Vec::new() },
+                PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::UVSphere { radius: 0.15, ..default() })),
+                    material: materials.add(StandardMaterial {
+                        base_color: BuildingKind::DroneHub.color(),
+                        emissive: Color::rgb(0.2, 0.4, 0.6),
+                        ..default()
+                    }),
+                    transform: Transform::from_translation(event.pos.to_world() + Vec3::Y * FLIGHT_HEIGHT),
+                    ..default()
+                },
+            ));
+        }
+    }
+
+    // drones go down with their hub, cargo and all
+    for event in removed_events.read().filter(|e| e.kind == BuildingKind::DroneHub) {
+        for (entity, drone, transform) in drone_query.iter().filter(|(_, d, _)| d.hub == event.entity) {
+            for stack in &drone.carrying {
+                drop_events.send(DropItem { stack: *stack, pos: transform.translation, magnetic: false });
+            }
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn remember_damaged_machines(
+    mut configs: ResMut<RebuildConfigs>,
+    machine_query: Query<(&GridPos, &Machine), Changed<Health>>,
+    mut removed_events: EventReader<BuildingRemoved>,
+) {
+    for (pos, machine) in &machine_query {
+        configs.0.insert(*pos, machine.config());
+    }
+
+    // taken apart on purpose, nothing to rebuild
+    for event in removed_events.read().filter(|e| !e.destroyed) {
+        configs.0.remove(&event.pos);
+    }
+}
+
+fn ghost_destroyed_buildings(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut configs: ResMut<RebuildConfigs>,
+    mut removed_events: EventReader<BuildingRemoved>,
+) {
+    for event in removed_events.read().filter(|e| e.destroyed) {
+        let machine = configs.0.remove(&event.pos);
+        spawn_ghost(&mut commands, &mut meshes, &mut materials, event.kind, event.pos, event.facing, machine);
+    }
+}
+
+fn assign_drone_tasks(
+    mut drone_query: Query<&mut Drone>,
+    hub_query: Query<&Transform, With<DroneHub>>,
+    ghost_query: Query<(Entity, &Ghost, &Transform)>,
+    marked_query: Query<(Entity, &Transform), With<MarkedForDeconstruction>>,
+    wear_query: Query<(Entity, &Wear, &Transform)>,
+    chest_query: Query<(Entity, &Inventory, &Transform), With<Chest>>,
+) {
+    // nothing gets worked on twice
+    let mut claimed: Vec<Entity> = drone_query.iter()
+        .filter_map(|d| match d.task {
+            DroneTask::Fetch { ghost, .. } | DroneTask::Build { ghost } => Some(ghost),
+            DroneTask::Deconstruct { building } => Some(building),
+            DroneTask::FetchKit { machine, .. } | DroneTask::Repair { machine } => Some(machine),
+            _ => None,
+        })
+        .collect();
+
+    for mut drone in &mut drone_query {
+        if drone.task != DroneTask::Idle {
+            continue;
+        }
+        let Ok(hub) = hub_query.get(drone.hub).map(|t| t.translation) else {
+            continue;
+        };
+
+        // get rid of whatever we're holding first
+        if let Some(stack) = drone.carrying.first().copied() {
+            let chest = chest_query.iter()
+                .filter(|(_, inv, _)| inv.space_for(stack.item) > 0)
+                .min_by(|a, b| a.2.translation.distance(hub).total_cmp(&b.2.translation.distance(hub)));
+
+            if let Some((chest, ..)) = chest {
+                drone.task = DroneTask::Deliver { chest };
+            }
+            continue;
+        }
+
+        let in_range = |pos: Vec3| pos.distance(hub) <= HUB_RANGE;
+
+        let ghost = ghost_query.iter()
+            .filter(|(e, _, t)| !claimed.contains(e) && in_range(t.translation))
+            .find_map(|(e, ghost, _)| {
+                chest_query.iter()
+                    .find(|(_, inv, _)| inv.contains_all(ghost.kind.cost()))
+                    .map(|(chest, ..)| (e, chest))
+            });
+
+        if let Some((ghost, chest)) = ghost {
+            drone.task = DroneTask::Fetch { ghost, chest };
+            claimed.push(ghost);
+            continue;
+        }
+
+        let worn = wear_query.iter()
+            .filter(|(e, w, t)| {
+                !claimed.contains(e) && w.durability < SERVICE_DURABILITY && in_range(t.translation)
+            })
+            .find_map(|(e, ..)| {
+                chest_query.iter()
+                    .find(|(_, inv, _)| inv.count(Item::RepairKit) > 0)
+                    .map(|(chest, ..)| (e, chest))
+            });
+
+        if let Some((machine, chest)) = worn {
+            drone.task = DroneTask::FetchKit { machine, chest };
+            claimed.push(machine);
+            continue;
+        }
+
+        let marked = marked_query.iter()
+            .find(|(e, t)| !claimed.contains(e) && in_range(t.translation));
+
+        if let Some((building, _)) = marked {
+            drone.task = DroneTask::Deconstruct { building };
+            claimed.push(building);
+        }
+    }
+}
+
+fn move_drones(
+    time: Res<Time>,
+    mut drone_query: Query<(&mut Drone, &mut Transform)>,
+    target_query: Query<&GlobalTransform, Without<Drone>>,
+) {
+    for (mut drone, mut transform) in &mut drone_query {
+        let target = match drone.task {
+            DroneTask::Idle => drone.hub,
+            DroneTask::Fetch { chest, .. }
+            | DroneTask::FetchKit { chest, .. }
+            | DroneTask::Deliver { chest } => chest,
+            DroneTask::Build { ghost } => ghost,
+            DroneTask::Deconstruct { building } => building,
+            DroneTask::Repair { machine } => machine,
+        };
+
+        // whatever we were heading for is gone
+        let Ok(target) = target_query.get(target) else {
+            drone.task = DroneTask::Idle;
+            continue;
+        };
+
+        let mut goal = target.translation();
+        goal.y = FLIGHT_HEIGHT;
+
+        let step = DRONE_SPEED * time.delta_seconds();
+        let to_goal = goal - transform.translation;
+        transform.translation += to_goal.clamp_length_max(step);
+    }
+}
+
+fn finish_drone_tasks(
+    mut commands: Commands,
+    research: Res<Research>,
+    grid: Res<BuildingGrid>,
+    mut configs: ResMut<RebuildConfigs>,
+    mut drone_query: Query<(&mut Drone, &Transform)>,
+    ghost_query: Query<(&Ghost, &GridPos, &Facing, &Transform)>,
+    chest_query: Query<&Transform, With<Chest>>,
+    mut inventory_query: Query<&mut Inventory>,
+    building_query: Query<(&Building, &Facing)>,
+    marked_query: Query<
+        (&Building, &Transform, Option<&Belt>, Option<&Machine>, Option<&ModuleSlots>),
+        With<MarkedForDeconstruction>,
+    >,
+    mut wear_query: Query<(&mut Wear, &Transform)>,
+    mut place_events: EventWriter<PlaceBuilding>,
+    mut remove_events: EventWriter<RemoveBuilding>,
+) {
+    let arrived = |from: &Transform, to: &Transform| {
+        from.translation.xz().distance(to.translation.xz()) <= ARRIVE_DISTANCE
+    };
+
+    for (mut drone, transform) in &mut drone_query {
+        match drone.task {
+            DroneTask::Idle => {}
+            DroneTask::Fetch { ghost, chest } => {
+                let (Ok((ghost_data, ..)), Ok(chest_transform)) = (ghost_query.get(ghost), chest_query.get(chest)) else {
+                    drone.task = DroneTask::Idle;
+                    continue;
+                };
+                if !arrived(transform, chest_transform) {
+                    continue;
+                }
+
+                // someone else got there first
+                let mut inventory = inventory_query.get_mut(chest).expect("chest without inventory");
+                let cost = ghost_data.kind.cost();
+                if !inventory.contains_all(cost) {
+                    drone.task = DroneTask::Idle;
+                    continue;
+                }
+
+                for (item, n) in cost {
+                    inventory.remove(*item, *n);
+                    drone.carrying.push(ItemStack::new(*item, *n));
+                }
+                drone.task = DroneTask::Build { ghost };
+            }
+            DroneTask::Build { ghost } => {
+                let Ok((ghost_data, pos, facing, ghost_transform)) = ghost_query.get(ghost) else {
+                    drone.task = DroneTask::Idle;
+                    continue;
+                };
+                if !arrived(transform, ghost_transform) {
+                    continue;
+                }
+
+                // blocked ghosts stay put, the materials go back to storage
+                drone.task = DroneTask::Idle;
+                let buildable = research.building_unlocked(ghost_data.kind)
+                    && can_place(ghost_data.kind, *pos, *facing, &grid, &building_query);
+                if !buildable {
+                    continue;
+                }
+
+                place_events.send(PlaceBuilding {
+                    kind: ghost_data.kind,
+                    pos: *pos,
+                    facing: *facing,
+                    supplied: true,
+                });
+                if let Some(machine) = ghost_data.machine {
+                    configs.0.insert(*pos, machine);
+                }
+                drone.carrying.clear();
+                commands.entity(ghost).despawn_recursive();
+            }
+            DroneTask::Deconstruct { building } => {
+                let Ok((data, building_transform, belt, machine, modules)) = marked_query.get(building) else {
+                    drone.task = DroneTask::Idle;
+                    continue;
+                };
+                if !arrived(transform, building_transform) {
+                    continue;
+                }
+
+                let inventory = inventory_query.get(building).ok();
+                drone.carrying.extend(building_contents(data.kind, inventory, belt, machine, modules));
+                remove_events.send(RemoveBuilding(building));
+                drone.task = DroneTask::Idle;
+            }
+            DroneTask::FetchKit { machine, chest } => {
+                let Ok(chest_transform) = chest_query.get(chest) else {
+                    drone.task = DroneTask::Idle;
+                    continue;
+                };
+                if !arrived(transform, chest_transform) {
+                    continue;
+                }
+
+                let mut inventory = inventory_query.get_mut(chest).expect("chest without inventory");
+                if inventory.remove(Item::RepairKit, 1) {
+                    drone.carrying.push(ItemStack::new(Item::RepairKit, 1));
+                    drone.task = DroneTask::Repair { machine };
+                } else {
+                    drone.task = DroneTask::Idle;
+                }
+            }
+            DroneTask::Repair { machine } => {
+                let Ok((mut wear, machine_transform)) = wear_query.get_mut(machine) else {
+                    drone.task = DroneTask::Idle;
+                    continue;
+                };
+                if !arrived(transform, machine_transform) {
+                    continue;
+                }
+
+                wear.repair();
+                drone.carrying.retain(|s| s.item != Item::RepairKit);
+                drone.task = DroneTask::Idle;
+            }
+            DroneTask::Deliver { chest } => {
+                let (Ok(chest_transform), Ok(mut inventory)) = (chest_query.get(chest), inventory_query.get_mut(chest)) else {
+                    drone.task = DroneTask::Idle;
+                    continue;
+                };
+                if !arrived(transform, chest_transform) {
+                    continue;
+                }
+
+                // whatever doesn't fit waits for the next chest
+                for stack in std::mem::take(&mut drone.carrying) {
+                    let leftover = inventory.insert(stack);
+                    if leftover > 0 {
+                        drone.carrying.push(ItemStack::new(stack.item, leftover));
+                    }
+                }
+                drone.task = DroneTask::Idle;
+            }
+        }
+    }
+}
+
+fn configure_built_machines(
+    mut configs: ResMut<RebuildConfigs>,
+    machine_query: Query<(Entity, &GridPos), Added<Machine>>,
+    mut configure_events: EventWriter<ConfigureMachine>,
+) {
+    for (entity, pos) in &machine_query {
+        if let Some(config) = configs.0.remove(pos) {
+            configure_events.send(ConfigureMachine { entity, config });
+        }
+    }
+}
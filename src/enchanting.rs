@@ -0,0 +1,280 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::{
+    building::{BuildingKind, BuildingPlaced},
+    equipment::{Equipment, GearBonuses, GearSlot},
+    input::{Action, InputMap},
+    interaction::{interact, Interactable, Interacted, INTERACT_RANGE},
+    inventory::Inventory,
+    item::Item,
+    locale::Locale,
+    state::GameplaySet,
+    stats::ItemConsumed,
+    Player,
+};
+
+pub const MAX_ENCHANTMENTS: usize = 2;
+
+pub struct EnchantingPlugin;
+
+impl Plugin for EnchantingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<OpenEnchanter>()
+            .add_systems(Startup, setup_enchanting_ui)
+            .add_systems(Update, (
+                init_tables,
+                toggle_enchanter.after(interact),
+                enchanting_input,
+                update_enchanting_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Enchantment {
+    Wisdom,
+    Focus,
+    Swiftness,
+    Searing,
+    Siphon,
+    Stabilizing,
+}
+
+impl Enchantment {
+    pub const ALL: [Enchantment; 6] = [
+        Enchantment::Wisdom,
+        Enchantment::Focus,
+        Enchantment::Swiftness,
+        Enchantment::Searing,
+        Enchantment::Siphon,
+        Enchantment::Stabilizing,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Enchantment::Wisdom => "Wisdom",
+            Enchantment::Focus => "Focus",
+            Enchantment::Swiftness => "Swiftness",
+            Enchantment::Searing => "Searing",
+            Enchantment::Siphon => "Siphon",
+            Enchantment::Stabilizing => "Stabilizing",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Enchantment::Wisdom => "+20 max mana",
+            Enchantment::Focus => "+1.5 mana regen",
+            Enchantment::Swiftness => "+10% speed",
+            Enchantment::Searing => "hits set targets burning, 4 per second",
+            Enchantment::Siphon => "hits restore 3 mana",
+            Enchantment::Stabilizing => "+30 flux before spells backfire",
+        }
+    }
+
+    // the kind of gear that can hold it
+    pub fn slot(&self) -> GearSlot {
+        match self {
+            Enchantment::Searing | Enchantment::Siphon => GearSlot::Weapon,
+            Enchantment::Swiftness | Enchantment::Stabilizing => GearSlot::Armor,
+            Enchantment::Wisdom | Enchantment::Focus => GearSlot::Trinket,
+        }
+    }
+
+    pub fn essence(&self) -> u32 {
+        match self {
+            Enchantment::Searing | Enchantment::Siphon => 4,
+            _ => 3,
+        }
+    }
+
+    pub fn apply(&self, bonuses: &mut GearBonuses) {
+        match self {
+            Enchantment::Wisdom => bonuses.max_mana += 20.0,
+            Enchantment::Focus => bonuses.mana_regen += 1.5,
+            Enchantment::Swiftness => bonuses.speed += 0.1,
+            Enchantment::Searing => bonuses.burn_on_hit += 4.0,
+            Enchantment::Siphon => bonuses.mana_on_hit += 3.0,
+            Enchantment::Stabilizing => bonuses.stability += 30.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct EnchantingTable;
+
+#[derive(Resource, Default)]
+struct OpenEnchanter {
+    table: Option<Entity>,
+    selected: usize,
+}
+
+#[derive(Component)]
+struct EnchantingUi;
+
+#[derive(Component)]
+struct EnchantingText;
+
+fn init_tables(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read().filter(|e| e.kind == BuildingKind::EnchantingTable) {
+        commands.entity(event.entity).insert((EnchantingTable, Interactable::new("Enchant")));
+    }
+}
+
+fn toggle_enchanter(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut open: ResMut<OpenEnchanter>,
+    player_query: Query<&Transform, With<Player>>,
+    table_query: Query<(Entity, &Transform), With<EnchantingTable>>,
+    mut interacted_events: EventReader<Interacted>,
+) {
+    let player = player_query.get_single().expect("not one player");
+
+    // close once the player walks away or the table is gone
+    if let Some(entity) = open.table {
+        let in_range = table_query.get(entity)
+            .is_ok_and(|(_, t)| t.translation.distance(player.translation) <= INTERACT_RANGE);
+
+        if !in_range {
+            open.table = None;
+        }
+    }
+
+    let used = interacted_events.read().map(|e| e.entity).find(|e| table_query.contains(*e));
+    if !input_map.just_pressed(Action::Interact, &keys) {
+        return;
+    }
+
+    if open.table.is_some() {
+        open.table = None;
+        return;
+    }
+
+    open.table = used;
+}
+
+fn enchanting_input(
+    keys: Res<Input<KeyCode>>,
+    mut open: ResMut<OpenEnchanter>,
+    mut player_query: Query<(&mut Inventory, &mut Equipment), With<Player>>,
+    mut consumed_events: EventWriter<ItemConsumed>,
+) {
+    if open.table.is_none() {
+        return;
+    }
+
+    let count = Enchantment::ALL.len();
+    if keys.just_pressed(KeyCode::Down) {
+        open.selected = (open.selected + 1) % count;
+    }
+    if keys.just_pressed(KeyCode::Up) {
+        open.selected = (open.selected + count - 1) % count;
+    }
+
+    if !keys.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    let (mut inventory, mut equipment) = player_query.get_single_mut().expect("not one player");
+    let enchantment = Enchantment::ALL[open.selected];
+
+    // only worn gear can be enchanted, and the essence goes in first so a
+    // full piece doesn't eat it
+    let Some(gear) = equipment.get_mut(enchantment.slot()) else {
+        return;
+    };
+    if gear.enchantments().count() >= MAX_ENCHANTMENTS
+        || !inventory.remove(Item::Essence, enchantment.essence())
+    {
+        return;
+    }
+
+    gear.enchant(enchantment);
+    consumed_events.send(ItemConsumed { item: Item::Essence, count: enchantment.essence() });
+}
+
+fn setup_enchanting_ui(mut commands: Commands) {
+    commands.spawn((
+        EnchantingUi,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(20.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            EnchantingText,
+            TextBundle::from_section("", TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            }),
+        ));
+    });
+}
+
+fn update_enchanting_ui(
+    open: Res<OpenEnchanter>,
+    locale: Res<Locale>,
+    player_query: Query<(&Inventory, &Equipment), With<Player>>,
+    mut ui_query: Query<&mut Visibility, With<EnchantingUi>>,
+    mut text_query: Query<&mut Text, With<EnchantingText>>,
+) {
+    let mut visibility = ui_query.get_single_mut().expect("not one enchanting ui");
+    if open.table.is_none() {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Visible;
+
+    let (inventory, equipment) = player_query.get_single().expect("not one player");
+
+    let mut content = locale.fill("Enchanting (Essence {})", &[&inventory.count(Item::Essence)]);
+    for slot in GearSlot::ALL {
+        let _ = match equipment.get(slot) {
+            Some(gear) => {
+                let enchantments = gear.enchantments()
+                    .map(|e| locale.get(e.name()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let rarity = if gear.item.gear_slot() == Some(GearSlot::Weapon) {
+                    format!("{} ", locale.get(gear.rarity.name()))
+                } else {
+                    String::new()
+                };
+                write!(content, "\n{}: {}{} [{}]", locale.get(slot.name()), rarity, locale.get(gear.item.name()), enchantments)
+            }
+            None => write!(content, "\n{}: -", locale.get(slot.name())),
+        };
+    }
+
+    content.push('\n');
+    for (i, enchantment) in Enchantment::ALL.iter().enumerate() {
+        let marker = if i == open.selected { '>' } else { ' ' };
+        let _ = write!(
+            content,
+            "\n{} {} ({}, {} essence): {}",
+            marker,
+            enchantment.name(),
+            enchantment.slot().name(),
+            enchantment.essence(),
+            enchantment.description(),
+        );
+    }
+
+    text_query.get_single_mut().expect("not one enchanting text").sections[0].value = content;
+}
@@ -0,0 +1,292 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    ai::{Archetype, Archetypes, Brain},
+    corruption::CorruptionMap,
+    daynight::TimeOfDay,
+    death::{DeathEffect, LeavesCorpse},
+    faction::{Faction, Member},
+    health::{Health, Resistances},
+    hitbox::weak_spot,
+    layer::Layer,
+    loot::{Loot, LootTableId},
+    nav::NavAgent,
+    perception::{Alertness, Awareness},
+    shaman::spawn_shaman,
+    stagger::Poise,
+    state::GameplaySet,
+    Player,
+};
+
+// half the size of the playable area
+pub const WORLD_EXTENT: f32 = 7.0;
+// seconds between spawns at night on clean ground
+const BASE_SPAWN_INTERVAL: f32 = 30.0;
+// how many of the spawns are shamans or slingers instead of constructs
+const SHAMAN_CHANCE: f64 = 0.2;
+const SLINGER_CHANCE: f64 = 0.2;
+// corruption at which the wildlife turns on the player
+const CRAWLER_CORRUPTION: f32 = 30.0;
+// enemies the night is worth, in spawn budget
+const NIGHT_BUDGET: u32 = 6;
+// and every this much corruption under the player's feet adds another
+const CORRUPTION_PER_BUDGET: f32 = 15.0;
+// enemies further away than this don't count against the budget, which
+// for now is about the whole map from anywhere on it
+const ACTIVE_RADIUS: f32 = 20.0;
+const MIN_SPAWN_DISTANCE: f32 = 4.0;
+// spawn points tried before giving up for the frame
+const SPAWN_ATTEMPTS: usize = 8;
+// seconds a dead crawler lies around
+const CORPSE_TIME: f32 = 15.0;
+// how much more a hit on a weak spot does
+const WEAK_SPOT_MULTIPLIER: f32 = 1.5;
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<SpawnDirector>()
+            .add_systems(Update, (update_budget, spawn_encounters, despawn_stragglers).chain().in_set(GameplaySet));
+    }
+}
+
+// anything turrets and spells should treat as an enemy
+#[derive(Component)]
+pub struct Hostile;
+
+#[derive(Component)]
+pub struct Construct;
+
+#[derive(Component)]
+pub struct Crawler;
+
+#[derive(Component)]
+pub struct Slinger;
+
+/// Keeps the number of enemies around the player within a budget that
+/// grows at night and on corrupted ground.
+#[derive(Resource)]
+pub struct SpawnDirector {
+    // seconds until the next spawn
+    countdown: f32,
+    // what the enemies near the player add up to, as of the last frame
+    pub active: u32,
+    pub budget: u32,
+}
+
+impl Default for SpawnDirector {
+    fn default() -> Self {
+        SpawnDirector { countdown: BASE_SPAWN_INTERVAL, active: 0, budget: 0 }
+    }
+}
+
+pub fn spawn_construct(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    pos: Vec3,
+) -> Entity {
+    commands.spawn((
+        Hostile,
+        Construct,
+        Brain::new(Archetype::Construct, pos),
+        Member(Faction::Wild),
+        Loot(LootTableId::Construct),
+        DeathEffect::Explode,
+        NavAgent::default(),
+        Awareness::default(),
+        Health::new(50.0),
+        // stone shrugs off blows but the mana holding it together doesn't like arcs
+        Resistances { physical: 0.4, frost: 0.2, arc: -0.5, ..default() },
+        Poise(0.5),
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Cube { size: 0.8 })),
+            material: materials.add(Color::rgb_u8(120, 40, 140).into()),
+            transform: Transform::from_translation(pos),
+            ..default()
+        },
+        // bevy only takes bundles of up to 15, so the physics go together
+        (RigidBody::Dynamic, Collider::cuboid(0.8, 0.8, 0.8), LockedAxes::ROTATION_LOCKED, Layer::enemy()),
+    ))
+    // the crystal on top holds it together
+    .with_children(|parent| weak_spot(parent, Vec3::Y * 0.45, 0.2, WEAK_SPOT_MULTIPLIER))
+    .id()
+}
+
+fn spawn_crawler(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    pos: Vec3,
+) -> Entity {
+    commands.spawn((
+        Hostile,
+        Crawler,
+        Brain::new(Archetype::Crawler, pos),
+        Member(Faction::Wild),
+        Loot(LootTableId::Crawler),
+        LeavesCorpse(CORPSE_TIME),
+        NavAgent::default(),
+        Awareness::default(),
+        Health::new(20.0),
+        // soaked through with corruption, burns well
+        Resistances { fire: -0.3, ..default() },
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::UVSphere { radius: 0.3, sectors: 10, stacks: 6 })),
+            material: materials.add(Color::rgb_u8(70, 90, 40).into()),
+            transform: Transform::from_translation(pos),
+            ..default()
+        },
+        (RigidBody::Dynamic, Collider::ball(0.3), LockedAxes::ROTATION_LOCKED, Layer::enemy()),
+    )).id()
+}
+
+pub fn spawn_slinger(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    pos: Vec3,
+) -> Entity {
+    commands.spawn((
+        Hostile,
+        Slinger,
+        Brain::new(Archetype::Slinger, pos),
+        Member(Faction::Wild),
+        Loot(LootTableId::Slinger),
+        DeathEffect::Explode,
+        NavAgent::default(),
+        Awareness::default(),
+        Health::new(30.0),
+        Resistances { physical: 0.2, arc: -0.3, ..default() },
+        Poise(0.2),
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Cylinder { radius: 0.3, height: 1.0, ..default() })),
+            material: materials.add(Color::rgb_u8(90, 50, 120).into()),
+            transform: Transform::from_translation(pos),
+            ..default()
+        },
+        (RigidBody::Dynamic, Collider::cylinder(1.0, 0.3), LockedAxes::ROTATION_LOCKED, Layer::enemy()),
+    ))
+    // its head pokes out over the top
+    .with_children(|parent| weak_spot(parent, Vec3::Y * 0.6, 0.2, WEAK_SPOT_MULTIPLIER))
+    .id()
+}
+
+fn update_budget(
+    archetypes: Res<Archetypes>,
+    time_of_day: Res<TimeOfDay>,
+    corruption: Res<CorruptionMap>,
+    mut director: ResMut<SpawnDirector>,
+    brain_query: Query<(&Brain, &Transform), With<Hostile>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let player = player_query.get_single().expect("not one player");
+
+    let night = if time_of_day.is_night() { NIGHT_BUDGET } else { 0 };
+    director.budget = night + (corruption.get(player.translation) / CORRUPTION_PER_BUDGET) as u32;
+    director.active = brain_query.iter()
+        .filter(|(_, t)| t.translation.distance(player.translation) <= ACTIVE_RADIUS)
+        .map(|(b, _)| archetypes.get(b.archetype).cost)
+        .sum();
+}
+
+// whether the player's camera could see it being spawned
+fn in_view(camera_query: &Query<(&Camera, &GlobalTransform)>, pos: Vec3) -> bool {
+    camera_query.iter().any(|(camera, transform)| {
+        camera.world_to_ndc(transform, pos)
+            .is_some_and(|ndc| ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0 && (0.0..=1.0).contains(&ndc.z))
+    })
+}
+
+fn spawn_encounters(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    archetypes: Res<Archetypes>,
+    corruption: Res<CorruptionMap>,
+    mut director: ResMut<SpawnDirector>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if director.active >= director.budget {
+        return;
+    }
+
+    let player = player_query.get_single().expect("not one player");
+    let mut rng = rand::thread_rng();
+
+    // corrupted ground around the player makes things crawl out faster
+    director.countdown -= time.delta_seconds() * corruption.spawn_rate_multiplier(player.translation);
+    if director.countdown > 0.0 {
+        return;
+    }
+
+    // a point on the edge of the map, never right in front of the player
+    let pos = (0..SPAWN_ATTEMPTS)
+        .map(|_| {
+            let along = rng.gen_range(-WORLD_EXTENT..WORLD_EXTENT);
+            let edge = if rng.gen_bool(0.5) { WORLD_EXTENT } else { -WORLD_EXTENT };
+            if rng.gen_bool(0.5) {
+                Vec3::new(along, 0.5, edge)
+            } else {
+                Vec3::new(edge, 0.5, along)
+            }
+        })
+        .find(|p| p.distance(player.translation) >= MIN_SPAWN_DISTANCE && !in_view(&camera_query, *p));
+    // everywhere is in plain sight, try again next frame
+    let Some(pos) = pos else {
+        return;
+    };
+
+    let archetype = if rng.gen_bool(SHAMAN_CHANCE) {
+        Archetype::Shaman
+    } else if rng.gen_bool(SLINGER_CHANCE) {
+        Archetype::Slinger
+    } else if corruption.get(pos) >= CRAWLER_CORRUPTION {
+        Archetype::Crawler
+    } else {
+        Archetype::Construct
+    };
+    director.countdown = BASE_SPAWN_INTERVAL;
+    if director.active + archetypes.get(archetype).cost > director.budget {
+        return;
+    }
+
+    match archetype {
+        Archetype::Construct => spawn_construct(&mut commands, &mut meshes, &mut materials, pos),
+        Archetype::Shaman => spawn_shaman(&mut commands, &mut meshes, &mut materials, pos),
+        Archetype::Crawler => spawn_crawler(&mut commands, &mut meshes, &mut materials, pos),
+        Archetype::Slinger => spawn_slinger(&mut commands, &mut meshes, &mut materials, pos),
+    };
+}
+
+// come morning the ones that never found anything wander off
+fn despawn_stragglers(
+    mut commands: Commands,
+    archetypes: Res<Archetypes>,
+    mut director: ResMut<SpawnDirector>,
+    brain_query: Query<(Entity, &Brain, &Awareness, &Transform), With<Hostile>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if director.active <= director.budget {
+        return;
+    }
+    let player = player_query.get_single().expect("not one player");
+
+    let straggler = brain_query.iter()
+        .filter(|(.., a, _)| a.alertness() == Alertness::Unaware)
+        .map(|(e, b, _, t)| (e, b, t.translation.distance(player.translation), t.translation))
+        .filter(|(_, _, d, pos)| *d >= MIN_SPAWN_DISTANCE && *d <= ACTIVE_RADIUS && !in_view(&camera_query, *pos))
+        .max_by(|a, b| a.2.total_cmp(&b.2));
+
+    if let Some((entity, brain, ..)) = straggler {
+        director.active -= archetypes.get(brain.archetype).cost;
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    building::{BuildingGrid, BuildingKind, GridPos},
+    power::WindTurbine,
+    state::GameplaySet,
+};
+
+// the river runs east to west across the whole map
+const RIVER_Z: i32 = 5;
+const RIVER_WIDTH: i32 = 2;
+const RIVER_LENGTH: i32 = 40;
+// seconds between changes in the weather
+const GUST_INTERVAL: f32 = 20.0;
+// how quickly the wind follows a change, per second
+const WIND_RESPONSE: f32 = 0.1;
+
+pub struct EnvironmentPlugin;
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Wind>()
+            .add_systems(Startup, setup_river)
+            .add_systems(Update, (update_wind, draw_wind).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Resource)]
+pub struct Wind {
+    // 0.0 is dead calm, 1.0 a storm
+    pub strength: f32,
+    // angle around the y axis, not wrapped so it never swings the long way round
+    pub direction: f32,
+    target_strength: f32,
+    target_direction: f32,
+    gust: Timer,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Wind {
+            strength: 0.4,
+            direction: 0.0,
+            target_strength: 0.4,
+            target_direction: 0.0,
+            gust: Timer::from_seconds(GUST_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+impl Wind {
+    pub fn velocity(&self) -> Vec3 {
+        Quat::from_rotation_y(self.direction) * Vec3::NEG_Z * self.strength
+    }
+
+    /// Whips up a full storm right away, it calms down with the next gust.
+    pub fn storm(&mut self) {
+        self.strength = 1.0;
+        self.target_strength = 1.0;
+        self.gust.reset();
+    }
+}
+
+fn setup_river(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut grid: ResMut<BuildingGrid>,
+) {
+    for x in -RIVER_LENGTH..=RIVER_LENGTH {
+        for z in RIVER_Z..RIVER_Z + RIVER_WIDTH {
+            grid.add_water(GridPos(IVec2::new(x, z)));
+        }
+    }
+
+    let length = (RIVER_LENGTH * 2 + 1) as f32;
+    let width = RIVER_WIDTH as f32;
+    commands.spawn(PbrBundle {
+        mesh: meshes.add(Mesh::from(shape::Box::new(length, 0.02, width))),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgb_u8(50, 110, 200),
+            perceptual_roughness: 0.1,
+            ..default()
+        }),
+        transform: Transform::from_xyz(0.0, 0.01, RIVER_Z as f32 - 0.5 + width / 2.0),
+        ..default()
+    });
+}
+
+fn update_wind(
+    time: Res<Time>,
+    mut wind: ResMut<Wind>,
+) {
+    if wind.gust.tick(time.delta()).just_finished() {
+        let mut rng = rand::thread_rng();
+        wind.target_strength = rng.gen_range(0.0..1.0);
+        wind.target_direction += rng.gen_range(-0.5..0.5);
+    }
+
+    // drift towards the new weather instead of snapping to it
+    let t = (WIND_RESPONSE * time.delta_seconds()).min(1.0);
+    wind.strength += (wind.target_strength - wind.strength) * t;
+    wind.direction += (wind.target_direction - wind.direction) * t;
+}
+
+fn draw_wind(
+    wind: Res<Wind>,
+    turbine_query: Query<&Transform, With<WindTurbine>>,
+    mut gizmos: Gizmos,
+) {
+    // a streamer on top of every turbine shows where the wind blows
+    for transform in &turbine_query {
+        let top = transform.translation + Vec3::Y * BuildingKind::WindTurbine.size().y / 2.0;
+        gizmos.line(top, top + wind.velocity(), Color::WHITE);
+    }
+}
@@ -0,0 +1,194 @@
+use bevy::prelude::*;
+
+use crate::{
+    character::{Modifier, ModifierSource, Stat, Stats},
+    enemy::Hostile,
+    implement::ImplementStats,
+    inventory::Inventory,
+    item::{Item, ItemStack},
+    magic::SpellHit,
+    projectile::ProjectileImpact,
+    state::GameplaySet,
+    status::{ApplyStatus, Status, StatusKind},
+    Player,
+};
+
+// seconds a searing hit keeps burning
+const BURN_DURATION: f32 = 3.0;
+
+pub struct EquipmentPlugin;
+
+impl Plugin for EquipmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            equip_input,
+            apply_gear_bonuses,
+            on_hit_effects,
+        ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GearSlot {
+    Weapon,
+    Armor,
+    Trinket,
+}
+
+impl GearSlot {
+    pub const ALL: [GearSlot; 3] = [GearSlot::Weapon, GearSlot::Armor, GearSlot::Trinket];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GearSlot::Weapon => "Weapon",
+            GearSlot::Armor => "Armor",
+            GearSlot::Trinket => "Trinket",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            GearSlot::Weapon => 0,
+            GearSlot::Armor => 1,
+            GearSlot::Trinket => 2,
+        }
+    }
+}
+
+/// What all enchantments on the equipped gear add up to.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GearBonuses {
+    pub max_mana: f32,
+    pub mana_regen: f32,
+    // fraction on top of the base speed
+    pub speed: f32,
+    // burning per second set on everything hit
+    pub burn_on_hit: f32,
+    pub mana_on_hit: f32,
+    pub stability: f32,
+    // from the wand or staff in hand
+    pub implement: ImplementStats,
+}
+
+impl GearBonuses {
+    pub fn modifiers(&self) -> [Modifier; 7] {
+        [
+            Modifier { stat: Stat::MaxMana, amount: self.max_mana },
+            Modifier { stat: Stat::ManaRegen, amount: self.mana_regen },
+            Modifier { stat: Stat::Speed, amount: self.speed },
+            Modifier { stat: Stat::CastSpeed, amount: self.implement.cast_speed },
+            Modifier { stat: Stat::ManaEfficiency, amount: self.implement.mana_efficiency },
+            Modifier { stat: Stat::ExtraProjectiles, amount: self.implement.extra_projectiles as f32 },
+            Modifier { stat: Stat::Stability, amount: self.stability },
+        ]
+    }
+}
+
+#[derive(Component, Default)]
+pub struct Equipment {
+    slots: [Option<ItemStack>; 3],
+}
+
+impl Equipment {
+    pub fn get(&self, slot: GearSlot) -> Option<&ItemStack> {
+        self.slots[slot.index()].as_ref()
+    }
+
+    pub fn get_mut(&mut self, slot: GearSlot) -> Option<&mut ItemStack> {
+        self.slots[slot.index()].as_mut()
+    }
+
+    /// Puts `stack` into the slot for its item and hands back whatever was
+    /// in there before, or `stack` itself if it isn't gear.
+    pub fn equip(&mut self, stack: ItemStack) -> Option<ItemStack> {
+        let Some(slot) = stack.item.gear_slot() else {
+            return Some(stack);
+        };
+
+        self.slots[slot.index()].replace(stack)
+    }
+
+    pub fn unequip(&mut self, slot: GearSlot) -> Option<ItemStack> {
+        self.slots[slot.index()].take()
+    }
+
+    pub fn bonuses(&self) -> GearBonuses {
+        let mut bonuses = GearBonuses {
+            implement: self.get(GearSlot::Weapon).and_then(ImplementStats::of).unwrap_or_default(),
+            ..default()
+        };
+        for enchantment in self.slots.iter().flatten().flat_map(|s| s.enchantments()) {
+            enchantment.apply(&mut bonuses);
+        }
+        bonuses
+    }
+}
+
+// puts on a piece of gear from the inventory, swapping out what was worn
+// before, so pressing it repeatedly cycles through everything carried
+fn equip_input(
+    keys: Res<Input<KeyCode>>,
+    mut player_query: Query<(&mut Inventory, &mut Equipment), With<Player>>,
+) {
+    if !keys.just_pressed(KeyCode::N) {
+        return;
+    }
+
+    let (mut inventory, mut equipment) = player_query.get_single_mut().expect("not one player");
+
+    let carried: Vec<Item> = Item::ALL.into_iter()
+        .filter(|i| i.gear_slot().is_some() && inventory.count(*i) > 0)
+        .collect();
+    // fill empty slots before swapping anything out
+    let Some(gear) = carried.iter()
+        .find(|i| i.gear_slot().is_some_and(|s| equipment.get(s).is_none()))
+        .or(carried.first())
+        .copied()
+    else {
+        return;
+    };
+    let Some(stack) = inventory.extract(Some(gear), 1) else {
+        return;
+    };
+
+    // the piece we just took out freed up a slot for the old one
+    if let Some(previous) = equipment.equip(stack) {
+        inventory.insert(previous);
+    }
+}
+
+fn apply_gear_bonuses(
+    mut wearer_query: Query<(&Equipment, &mut Stats), Changed<Equipment>>,
+) {
+    for (equipment, mut stats) in &mut wearer_query {
+        stats.set_modifiers(ModifierSource::Gear, equipment.bonuses().modifiers());
+    }
+}
+
+fn on_hit_effects(
+    mut wearer_query: Query<(&Equipment, &mut Stats)>,
+    hostile_query: Query<(), With<Hostile>>,
+    mut hit_events: EventReader<SpellHit>,
+    mut impact_events: EventReader<ProjectileImpact>,
+    mut status_events: EventWriter<ApplyStatus>,
+) {
+    let spell_hits = hit_events.read().map(|e| (e.caster, e.target));
+    let projectile_hits = impact_events.read().filter_map(|e| Some((e.owner, e.target?)));
+
+    for (caster, target) in spell_hits.chain(projectile_hits) {
+        let Ok((equipment, mut stats)) = wearer_query.get_mut(caster) else {
+            continue;
+        };
+        let bonuses = equipment.bonuses();
+
+        if bonuses.mana_on_hit > 0.0 {
+            stats.restore_mana(bonuses.mana_on_hit);
+        }
+        if bonuses.burn_on_hit > 0.0 && hostile_query.contains(target) {
+            status_events.send(ApplyStatus {
+                target,
+                status: Status::new(StatusKind::Burning, BURN_DURATION, bonuses.burn_on_hit),
+            });
+        }
+    }
+}
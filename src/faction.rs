@@ -0,0 +1,382 @@
+use std::fmt::Write;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    dialogue::{Conversation, Speaker},
+    health::Died,
+    input::{Action, InputMap},
+    interaction::{interact, Interactable, INTERACT_RANGE},
+    inventory::Inventory,
+    item::{Item, ItemStack},
+    locale::Locale,
+    state::GameplaySet,
+    Player,
+};
+
+const MAX_REPUTATION: f32 = 100.0;
+// reputation from which a faction's attitude shifts a step either way
+const SHIFT_REPUTATION: f32 = 30.0;
+const KILL_PENALTY: f32 = 10.0;
+// killing off their enemies goes down well with everyone else
+const KILL_REWARD: f32 = 2.0;
+const TRADE_REWARD: f32 = 1.0;
+const TRADER_POS: Vec3 = Vec3::new(-4.0, 0.5, 4.0);
+// what the traders sell, for this many mana crystals at neutral standing
+const OFFERS: [(Item, u32); 4] = [
+    (Item::IronPlate, 1),
+    (Item::CopperPlate, 1),
+    (Item::Essence, 4),
+    (Item::RepairKit, 6),
+];
+
+pub struct FactionPlugin;
+
+impl Plugin for FactionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(Factions::starter())
+            .init_resource::<OpenTrader>()
+            .add_event::<OpenTrade>()
+            .add_systems(Startup, (spawn_trader, setup_trade_ui))
+            .add_systems(Update, (
+                reputation_from_kills,
+                toggle_trader.after(interact),
+                trade_input,
+                update_trade_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Faction {
+    Player,
+    // the constructs and everything corruption has twisted
+    Wild,
+    // the rival mages the shamans answer to
+    Guild,
+    Traders,
+}
+
+impl Faction {
+    pub const ALL: [Faction; 4] = [Faction::Player, Faction::Wild, Faction::Guild, Faction::Traders];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Faction::Player => "Player",
+            Faction::Wild => "Wild Constructs",
+            Faction::Guild => "Mage Guild",
+            Faction::Traders => "Traders",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stance {
+    Hostile,
+    Neutral,
+    Friendly,
+}
+
+impl Stance {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stance::Hostile => "hostile",
+            Stance::Neutral => "neutral",
+            Stance::Friendly => "friendly",
+        }
+    }
+
+    fn friendlier(self) -> Stance {
+        match self {
+            Stance::Hostile => Stance::Neutral,
+            _ => Stance::Friendly,
+        }
+    }
+
+    fn hostiler(self) -> Stance {
+        match self {
+            Stance::Friendly => Stance::Neutral,
+            _ => Stance::Hostile,
+        }
+    }
+}
+
+/// Who likes whom, and how much each faction thinks of the player.
+#[derive(Resource, Default)]
+pub struct Factions {
+    // looked up both ways round, anything missing is neutral
+    stances: HashMap<(Faction, Faction), Stance>,
+    reputation: HashMap<Faction, f32>,
+}
+
+impl Factions {
+    fn starter() -> Self {
+        let mut factions = Factions::default();
+        factions.set(Faction::Wild, Faction::Player, Stance::Hostile);
+        factions.set(Faction::Wild, Faction::Guild, Stance::Hostile);
+        factions.set(Faction::Wild, Faction::Traders, Stance::Hostile);
+        factions.set(Faction::Guild, Faction::Player, Stance::Hostile);
+        factions.set(Faction::Traders, Faction::Player, Stance::Friendly);
+        factions
+    }
+
+    pub fn set(&mut self, a: Faction, b: Faction, stance: Stance) {
+        self.stances.insert((a, b), stance);
+        self.stances.insert((b, a), stance);
+    }
+
+    pub fn stance(&self, a: Faction, b: Faction) -> Stance {
+        if a == b {
+            return Stance::Friendly;
+        }
+        self.stances.get(&(a, b)).copied().unwrap_or(Stance::Neutral)
+    }
+
+    pub fn reputation(&self, faction: Faction) -> f32 {
+        self.reputation.get(&faction).copied().unwrap_or(0.0)
+    }
+
+    pub fn change_reputation(&mut self, faction: Faction, amount: f32) {
+        if faction == Faction::Player {
+            return;
+        }
+        let reputation = self.reputation.entry(faction).or_insert(0.0);
+        *reputation = (*reputation + amount).clamp(-MAX_REPUTATION, MAX_REPUTATION);
+    }
+
+    /// How a faction treats the player, its usual stance swayed by reputation.
+    pub fn attitude(&self, faction: Faction) -> Stance {
+        let stance = self.stance(faction, Faction::Player);
+        let reputation = self.reputation(faction);
+        if reputation >= SHIFT_REPUTATION {
+            stance.friendlier()
+        } else if reputation <= -SHIFT_REPUTATION {
+            stance.hostiler()
+        } else {
+            stance
+        }
+    }
+
+    // half price at the best standing, one and a half times at the worst
+    pub fn price(&self, faction: Faction, base: u32) -> u32 {
+        let factor = 1.0 - self.reputation(faction) / MAX_REPUTATION / 2.0;
+        ((base as f32 * factor).ceil() as u32).max(1)
+    }
+}
+
+/// Which faction this belongs to.
+#[derive(Component, Clone, Copy)]
+pub struct Member(pub Faction);
+
+#[derive(Component)]
+pub struct Trader;
+
+#[derive(Resource, Default)]
+pub struct OpenTrader {
+    trader: Option<Entity>,
+    selected: usize,
+}
+
+impl OpenTrader {
+    pub fn trader(&self) -> Option<Entity> {
+        self.trader
+    }
+}
+
+/// Shows a trader's offers, sent once the player asks for them.
+#[derive(Event)]
+pub struct OpenTrade {
+    pub trader: Entity,
+}
+
+#[derive(Component)]
+struct TradeUi;
+
+#[derive(Component)]
+struct TradeText;
+
+fn spawn_trader(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        Trader,
+        Member(Faction::Traders),
+        Speaker(Conversation::Trader),
+        Interactable::new("Talk"),
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Capsule { radius: 0.3, depth: 0.6, ..default() })),
+            material: materials.add(Color::rgb_u8(200, 160, 60).into()),
+            transform: Transform::from_translation(TRADER_POS),
+            ..default()
+        },
+    ));
+}
+
+fn reputation_from_kills(
+    mut factions: ResMut<Factions>,
+    member_query: Query<&Member>,
+    player_query: Query<(), With<Player>>,
+    mut died_events: EventReader<Died>,
+) {
+    for event in died_events.read() {
+        let Ok(Member(victim)) = member_query.get(event.entity) else {
+            continue;
+        };
+        if !event.killer.is_some_and(|k| player_query.contains(k)) {
+            continue;
+        }
+
+        factions.change_reputation(*victim, -KILL_PENALTY);
+        for faction in Faction::ALL {
+            if faction != *victim && factions.stance(faction, *victim) == Stance::Hostile {
+                factions.change_reputation(faction, KILL_REWARD);
+            }
+        }
+    }
+}
+
+pub fn toggle_trader(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut open: ResMut<OpenTrader>,
+    player_query: Query<&Transform, With<Player>>,
+    trader_query: Query<&Transform, With<Trader>>,
+    mut trade_events: EventReader<OpenTrade>,
+) {
+    let player = player_query.get_single().expect("not one player");
+
+    // close once the player walks away
+    if let Some(entity) = open.trader {
+        let in_range = trader_query.get(entity)
+            .is_ok_and(|t| t.translation.distance(player.translation) <= INTERACT_RANGE);
+
+        if !in_range {
+            open.trader = None;
+        }
+    }
+
+    // asking in conversation opens the offers, the interact key puts them away
+    if let Some(event) = trade_events.read().last() {
+        open.trader = Some(event.trader);
+        open.selected = 0;
+    } else if input_map.just_pressed(Action::Interact, &keys) {
+        open.trader = None;
+    }
+}
+
+fn trade_input(
+    keys: Res<Input<KeyCode>>,
+    mut open: ResMut<OpenTrader>,
+    mut factions: ResMut<Factions>,
+    member_query: Query<&Member>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+) {
+    let Some(trader) = open.trader else {
+        return;
+    };
+
+    let count = OFFERS.len();
+    if keys.just_pressed(KeyCode::Down) {
+        open.selected = (open.selected + 1) % count;
+    }
+    if keys.just_pressed(KeyCode::Up) {
+        open.selected = (open.selected + count - 1) % count;
+    }
+
+    if !keys.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    // nobody sells to someone they can't stand
+    let Member(faction) = *member_query.get(trader).expect("trader without a faction");
+    if factions.attitude(faction) == Stance::Hostile {
+        return;
+    }
+
+    let mut inventory = player_query.get_single_mut().expect("not one player");
+    let (item, base) = OFFERS[open.selected];
+    let stack = ItemStack::new(item, 1);
+    if !inventory.can_insert(stack) || !inventory.remove(Item::ManaCrystal, factions.price(faction, base)) {
+        return;
+    }
+
+    inventory.insert(stack);
+    factions.change_reputation(faction, TRADE_REWARD);
+}
+
+fn setup_trade_ui(mut commands: Commands) {
+    commands.spawn((
+        TradeUi,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(20.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            TradeText,
+            TextBundle::from_section("", TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            }),
+        ));
+    });
+}
+
+fn update_trade_ui(
+    open: Res<OpenTrader>,
+    factions: Res<Factions>,
+    locale: Res<Locale>,
+    member_query: Query<&Member>,
+    player_query: Query<&Inventory, With<Player>>,
+    mut ui_query: Query<&mut Visibility, With<TradeUi>>,
+    mut text_query: Query<&mut Text, With<TradeText>>,
+) {
+    let mut visibility = ui_query.get_single_mut().expect("not one trade ui");
+    let Some(Ok(Member(faction))) = open.trader.map(|t| member_query.get(t)) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Visible;
+
+    let inventory = player_query.get_single().expect("not one player");
+
+    let mut content = format!(
+        "{} ({} {})",
+        locale.get(faction.name()),
+        locale.get(Item::ManaCrystal.name()),
+        inventory.count(Item::ManaCrystal),
+    );
+    for other in Faction::ALL.into_iter().filter(|f| *f != Faction::Player) {
+        let _ = write!(
+            content,
+            "\n{}: {:+.0} ({})",
+            locale.get(other.name()),
+            factions.reputation(other),
+            locale.get(factions.attitude(other).name()),
+        );
+    }
+
+    content.push('\n');
+    if factions.attitude(*faction) == Stance::Hostile {
+        let _ = write!(content, "\n{}", locale.get("They won't trade with you"));
+    }
+    for (i, (item, base)) in OFFERS.iter().enumerate() {
+        let marker = if i == open.selected { '>' } else { ' ' };
+        let price = locale.fill("{} crystals", &[&factions.price(*faction, *base)]);
+        let _ = write!(content, "\n{} {}: {}", marker, locale.get(item.name()), price);
+    }
+
+    text_query.get_single_mut().expect("not one trade text").sections[0].value = content;
+}
@@ -0,0 +1,481 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    building::{AimPoint, BuildingKind, BuildingPlaced, BuildingRemoved},
+    cooldown::{Ability, Cooldowns},
+    enemy::Hostile,
+    ground::DropItem,
+    health::{Damage, DamageEvent, DamageType, Health, Resistances},
+    input::{Action, InputMap},
+    interaction::{interact, Interactable, Interacted},
+    inventory::Inventory,
+    item::{Item, ItemStack},
+    locale::Locale,
+    state::GameplaySet,
+    storage::Chest,
+    telekinesis::Held,
+    wildlife::Wildlife,
+};
+
+const GOLEM_SPEED: f32 = 2.0;
+const GOLEM_HEALTH: f32 = 120.0;
+// close enough to a waypoint or to reach into a chest
+const ARRIVE_DISTANCE: f32 = 1.0;
+const ATTACK_RANGE: f32 = 1.3;
+const ATTACK_INTERVAL: f32 = 1.2;
+const ATTACK_DAMAGE: f32 = 15.0;
+// how much one haul trip carries
+const HAUL_AMOUNT: u32 = 20;
+const GUARD_RADIUS: f32 = 6.0;
+// seconds spent at each waypoint
+const PATROL_PAUSE: f32 = 1.5;
+
+pub struct GolemPlugin;
+
+impl Plugin for GolemPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<OpenGolem>()
+            .add_systems(Startup, setup_golem_ui)
+            .add_systems(Update, (
+                forge_golems,
+                toggle_golem.after(interact),
+                programming_input,
+                run_golems,
+                update_golem_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+/// One step of a golem's program.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Instruction {
+    MoveTo(Vec3),
+    // walks over to the chest and takes up to `count` of `item`, or of
+    // anything if it's `None`
+    Take { chest: Entity, item: Option<Item>, count: u32 },
+    // walks over to the chest and empties its hands into it
+    Put { chest: Entity },
+    // seconds
+    Wait(f32),
+    // fights every hostile that comes within `radius` of `center`, done
+    // once there are none left
+    Guard { center: Vec3, radius: f32 },
+}
+
+/// A list of instructions, run top to bottom.
+#[derive(Clone, Debug, Default)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+    // start over at the top after the last one
+    pub looping: bool,
+}
+
+impl Program {
+    // stops to look around at every waypoint
+    pub fn patrol(points: &[Vec3]) -> Self {
+        Program {
+            instructions: points.iter()
+                .flat_map(|p| [Instruction::MoveTo(*p), Instruction::Wait(PATROL_PAUSE)])
+                .collect(),
+            looping: true,
+        }
+    }
+
+    pub fn haul(from: Entity, to: Entity, item: Option<Item>) -> Self {
+        Program {
+            instructions: vec![
+                Instruction::Take { chest: from, item, count: HAUL_AMOUNT },
+                Instruction::Put { chest: to },
+            ],
+            looping: true,
+        }
+    }
+
+    pub fn guard(center: Vec3, radius: f32) -> Self {
+        Program {
+            instructions: vec![Instruction::Guard { center, radius }],
+            looping: true,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Golem {
+    pub forge: Entity,
+    pub program: Program,
+    // the instruction being run
+    pub step: usize,
+    pub carrying: Option<ItemStack>,
+    waited: f32,
+}
+
+impl Golem {
+    /// Swaps in a new program and starts it from the top.
+    pub fn load(&mut self, program: Program) {
+        self.program = program;
+        self.step = 0;
+        self.waited = 0.0;
+    }
+
+    fn current(&self) -> Option<Instruction> {
+        self.program.instructions.get(self.step).copied()
+    }
+
+    fn advance(&mut self) {
+        self.step += 1;
+        self.waited = 0.0;
+        if self.step >= self.program.instructions.len() && self.program.looping {
+            self.step = 0;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ProgramKind {
+    Patrol,
+    Haul,
+    Guard,
+}
+
+impl ProgramKind {
+    const ALL: [ProgramKind; 3] = [ProgramKind::Patrol, ProgramKind::Haul, ProgramKind::Guard];
+
+    fn name(&self) -> &'static str {
+        match self {
+            ProgramKind::Patrol => "Patrol",
+            ProgramKind::Haul => "Haul",
+            ProgramKind::Guard => "Guard",
+        }
+    }
+
+    fn hint(&self) -> &'static str {
+        match self {
+            ProgramKind::Patrol => "Enter adds the aim point as a waypoint",
+            ProgramKind::Haul => "Enter on a chest sets where to take from, then where to put",
+            ProgramKind::Guard => "Enter sets the aim point as the spot to guard",
+        }
+    }
+}
+
+// the program being put together for the golem the player is talking to
+#[derive(Resource, Default)]
+struct OpenGolem {
+    golem: Option<Entity>,
+    selected: usize,
+    points: Vec<Vec3>,
+    chests: Vec<Entity>,
+}
+
+impl OpenGolem {
+    fn kind(&self) -> ProgramKind {
+        ProgramKind::ALL[self.selected]
+    }
+
+    fn program(&self) -> Option<Program> {
+        match self.kind() {
+            ProgramKind::Patrol if !self.points.is_empty() => Some(Program::patrol(&self.points)),
+            ProgramKind::Haul if self.chests.len() == 2 => Some(Program::haul(self.chests[0], self.chests[1], None)),
+            ProgramKind::Guard => self.points.first().map(|c| Program::guard(*c, GUARD_RADIUS)),
+            _ => None,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.points.clear();
+        self.chests.clear();
+    }
+}
+
+#[derive(Component)]
+struct GolemUi;
+
+#[derive(Component)]
+struct GolemText;
+
+fn forge_golems(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut placed_events: EventReader<BuildingPlaced>,
+    mut removed_events: EventReader<BuildingRemoved>,
+    golem_query: Query<(Entity, &Golem, &Transform)>,
+    mut drop_events: EventWriter<DropItem>,
+) {
+    // every forge animates one golem right next to it
+    for event in placed_events.read().filter(|e| e.kind == BuildingKind::GolemForge) {
+        commands.spawn((
+            Golem { forge: event.entity, program: Program::default(), step: 0, carrying: None, waited: 0.0 },
+            Interactable::new("Program Golem"),
+            Health::new(GOLEM_HEALTH),
+            Resistances { physical: 0.3, ..default() },
+            PbrBundle {
+                mesh: meshes.add(Mesh::from(shape::Box::new(0.7, 1.2, 0.5))),
+                material: materials.add(StandardMaterial {
+                    base_color: BuildingKind::GolemForge.color(),
+                    emissive: Color::rgb(0.1, 0.3, 0.4),
+                    ..default()
+                }),
+                transform: Transform::from_translation(event.pos.to_world() + Vec3::new(1.0, 0.6, 0.0)),
+                ..default()
+            },
+            RigidBody::Dynamic,
+            Collider::cuboid(0.7, 1.2, 0.5),
+            LockedAxes::ROTATION_LOCKED,
+        ));
+    }
+
+    // and crumbles when it's torn down
+    for event in removed_events.read().filter(|e| e.kind == BuildingKind::GolemForge) {
+        for (entity, golem, transform) in golem_query.iter().filter(|(_, g, _)| g.forge == event.entity) {
+            if let Some(stack) = golem.carrying {
+                drop_events.send(DropItem { stack, pos: transform.translation, magnetic: false });
+            }
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn toggle_golem(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut open: ResMut<OpenGolem>,
+    mut golem_query: Query<&mut Golem>,
+    mut interacted_events: EventReader<Interacted>,
+) {
+    if open.golem.is_some_and(|e| !golem_query.contains(e)) {
+        open.golem = None;
+    }
+
+    let used = interacted_events.read().map(|e| e.entity).find(|e| golem_query.contains(*e));
+
+    if !input_map.just_pressed(Action::Interact, &keys) {
+        return;
+    }
+
+    // closing the panel uploads whatever was put together
+    if let Some(entity) = open.golem.take() {
+        if let (Some(program), Ok(mut golem)) = (open.program(), golem_query.get_mut(entity)) {
+            golem.load(program);
+        }
+        open.clear();
+        return;
+    }
+
+    open.golem = used;
+}
+
+fn programming_input(
+    keys: Res<Input<KeyCode>>,
+    aim: Res<AimPoint>,
+    mut open: ResMut<OpenGolem>,
+    chest_query: Query<(Entity, &Transform), With<Chest>>,
+) {
+    if open.golem.is_none() {
+        return;
+    }
+
+    // switching what to program starts it over
+    let count = ProgramKind::ALL.len();
+    if keys.just_pressed(KeyCode::Down) {
+        open.selected = (open.selected + 1) % count;
+        open.clear();
+    }
+    if keys.just_pressed(KeyCode::Up) {
+        open.selected = (open.selected + count - 1) % count;
+        open.clear();
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        open.clear();
+    }
+
+    if !keys.just_pressed(KeyCode::Return) {
+        return;
+    }
+    let Some(point) = aim.0 else {
+        return;
+    };
+
+    match open.kind() {
+        ProgramKind::Patrol => open.points.push(point),
+        ProgramKind::Guard => open.points = vec![point],
+        ProgramKind::Haul => {
+            let chest = chest_query.iter()
+                .map(|(e, t)| (e, t.translation.distance(point)))
+                .filter(|(_, d)| *d <= ARRIVE_DISTANCE)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(e, _)| e);
+            if let Some(chest) = chest {
+                if open.chests.len() == 2 {
+                    open.chests.clear();
+                }
+                open.chests.push(chest);
+            }
+        }
+    }
+}
+
+// steers towards `target` and says whether it's there yet
+fn walk_to(velocity: &mut LinearVelocity, from: Vec3, target: Vec3, reach: f32) -> bool {
+    let mut offset = target - from;
+    offset.y = 0.0;
+
+    let arrived = offset.length() <= reach;
+    let direction = if arrived { Vec3::ZERO } else { offset.normalize_or_zero() };
+    velocity.x = direction.x * GOLEM_SPEED;
+    velocity.z = direction.z * GOLEM_SPEED;
+    arrived
+}
+
+fn run_golems(
+    time: Res<Time>,
+    mut cooldowns: ResMut<Cooldowns>,
+    mut golem_query: Query<(Entity, &mut Golem, &Transform, &mut LinearVelocity), Without<Held>>,
+    mut chest_query: Query<(&Transform, &mut Inventory), With<Chest>>,
+    hostile_query: Query<(Entity, &Transform), (With<Hostile>, With<Health>, Without<Wildlife>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, mut golem, transform, mut velocity) in &mut golem_query {
+        let pos = transform.translation;
+
+        let Some(instruction) = golem.current() else {
+            walk_to(&mut velocity, pos, pos, 0.0);
+            continue;
+        };
+
+        let done = match instruction {
+            Instruction::MoveTo(target) => walk_to(&mut velocity, pos, target, ARRIVE_DISTANCE),
+            Instruction::Wait(seconds) => {
+                walk_to(&mut velocity, pos, pos, 0.0);
+                golem.waited += time.delta_seconds();
+                golem.waited >= seconds
+            }
+            Instruction::Take { chest, item, count } => {
+                // a chest that's gone can't be waited on
+                let Ok((chest_transform, mut inventory)) = chest_query.get_mut(chest) else {
+                    golem.advance();
+                    continue;
+                };
+                if !walk_to(&mut velocity, pos, chest_transform.translation, ARRIVE_DISTANCE) {
+                    continue;
+                }
+
+                // wait around until there's something to take
+                if golem.carrying.is_none() {
+                    golem.carrying = inventory.extract(item, count);
+                }
+                golem.carrying.is_some()
+            }
+            Instruction::Put { chest } => {
+                let Ok((chest_transform, mut inventory)) = chest_query.get_mut(chest) else {
+                    golem.advance();
+                    continue;
+                };
+                if !walk_to(&mut velocity, pos, chest_transform.translation, ARRIVE_DISTANCE) {
+                    continue;
+                }
+
+                // whatever doesn't fit stays in hand until there's room
+                if let Some(stack) = golem.carrying {
+                    let left = inventory.insert(stack);
+                    golem.carrying = (left > 0).then_some(ItemStack { count: left, ..stack });
+                }
+                golem.carrying.is_none()
+            }
+            Instruction::Guard { center, radius } => {
+                let target = hostile_query.iter()
+                    .filter(|(_, t)| t.translation.distance(center) <= radius)
+                    .min_by(|a, b| a.1.translation.distance(pos).total_cmp(&b.1.translation.distance(pos)));
+
+                match target {
+                    Some((target, target_transform)) => {
+                        if walk_to(&mut velocity, pos, target_transform.translation, ATTACK_RANGE)
+                            && cooldowns.is_ready(entity, Ability::GolemAttack)
+                        {
+                            cooldowns.start(entity, Ability::GolemAttack, ATTACK_INTERVAL);
+                            damage_events.send(DamageEvent::new(
+                                target,
+                                Damage::new(ATTACK_DAMAGE, DamageType::Physical),
+                                Some(entity),
+                            ));
+                        }
+                        false
+                    }
+                    // nothing to fight, head back to the post
+                    None => walk_to(&mut velocity, pos, center, ARRIVE_DISTANCE),
+                }
+            }
+        };
+
+        if done {
+            golem.advance();
+        }
+    }
+}
+
+fn setup_golem_ui(mut commands: Commands) {
+    commands.spawn((
+        GolemUi,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(20.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            GolemText,
+            TextBundle::from_section("", TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            }),
+        ));
+    });
+}
+
+fn update_golem_ui(
+    locale: Res<Locale>,
+    open: Res<OpenGolem>,
+    golem_query: Query<&Golem>,
+    mut ui_query: Query<&mut Visibility, With<GolemUi>>,
+    mut text_query: Query<&mut Text, With<GolemText>>,
+) {
+    let mut visibility = ui_query.get_single_mut().expect("not one golem ui");
+    let Some(golem) = open.golem.and_then(|e| golem_query.get(e).ok()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Visible;
+
+    let mut content = locale.fill("Golem ({} steps loaded)", &[&golem.program.instructions.len()]);
+    for (i, kind) in ProgramKind::ALL.iter().enumerate() {
+        let marker = if i == open.selected { '>' } else { ' ' };
+        let _ = write!(content, "\n{} {}", marker, locale.get(kind.name()));
+    }
+
+    let progress = match open.kind() {
+        ProgramKind::Patrol => locale.fill("{} waypoints", &[&open.points.len()]),
+        ProgramKind::Haul => locale.fill("{}/2 chests", &[&open.chests.len()]),
+        ProgramKind::Guard if open.points.is_empty() => locale.get("Post not set").to_string(),
+        ProgramKind::Guard => locale.get("Post set").to_string(),
+    };
+    let _ = write!(
+        content,
+        "\n\n{}\n{}\n{}",
+        locale.get(open.kind().hint()),
+        progress,
+        locale.get("Backspace clears, E uploads and closes"),
+    );
+
+    text_query.get_single_mut().expect("not one golem text").sections[0].value = content;
+}
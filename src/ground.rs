@@ -0,0 +1,266 @@
+use bevy::prelude::*;
+
+use crate::{
+    belt::BeltItemAssets,
+    building::{BuildingKind, BuildingPlaced},
+    inventory::Inventory,
+    item::ItemStack,
+    state::GameplaySet,
+    storage::{Chest, CHEST_SLOTS},
+    Player,
+};
+
+const ITEM_HEIGHT: f32 = 0.15;
+const VACUUM_SPEED: f32 = 4.0;
+// close enough to be picked up
+const PICKUP_DISTANCE: f32 = 0.4;
+const COLLECTOR_RADIUS: f32 = 3.0;
+
+pub struct GroundItemPlugin;
+
+impl Plugin for GroundItemPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<GroundItemSettings>()
+            .add_event::<DropItem>()
+            .add_systems(Update, (
+                init_collectors,
+                drop_player_items,
+                spawn_dropped_items,
+                merge_ground_items,
+                expire_ground_items,
+                vacuum_ground_items,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Resource)]
+pub struct GroundItemSettings {
+    // seconds until an untouched stack disappears
+    pub despawn_after: f32,
+    pub pickup_radius: f32,
+    // how far away loot starts flying towards the player
+    pub magnet_radius: f32,
+    pub merge_radius: f32,
+}
+
+impl Default for GroundItemSettings {
+    fn default() -> Self {
+        GroundItemSettings {
+            despawn_after: 300.0,
+            pickup_radius: 1.5,
+            magnet_radius: 5.0,
+            merge_radius: 1.0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct GroundItem {
+    pub stack: ItemStack,
+    // seconds since it was dropped
+    pub age: f32,
+    // pulled in from further away, for loot
+    pub magnetic: bool,
+}
+
+/// A chest that pulls in nearby ground items.
+#[derive(Component)]
+pub struct Collector {
+    pub radius: f32,
+}
+
+#[derive(Event)]
+pub struct DropItem {
+    pub stack: ItemStack,
+    pub pos: Vec3,
+    pub magnetic: bool,
+}
+
+fn init_collectors(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read().filter(|e| e.kind == BuildingKind::Collector) {
+        commands.entity(event.entity).insert((
+            Chest,
+            Collector { radius: COLLECTOR_RADIUS },
+            Inventory::new(CHEST_SLOTS),
+        ));
+    }
+}
+
+fn drop_player_items(
+    keys: Res<Input<KeyCode>>,
+    mut player_query: Query<(&mut Inventory, &Transform), With<Player>>,
+    mut drop_events: EventWriter<DropItem>,
+) {
+    if !keys.just_pressed(KeyCode::X) {
+        return;
+    }
+
+    // throw the first stack in front of the player
+    let (mut inventory, transform) = player_query.get_single_mut().expect("not one player");
+    if let Some(stack) = inventory.extract(None, u32::MAX) {
+        let mut forward = transform.forward();
+        forward.y = 0.0;
+
+        drop_events.send(DropItem {
+            stack,
+            pos: transform.translation + forward.normalize_or_zero() * 1.5,
+            magnetic: false,
+        });
+    }
+}
+
+fn ground_item_transform(stack: ItemStack, pos: Vec3) -> Transform {
+    // bigger stacks look a little bigger
+    let fill = stack.count as f32 / stack.item.max_stack() as f32;
+    Transform::from_xyz(pos.x, ITEM_HEIGHT, pos.z).with_scale(Vec3::splat(0.6 + 0.6 * fill))
+}
+
+fn spawn_dropped_items(
+    mut commands: Commands,
+    assets: Res<BeltItemAssets>,
+    settings: Res<GroundItemSettings>,
+    mut ground_query: Query<(&mut GroundItem, &Transform)>,
+    mut drop_events: EventReader<DropItem>,
+) {
+    for event in drop_events.read() {
+        let mut remaining = event.stack.count;
+
+        // top up stacks lying around first
+        for (mut ground, transform) in &mut ground_query {
+            let close = transform.translation.distance(event.pos) <= settings.merge_radius;
+            if !close || ground.stack.item != event.stack.item {
+                continue;
+            }
+
+            let moved = remaining.min(ground.stack.item.max_stack() - ground.stack.count);
+            ground.stack.count += moved;
+            ground.age = 0.0;
+            ground.magnetic |= event.magnetic;
+            remaining -= moved;
+        }
+
+        while remaining > 0 {
+            let count = remaining.min(event.stack.item.max_stack());
+            let stack = ItemStack::new(event.stack.item, count);
+            remaining -= count;
+
+            commands.spawn((
+                GroundItem { stack, age: 0.0, magnetic: event.magnetic },
+                PbrBundle {
+                    mesh: assets.mesh.clone(),
+                    material: assets.materials[&stack.item].clone(),
+                    transform: ground_item_transform(stack, event.pos),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+fn merge_ground_items(
+    mut commands: Commands,
+    settings: Res<GroundItemSettings>,
+    mut ground_query: Query<(Entity, &mut GroundItem, &mut Transform)>,
+) {
+    let mut combinations = ground_query.iter_combinations_mut();
+    while let Some([(_, mut a, a_transform), (b_entity, mut b, b_transform)]) = combinations.fetch_next() {
+        let mergeable = a.stack.item == b.stack.item
+            && a.stack.count > 0
+            && b.stack.count > 0
+            && a_transform.translation.distance(b_transform.translation) <= settings.merge_radius;
+        if !mergeable {
+            continue;
+        }
+
+        let moved = b.stack.count.min(a.stack.item.max_stack() - a.stack.count);
+        a.stack.count += moved;
+        b.stack.count -= moved;
+        a.age = a.age.min(b.age);
+        a.magnetic |= b.magnetic;
+
+        if b.stack.count == 0 {
+            commands.entity(b_entity).despawn_recursive();
+        }
+    }
+
+    for (_, ground, mut transform) in &mut ground_query {
+        if ground.stack.count > 0 {
+            *transform = ground_item_transform(ground.stack, transform.translation);
+        }
+    }
+}
+
+fn expire_ground_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<GroundItemSettings>,
+    mut ground_query: Query<(Entity, &mut GroundItem)>,
+) {
+    for (entity, mut ground) in &mut ground_query {
+        ground.age += time.delta_seconds();
+        if ground.age >= settings.despawn_after {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn vacuum_ground_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<GroundItemSettings>,
+    mut ground_query: Query<(Entity, &mut GroundItem, &mut Transform)>,
+    mut player_query: Query<(&mut Inventory, &Transform), (With<Player>, Without<GroundItem>)>,
+    mut collector_query: Query<
+        (&mut Inventory, &Transform, &Collector),
+        (Without<Player>, Without<GroundItem>),
+    >,
+) {
+    let (mut player_inventory, player_transform) = player_query.get_single_mut().expect("not one player");
+
+    for (entity, mut ground, mut transform) in &mut ground_query {
+        if ground.stack.count == 0 {
+            continue;
+        }
+
+        // the player has priority over collectors
+        let player_pos = player_transform.translation;
+        let radius = if ground.magnetic { settings.magnet_radius } else { settings.pickup_radius };
+        let target = if player_pos.distance(transform.translation) <= radius
+            && player_inventory.space_for(ground.stack.item) > 0
+        {
+            Some((&mut *player_inventory, player_pos))
+        } else {
+            collector_query.iter_mut()
+                .filter(|(inv, t, c)| {
+                    t.translation.distance(transform.translation) <= c.radius
+                        && inv.space_for(ground.stack.item) > 0
+                })
+                .min_by(|a, b| {
+                    a.1.translation.distance(transform.translation)
+                        .total_cmp(&b.1.translation.distance(transform.translation))
+                })
+                .map(|(inv, t, _)| (inv.into_inner(), t.translation))
+        };
+
+        let Some((inventory, target_pos)) = target else {
+            continue;
+        };
+
+        let mut to_target = target_pos - transform.translation;
+        to_target.y = 0.0;
+
+        if to_target.length() > PICKUP_DISTANCE {
+            transform.translation += to_target.normalize() * VACUUM_SPEED * time.delta_seconds();
+            continue;
+        }
+
+        ground.stack.count = inventory.insert(ground.stack);
+        if ground.stack.count == 0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use bevy::prelude::*;
+
+use crate::{building::Building, state::GameplaySet, Player};
+
+// where the player comes back after dying
+const RESPAWN_POS: Vec3 = Vec3::new(0.0, 1.0, 0.0);
+// seconds of safety after coming back
+const RESPAWN_INVULNERABILITY: f32 = 3.0;
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<DamageEvent>()
+            .add_event::<DamageDealt>()
+            .add_event::<Died>()
+            .add_systems(Update, (tick_invulnerability, respawn_player).in_set(GameplaySet))
+            .add_systems(PostUpdate, (apply_damage, despawn_dead).chain());
+    }
+}
+
+#[derive(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Health { current: max, max }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+
+    // only the damage pipeline hurts things, everything else sends a `DamageEvent`
+    fn take(&mut self, damage: Damage, resistances: Option<&Resistances>) -> f32 {
+        let amount = resistances.map_or(damage.amount, |r| r.mitigate(damage));
+        self.current -= amount;
+        amount
+    }
+
+    pub fn heal(&mut self, amount: f32) {
+        self.current = (self.current + amount).min(self.max);
+    }
+}
+
+/// Ignores all damage until `remaining` runs out.
+#[derive(Component)]
+pub struct Invulnerable {
+    pub remaining: f32,
+}
+
+/// Can't be hit by the same source again for `window` seconds, so a swarm
+/// of attackers can't land everything in the same instant. Periodic
+/// damage neither counts nor gets blocked.
+#[derive(Component)]
+pub struct HitImmunity {
+    pub window: f32,
+    // sources that hit recently and the seconds until they can again
+    recent: Vec<(Entity, f32)>,
+}
+
+impl HitImmunity {
+    pub fn new(window: f32) -> Self {
+        HitImmunity { window, recent: Vec::new() }
+    }
+
+    pub fn is_immune_to(&self, source: Entity) -> bool {
+        self.recent.iter().any(|(e, _)| *e == source)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DamageType {
+    Physical,
+    Fire,
+    Frost,
+    Arc,
+}
+
+impl DamageType {
+    pub fn color(&self) -> Color {
+        match self {
+            DamageType::Physical => Color::WHITE,
+            DamageType::Fire => Color::ORANGE,
+            DamageType::Frost => Color::CYAN,
+            DamageType::Arc => Color::VIOLET,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Damage {
+    pub amount: f32,
+    pub kind: DamageType,
+}
+
+impl Damage {
+    pub fn new(amount: f32, kind: DamageType) -> Self {
+        Damage { amount, kind }
+    }
+}
+
+/// The fraction of each damage type that gets shrugged off, 1.0 is
+/// immune and negative values are weaknesses.
+#[derive(Component, Clone, Copy, Default)]
+pub struct Resistances {
+    pub physical: f32,
+    pub fire: f32,
+    pub frost: f32,
+    pub arc: f32,
+    // on top of every type, from status effects
+    pub bonus: f32,
+}
+
+impl Resistances {
+    pub fn get(&self, kind: DamageType) -> f32 {
+        let resistance = match kind {
+            DamageType::Physical => self.physical,
+            DamageType::Fire => self.fire,
+            DamageType::Frost => self.frost,
+            DamageType::Arc => self.arc,
+        };
+        resistance + self.bonus
+    }
+
+    pub fn mitigate(&self, damage: Damage) -> f32 {
+        damage.amount * (1.0 - self.get(damage.kind).min(1.0))
+    }
+}
+
+/// Hurts `target`, whether it's the player, an enemy, a machine or a wall.
+#[derive(Event, Clone, Copy)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub damage: Damage,
+    // whoever dealt it, if anyone
+    pub source: Option<Entity>,
+    // a tick of something ongoing like a burn or a beam
+    pub periodic: bool,
+}
+
+impl DamageEvent {
+    pub fn new(target: Entity, damage: Damage, source: Option<Entity>) -> Self {
+        DamageEvent { target, damage, source, periodic: false }
+    }
+
+    pub fn periodic(self) -> Self {
+        DamageEvent { periodic: true, ..self }
+    }
+}
+
+/// What actually got through after resistances.
+#[derive(Event)]
+pub struct DamageDealt {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub kind: DamageType,
+    pub amount: f32,
+    // hit a weakness and did more than it would have unresisted
+    pub critical: bool,
+}
+
+/// Sent once when something's health runs out.
+#[derive(Event)]
+pub struct Died {
+    pub entity: Entity,
+    pub killer: Option<Entity>,
+}
+
+pub fn apply_damage(
+    mut health_query: Query<(&mut Health, Option<&Resistances>, Option<&mut HitImmunity>, Has<Invulnerable>)>,
+    mut damage_events: EventReader<DamageEvent>,
+    mut dealt_events: EventWriter<DamageDealt>,
+    mut died_events: EventWriter<Died>,
+) {
+    for event in damage_events.read() {
+        let Ok((mut health, resistances, immunity, invulnerable)) = health_query.get_mut(event.target) else {
+            continue;
+        };
+        // already dead things don't die twice
+        if invulnerable || health.is_dead() {
+            continue;
+        }
+        if let (Some(mut immunity), Some(source), false) = (immunity, event.source, event.periodic) {
+            if immunity.is_immune_to(source) {
+                continue;
+            }
+            let window = immunity.window;
+            immunity.recent.push((source, window));
+        }
+
+        let amount = health.take(event.damage, resistances);
+        dealt_events.send(DamageDealt {
+            target: event.target,
+            source: event.source,
+            kind: event.damage.kind,
+            amount,
+            critical: amount > event.damage.amount,
+        });
+        if health.is_dead() {
+            died_events.send(Died { entity: event.target, killer: event.source });
+        }
+    }
+}
+
+fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut invulnerable_query: Query<(Entity, &mut Invulnerable)>,
+    mut immunity_query: Query<&mut HitImmunity>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut invulnerable) in &mut invulnerable_query {
+        invulnerable.remaining -= dt;
+        if invulnerable.remaining <= 0.0 {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+    }
+
+    for mut immunity in &mut immunity_query {
+        immunity.recent.retain_mut(|(_, remaining)| {
+            *remaining -= dt;
+            *remaining > 0.0
+        });
+    }
+}
+
+// back at the start with full health and a moment to get their bearings
+fn respawn_player(
+    mut commands: Commands,
+    mut player_query: Query<(&mut Health, &mut Transform), With<Player>>,
+    mut died_events: EventReader<Died>,
+) {
+    for event in died_events.read() {
+        let Ok((mut health, mut transform)) = player_query.get_mut(event.entity) else {
+            continue;
+        };
+
+        health.current = health.max;
+        transform.translation = RESPAWN_POS;
+        commands.entity(event.entity).insert(Invulnerable { remaining: RESPAWN_INVULNERABILITY });
+    }
+}
+
+pub fn despawn_dead(
+    mut commands: Commands,
+    // buildings are removed through the grid instead, and the player
+    // has to stick around for everything that expects one
+    keep_query: Query<(), Or<(With<Building>, With<Player>)>>,
+    mut died_events: EventReader<Died>,
+) {
+    for event in died_events.read() {
+        if keep_query.contains(event.entity) {
+            continue;
+        }
+        if let Some(entity) = commands.get_entity(event.entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resistances_shrug_off_their_fraction() {
+        let resistances = Resistances { physical: 0.75, arc: -0.5, ..default() };
+
+        assert_eq!(resistances.mitigate(Damage::new(10.0, DamageType::Physical)), 2.5);
+        // weaknesses make it hurt more
+        assert_eq!(resistances.mitigate(Damage::new(10.0, DamageType::Arc)), 15.0);
+        assert_eq!(resistances.mitigate(Damage::new(10.0, DamageType::Fire)), 10.0);
+    }
+
+    #[test]
+    fn resistance_never_heals() {
+        let resistances = Resistances { frost: 1.5, ..default() };
+
+        assert_eq!(resistances.mitigate(Damage::new(10.0, DamageType::Frost)), 0.0);
+    }
+
+    #[test]
+    fn taking_damage_goes_through_resistances() {
+        let mut health = Health::new(20.0);
+        let resistances = Resistances { physical: 0.5, ..default() };
+
+        assert_eq!(health.take(Damage::new(10.0, DamageType::Physical), Some(&resistances)), 5.0);
+        assert_eq!(health.take(Damage::new(10.0, DamageType::Fire), None), 10.0);
+        assert_eq!(health.current, 5.0);
+        assert!(!health.is_dead());
+    }
+
+    #[test]
+    fn healing_stops_at_max() {
+        let mut health = Health::new(20.0);
+        health.take(Damage::new(5.0, DamageType::Physical), None);
+
+        health.heal(100.0);
+        assert_eq!(health.current, health.max);
+    }
+
+    #[test]
+    fn bonus_applies_to_every_type() {
+        let resistances = Resistances { fire: 0.25, bonus: 0.25, ..default() };
+
+        assert_eq!(resistances.get(DamageType::Fire), 0.5);
+        assert_eq!(resistances.get(DamageType::Frost), 0.25);
+        // still capped with the bonus on top
+        assert_eq!(Resistances { frost: 0.8, bonus: 0.5, ..default() }.mitigate(Damage::new(10.0, DamageType::Frost)), 0.0);
+    }
+}
@@ -0,0 +1,128 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::{BuildingGrid, BuildingKind, BuildingPlaced, Facing, GridPos},
+    machine::Machine,
+    state::GameplaySet,
+};
+
+pub const AMBIENT_TEMPERATURE: f32 = 20.0;
+// machines start slowing down here
+pub const THROTTLE_TEMPERATURE: f32 = 80.0;
+// and shut off completely here until they are back below `RESTART_TEMPERATURE`
+pub const SHUTDOWN_TEMPERATURE: f32 = 100.0;
+pub const RESTART_TEMPERATURE: f32 = 60.0;
+
+// fraction of the excess heat lost to the air every second
+const AIR_COOLING: f32 = 0.02;
+
+pub struct HeatPlugin;
+
+impl Plugin for HeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            init_heat,
+            update_heat,
+            show_heat,
+        ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Component)]
+pub struct Heat {
+    pub temperature: f32,
+    pub overheated: bool,
+}
+
+impl Default for Heat {
+    fn default() -> Self {
+        Heat {
+            temperature: AMBIENT_TEMPERATURE,
+            overheated: false,
+        }
+    }
+}
+
+impl Heat {
+    /// Multiplier for the machine's crafting speed.
+    pub fn speed_factor(&self) -> f32 {
+        if self.overheated {
+            return 0.0;
+        }
+
+        let over = (self.temperature - THROTTLE_TEMPERATURE).max(0.0);
+        1.0 - 0.75 * (over / (SHUTDOWN_TEMPERATURE - THROTTLE_TEMPERATURE)).min(1.0)
+    }
+}
+
+// takes heat away from adjacent machines
+#[derive(Component)]
+pub struct Cooler {
+    pub strength: f32,
+}
+
+fn init_heat(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read() {
+        match event.kind {
+            BuildingKind::Assembler => {
+                commands.entity(event.entity).insert(Heat::default());
+            }
+            BuildingKind::HeatSink => {
+                commands.entity(event.entity).insert(Cooler { strength: 0.05 });
+            }
+            BuildingKind::WaterPipe => {
+                commands.entity(event.entity).insert(Cooler { strength: 0.15 });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update_heat(
+    time: Res<Time>,
+    grid: Res<BuildingGrid>,
+    cooler_query: Query<&Cooler>,
+    mut heat_query: Query<(&mut Heat, &Machine, &GridPos)>,
+) {
+    let dt = time.delta_seconds();
+
+    for (mut heat, machine, pos) in &mut heat_query {
+        let generated = match (machine.working, machine.recipe) {
+            (true, Some(recipe)) => recipe.mana() * 2.0 * heat.speed_factor(),
+            _ => 0.0,
+        };
+
+        let cooling = [Facing::North, Facing::East, Facing::South, Facing::West].into_iter()
+            .filter_map(|f| grid.get(pos.step(f)))
+            .filter_map(|e| cooler_query.get(e).ok())
+            .map(|c| c.strength)
+            .sum::<f32>() + AIR_COOLING;
+
+        let excess = heat.temperature - AMBIENT_TEMPERATURE;
+        heat.temperature += (generated - excess * cooling) * dt;
+
+        if heat.temperature >= SHUTDOWN_TEMPERATURE {
+            heat.overheated = true;
+        } else if heat.temperature <= RESTART_TEMPERATURE {
+            heat.overheated = false;
+        }
+    }
+}
+
+fn show_heat(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    heat_query: Query<(&Heat, &Handle<StandardMaterial>), Changed<Heat>>,
+) {
+    // machines glow red as they approach shutdown
+    for (heat, handle) in &heat_query {
+        let t = ((heat.temperature - AMBIENT_TEMPERATURE)
+            / (SHUTDOWN_TEMPERATURE - AMBIENT_TEMPERATURE)).clamp(0.0, 1.0);
+
+        if let Some(material) = materials.get_mut(handle) {
+            material.emissive = Color::rgb(t * t, 0.0, 0.0);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    enemy::Hostile,
+    health::{Damage, DamageEvent, Health},
+    layer::Layer,
+    state::GameplaySet,
+};
+
+// degrees between the casts that make up a swing, fine enough that nothing
+// slips through between two of them
+const SWEEP_STEP: f32 = 15.0;
+// how thick the blade of a swing is
+const SWEEP_RADIUS: f32 = 0.2;
+const MAX_HITS: u32 = 8;
+
+pub struct HitboxPlugin;
+
+impl Plugin for HitboxPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<MeleeSwing>()
+            .add_systems(Update, resolve_swings.in_set(GameplaySet));
+    }
+}
+
+/// A region of `owner` that takes `multiplier` times the damage when it's
+/// what gets hit, e.g. a weak spot. Lives on a sensor child.
+#[derive(Component, Clone, Copy)]
+pub struct Hurtbox {
+    pub owner: Entity,
+    pub multiplier: f32,
+}
+
+/// A melee attack sweeping an arc of `arc` degrees around `facing`, hitting
+/// everything in `range` once.
+#[derive(Event)]
+pub struct MeleeSwing {
+    pub attacker: Entity,
+    pub origin: Vec3,
+    pub facing: Vec3,
+    pub arc: f32,
+    pub range: f32,
+    pub damage: Damage,
+}
+
+/// Adds a weak spot at `offset` to the entity being built.
+pub fn weak_spot(parent: &mut ChildBuilder, offset: Vec3, radius: f32, multiplier: f32) {
+    let owner = parent.parent_entity();
+    parent.spawn((
+        Hurtbox { owner, multiplier },
+        TransformBundle::from_transform(Transform::from_translation(offset)),
+        Collider::ball(radius),
+        Sensor,
+        Layer::enemy_hurtbox(),
+    ));
+}
+
+/// Whatever actually takes the damage for a collider that got hit, and
+/// how much of it.
+pub fn resolve_hit(hurtbox_query: &Query<&Hurtbox>, entity: Entity) -> (Entity, f32) {
+    hurtbox_query.get(entity).map_or((entity, 1.0), |h| (h.owner, h.multiplier))
+}
+
+fn resolve_swings(
+    spatial_query: SpatialQuery,
+    hurtbox_query: Query<&Hurtbox>,
+    target_query: Query<Has<Hostile>, With<Health>>,
+    mut swing_events: EventReader<MeleeSwing>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for event in swing_events.read() {
+        let Ok(attacker_hostile) = target_query.get(event.attacker) else {
+            continue;
+        };
+        let facing = Vec3::new(event.facing.x, 0.0, event.facing.z).normalize_or_zero();
+        let steps = (event.arc / SWEEP_STEP).ceil().max(1.0) as u32;
+
+        // the best region of everything the arc passed through
+        let mut hits: Vec<(Entity, f32)> = Vec::new();
+        for step in 0..=steps {
+            let angle = (step as f32 / steps as f32 - 0.5) * event.arc;
+            let direction = Quat::from_rotation_y(angle.to_radians()) * facing;
+            let shape_hits = spatial_query.shape_hits(
+                &Collider::ball(SWEEP_RADIUS),
+                event.origin,
+                Quat::IDENTITY,
+                direction,
+                event.range,
+                MAX_HITS,
+                false,
+                SpatialQueryFilter::new().without_entities([event.attacker]),
+            );
+
+            for hit in shape_hits {
+                let (target, multiplier) = resolve_hit(&hurtbox_query, hit.entity);
+                // no hitting its own side
+                let enemy = target_query.get(target).is_ok_and(|hostile| hostile != attacker_hostile);
+                if target == event.attacker || !enemy {
+                    continue;
+                }
+                match hits.iter_mut().find(|(e, _)| *e == target) {
+                    Some((_, best)) => *best = best.max(multiplier),
+                    None => hits.push((target, multiplier)),
+                }
+            }
+        }
+
+        for (target, multiplier) in hits {
+            let damage = Damage::new(event.damage.amount * multiplier, event.damage.kind);
+            damage_events.send(DamageEvent::new(target, damage, Some(event.attacker)));
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use bevy::prelude::*;
+
+use crate::{
+    blueprint::{BlueprintTool, ToolState},
+    brewing::{DrinkPotion, Potion},
+    building::BuildMode,
+    character::Stats,
+    cooldown::{Ability, Cooldowns},
+    crafting::CraftingPanel,
+    deconstruct::DeconstructTool,
+    dialogue::Dialogue,
+    hud::HudElement,
+    input::{Action, InputMap},
+    inventory::Inventory,
+    inventory_ui::InventoryScreen,
+    magic::{Caster, CastSpell, InterruptCast, ReleaseCast, SpellBook, SpellId},
+    map::WorldMap,
+    radial::RadialMenu,
+    state::GameplaySet,
+    tech_tree::TechTree,
+    Player,
+};
+
+pub const HOTBAR_SLOTS: usize = 9;
+const SLOT_SIZE: f32 = 56.0;
+const SLOT_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.7);
+const POTION_SLOT_COLOR: Color = Color::rgba(0.15, 0.0, 0.2, 0.7);
+const SELECTED_COLOR: Color = Color::rgba(0.9, 0.8, 0.3, 1.0);
+
+const SLOT_KEYS: [KeyCode; HOTBAR_SLOTS] = [
+    KeyCode::Key1, KeyCode::Key2, KeyCode::Key3,
+    KeyCode::Key4, KeyCode::Key5, KeyCode::Key6,
+    KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+];
+
+pub struct HotbarPlugin;
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_hotbar_ui)
+            .add_systems(Update, (hotbar_input, update_hotbar_ui).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HotbarEntry {
+    Spell(SpellId),
+    // drunk straight out of the inventory
+    Potion(Potion),
+}
+
+/// The spells and potions the player has equipped.
+#[derive(Component, Clone, Default)]
+pub struct Hotbar {
+    pub slots: [Option<HotbarEntry>; HOTBAR_SLOTS],
+    pub selected: usize,
+}
+
+/// What the UI needs to draw a single slot.
+pub struct SlotView<'a> {
+    pub key: usize,
+    pub entry: HotbarEntry,
+    pub name: &'a str,
+    pub selected: bool,
+    // 1.0 right after casting, down to 0.0 once it's ready again
+    pub cooldown: f32,
+    // enough mana for a spell, at least one left of a potion
+    pub affordable: bool,
+    // how many potions are left
+    pub count: Option<u32>,
+}
+
+impl Hotbar {
+    pub fn from_spells(spells: &[SpellId]) -> Self {
+        let mut hotbar = Hotbar::default();
+        for (slot, spell) in spells.iter().take(HOTBAR_SLOTS).enumerate() {
+            hotbar.slots[slot] = Some(HotbarEntry::Spell(*spell));
+        }
+        hotbar
+    }
+
+    pub fn equip(&mut self, slot: usize, entry: HotbarEntry) {
+        if let Some(s) = self.slots.get_mut(slot) {
+            *s = Some(entry);
+        }
+    }
+
+    pub fn unequip(&mut self, slot: usize) {
+        if let Some(s) = self.slots.get_mut(slot) {
+            *s = None;
+        }
+    }
+
+    pub fn selected_entry(&self) -> Option<HotbarEntry> {
+        self.slots[self.selected]
+    }
+
+    /// Every equipped slot in order, ready to be rendered.
+    pub fn view<'a>(
+        &self,
+        book: &'a SpellBook,
+        owner: Entity,
+        cooldowns: &Cooldowns,
+        stats: &Stats,
+        inventory: &Inventory,
+    ) -> Vec<SlotView<'a>> {
+        self.slots.iter()
+            .enumerate()
+            .filter_map(|(i, s)| Some((i, (*s)?)))
+            .filter_map(|(i, entry)| {
+                let selected = i == self.selected;
+                match entry {
+                    HotbarEntry::Spell(id) => {
+                        let spell = book.get(id)?;
+                        Some(SlotView {
+                            key: i + 1,
+                            entry,
+                            name: &spell.name,
+                            selected,
+                            cooldown: cooldowns.fraction(owner, Ability::Spell(id)),
+                            affordable: stats.mana >= stats.spell_cost(spell.cost),
+                            count: None,
+                        })
+                    }
+                    HotbarEntry::Potion(potion) => {
+                        let count = inventory.count(potion.item());
+                        Some(SlotView {
+                            key: i + 1,
+                            entry,
+                            name: potion.name(),
+                            selected,
+                            cooldown: 0.0,
+                            affordable: count > 0,
+                            count: Some(count),
+                        })
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+// every spell in the book, then every potion
+fn all_entries(book: &SpellBook) -> Vec<HotbarEntry> {
+    book.ids().map(HotbarEntry::Spell)
+        .chain(Potion::ALL.into_iter().map(HotbarEntry::Potion))
+        .collect()
+}
+
+fn hotbar_input(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mouse: Res<Input<MouseButton>>,
+    book: Res<SpellBook>,
+    // every screen that takes the mouse over, grouped to stay under the parameter limit
+    screens: (Res<InventoryScreen>, Res<CraftingPanel>, Res<WorldMap>, Res<TechTree>, Res<Dialogue>, Res<RadialMenu>),
+    build_mode: Res<BuildMode>,
+    blueprint_tool: Res<BlueprintTool>,
+    deconstruct_tool: Res<DeconstructTool>,
+    mut player_query: Query<(Entity, &mut Hotbar), (With<Player>, With<Caster>)>,
+    mut cast_events: EventWriter<CastSpell>,
+    mut interrupt_events: EventWriter<InterruptCast>,
+    mut release_events: EventWriter<ReleaseCast>,
+    mut drink_events: EventWriter<DrinkPotion>,
+) {
+    let (player, mut hotbar) = player_query.get_single_mut().expect("not one player");
+
+    // channeled spells keep going until their button is let go
+    if mouse.just_released(MouseButton::Left) || keys.any_just_released(SLOT_KEYS) {
+        release_events.send(ReleaseCast { caster: player });
+    }
+
+    // swap the selected slot to the next spell in the book, or potion after
+    // that, and clear it once past the last one
+    if input_map.just_pressed(Action::CycleHotbar, &keys) {
+        let entries = all_entries(&book);
+        let current = hotbar.selected_entry().and_then(|s| entries.iter().position(|e| *e == s));
+        let slot = hotbar.selected;
+        match entries.get(current.map_or(0, |i| i + 1)) {
+            Some(next) => hotbar.equip(slot, *next),
+            None => hotbar.unequip(slot),
+        }
+    }
+
+    let mut activate = |entry: HotbarEntry| match entry {
+        HotbarEntry::Spell(spell) => cast_events.send(CastSpell { caster: player, spell }),
+        HotbarEntry::Potion(potion) => drink_events.send(DrinkPotion { drinker: player, potion }),
+    };
+
+    for (slot, key) in SLOT_KEYS.iter().enumerate() {
+        if !keys.just_pressed(*key) {
+            continue;
+        }
+
+        hotbar.selected = slot;
+        if let Some(entry) = hotbar.slots[slot] {
+            activate(entry);
+        }
+    }
+
+    // the mouse belongs to whichever tool or screen is open
+    let (inventory_screen, crafting_panel, world_map, tech_tree, dialogue, radial_menu) = screens;
+    let tool_open = inventory_screen.open
+        || crafting_panel.open
+        || world_map.open
+        || tech_tree.open
+        || dialogue.is_open()
+        || radial_menu.open
+        || build_mode.active
+        || blueprint_tool.state != ToolState::Off
+        || deconstruct_tool.active;
+    if tool_open {
+        return;
+    }
+
+    if let (true, Some(entry)) = (mouse.just_pressed(MouseButton::Left), hotbar.selected_entry()) {
+        activate(entry);
+    }
+    if mouse.just_pressed(MouseButton::Right) {
+        interrupt_events.send(InterruptCast { caster: player });
+    }
+}
+
+/// The box for one hotbar slot, its parts are looked up through it.
+#[derive(Component)]
+struct SlotUi {
+    slot: usize,
+    name: Entity,
+    count: Entity,
+    sweep: Entity,
+}
+
+fn setup_hotbar_ui(mut commands: Commands) {
+    let text = |value: String, size: f32| TextBundle::from_section(value, TextStyle {
+        font_size: size,
+        color: Color::WHITE,
+        ..default()
+    });
+
+    commands.spawn((
+        HudElement::Hotbar,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                bottom: Val::Px(8.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        },
+    )).with_children(|parent| {
+        for slot in 0..HOTBAR_SLOTS {
+            let mut slot_box = parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(SLOT_SIZE),
+                    height: Val::Px(SLOT_SIZE),
+                    border: UiRect::all(Val::Px(2.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: SLOT_COLOR.into(),
+                border_color: Color::NONE.into(),
+                ..default()
+            });
+
+            let (mut name, mut count, mut sweep) = (Entity::PLACEHOLDER, Entity::PLACEHOLDER, Entity::PLACEHOLDER);
+            slot_box.with_children(|slot_box| {
+                // ui nodes can't be cut into wedges, so the sweep wipes down from the top instead
+                sweep = slot_box.spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(0.0),
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(0.0),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                    ..default()
+                }).id();
+                name = slot_box.spawn(text(String::new(), 11.0)).id();
+                // the key in the top left, how many are left in the bottom right
+                slot_box.spawn(text((slot + 1).to_string(), 12.0).with_style(Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(1.0),
+                    left: Val::Px(3.0),
+                    ..default()
+                }));
+                count = slot_box.spawn(text(String::new(), 12.0).with_style(Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(1.0),
+                    right: Val::Px(3.0),
+                    ..default()
+                })).id();
+            });
+            slot_box.insert(SlotUi { slot, name, count, sweep });
+        }
+    });
+}
+
+fn update_hotbar_ui(
+    book: Res<SpellBook>,
+    cooldowns: Res<Cooldowns>,
+    player_query: Query<(Entity, &Hotbar, &Stats, &Inventory), With<Player>>,
+    mut slot_query: Query<(&SlotUi, &mut BorderColor, &mut BackgroundColor)>,
+    mut text_query: Query<&mut Text>,
+    mut sweep_query: Query<&mut Style>,
+) {
+    let (player, hotbar, stats, inventory) = player_query.get_single().expect("not one player");
+    let views = hotbar.view(&book, player, &cooldowns, stats, inventory);
+
+    for (ui, mut border, mut background) in &mut slot_query {
+        let view = views.iter().find(|v| v.key == ui.slot + 1);
+
+        // empty slots have no view but can still be selected
+        let selected = view.map_or(ui.slot == hotbar.selected, |v| v.selected);
+        *border = if selected { SELECTED_COLOR } else { Color::NONE }.into();
+        let potion = view.is_some_and(|v| matches!(v.entry, HotbarEntry::Potion(_)));
+        *background = if potion { POTION_SLOT_COLOR } else { SLOT_COLOR }.into();
+        if let Ok(mut sweep) = sweep_query.get_mut(ui.sweep) {
+            sweep.height = Val::Percent(view.map_or(0.0, |v| v.cooldown) * 100.0);
+        }
+        if let Ok(mut text) = text_query.get_mut(ui.name) {
+            let section = &mut text.sections[0];
+            section.value = view.map_or(String::new(), |v| v.name.to_string());
+            // greyed out while it can't be used
+            section.style.color = if view.is_none_or(|v| v.affordable) { Color::WHITE } else { Color::GRAY };
+        }
+        if let Ok(mut text) = text_query.get_mut(ui.count) {
+            text.sections[0].value = view.and_then(|v| v.count).map_or(String::new(), |c| c.to_string());
+        }
+    }
+}
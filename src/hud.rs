@@ -0,0 +1,280 @@
+use bevy::prelude::*;
+
+use crate::{
+    character::Stats,
+    health::Health,
+    input::{Action, InputMap},
+    settings::{Palette, Settings},
+    state::GameplaySet,
+    Player,
+};
+
+const BAR_WIDTH: f32 = 200.0;
+const BAR_HEIGHT: f32 = 12.0;
+// how quickly the fill catches up with the real value, per second
+const FILL_RATE: f32 = 8.0;
+// the chunk that was just lost lingers, then drains at this many bars per second
+const TRAIL_DELAY: f32 = 0.5;
+const TRAIL_RATE: f32 = 0.5;
+// below this fraction the bar starts pulsing
+const LOW: f32 = 0.25;
+const PULSE_SPEED: f32 = 8.0;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Hud>()
+            .init_resource::<Faded>()
+            .add_systems(Startup, setup_hud)
+            .add_systems(Update, (toggle_hud, update_bars, show_hud).chain().in_set(GameplaySet))
+            // after everything has picked its colors for the frame
+            .add_systems(PostUpdate, fade_hud)
+            .add_systems(PreUpdate, unfade_hud);
+    }
+}
+
+/// Anything that wants a clean screen sets `hidden`.
+#[derive(Resource, Default)]
+pub struct Hud {
+    pub hidden: bool,
+}
+
+/// The root of a part of the hud, shown or hidden as a whole and faded by
+/// the hud opacity setting.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HudElement {
+    Bars,
+    Hotbar,
+    Minimap,
+}
+
+impl HudElement {
+    fn shown(&self, settings: &Settings) -> bool {
+        match self {
+            HudElement::Bars => settings.show_bars,
+            HudElement::Hotbar => settings.show_hotbar,
+            HudElement::Minimap => settings.show_minimap,
+        }
+    }
+}
+
+// alphas from before fade_hud touched them
+#[derive(Resource, Default)]
+struct Faded {
+    backgrounds: Vec<(Entity, f32)>,
+    texts: Vec<(Entity, Vec<f32>)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Meter {
+    Health,
+    Mana,
+    Stamina,
+}
+
+impl Meter {
+    const ALL: [Meter; 3] = [Meter::Health, Meter::Mana, Meter::Stamina];
+
+    fn color(&self, palette: &Palette) -> Color {
+        match self {
+            Meter::Health => palette.health,
+            Meter::Mana => palette.mana,
+            Meter::Stamina => palette.stamina,
+        }
+    }
+
+    fn fraction(&self, health: &Health, stats: &Stats) -> f32 {
+        let (current, max) = match self {
+            Meter::Health => (health.current, health.max),
+            Meter::Mana => (stats.mana, stats.max_mana),
+            Meter::Stamina => (stats.stamina, stats.max_stamina),
+        };
+        if max > 0.0 { (current / max).clamp(0.0, 1.0) } else { 0.0 }
+    }
+}
+
+#[derive(Component)]
+struct Bar {
+    meter: Meter,
+    fill: Entity,
+    trail: Entity,
+    // what's drawn, lagging behind the real value
+    shown: f32,
+    trailing: f32,
+    // seconds until the trail starts draining
+    trail_delay: f32,
+    last: f32,
+}
+
+fn setup_hud(mut commands: Commands, settings: Res<Settings>) {
+    let fill_style = Style {
+        position_type: PositionType::Absolute,
+        height: Val::Percent(100.0),
+        width: Val::Percent(100.0),
+        ..default()
+    };
+
+    commands.spawn((
+        HudElement::Bars,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(20.0),
+                bottom: Val::Px(20.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        },
+    )).with_children(|parent| {
+        for meter in Meter::ALL {
+            let mut bar = parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(BAR_WIDTH),
+                    height: Val::Px(BAR_HEIGHT),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                ..default()
+            });
+            let mut trail = Entity::PLACEHOLDER;
+            let mut fill = Entity::PLACEHOLDER;
+            bar.with_children(|bar| {
+                trail = bar.spawn(NodeBundle {
+                    style: fill_style.clone(),
+                    background_color: Color::rgba(1.0, 1.0, 1.0, 0.6).into(),
+                    ..default()
+                }).id();
+                fill = bar.spawn(NodeBundle {
+                    style: fill_style.clone(),
+                    background_color: meter.color(settings.palette()).into(),
+                    ..default()
+                }).id();
+            });
+            bar.insert(Bar { meter, fill, trail, shown: 1.0, trailing: 1.0, trail_delay: 0.0, last: 1.0 });
+        }
+    });
+}
+
+// a quick way to take screenshots without the bars in the way
+fn toggle_hud(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut hud: ResMut<Hud>,
+) {
+    if input_map.just_pressed(Action::ToggleHud, &keys) {
+        hud.hidden = !hud.hidden;
+    }
+}
+
+fn update_bars(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    player_query: Query<(&Health, &Stats), With<Player>>,
+    mut bar_query: Query<&mut Bar>,
+    mut fill_query: Query<(&mut Style, &mut BackgroundColor)>,
+) {
+    let (health, stats) = player_query.get_single().expect("not one player");
+    let dt = time.delta_seconds();
+
+    for mut bar in &mut bar_query {
+        let actual = bar.meter.fraction(health, stats);
+
+        bar.shown += (actual - bar.shown) * (FILL_RATE * dt).min(1.0);
+        // just lost some, let it sink in before draining
+        if actual < bar.last {
+            bar.trail_delay = TRAIL_DELAY;
+        }
+        bar.last = actual;
+        if actual < bar.trailing {
+            bar.trail_delay -= dt;
+            if bar.trail_delay <= 0.0 {
+                bar.trailing = (bar.trailing - TRAIL_RATE * dt).max(actual);
+            }
+        } else {
+            bar.trailing = bar.shown;
+        }
+
+        if let Ok((mut style, _)) = fill_query.get_mut(bar.trail) {
+            style.width = Val::Percent(bar.trailing * 100.0);
+        }
+        if let Ok((mut style, mut color)) = fill_query.get_mut(bar.fill) {
+            style.width = Val::Percent(bar.shown * 100.0);
+            let pulse = if actual < LOW {
+                0.6 + 0.4 * (time.elapsed_seconds() * PULSE_SPEED).sin().abs()
+            } else {
+                1.0
+            };
+            *color = (bar.meter.color(settings.palette()) * pulse).with_a(1.0).into();
+        }
+    }
+}
+
+fn show_hud(
+    hud: Res<Hud>,
+    settings: Res<Settings>,
+    mut root_query: Query<(&HudElement, &mut Visibility)>,
+) {
+    if !hud.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    for (element, mut visibility) in &mut root_query {
+        let shown = !hud.hidden && element.shown(&settings);
+        *visibility = if shown { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// there's no opacity for a whole ui tree, so every node under a hud root is
+// faded on its own for drawing, and put back before the next frame's systems
+fn fade_hud(
+    settings: Res<Settings>,
+    mut faded: ResMut<Faded>,
+    root_query: Query<Entity, With<HudElement>>,
+    children_query: Query<&Children>,
+    mut color_query: Query<&mut BackgroundColor>,
+    mut text_query: Query<&mut Text>,
+) {
+    if settings.hud_opacity >= 1.0 {
+        return;
+    }
+
+    for root in &root_query {
+        for entity in std::iter::once(root).chain(children_query.iter_descendants(root)) {
+            if let Ok(mut color) = color_query.get_mut(entity) {
+                let alpha = color.0.a();
+                faded.backgrounds.push((entity, alpha));
+                color.0.set_a(alpha * settings.hud_opacity);
+            }
+            if let Ok(mut text) = text_query.get_mut(entity) {
+                faded.texts.push((entity, text.sections.iter().map(|s| s.style.color.a()).collect()));
+                for section in &mut text.sections {
+                    let alpha = section.style.color.a();
+                    section.style.color.set_a(alpha * settings.hud_opacity);
+                }
+            }
+        }
+    }
+}
+
+fn unfade_hud(
+    mut faded: ResMut<Faded>,
+    mut color_query: Query<&mut BackgroundColor>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (entity, alpha) in faded.backgrounds.drain(..) {
+        if let Ok(mut color) = color_query.get_mut(entity) {
+            color.0.set_a(alpha);
+        }
+    }
+    for (entity, alphas) in faded.texts.drain(..) {
+        if let Ok(mut text) = text_query.get_mut(entity) {
+            for (section, alpha) in text.sections.iter_mut().zip(alphas) {
+                section.style.color.set_a(alpha);
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use rand::Rng;
+
+use crate::item::{Item, ItemStack};
+
+/// How well a casting implement turned out, rolled when it's crafted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl Rarity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rarity::Common => "Common",
+            Rarity::Uncommon => "Uncommon",
+            Rarity::Rare => "Rare",
+            Rarity::Legendary => "Legendary",
+        }
+    }
+
+    // scales everything an implement does
+    fn multiplier(&self) -> f32 {
+        match self {
+            Rarity::Common => 1.0,
+            Rarity::Uncommon => 1.3,
+            Rarity::Rare => 1.6,
+            Rarity::Legendary => 2.0,
+        }
+    }
+
+    pub fn roll(rng: &mut impl Rng) -> Rarity {
+        match rng.gen_range(0..100) {
+            0..=59 => Rarity::Common,
+            60..=84 => Rarity::Uncommon,
+            85..=96 => Rarity::Rare,
+            _ => Rarity::Legendary,
+        }
+    }
+}
+
+/// What a wand or staff does to the spells cast through it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ImplementStats {
+    // fraction added to the casting speed
+    pub cast_speed: f32,
+    // fraction taken off every mana cost
+    pub mana_efficiency: f32,
+    pub extra_projectiles: u32,
+}
+
+impl ImplementStats {
+    /// `None` if the stack isn't a casting implement.
+    pub fn of(stack: &ItemStack) -> Option<ImplementStats> {
+        let m = stack.rarity.multiplier();
+        let rare = stack.rarity >= Rarity::Rare;
+        let legendary = stack.rarity == Rarity::Legendary;
+
+        // wands are quick, staves are frugal and split projectiles
+        match stack.item {
+            Item::Wand => Some(ImplementStats {
+                cast_speed: 0.2 * m,
+                mana_efficiency: 0.0,
+                extra_projectiles: legendary as u32,
+            }),
+            Item::Staff => Some(ImplementStats {
+                cast_speed: 0.0,
+                mana_efficiency: 0.1 * m,
+                extra_projectiles: rare as u32 + legendary as u32,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Rolls the rarity if `stack` is an implement, anything else comes back as is.
+pub fn with_rolled_rarity(mut stack: ItemStack) -> ItemStack {
+    if matches!(stack.item, Item::Wand | Item::Staff) {
+        stack.rarity = Rarity::roll(&mut rand::thread_rng());
+    }
+    stack
+}
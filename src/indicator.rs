@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+
+use crate::{
+    alert::{AlertKind, Alerts},
+    enemy::Hostile,
+    perception::{Alertness, Awareness},
+    quest::QuestLog,
+    raid::RaidDirector,
+    state::GameplaySet,
+    Player,
+};
+
+// pointers on screen at once, the closest things win
+const POOL_SIZE: usize = 12;
+// how far in from the edge of the screen they sit, in ndc
+const MARGIN: f32 = 0.9;
+const FONT_SIZE: f32 = 18.0;
+// faded out completely at the max distance, never fainter than this
+const MIN_ALPHA: f32 = 0.3;
+
+pub struct IndicatorPlugin;
+
+impl Plugin for IndicatorPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<IndicatorSettings>()
+            .add_systems(Startup, setup_indicators)
+            .add_systems(Update, update_indicators.in_set(GameplaySet));
+    }
+}
+
+#[derive(Resource)]
+pub struct IndicatorSettings {
+    pub enabled: bool,
+    // nothing further away than this gets pointed at
+    pub max_distance: f32,
+}
+
+impl Default for IndicatorSettings {
+    fn default() -> Self {
+        IndicatorSettings {
+            enabled: true,
+            max_distance: 40.0,
+        }
+    }
+}
+
+/// What an indicator is pointing at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Target {
+    // an enemy that knows about the player
+    Enemy,
+    // a building that needs defending
+    Objective,
+    // where the next raid comes from
+    Raid,
+    // where the tracked quest wants the player to go
+    Quest,
+}
+
+impl Target {
+    fn color(&self) -> Color {
+        match self {
+            Target::Enemy => Color::RED,
+            Target::Objective => Color::YELLOW,
+            Target::Raid => Color::ORANGE,
+            Target::Quest => Color::GREEN,
+        }
+    }
+}
+
+/// One of the pooled pointers.
+#[derive(Component)]
+struct Indicator;
+
+fn setup_indicators(mut commands: Commands) {
+    for _ in 0..POOL_SIZE {
+        commands.spawn((
+            Indicator,
+            TextBundle {
+                text: Text::from_section("", TextStyle {
+                    font_size: FONT_SIZE,
+                    color: Color::WHITE,
+                    ..default()
+                }),
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+    }
+}
+
+fn update_indicators(
+    settings: Res<IndicatorSettings>,
+    alerts: Res<Alerts>,
+    director: Res<RaidDirector>,
+    quest_log: Res<QuestLog>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    player_query: Query<&Transform, With<Player>>,
+    enemy_query: Query<(&Transform, &Awareness), With<Hostile>>,
+    building_query: Query<&Transform, (Without<Player>, Without<Hostile>)>,
+    mut indicator_query: Query<(&mut Text, &mut Style, &mut Visibility), With<Indicator>>,
+) {
+    let (camera, camera_transform) = camera_query.get_single().expect("not one camera");
+    let player = player_query.get_single().expect("not one player").translation;
+
+    let mut targets: Vec<(Target, Vec3)> = Vec::new();
+    if settings.enabled {
+        targets.extend(enemy_query.iter()
+            .filter(|(_, a)| a.alertness() == Alertness::Alert)
+            .map(|(t, _)| (Target::Enemy, t.translation)));
+        targets.extend(alerts.0.iter()
+            .filter(|a| a.kind == AlertKind::UnderAttack)
+            .filter_map(|a| building_query.get(a.entity).ok())
+            .map(|t| (Target::Objective, t.translation)));
+        if let Some(edge) = director.edge {
+            targets.push((Target::Raid, edge.closest_to(player)));
+        }
+        targets.extend(quest_log.marker.map(|pos| (Target::Quest, pos)));
+    }
+    targets.retain(|(_, pos)| {
+        pos.distance(player) <= settings.max_distance && !on_screen(camera, camera_transform, *pos)
+    });
+    targets.sort_by(|a, b| a.1.distance(player).total_cmp(&b.1.distance(player)));
+
+    let view = camera.logical_viewport_size().unwrap_or(Vec2::ONE);
+    let to_camera = camera_transform.compute_matrix().inverse();
+    let mut targets = targets.into_iter();
+
+    for (mut text, mut style, mut visibility) in &mut indicator_query {
+        let Some((target, pos)) = targets.next() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        // which way it is from the middle of the screen, works behind the camera too
+        let local = to_camera.transform_point3(pos);
+        let direction = Vec2::new(local.x, local.y).normalize_or_zero();
+        let edge = direction / direction.x.abs().max(direction.y.abs()).max(f32::EPSILON) * MARGIN;
+
+        let arrow = if direction.x.abs() > direction.y.abs() {
+            if direction.x > 0.0 { ">" } else { "<" }
+        } else if direction.y > 0.0 {
+            "^"
+        } else {
+            "v"
+        };
+        let distance = pos.distance(player);
+        let alpha = (1.0 - distance / settings.max_distance).max(MIN_ALPHA);
+
+        let section = &mut text.sections[0];
+        section.value = format!("{} {:.0}m", arrow, distance);
+        section.style.color = target.color().with_a(alpha);
+        style.left = Val::Px((edge.x + 1.0) / 2.0 * view.x);
+        style.top = Val::Px((1.0 - edge.y) / 2.0 * view.y);
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn on_screen(camera: &Camera, transform: &GlobalTransform, pos: Vec3) -> bool {
+    camera.world_to_ndc(transform, pos)
+        .is_some_and(|ndc| ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0 && (0.0..=1.0).contains(&ndc.z))
+}
@@ -0,0 +1,415 @@
+use std::fs;
+
+use bevy::{prelude::*, ui::FocusPolicy, utils::HashMap};
+
+use crate::{
+    locale::{Locale, Localized},
+    state::{toggle_pause, GameState},
+};
+
+// next to the executable's working directory, one `Action=Key` per line
+const KEYBINDS_PATH: &str = "keybinds.cfg";
+const BUTTON_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.7);
+const CONFLICT_COLOR: Color = Color::rgb(1.0, 0.4, 0.3);
+
+// everything a binding can be set to, escape is kept for backing out
+const BINDABLE: [KeyCode; 80] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+    KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+    KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+    KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6,
+    KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::Space, KeyCode::Tab, KeyCode::Capital, KeyCode::Return, KeyCode::Back, KeyCode::Grave,
+    KeyCode::ShiftLeft, KeyCode::ShiftRight, KeyCode::ControlLeft, KeyCode::ControlRight,
+    KeyCode::AltLeft, KeyCode::AltRight,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Comma, KeyCode::Period, KeyCode::Slash, KeyCode::Semicolon, KeyCode::Apostrophe,
+    KeyCode::BracketLeft, KeyCode::BracketRight, KeyCode::Minus, KeyCode::Equals, KeyCode::Backslash,
+    KeyCode::Insert, KeyCode::Delete, KeyCode::Home, KeyCode::End, KeyCode::PageUp, KeyCode::PageDown,
+];
+
+pub struct KeybindPlugin;
+
+impl Plugin for KeybindPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(InputMap::load())
+            .init_resource::<ControlsMenu>()
+            .add_systems(OnExit(GameState::MainMenu), close_controls)
+            .add_systems(OnExit(GameState::Paused), close_controls)
+            .add_systems(Update, (
+                show_controls_menu,
+                controls_input,
+                // gets to escape before the pause menu does
+                capture_binding.before(toggle_pause),
+                update_controls_ui,
+                save_bindings,
+            ).chain());
+    }
+}
+
+/// Everything the player can rebind.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveForward,
+    MoveLeft,
+    MoveBack,
+    MoveRight,
+    Jump,
+    Dodge,
+    Interact,
+    Inventory,
+    Crafting,
+    Journal,
+    Research,
+    CycleHotbar,
+    QuickSelect,
+    Map,
+    MinimapZoom,
+    ToggleHud,
+    DebugOverlay,
+}
+
+impl Action {
+    pub const ALL: [Action; 17] = [
+        Action::MoveForward,
+        Action::MoveLeft,
+        Action::MoveBack,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Dodge,
+        Action::Interact,
+        Action::Inventory,
+        Action::Crafting,
+        Action::Journal,
+        Action::Research,
+        Action::CycleHotbar,
+        Action::QuickSelect,
+        Action::Map,
+        Action::MinimapZoom,
+        Action::ToggleHud,
+        Action::DebugOverlay,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveForward => "Move forward",
+            Action::MoveLeft => "Move left",
+            Action::MoveBack => "Move back",
+            Action::MoveRight => "Move right",
+            Action::Jump => "Jump / fly up",
+            Action::Dodge => "Dodge / fly down",
+            Action::Interact => "Interact",
+            Action::Inventory => "Inventory",
+            Action::Crafting => "Crafting",
+            Action::Journal => "Journal",
+            Action::Research => "Research",
+            Action::CycleHotbar => "Cycle hotbar slot",
+            Action::QuickSelect => "Quick select",
+            Action::Map => "Map",
+            Action::MinimapZoom => "Minimap zoom",
+            Action::ToggleHud => "Toggle HUD",
+            Action::DebugOverlay => "Debug overlay",
+        }
+    }
+
+    pub fn default_key(&self) -> KeyCode {
+        match self {
+            Action::MoveForward => KeyCode::W,
+            Action::MoveLeft => KeyCode::A,
+            Action::MoveBack => KeyCode::S,
+            Action::MoveRight => KeyCode::D,
+            Action::Jump => KeyCode::Space,
+            Action::Dodge => KeyCode::ControlLeft,
+            Action::Interact => KeyCode::E,
+            Action::Inventory => KeyCode::Tab,
+            Action::Crafting => KeyCode::Q,
+            Action::Journal => KeyCode::F2,
+            Action::Research => KeyCode::U,
+            Action::CycleHotbar => KeyCode::Grave,
+            Action::QuickSelect => KeyCode::Capital,
+            Action::Map => KeyCode::M,
+            Action::MinimapZoom => KeyCode::Z,
+            Action::ToggleHud => KeyCode::F1,
+            Action::DebugOverlay => KeyCode::F3,
+        }
+    }
+}
+
+/// Which key each action is bound to.
+#[derive(Resource)]
+pub struct InputMap {
+    keys: HashMap<Action, KeyCode>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        InputMap { keys: Action::ALL.iter().map(|a| (*a, a.default_key())).collect() }
+    }
+}
+
+impl InputMap {
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.keys.get(&action).copied().unwrap_or(action.default_key())
+    }
+
+    pub fn pressed(&self, action: Action, keys: &Input<KeyCode>) -> bool {
+        keys.pressed(self.key(action))
+    }
+
+    pub fn just_pressed(&self, action: Action, keys: &Input<KeyCode>) -> bool {
+        keys.just_pressed(self.key(action))
+    }
+
+    pub fn just_released(&self, action: Action, keys: &Input<KeyCode>) -> bool {
+        keys.just_released(self.key(action))
+    }
+
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        self.keys.insert(action, key);
+    }
+
+    /// The other actions sharing a key with `action`.
+    pub fn conflicts(&self, action: Action) -> Vec<Action> {
+        Action::ALL.into_iter()
+            .filter(|a| *a != action && self.key(*a) == self.key(action))
+            .collect()
+    }
+
+    /// Starts from the defaults, anything unreadable in the file is skipped.
+    fn load() -> Self {
+        let mut map = InputMap::default();
+        let Ok(file) = fs::read_to_string(KEYBINDS_PATH) else {
+            return map;
+        };
+
+        for (action, key) in file.lines().filter_map(|l| l.split_once('=')) {
+            let action = Action::ALL.into_iter().find(|a| format!("{a:?}") == action.trim());
+            let key = BINDABLE.into_iter().find(|k| format!("{k:?}") == key.trim());
+            if let (Some(action), Some(key)) = (action, key) {
+                map.bind(action, key);
+            }
+        }
+        map
+    }
+
+    fn save(&self) {
+        let file: String = Action::ALL.iter()
+            .map(|a| format!("{a:?}={:?}\n", self.key(*a)))
+            .collect();
+        if let Err(error) = fs::write(KEYBINDS_PATH, file) {
+            warn!("couldn't save key bindings: {error}");
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ControlsMenu {
+    pub open: bool,
+    // waiting for a key to bind to this
+    capturing: Option<Action>,
+}
+
+#[derive(Component)]
+struct ControlsPanel;
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+enum ControlsButton {
+    Rebind(Action),
+    Reset,
+    Back,
+}
+
+#[derive(Component)]
+struct KeyLabel(Action);
+
+#[derive(Component)]
+struct ConflictLabel(Action);
+
+fn close_controls(mut menu: ResMut<ControlsMenu>) {
+    menu.open = false;
+    menu.capturing = None;
+}
+
+fn show_controls_menu(
+    mut commands: Commands,
+    menu: Res<ControlsMenu>,
+    panel_query: Query<Entity, With<ControlsPanel>>,
+    mut was_open: Local<bool>,
+) {
+    if menu.open == *was_open {
+        return;
+    }
+    *was_open = menu.open;
+
+    for panel in &panel_query {
+        commands.entity(panel).despawn_recursive();
+    }
+    if !menu.open {
+        return;
+    }
+
+    let text = |value: &str, size: f32| TextBundle::from_section(value, TextStyle {
+        font_size: size,
+        color: Color::WHITE,
+        ..default()
+    });
+    let button = |kind: ControlsButton, width: f32| (
+        kind,
+        ButtonBundle {
+            style: Style {
+                width: Val::Px(width),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: BUTTON_COLOR.into(),
+            ..default()
+        },
+    );
+
+    commands.spawn((
+        ControlsPanel,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.95).into(),
+            // over the settings it was opened from
+            z_index: ZIndex::Global(12),
+            focus_policy: FocusPolicy::Block,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((Localized("Controls"), text("Controls", 22.0)));
+
+        for action in Action::ALL {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(8.0),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            }).with_children(|row| {
+                row.spawn((Localized(action.name()), text(action.name(), 18.0).with_style(Style {
+                    width: Val::Px(200.0),
+                    ..default()
+                })));
+                row.spawn(button(ControlsButton::Rebind(action), 140.0))
+                    .with_children(|b| { b.spawn((KeyLabel(action), text("", 18.0))); });
+                row.spawn((ConflictLabel(action), text("", 14.0).with_style(Style {
+                    width: Val::Px(200.0),
+                    ..default()
+                })));
+            });
+        }
+
+        parent.spawn(NodeBundle {
+            style: Style {
+                column_gap: Val::Px(8.0),
+                margin: UiRect::top(Val::Px(12.0)),
+                ..default()
+            },
+            ..default()
+        }).with_children(|row| {
+            row.spawn(button(ControlsButton::Reset, 200.0))
+                .with_children(|b| { b.spawn((Localized("Reset to defaults"), text("Reset to defaults", 20.0))); });
+            row.spawn(button(ControlsButton::Back, 200.0))
+                .with_children(|b| { b.spawn((Localized("Back"), text("Back", 20.0))); });
+        });
+    });
+}
+
+fn controls_input(
+    mut menu: ResMut<ControlsMenu>,
+    mut input_map: ResMut<InputMap>,
+    button_query: Query<(&ControlsButton, &Interaction), Changed<Interaction>>,
+) {
+    if !menu.open {
+        return;
+    }
+
+    for (button, interaction) in &button_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            ControlsButton::Rebind(action) => menu.capturing = Some(*action),
+            ControlsButton::Reset => *input_map = InputMap::default(),
+            ControlsButton::Back => {
+                menu.open = false;
+                menu.capturing = None;
+            }
+        }
+    }
+}
+
+// the next key pressed becomes the binding, escape keeps the old one
+fn capture_binding(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut menu: ResMut<ControlsMenu>,
+    mut input_map: ResMut<InputMap>,
+) {
+    let Some(action) = menu.capturing else {
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        // only cancels the capture, it shouldn't also leave the menu
+        keys.reset(KeyCode::Escape);
+        menu.capturing = None;
+    } else if let Some(key) = keys.get_just_pressed().find(|k| BINDABLE.contains(k)) {
+        input_map.bind(action, *key);
+        menu.capturing = None;
+    }
+}
+
+fn update_controls_ui(
+    menu: Res<ControlsMenu>,
+    input_map: Res<InputMap>,
+    locale: Res<Locale>,
+    mut key_query: Query<(&KeyLabel, &mut Text), Without<ConflictLabel>>,
+    mut conflict_query: Query<(&ConflictLabel, &mut Text)>,
+) {
+    if !menu.open {
+        return;
+    }
+
+    for (KeyLabel(action), mut text) in &mut key_query {
+        text.sections[0].value = if menu.capturing == Some(*action) {
+            locale.get("Press a key").to_string()
+        } else {
+            format!("{:?}", input_map.key(*action))
+        };
+    }
+
+    // sharing a key isn't forbidden, but it's rarely what was meant
+    for (ConflictLabel(action), mut text) in &mut conflict_query {
+        let conflicts = input_map.conflicts(*action);
+        text.sections[0].value = if conflicts.is_empty() {
+            String::new()
+        } else {
+            let names: Vec<&str> = conflicts.iter().map(|a| locale.get(a.name())).collect();
+            locale.fill("also {}", &[&names.join(", ")])
+        };
+        text.sections[0].style.color = CONFLICT_COLOR;
+    }
+}
+
+fn save_bindings(input_map: Res<InputMap>) {
+    // skips the first frame, when it was just loaded
+    if input_map.is_changed() && !input_map.is_added() {
+        input_map.save();
+    }
+}
@@ -0,0 +1,135 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    camera::Camera,
+    hitbox::{resolve_hit, Hurtbox},
+    input::{Action, InputMap},
+    locale::Locale,
+    state::GameplaySet,
+    Player,
+};
+
+/// How close the player has to be to use something.
+pub const INTERACT_RANGE: f32 = 2.5;
+// the camera sits behind the player, so the ray has to reach past them
+const RAY_RANGE: f32 = 30.0;
+
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<Interacted>()
+            .init_resource::<Focus>()
+            .add_systems(Startup, setup_prompt)
+            .add_systems(Update, (find_focus, interact, update_prompt).chain().in_set(GameplaySet));
+    }
+}
+
+/// Something the player can use with E, e.g. a chest with the prompt "Open Chest".
+#[derive(Component)]
+pub struct Interactable {
+    pub prompt: &'static str,
+}
+
+impl Interactable {
+    pub fn new(prompt: &'static str) -> Self {
+        Self { prompt }
+    }
+}
+
+/// Sent when the player uses an interactable.
+#[derive(Event)]
+pub struct Interacted {
+    pub entity: Entity,
+}
+
+/// The interactable the player would use by pressing E right now.
+#[derive(Resource, Default)]
+pub struct Focus(pub Option<Entity>);
+
+#[derive(Component)]
+struct Prompt;
+
+fn setup_prompt(mut commands: Commands) {
+    commands.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            top: Val::Percent(60.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        ..default()
+    }).with_children(|parent| {
+        parent.spawn((
+            Prompt,
+            TextBundle::from_section("", TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..default()
+            }),
+        ));
+    });
+}
+
+fn find_focus(
+    mut focus: ResMut<Focus>,
+    spatial_query: SpatialQuery,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    camera_query: Query<&Transform, (With<Camera>, Without<Player>)>,
+    interactable_query: Query<(Entity, &Transform), With<Interactable>>,
+    hurtbox_query: Query<&Hurtbox>,
+) {
+    let (player, transform) = player_query.get_single().expect("not one player");
+    let camera = camera_query.get_single().expect("not one camera");
+    let in_range = |entity: Entity| interactable_query.get(entity)
+        .is_ok_and(|(_, t)| t.translation.distance(transform.translation) <= INTERACT_RANGE);
+
+    // whatever is under the crosshair wins
+    let looked_at = spatial_query.cast_ray(
+        camera.translation,
+        camera.forward(),
+        RAY_RANGE,
+        true,
+        SpatialQueryFilter::new().without_entities([player]),
+    ).map(|hit| resolve_hit(&hurtbox_query, hit.entity).0);
+
+    // otherwise the nearest one, since not everything has a collider to hit
+    focus.0 = looked_at.filter(|e| in_range(*e)).or_else(|| {
+        interactable_query.iter()
+            .map(|(e, t)| (e, t.translation.distance(transform.translation)))
+            .filter(|(_, d)| *d <= INTERACT_RANGE)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(e, _)| e)
+    });
+}
+
+pub fn interact(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    focus: Res<Focus>,
+    mut interacted_events: EventWriter<Interacted>,
+) {
+    if !input_map.just_pressed(Action::Interact, &keys) {
+        return;
+    }
+    if let Some(entity) = focus.0 {
+        interacted_events.send(Interacted { entity });
+    }
+}
+
+fn update_prompt(
+    focus: Res<Focus>,
+    input_map: Res<InputMap>,
+    locale: Res<Locale>,
+    interactable_query: Query<&Interactable>,
+    mut prompt_query: Query<&mut Text, With<Prompt>>,
+) {
+    let mut text = prompt_query.get_single_mut().expect("not one prompt");
+    text.sections[0].value = focus.0
+        .and_then(|e| interactable_query.get(e).ok())
+        .map(|i| format!("{:?} — {}", input_map.key(Action::Interact), locale.get(i.prompt)))
+        .unwrap_or_default();
+}
@@ -0,0 +1,247 @@
+use bevy::prelude::*;
+
+use crate::item::{Item, ItemStack};
+
+#[derive(Clone, Debug, Default)]
+pub struct Slot {
+    pub stack: Option<ItemStack>,
+    // if set, only this item may be put into the slot
+    pub filter: Option<Item>,
+}
+
+impl Slot {
+    pub fn accepts(&self, item: Item) -> bool {
+        self.filter.is_none_or(|f| f == item)
+            && self.stack.is_none_or(|s| s.item == item)
+    }
+
+    fn space_for(&self, item: Item) -> u32 {
+        if !self.accepts(item) {
+            return 0;
+        }
+
+        item.max_stack() - self.stack.map_or(0, |s| s.count)
+    }
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct Inventory {
+    slots: Vec<Slot>,
+}
+
+impl Inventory {
+    pub fn new(size: usize) -> Self {
+        Inventory {
+            slots: vec![Slot::default(); size],
+        }
+    }
+
+    pub fn slots(&self) -> &[Slot] {
+        &self.slots
+    }
+
+    pub fn set_filter(&mut self, slot: usize, filter: Option<Item>) {
+        if let Some(slot) = self.slots.get_mut(slot) {
+            slot.filter = filter;
+        }
+    }
+
+    pub fn space_for(&self, item: Item) -> u32 {
+        self.slots.iter().map(|s| s.space_for(item)).sum()
+    }
+
+    pub fn can_insert(&self, stack: ItemStack) -> bool {
+        self.space_for(stack.item) >= stack.count
+    }
+
+    pub fn count(&self, item: Item) -> u32 {
+        self.slots.iter()
+            .filter_map(|s| s.stack)
+            .filter(|s| s.item == item)
+            .map(|s| s.count)
+            .sum()
+    }
+
+    pub fn contains_all(&self, items: &[(Item, u32)]) -> bool {
+        items.iter().all(|(item, n)| self.count(*item) >= *n)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(|s| s.stack.is_none())
+    }
+
+    // every slot holds a full stack
+    pub fn is_full(&self) -> bool {
+        self.slots.iter().all(|s| s.stack.is_some_and(|s| s.count >= s.item.max_stack()))
+    }
+
+    /// Inserts as much of `stack` as fits and returns the amount that didn't.
+    pub fn insert(&mut self, stack: ItemStack) -> u32 {
+        let mut remaining = stack.count;
+
+        // top up existing stacks first so we don't fragment the inventory
+        for slot in self.slots.iter_mut().filter(|s| s.stack.is_some()) {
+            remaining -= Self::fill(slot, stack, remaining);
+        }
+
+        for slot in self.slots.iter_mut().filter(|s| s.stack.is_none()) {
+            remaining -= Self::fill(slot, stack, remaining);
+        }
+
+        remaining
+    }
+
+    /// Inserts all of `stack` or, if it doesn't fit, none of it. Belts and
+    /// other automation hand items over this way so nothing is half moved.
+    pub fn try_insert(&mut self, stack: ItemStack) -> bool {
+        if !self.can_insert(stack) {
+            return false;
+        }
+
+        self.insert(stack);
+        true
+    }
+
+    fn fill(slot: &mut Slot, stack: ItemStack, amount: u32) -> u32 {
+        let moved = slot.space_for(stack.item).min(amount);
+
+        if moved > 0 {
+            let current = slot.stack.unwrap_or(ItemStack { count: 0, ..stack });
+            slot.stack = Some(ItemStack { count: current.count + moved, ..current });
+        }
+
+        moved
+    }
+
+    /// Takes up to `max` items out of the first stack matching `item`
+    /// (or any stack if `item` is `None`).
+    pub fn extract(&mut self, item: Option<Item>, max: u32) -> Option<ItemStack> {
+        let slot = self.slots.iter_mut()
+            .find(|s| s.stack.is_some_and(|s| item.is_none_or(|i| i == s.item)))?;
+
+        let stack = slot.stack.as_mut()?;
+        let taken = stack.count.min(max);
+        stack.count -= taken;
+
+        let result = ItemStack { count: taken, ..*stack };
+        if stack.count == 0 {
+            slot.stack = None;
+        }
+
+        Some(result)
+    }
+
+    /// Takes up to `max` items out of one particular slot.
+    pub fn take(&mut self, slot: usize, max: u32) -> Option<ItemStack> {
+        let slot = self.slots.get_mut(slot)?;
+        let stack = slot.stack.as_mut()?;
+        let taken = stack.count.min(max);
+        stack.count -= taken;
+
+        let result = ItemStack { count: taken, ..*stack };
+        if stack.count == 0 {
+            slot.stack = None;
+        }
+
+        Some(result)
+    }
+
+    /// Puts `stack` into one particular slot, topping up a matching stack
+    /// or swapping out a different one. Hands back whatever isn't in there
+    /// afterwards.
+    pub fn place(&mut self, slot: usize, stack: ItemStack) -> Option<ItemStack> {
+        let Some(target) = self.slots.get_mut(slot) else {
+            return Some(stack);
+        };
+        if target.filter.is_some_and(|f| f != stack.item) {
+            return Some(stack);
+        }
+
+        match target.stack {
+            Some(current) if current.item == stack.item => {
+                let moved = Self::fill(target, stack, stack.count);
+                (moved < stack.count).then_some(ItemStack { count: stack.count - moved, ..stack })
+            }
+            _ => target.stack.replace(stack),
+        }
+    }
+
+    /// Removes exactly `count` of `item` if available, otherwise nothing.
+    pub fn remove(&mut self, item: Item, count: u32) -> bool {
+        if self.count(item) < count {
+            return false;
+        }
+
+        let mut remaining = count;
+        while remaining > 0 {
+            let Some(taken) = self.extract(Some(item), remaining) else {
+                break;
+            };
+            remaining -= taken.count;
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_tops_up_before_using_empty_slots() {
+        let mut inventory = Inventory::new(3);
+        inventory.insert(ItemStack::new(Item::Stone, 10));
+        inventory.insert(ItemStack::new(Item::Wood, 5));
+
+        assert_eq!(inventory.insert(ItemStack::new(Item::Stone, 45)), 0);
+        assert_eq!(inventory.slots()[0].stack.map(|s| s.count), Some(50));
+        assert_eq!(inventory.slots()[1].stack.map(|s| s.item), Some(Item::Wood));
+        assert_eq!(inventory.slots()[2].stack.map(|s| s.count), Some(5));
+    }
+
+    #[test]
+    fn insert_respects_stack_sizes_and_returns_the_rest() {
+        let mut inventory = Inventory::new(2);
+
+        assert_eq!(inventory.insert(ItemStack::new(Item::ManaCrystal, 50)), 10);
+        assert_eq!(inventory.count(Item::ManaCrystal), 40);
+        assert!(!inventory.can_insert(ItemStack::new(Item::ManaCrystal, 1)));
+    }
+
+    #[test]
+    fn filtered_slots_only_take_their_item() {
+        let mut inventory = Inventory::new(2);
+        inventory.set_filter(0, Some(Item::Wood));
+
+        assert_eq!(inventory.insert(ItemStack::new(Item::Stone, 60)), 10);
+        assert!(inventory.slots()[0].stack.is_none());
+        assert_eq!(inventory.space_for(Item::Wood), 50);
+        assert_eq!(inventory.space_for(Item::Stone), 0);
+    }
+
+    #[test]
+    fn extract_takes_from_matching_stacks() {
+        let mut inventory = Inventory::new(2);
+        inventory.insert(ItemStack::new(Item::Wood, 3));
+        inventory.insert(ItemStack::new(Item::Stone, 8));
+
+        let taken = inventory.extract(Some(Item::Stone), 5).expect("stone in there");
+        assert_eq!((taken.item, taken.count), (Item::Stone, 5));
+        assert_eq!(inventory.extract(None, u32::MAX).map(|s| (s.item, s.count)), Some((Item::Wood, 3)));
+        assert!(inventory.extract(Some(Item::Wood), 1).is_none());
+        assert!(inventory.remove(Item::Stone, 3));
+        assert!(inventory.is_empty());
+    }
+
+    #[test]
+    fn try_insert_is_all_or_nothing() {
+        let mut inventory = Inventory::new(1);
+        inventory.insert(ItemStack::new(Item::Stone, 45));
+
+        assert!(!inventory.try_insert(ItemStack::new(Item::Stone, 10)));
+        assert_eq!(inventory.count(Item::Stone), 45);
+        assert!(inventory.try_insert(ItemStack::new(Item::Stone, 5)));
+        assert_eq!(inventory.count(Item::Stone), 50);
+    }
+}
@@ -0,0 +1,378 @@
+use bevy::{
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    brewing::{DrinkPotion, Potion},
+    camera::{CursorUser, CursorUsers},
+    equipment::{Equipment, GearSlot},
+    ground::DropItem,
+    input::{Action, InputMap},
+    inventory::Inventory,
+    item::ItemStack,
+    locale::Locale,
+    state::GameplaySet,
+    tooltip::ItemTooltip,
+    Player,
+};
+
+const COLUMNS: usize = 6;
+const SLOT_SIZE: f32 = 48.0;
+const SLOT_GAP: f32 = 4.0;
+const SLOT_COLOR: Color = Color::rgba(0.2, 0.2, 0.2, 0.8);
+// far enough in front of the player that it isn't picked right back up
+const DROP_DISTANCE: f32 = 2.0;
+
+pub struct InventoryUiPlugin;
+
+impl Plugin for InventoryUiPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<InventoryScreen>()
+            .add_systems(Update, (
+                toggle_inventory,
+                show_inventory,
+                drag_items,
+                update_inventory_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct InventoryScreen {
+    pub open: bool,
+    // picked up and following the cursor, and where it came from
+    held: Option<(ItemStack, SlotRef)>,
+}
+
+impl InventoryScreen {
+    pub fn is_holding(&self) -> bool {
+        self.held.is_some()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SlotRef {
+    Bag(usize),
+    Gear(GearSlot),
+}
+
+#[derive(Component)]
+struct InventoryPanel;
+
+// everything outside of this counts as throwing things away
+#[derive(Component)]
+struct InventoryBox;
+
+#[derive(Component)]
+struct ItemSlot(SlotRef);
+
+#[derive(Component)]
+struct HeldIcon;
+
+fn toggle_inventory(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut screen: ResMut<InventoryScreen>,
+    mut cursor_users: ResMut<CursorUsers>,
+    mut player_query: Query<(&mut Inventory, &mut Equipment, &Transform), With<Player>>,
+    mut drop_events: EventWriter<DropItem>,
+) {
+    if !input_map.just_pressed(Action::Inventory, &keys) {
+        return;
+    }
+
+    screen.open = !screen.open;
+    cursor_users.set(CursorUser::Inventory, screen.open);
+
+    // nothing stays stuck to the cursor once it's closed
+    if let Some((stack, origin)) = screen.held.take() {
+        let (mut inventory, mut equipment, transform) = player_query.get_single_mut().expect("not one player");
+        if let Some(rest) = stow(stack, origin, &mut inventory, &mut equipment) {
+            drop_events.send(DropItem { stack: rest, pos: drop_point(transform), magnetic: false });
+        }
+    }
+}
+
+fn show_inventory(
+    mut commands: Commands,
+    screen: Res<InventoryScreen>,
+    player_query: Query<&Inventory, With<Player>>,
+    panel_query: Query<Entity, With<InventoryPanel>>,
+    mut was_open: Local<bool>,
+) {
+    if screen.open == *was_open {
+        return;
+    }
+    *was_open = screen.open;
+
+    for panel in &panel_query {
+        commands.entity(panel).despawn_recursive();
+    }
+    if !screen.open {
+        return;
+    }
+
+    let inventory = player_query.get_single().expect("not one player");
+    let slot = |slot: SlotRef| (
+        ItemSlot(slot),
+        ItemTooltip::default(),
+        NodeBundle {
+            style: Style {
+                width: Val::Px(SLOT_SIZE),
+                height: Val::Px(SLOT_SIZE),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: SLOT_COLOR.into(),
+            ..default()
+        },
+    );
+    let label = || TextBundle::from_section("", TextStyle {
+        font_size: 12.0,
+        color: Color::WHITE,
+        ..default()
+    });
+
+    commands.spawn((
+        InventoryPanel,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            InventoryBox,
+            NodeBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(12.0)),
+                    column_gap: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                ..default()
+            },
+        )).with_children(|parent| {
+            // what's worn on the left, what's carried on the right
+            parent.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(SLOT_GAP),
+                    ..default()
+                },
+                ..default()
+            }).with_children(|column| {
+                for gear in GearSlot::ALL {
+                    column.spawn(slot(SlotRef::Gear(gear))).with_children(|s| { s.spawn(label()); });
+                }
+            });
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(COLUMNS as f32 * (SLOT_SIZE + SLOT_GAP)),
+                    flex_wrap: FlexWrap::Wrap,
+                    row_gap: Val::Px(SLOT_GAP),
+                    column_gap: Val::Px(SLOT_GAP),
+                    ..default()
+                },
+                ..default()
+            }).with_children(|grid| {
+                for i in 0..inventory.slots().len() {
+                    grid.spawn(slot(SlotRef::Bag(i))).with_children(|s| { s.spawn(label()); });
+                }
+            });
+        });
+
+        parent.spawn((
+            HeldIcon,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(SLOT_SIZE),
+                    height: Val::Px(SLOT_SIZE),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(5),
+                ..default()
+            },
+        )).with_children(|s| { s.spawn(label()); });
+    });
+}
+
+// left drags a whole stack and right half of it, shift-click uses an item,
+// and letting go outside the window throws it away
+fn drag_items(
+    mouse: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    mut screen: ResMut<InventoryScreen>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    slot_query: Query<(&ItemSlot, &Node, &GlobalTransform)>,
+    box_query: Query<(&Node, &GlobalTransform), With<InventoryBox>>,
+    mut player_query: Query<(Entity, &mut Inventory, &mut Equipment, &Transform), With<Player>>,
+    mut drop_events: EventWriter<DropItem>,
+    mut drink_events: EventWriter<DrinkPotion>,
+) {
+    if !screen.open {
+        return;
+    }
+    let Some(cursor) = window_query.get_single().expect("not one window").cursor_position() else {
+        return;
+    };
+    let (player, mut inventory, mut equipment, transform) = player_query.get_single_mut().expect("not one player");
+
+    let hovered = slot_query.iter()
+        .find(|(_, node, t)| node.logical_rect(t).contains(cursor))
+        .map(|(slot, ..)| slot.0);
+
+    if let Some((stack, origin)) = screen.held {
+        if !mouse.any_just_released([MouseButton::Left, MouseButton::Right]) {
+            return;
+        }
+        screen.held = None;
+
+        let inside = box_query.iter().any(|(node, t)| node.logical_rect(t).contains(cursor));
+        let rest = match hovered {
+            Some(SlotRef::Bag(i)) => inventory.place(i, stack),
+            Some(SlotRef::Gear(gear)) if stack.item.gear_slot() == Some(gear) => equipment.equip(stack),
+            _ if !inside => {
+                drop_events.send(DropItem { stack, pos: drop_point(transform), magnetic: false });
+                None
+            }
+            _ => Some(stack),
+        };
+        // whatever got swapped out goes where the held stack came from
+        if let Some(rest) = rest.and_then(|r| stow(r, origin, &mut inventory, &mut equipment)) {
+            drop_events.send(DropItem { stack: rest, pos: drop_point(transform), magnetic: false });
+        }
+        return;
+    }
+
+    let Some(slot) = hovered else {
+        return;
+    };
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+
+    if mouse.just_pressed(MouseButton::Left) && shift {
+        let SlotRef::Bag(i) = slot else {
+            return;
+        };
+        let Some(stack) = inventory.slots()[i].stack else {
+            return;
+        };
+        if let Some(potion) = Potion::ALL.into_iter().find(|p| p.item() == stack.item) {
+            drink_events.send(DrinkPotion { drinker: player, potion });
+        } else if stack.item.gear_slot().is_some() {
+            let worn = inventory.take(i, 1).and_then(|s| equipment.equip(s));
+            if let Some(previous) = worn {
+                inventory.place(i, previous);
+            }
+        }
+    } else if mouse.just_pressed(MouseButton::Left) {
+        screen.held = match slot {
+            SlotRef::Bag(i) => inventory.take(i, u32::MAX),
+            SlotRef::Gear(gear) => equipment.unequip(gear),
+        }.map(|stack| (stack, slot));
+    } else if mouse.just_pressed(MouseButton::Right) {
+        if let SlotRef::Bag(i) = slot {
+            let half = inventory.slots()[i].stack.map_or(0, |s| s.count.div_ceil(2));
+            screen.held = inventory.take(i, half).map(|stack| (stack, slot));
+        }
+    }
+}
+
+fn update_inventory_ui(
+    screen: Res<InventoryScreen>,
+    locale: Res<Locale>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    player_query: Query<(&Inventory, &Equipment), With<Player>>,
+    mut slot_query: Query<(&ItemSlot, &mut ItemTooltip, &mut BackgroundColor, &Children)>,
+    mut held_query: Query<
+        (&mut Style, &mut Visibility, &mut BackgroundColor, &Children),
+        (With<HeldIcon>, Without<ItemSlot>),
+    >,
+    mut text_query: Query<&mut Text>,
+) {
+    if !screen.open {
+        return;
+    }
+    let (inventory, equipment) = player_query.get_single().expect("not one player");
+
+    for (ItemSlot(slot), mut tooltip, mut background, children) in &mut slot_query {
+        let stack = match slot {
+            SlotRef::Bag(i) => inventory.slots()[*i].stack,
+            SlotRef::Gear(gear) => equipment.get(*gear).copied(),
+        };
+        // nothing to read up on while dragging something around
+        tooltip.0 = stack.filter(|_| screen.held.is_none());
+        *background = stack.map_or(SLOT_COLOR, |s| s.item.color().with_a(0.8)).into();
+
+        let Some(mut text) = children.first().and_then(|c| text_query.get_mut(*c).ok()) else {
+            continue;
+        };
+        text.sections[0].value = match (stack, slot) {
+            (Some(stack), _) => describe(stack, &locale),
+            // so the empty gear slots say what goes in them
+            (None, SlotRef::Gear(gear)) => locale.get(gear.name()).to_string(),
+            (None, SlotRef::Bag(_)) => String::new(),
+        };
+    }
+
+    let (mut style, mut visibility, mut background, children) =
+        held_query.get_single_mut().expect("not one held icon");
+    let cursor = window_query.get_single().expect("not one window").cursor_position();
+    let (Some((stack, _)), Some(cursor)) = (screen.held, cursor) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    *visibility = Visibility::Visible;
+    style.left = Val::Px(cursor.x - SLOT_SIZE / 2.0);
+    style.top = Val::Px(cursor.y - SLOT_SIZE / 2.0);
+    *background = stack.item.color().with_a(0.8).into();
+    if let Some(mut text) = children.first().and_then(|c| text_query.get_mut(*c).ok()) {
+        text.sections[0].value = describe(stack, &locale);
+    }
+}
+
+fn describe(stack: ItemStack, locale: &Locale) -> String {
+    let name = locale.get(stack.item.name());
+    if stack.count > 1 {
+        format!("{}\n{}", name, stack.count)
+    } else {
+        name.to_string()
+    }
+}
+
+// puts `stack` back where it came from, or anywhere it fits, and hands
+// back what doesn't
+fn stow(
+    stack: ItemStack,
+    origin: SlotRef,
+    inventory: &mut Inventory,
+    equipment: &mut Equipment,
+) -> Option<ItemStack> {
+    let stack = match origin {
+        SlotRef::Bag(i) => inventory.place(i, stack)?,
+        SlotRef::Gear(gear) if equipment.get(gear).is_none() => equipment.equip(stack)?,
+        SlotRef::Gear(_) => stack,
+    };
+
+    let remaining = inventory.insert(stack);
+    (remaining > 0).then_some(ItemStack { count: remaining, ..stack })
+}
+
+fn drop_point(transform: &Transform) -> Vec3 {
+    let forward = transform.forward();
+    transform.translation + Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero() * DROP_DISTANCE
+}
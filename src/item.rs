@@ -0,0 +1,236 @@
+use bevy::prelude::*;
+
+use crate::{
+    enchanting::{Enchantment, MAX_ENCHANTMENTS},
+    equipment::GearSlot,
+    implement::Rarity,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Item {
+    Stone,
+    Wood,
+    IronOre,
+    CopperOre,
+    IronPlate,
+    CopperPlate,
+    Gear,
+    ManaCrystal,
+    SpeedModule,
+    EfficiencyModule,
+    RangeModule,
+    RepairKit,
+    BoltRune,
+    NovaRune,
+    BeamRune,
+    FireRune,
+    FrostRune,
+    ArcRune,
+    PowerRune,
+    HasteRune,
+    Essence,
+    Staff,
+    Wand,
+    Robe,
+    Amulet,
+    HealingPotion,
+    HastePotion,
+    ResistancePotion,
+}
+
+impl Item {
+    pub const ALL: [Item; 28] = [
+        Item::Stone,
+        Item::Wood,
+        Item::IronOre,
+        Item::CopperOre,
+        Item::IronPlate,
+        Item::CopperPlate,
+        Item::Gear,
+        Item::ManaCrystal,
+        Item::SpeedModule,
+        Item::EfficiencyModule,
+        Item::RangeModule,
+        Item::RepairKit,
+        Item::BoltRune,
+        Item::NovaRune,
+        Item::BeamRune,
+        Item::FireRune,
+        Item::FrostRune,
+        Item::ArcRune,
+        Item::PowerRune,
+        Item::HasteRune,
+        Item::Essence,
+        Item::Staff,
+        Item::Wand,
+        Item::Robe,
+        Item::Amulet,
+        Item::HealingPotion,
+        Item::HastePotion,
+        Item::ResistancePotion,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Item::Stone => "Stone",
+            Item::Wood => "Wood",
+            Item::IronOre => "Iron Ore",
+            Item::CopperOre => "Copper Ore",
+            Item::IronPlate => "Iron Plate",
+            Item::CopperPlate => "Copper Plate",
+            Item::Gear => "Gear",
+            Item::ManaCrystal => "Mana Crystal",
+            Item::SpeedModule => "Speed Module",
+            Item::EfficiencyModule => "Efficiency Module",
+            Item::RangeModule => "Range Module",
+            Item::RepairKit => "Repair Kit",
+            Item::BoltRune => "Bolt Rune",
+            Item::NovaRune => "Nova Rune",
+            Item::BeamRune => "Beam Rune",
+            Item::FireRune => "Fire Rune",
+            Item::FrostRune => "Frost Rune",
+            Item::ArcRune => "Arc Rune",
+            Item::PowerRune => "Power Rune",
+            Item::HasteRune => "Haste Rune",
+            Item::Essence => "Essence",
+            Item::Staff => "Staff",
+            Item::Wand => "Wand",
+            Item::Robe => "Robe",
+            Item::Amulet => "Amulet",
+            Item::HealingPotion => "Healing Potion",
+            Item::HastePotion => "Haste Potion",
+            Item::ResistancePotion => "Resistance Potion",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Item::Stone => "Mined from the ground, good for building and runes.",
+            Item::Wood => "Chopped from trees.",
+            Item::IronOre => "Smelted into iron plates.",
+            Item::CopperOre => "Smelted into copper plates.",
+            Item::IronPlate => "The backbone of most machinery.",
+            Item::CopperPlate => "Conducts mana well.",
+            Item::Gear => "Keeps machines turning.",
+            Item::ManaCrystal => "Condensed mana, spent on almost everything magical.",
+            Item::SpeedModule => "Makes a machine work faster at a higher mana cost.",
+            Item::EfficiencyModule => "Makes a machine use less mana.",
+            Item::RangeModule => "Extends the reach of a machine.",
+            Item::RepairKit => "Restores a worn down machine.",
+            Item::BoltRune => "Shapes an inscribed spell into a bolt.",
+            Item::NovaRune => "Shapes an inscribed spell into a nova.",
+            Item::BeamRune => "Shapes an inscribed spell into a beam.",
+            Item::FireRune => "Inscribes a spell with fire.",
+            Item::FrostRune => "Inscribes a spell with frost.",
+            Item::ArcRune => "Inscribes a spell with lightning.",
+            Item::PowerRune => "Makes an inscribed spell hit harder.",
+            Item::HasteRune => "Makes an inscribed spell quicker to cast.",
+            Item::Essence => "Distilled magic, bound into gear when enchanting.",
+            Item::Staff => "A frugal casting implement.",
+            Item::Wand => "A quick casting implement.",
+            Item::Robe => "Armor that holds enchantments.",
+            Item::Amulet => "A trinket that holds enchantments.",
+            Item::HealingPotion => "Heals over a few seconds.",
+            Item::HastePotion => "Makes you faster for a while.",
+            Item::ResistancePotion => "Softens incoming damage for a while.",
+        }
+    }
+
+    pub fn max_stack(&self) -> u32 {
+        match self {
+            Item::ManaCrystal => 20,
+            Item::SpeedModule | Item::EfficiencyModule | Item::RangeModule | Item::RepairKit => 10,
+            Item::HealingPotion | Item::HastePotion | Item::ResistancePotion => 10,
+            item if item.is_rune() => 10,
+            // every piece of gear carries its own enchantments
+            item if item.gear_slot().is_some() => 1,
+            Item::Gear => 100,
+            _ => 50,
+        }
+    }
+
+    pub fn is_rune(&self) -> bool {
+        matches!(
+            self,
+            Item::BoltRune
+                | Item::NovaRune
+                | Item::BeamRune
+                | Item::FireRune
+                | Item::FrostRune
+                | Item::ArcRune
+                | Item::PowerRune
+                | Item::HasteRune
+        )
+    }
+
+    pub fn gear_slot(&self) -> Option<GearSlot> {
+        match self {
+            Item::Staff | Item::Wand => Some(GearSlot::Weapon),
+            Item::Robe => Some(GearSlot::Armor),
+            Item::Amulet => Some(GearSlot::Trinket),
+            _ => None,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Item::Stone => Color::GRAY,
+            Item::Wood => Color::rgb_u8(133, 94, 66),
+            Item::IronOre => Color::rgb_u8(110, 90, 85),
+            Item::CopperOre => Color::rgb_u8(184, 115, 51),
+            Item::IronPlate => Color::SILVER,
+            Item::CopperPlate => Color::ORANGE,
+            Item::Gear => Color::DARK_GRAY,
+            Item::ManaCrystal => Color::CYAN,
+            Item::SpeedModule => Color::rgb_u8(80, 140, 255),
+            Item::EfficiencyModule => Color::rgb_u8(90, 220, 90),
+            Item::RangeModule => Color::rgb_u8(230, 200, 60),
+            Item::RepairKit => Color::rgb_u8(200, 50, 50),
+            Item::BoltRune => Color::rgb_u8(150, 120, 255),
+            Item::NovaRune => Color::rgb_u8(200, 150, 255),
+            Item::BeamRune => Color::rgb_u8(255, 120, 200),
+            Item::FireRune => Color::rgb_u8(255, 110, 30),
+            Item::FrostRune => Color::rgb_u8(150, 220, 255),
+            Item::ArcRune => Color::rgb_u8(240, 240, 90),
+            Item::PowerRune => Color::rgb_u8(170, 30, 60),
+            Item::HasteRune => Color::rgb_u8(60, 200, 160),
+            Item::Essence => Color::rgb_u8(210, 130, 255),
+            Item::Staff => Color::rgb_u8(120, 80, 40),
+            Item::Wand => Color::rgb_u8(200, 180, 150),
+            Item::Robe => Color::rgb_u8(70, 60, 150),
+            Item::Amulet => Color::GOLD,
+            Item::HealingPotion => Color::rgb_u8(230, 40, 70),
+            Item::HastePotion => Color::rgb_u8(250, 220, 60),
+            Item::ResistancePotion => Color::rgb_u8(120, 130, 150),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ItemStack {
+    pub item: Item,
+    pub count: u32,
+    // only ever set on gear, which doesn't stack
+    pub enchantments: [Option<Enchantment>; MAX_ENCHANTMENTS],
+    pub rarity: Rarity,
+}
+
+impl ItemStack {
+    pub fn new(item: Item, count: u32) -> Self {
+        ItemStack { item, count, enchantments: [None; MAX_ENCHANTMENTS], rarity: Rarity::Common }
+    }
+
+    pub fn enchantments(&self) -> impl Iterator<Item = Enchantment> + '_ {
+        self.enchantments.iter().flatten().copied()
+    }
+
+    /// Puts `enchantment` into the first free socket, if there is one.
+    pub fn enchant(&mut self, enchantment: Enchantment) -> bool {
+        let Some(free) = self.enchantments.iter_mut().find(|e| e.is_none()) else {
+            return false;
+        };
+
+        *free = Some(enchantment);
+        true
+    }
+}
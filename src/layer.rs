@@ -0,0 +1,39 @@
+use bevy_xpbd_3d::prelude::*;
+
+/// Collision layers, anything that doesn't set its own `CollisionLayers`
+/// is on all of them and collides with everything.
+#[derive(PhysicsLayer)]
+pub enum Layer {
+    World,
+    Enemy,
+    // weak spots and the like, see hitbox.rs
+    EnemyHurtbox,
+    PlayerProjectile,
+    EnemyProjectile,
+    Barrier,
+}
+
+impl Layer {
+    // enemies are solid to everything but their own projectiles
+    pub fn enemy() -> CollisionLayers {
+        CollisionLayers::new([Layer::Enemy], [Layer::World, Layer::Enemy, Layer::PlayerProjectile, Layer::Barrier])
+    }
+
+    pub fn player_projectile() -> CollisionLayers {
+        CollisionLayers::new([Layer::PlayerProjectile], [Layer::World, Layer::Enemy, Layer::EnemyHurtbox])
+    }
+
+    // only there to be hit, nothing bumps into them
+    pub fn enemy_hurtbox() -> CollisionLayers {
+        CollisionLayers::new([Layer::EnemyHurtbox], [Layer::PlayerProjectile])
+    }
+
+    pub fn enemy_projectile() -> CollisionLayers {
+        CollisionLayers::new([Layer::EnemyProjectile], [Layer::World, Layer::Barrier])
+    }
+
+    // barriers only care about the enemy and what it throws at them
+    pub fn barrier() -> CollisionLayers {
+        CollisionLayers::new([Layer::Barrier], [Layer::Enemy, Layer::EnemyProjectile])
+    }
+}
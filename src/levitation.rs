@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    character::Stats,
+    dilation::TimeDilation,
+    input::{Action, InputMap},
+    magic::{SpellBook, SpellCast, SpellEffect},
+    minecart::Riding,
+    state::GameplaySet,
+};
+
+// how hard the caster gets slammed down when the mana runs dry
+const HARD_LANDING_SPEED: f32 = 12.0;
+
+pub struct LevitationPlugin;
+
+impl Plugin for LevitationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (toggle_flight, sustain_flight, steer_flight).chain().in_set(GameplaySet));
+    }
+}
+
+/// Gravity is off and the vertical movement keys are live.
+#[derive(Component)]
+pub struct Flying {
+    pub mana_per_second: f32,
+    pub climb_speed: f32,
+}
+
+fn toggle_flight(
+    mut commands: Commands,
+    book: Res<SpellBook>,
+    flying_query: Query<(), With<Flying>>,
+    mut cast_events: EventReader<SpellCast>,
+) {
+    for event in cast_events.read() {
+        let Some(SpellEffect::Levitate { mana_per_second, climb_speed }) =
+            book.get(event.spell).map(|s| s.effect)
+        else {
+            continue;
+        };
+
+        // casting it again is a soft landing
+        if flying_query.contains(event.caster) {
+            commands.entity(event.caster).remove::<Flying>().insert(GravityScale(1.0));
+        } else {
+            commands.entity(event.caster).insert((Flying { mana_per_second, climb_speed }, GravityScale(0.0)));
+        }
+    }
+}
+
+fn sustain_flight(
+    mut commands: Commands,
+    time: Res<Time>,
+    dilation: Res<TimeDilation>,
+    mut flying_query: Query<(Entity, &Flying, &mut Stats, &mut LinearVelocity)>,
+) {
+    let dt = dilation.unscaled(&time);
+
+    for (entity, flying, mut stats, mut velocity) in &mut flying_query {
+        if stats.spend_mana(flying.mana_per_second * dt) {
+            continue;
+        }
+
+        commands.entity(entity).remove::<Flying>().insert(GravityScale(1.0));
+        velocity.y = -HARD_LANDING_SPEED;
+    }
+}
+
+fn steer_flight(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    dilation: Res<TimeDilation>,
+    mut flying_query: Query<(&Flying, &mut LinearVelocity), Without<Riding>>,
+) {
+    for (flying, mut velocity) in &mut flying_query {
+        let mut climb = 0.0;
+        if input_map.pressed(Action::Jump, &keys) {
+            climb += 1.0;
+        }
+        if input_map.pressed(Action::Dodge, &keys) {
+            climb -= 1.0;
+        }
+
+        // hover in place when neither is held
+        velocity.y = climb * flying.climb_speed / dilation.factor;
+    }
+}
@@ -0,0 +1,152 @@
+use bevy::{asset::{Asset, LoadState, UntypedAssetId}, prelude::*};
+
+use crate::{locale::Localized, state::GameState};
+
+// frames to hold the screen once everything is loaded, so freshly made
+// meshes and materials reach the gpu before the world is shown
+const SETTLE_FRAMES: u32 = 3;
+const BAR_WIDTH: f32 = 300.0;
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Preload>()
+            .add_systems(OnEnter(GameState::Loading), setup_loading_screen)
+            .add_systems(OnExit(GameState::Loading), despawn_loading_screen)
+            .add_systems(Update, track_loading.run_if(in_state(GameState::Loading)));
+    }
+}
+
+/// Files from disk the game can't start without. Plugins add theirs on
+/// startup and the handles are kept for good, so nothing is loaded again
+/// the first time it's needed.
+#[derive(Resource, Default)]
+pub struct Preload(Vec<UntypedHandle>);
+
+impl Preload {
+    pub fn add<A: Asset>(&mut self, handle: Handle<A>) {
+        self.0.push(handle.untyped());
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct ProgressBar;
+
+fn setup_loading_screen(mut commands: Commands) {
+    commands.spawn((
+        LoadingScreen,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            // opaque so nothing pops in behind it
+            background_color: Color::BLACK.into(),
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            Localized("Loading"),
+            TextBundle::from_section("Loading", TextStyle {
+                font_size: 24.0,
+                color: Color::WHITE,
+                ..default()
+            }),
+        ));
+        parent.spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(BAR_WIDTH),
+                height: Val::Px(8.0),
+                ..default()
+            },
+            background_color: Color::DARK_GRAY.into(),
+            ..default()
+        }).with_children(|bar| {
+            bar.spawn((
+                ProgressBar,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: Color::WHITE.into(),
+                    ..default()
+                },
+            ));
+        });
+    });
+}
+
+fn despawn_loading_screen(
+    mut commands: Commands,
+    screen_query: Query<Entity, With<LoadingScreen>>,
+) {
+    for screen in &screen_query {
+        commands.entity(screen).despawn_recursive();
+    }
+}
+
+// the world is built when loading starts, this waits for whatever it uses
+// and everything that was preloaded
+fn track_loading(
+    asset_server: Res<AssetServer>,
+    preload: Res<Preload>,
+    mesh_query: Query<&Handle<Mesh>>,
+    material_query: Query<&Handle<StandardMaterial>>,
+    image_query: Query<&UiImage>,
+    mut bar_query: Query<&mut Style, With<ProgressBar>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut settled: Local<u32>,
+) {
+    let handles: Vec<UntypedAssetId> = mesh_query.iter().map(|h| h.id().untyped())
+        .chain(material_query.iter().map(|h| h.id().untyped()))
+        .chain(image_query.iter().map(|i| i.texture.id().untyped()))
+        .collect();
+
+    // assets made in code never go through the server and are ready right away,
+    // and a failed load shouldn't keep the game from starting
+    let built = handles.iter()
+        .filter(|id| !matches!(asset_server.get_load_state(**id), Some(LoadState::Loading | LoadState::NotLoaded)))
+        .count();
+
+    // the preloaded ones have to be there though
+    let mut loaded = 0;
+    for handle in &preload.0 {
+        match asset_server.get_load_state(handle.id()) {
+            Some(LoadState::Loaded) => loaded += 1,
+            Some(LoadState::Failed) => panic!("couldn't load {:?}", handle.path()),
+            _ => {}
+        }
+    }
+
+    let total = handles.len() + preload.0.len();
+    let done = built + loaded;
+    let progress = if total == 0 { 1.0 } else { done as f32 / total as f32 };
+
+    let mut bar = bar_query.get_single_mut().expect("not one progress bar");
+    bar.width = Val::Percent(progress * 100.0);
+
+    if done < total {
+        *settled = 0;
+        return;
+    }
+
+    *settled += 1;
+    if *settled >= SETTLE_FRAMES {
+        *settled = 0;
+        next_state.set(GameState::InGame);
+    }
+}
@@ -0,0 +1,106 @@
+use std::fmt::Display;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::settings::Settings;
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Locale>()
+            .add_systems(Update, (switch_language, localize_text).chain());
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [
+        Language::English,
+        Language::German,
+    ];
+
+    // in the language itself, so it can be found when nothing else is readable
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    // one `English text = translation` per line, english is what's written in code
+    fn file(&self) -> &'static str {
+        match self {
+            Language::English => "",
+            Language::German => include_str!("../assets/locale/de.txt"),
+        }
+    }
+}
+
+/// The game's text in the chosen language. Text is looked up by its english
+/// wording, anything missing from a language stays english.
+#[derive(Resource, Default)]
+pub struct Locale {
+    language: Language,
+    strings: HashMap<&'static str, &'static str>,
+}
+
+impl Locale {
+    pub fn new(language: Language) -> Self {
+        let strings = language.file().lines()
+            .filter(|l| !l.starts_with('#'))
+            .filter_map(|l| l.split_once(" = "))
+            .map(|(english, translated)| (english.trim(), translated.trim()))
+            .collect();
+        Locale { language, strings }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn get<'a>(&'a self, text: &'a str) -> &'a str {
+        self.strings.get(text).copied().unwrap_or(text)
+    }
+
+    /// Translates `template` and fills in its `{}` in order.
+    pub fn fill(&self, template: &str, args: &[&dyn Display]) -> String {
+        let mut parts = self.get(template).split("{}");
+        let mut filled = parts.next().unwrap_or_default().to_string();
+        for (i, part) in parts.enumerate() {
+            if let Some(arg) = args.get(i) {
+                filled.push_str(&arg.to_string());
+            }
+            filled.push_str(part);
+        }
+        filled
+    }
+}
+
+/// Text that shows `0` translated, and follows language changes.
+#[derive(Component)]
+pub struct Localized(pub &'static str);
+
+fn switch_language(settings: Res<Settings>, mut locale: ResMut<Locale>) {
+    if settings.language != locale.language() {
+        *locale = Locale::new(settings.language);
+    }
+}
+
+fn localize_text(
+    locale: Res<Locale>,
+    mut text_query: Query<(Ref<Localized>, &mut Text)>,
+) {
+    for (localized, mut text) in &mut text_query {
+        if locale.is_changed() || localized.is_added() {
+            text.sections[0].value = locale.get(localized.0).to_string();
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use bevy::{prelude::*, utils::HashMap};
+use rand::Rng;
+
+use crate::{
+    building::BuildingPlaced,
+    corruption::CorruptionMap,
+    daynight::TimeOfDay,
+    ground::DropItem,
+    health::{apply_damage, despawn_dead, Died},
+    interaction::{interact, Interactable, Interacted},
+    item::{Item, ItemStack},
+    research::{Research, Technology},
+    state::GameplaySet,
+};
+
+const CACHE_COUNT: usize = 3;
+// caches are hidden at least this far out from the spawn
+const CACHE_MIN_DISTANCE: f32 = 3.0;
+const CACHE_MAX_DISTANCE: f32 = 6.5;
+// corruption from which corrupted drops show up
+const CORRUPTED_LEVEL: f32 = 30.0;
+
+pub struct LootPlugin;
+
+impl Plugin for LootPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(LootTables::starter())
+            .add_systems(Startup, spawn_caches)
+            .add_systems(Update, (init_building_loot, open_caches.after(interact)).chain().in_set(GameplaySet))
+            // the dead are still around to be looted until they're despawned
+            .add_systems(PostUpdate, drop_loot.after(apply_damage).before(despawn_dead));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LootTableId {
+    Construct,
+    Shaman,
+    Crawler,
+    Slinger,
+    Hare,
+    Stag,
+    // what's left of a destroyed building
+    Wreckage,
+    Cache,
+}
+
+/// When an entry can drop at all.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LootCondition {
+    Always,
+    Night,
+    Researched(Technology),
+    // only from corrupted ground
+    Corrupted,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LootEntry {
+    // `None` is a roll that comes up empty
+    pub item: Option<Item>,
+    pub weight: u32,
+    pub min: u32,
+    pub max: u32,
+    pub condition: LootCondition,
+}
+
+impl LootEntry {
+    pub fn new(item: Item, weight: u32, min: u32, max: u32) -> Self {
+        LootEntry { item: Some(item), weight, min, max, condition: LootCondition::Always }
+    }
+
+    pub fn nothing(weight: u32) -> Self {
+        LootEntry { item: None, weight, min: 0, max: 0, condition: LootCondition::Always }
+    }
+
+    pub fn when(self, condition: LootCondition) -> Self {
+        LootEntry { condition, ..self }
+    }
+}
+
+/// Picks one entry by weight for each roll.
+#[derive(Clone, Debug)]
+pub struct LootTable {
+    pub rolls: u32,
+    pub entries: Vec<LootEntry>,
+}
+
+/// What the world looks like where something drops its loot.
+pub struct LootContext<'a> {
+    pub night: bool,
+    pub corruption: f32,
+    pub research: &'a Research,
+}
+
+impl LootContext<'_> {
+    fn allows(&self, condition: LootCondition) -> bool {
+        match condition {
+            LootCondition::Always => true,
+            LootCondition::Night => self.night,
+            LootCondition::Researched(tech) => self.research.unlocked.contains(&tech),
+            LootCondition::Corrupted => self.corruption >= CORRUPTED_LEVEL,
+        }
+    }
+}
+
+/// Every loot table, kept as plain data like the spells.
+#[derive(Resource, Default)]
+pub struct LootTables {
+    tables: HashMap<LootTableId, LootTable>,
+}
+
+impl LootTables {
+    fn starter() -> Self {
+        let mut tables = LootTables::default();
+        tables.add(LootTableId::Construct, 2, vec![
+            LootEntry::new(Item::Stone, 6, 2, 5),
+            LootEntry::new(Item::ManaCrystal, 2, 1, 1),
+            LootEntry::new(Item::Essence, 1, 1, 1).when(LootCondition::Night),
+            LootEntry::nothing(3),
+        ]);
+        tables.add(LootTableId::Shaman, 1, vec![
+            LootEntry::new(Item::Essence, 3, 1, 2),
+            LootEntry::new(Item::HealingPotion, 1, 1, 1).when(LootCondition::Researched(Technology::Alchemy)),
+            LootEntry::nothing(2),
+        ]);
+        tables.add(LootTableId::Crawler, 1, vec![
+            LootEntry::new(Item::Wood, 3, 1, 3),
+            LootEntry::new(Item::Essence, 2, 1, 1).when(LootCondition::Corrupted),
+            LootEntry::nothing(4),
+        ]);
+        tables.add(LootTableId::Slinger, 1, vec![
+            LootEntry::new(Item::ManaCrystal, 3, 1, 2),
+            LootEntry::new(Item::Stone, 2, 2, 4),
+            LootEntry::nothing(2),
+        ]);
+        tables.add(LootTableId::Hare, 1, vec![
+            LootEntry::new(Item::Essence, 1, 1, 1),
+            LootEntry::nothing(2),
+        ]);
+        tables.add(LootTableId::Stag, 2, vec![
+            LootEntry::new(Item::Essence, 2, 1, 2),
+            LootEntry::new(Item::Wood, 1, 1, 2),
+            LootEntry::nothing(1),
+        ]);
+        tables.add(LootTableId::Wreckage, 2, vec![
+            LootEntry::new(Item::IronPlate, 4, 1, 3),
+            LootEntry::new(Item::Gear, 2, 1, 2),
+            LootEntry::nothing(3),
+        ]);
+        tables.add(LootTableId::Cache, 3, vec![
+            LootEntry::new(Item::IronPlate, 4, 5, 15),
+            LootEntry::new(Item::CopperPlate, 4, 5, 10),
+            LootEntry::new(Item::ManaCrystal, 3, 1, 4),
+            LootEntry::new(Item::RepairKit, 1, 1, 2),
+            LootEntry::new(Item::Wand, 1, 1, 1).when(LootCondition::Researched(Technology::Enchanting)),
+        ]);
+        tables
+    }
+
+    pub fn add(&mut self, id: LootTableId, rolls: u32, entries: Vec<LootEntry>) {
+        self.tables.insert(id, LootTable { rolls, entries });
+    }
+
+    pub fn roll(&self, id: LootTableId, context: &LootContext, rng: &mut impl Rng) -> Vec<ItemStack> {
+        let Some(table) = self.tables.get(&id) else {
+            return Vec::new();
+        };
+        let entries: Vec<&LootEntry> = table.entries.iter().filter(|e| context.allows(e.condition)).collect();
+        let total: u32 = entries.iter().map(|e| e.weight).sum();
+        if total == 0 {
+            return Vec::new();
+        }
+
+        let mut drops = Vec::new();
+        for _ in 0..table.rolls {
+            let mut pick = rng.gen_range(0..total);
+            let entry = entries.iter()
+                .find(|e| {
+                    if pick < e.weight {
+                        return true;
+                    }
+                    pick -= e.weight;
+                    false
+                })
+                .expect("weights add up");
+            if let Some(item) = entry.item {
+                drops.push(ItemStack::new(item, rng.gen_range(entry.min..=entry.max)));
+            }
+        }
+        drops
+    }
+}
+
+/// Dropped when this dies.
+#[derive(Component)]
+pub struct Loot(pub LootTableId);
+
+/// A stash lying around the map, opened by walking up to it and pressing E.
+#[derive(Component)]
+pub struct Cache;
+
+fn spawn_caches(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut rng = rand::thread_rng();
+    let mesh = meshes.add(Mesh::from(shape::Box::new(0.6, 0.4, 0.4)));
+    let material = materials.add(Color::rgb_u8(140, 100, 50).into());
+
+    for _ in 0..CACHE_COUNT {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let distance = rng.gen_range(CACHE_MIN_DISTANCE..CACHE_MAX_DISTANCE);
+        commands.spawn((
+            Cache,
+            Loot(LootTableId::Cache),
+            Interactable::new("Open Cache"),
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_xyz(angle.cos() * distance, 0.2, angle.sin() * distance),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn init_building_loot(mut commands: Commands, mut placed_events: EventReader<BuildingPlaced>) {
+    for event in placed_events.read() {
+        commands.entity(event.entity).insert(Loot(LootTableId::Wreckage));
+    }
+}
+
+fn scatter_loot(
+    tables: &LootTables,
+    id: LootTableId,
+    pos: Vec3,
+    context: &LootContext,
+    drop_events: &mut EventWriter<DropItem>,
+) {
+    let mut rng = rand::thread_rng();
+    for stack in tables.roll(id, context, &mut rng) {
+        // spread out a little so it doesn't all land in one heap
+        let offset = Vec3::new(rng.gen_range(-0.5..0.5), 0.0, rng.gen_range(-0.5..0.5));
+        drop_events.send(DropItem { stack, pos: pos + offset, magnetic: true });
+    }
+}
+
+fn open_caches(
+    mut commands: Commands,
+    tables: Res<LootTables>,
+    research: Res<Research>,
+    time_of_day: Res<TimeOfDay>,
+    corruption: Res<CorruptionMap>,
+    cache_query: Query<(&Loot, &Transform), With<Cache>>,
+    mut interacted_events: EventReader<Interacted>,
+    mut drop_events: EventWriter<DropItem>,
+) {
+    for event in interacted_events.read() {
+        let Ok((loot, transform)) = cache_query.get(event.entity) else {
+            continue;
+        };
+
+        let pos = transform.translation;
+        let context = LootContext {
+            night: time_of_day.is_night(),
+            corruption: corruption.get(pos),
+            research: &research,
+        };
+        scatter_loot(&tables, loot.0, pos, &context, &mut drop_events);
+        commands.entity(event.entity).despawn_recursive();
+    }
+}
+
+fn drop_loot(
+    tables: Res<LootTables>,
+    research: Res<Research>,
+    time_of_day: Res<TimeOfDay>,
+    corruption: Res<CorruptionMap>,
+    loot_query: Query<(&Loot, &Transform)>,
+    mut died_events: EventReader<Died>,
+    mut drop_events: EventWriter<DropItem>,
+) {
+    for event in died_events.read() {
+        let Ok((loot, transform)) = loot_query.get(event.entity) else {
+            continue;
+        };
+
+        let pos = transform.translation;
+        let context = LootContext {
+            night: time_of_day.is_night(),
+            corruption: corruption.get(pos),
+            research: &research,
+        };
+        scatter_loot(&tables, loot.0, pos, &context, &mut drop_events);
+    }
+}
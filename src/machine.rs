@@ -0,0 +1,425 @@
+use bevy::prelude::*;
+
+use crate::{
+    alert::{AlertKind, RaiseAlert},
+    belt::Belt,
+    circuit::CircuitCondition,
+    building::{AimPoint, BuildingGrid, BuildingKind, BuildingPlaced, Facing, GridPos},
+    corruption::CorruptionEmitter,
+    ground::DropItem,
+    heat::Heat,
+    interaction::Interactable,
+    inventory::Inventory,
+    item::ItemStack,
+    locale::Locale,
+    module::{self, ModuleSlots},
+    power::{ManaConsumer, ManaGrid},
+    recipe::Recipe,
+    research::Research,
+    state::GameplaySet,
+    stats::{ItemConsumed, ItemProduced},
+    streaming::Dormant,
+    wear::Wear,
+    Player,
+};
+
+// output limits the player can cycle through, none means a full stack
+const LIMITS: [Option<u32>; 4] = [None, Some(10), Some(25), Some(50)];
+
+pub struct MachinePlugin;
+
+impl Plugin for MachinePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<ConfigureMachine>()
+            .add_systems(Startup, setup_machine_ui)
+            .add_systems(Update, (
+                init_machines,
+                machine_input,
+                configure_machines,
+                run_machines,
+                output_to_belts,
+                update_machine_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Component)]
+pub struct Machine {
+    pub recipe: Option<Recipe>,
+    // seconds spent on the current craft
+    pub progress: f32,
+    pub working: bool,
+    // finished items waiting to be put on a belt
+    pub output: u32,
+    // stop crafting once this many products are waiting
+    pub limit: Option<u32>,
+    pub enabled: bool,
+}
+
+impl Machine {
+    pub fn new(recipe: Option<Recipe>) -> Self {
+        Machine {
+            recipe,
+            progress: 0.0,
+            working: false,
+            output: 0,
+            limit: None,
+            enabled: true,
+        }
+    }
+
+    pub fn config(&self) -> MachineConfig {
+        MachineConfig {
+            recipe: self.recipe,
+            limit: self.limit,
+            enabled: self.enabled,
+        }
+    }
+
+    pub fn output_limit(&self) -> u32 {
+        let max = self.recipe.map_or(0, |r| r.output().item.max_stack());
+        self.limit.map_or(max, |l| l.min(max))
+    }
+
+    /// Runs `seconds` of crafting at `speed` in one go, for machines that
+    /// aren't simulated frame by frame. Returns how many crafts finished.
+    pub fn fast_forward(&mut self, inventory: &mut Inventory, seconds: f32, speed: f32) -> u32 {
+        let Some(recipe) = self.recipe else {
+            return 0;
+        };
+
+        let mut budget = seconds * speed;
+        let mut crafts = 0;
+        while budget > 0.0 {
+            if !self.working {
+                let blocked = !self.enabled
+                    || self.output >= self.output_limit()
+                    || !inventory.contains_all(recipe.inputs());
+                if blocked {
+                    break;
+                }
+
+                for (item, n) in recipe.inputs() {
+                    inventory.remove(*item, *n);
+                }
+                self.working = true;
+                self.progress = 0.0;
+            }
+
+            let needed = recipe.time() - self.progress;
+            if budget < needed {
+                self.progress += budget;
+                break;
+            }
+
+            budget -= needed;
+            self.output += recipe.output().count;
+            self.working = false;
+            crafts += 1;
+        }
+
+        crafts
+    }
+}
+
+/// The player facing settings of a machine, kept separate so
+/// blueprints can carry them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MachineConfig {
+    pub recipe: Option<Recipe>,
+    pub limit: Option<u32>,
+    pub enabled: bool,
+}
+
+#[derive(Event)]
+pub struct ConfigureMachine {
+    pub entity: Entity,
+    pub config: MachineConfig,
+}
+
+#[derive(Component)]
+struct MachineText;
+
+/// An input inventory with one slot per ingredient, filtered so belts
+/// can only deliver what the recipe needs.
+pub fn input_inventory(recipe: Option<Recipe>) -> Inventory {
+    let inputs = recipe.map_or(&[][..], |r| r.inputs());
+    let mut inventory = Inventory::new(inputs.len());
+
+    for (i, (item, _)) in inputs.iter().enumerate() {
+        inventory.set_filter(i, Some(*item));
+    }
+
+    inventory
+}
+
+/// The researched recipe after (or before) `current`.
+pub fn cycle_recipe(research: &Research, current: Option<Recipe>, forward: bool) -> Option<Recipe> {
+    let unlocked: Vec<Recipe> = Recipe::ALL.iter()
+        .copied()
+        .filter(|r| research.recipe_unlocked(*r))
+        .collect();
+    let Some(i) = current.and_then(|r| unlocked.iter().position(|u| *u == r)) else {
+        return unlocked.first().copied();
+    };
+
+    let step = if forward { 1 } else { unlocked.len() - 1 };
+    unlocked.get((i + step) % unlocked.len()).copied()
+}
+
+pub fn cycle_limit(current: Option<u32>) -> Option<u32> {
+    let i = LIMITS.iter().position(|l| *l == current).unwrap_or(0);
+    LIMITS[(i + 1) % LIMITS.len()]
+}
+
+fn init_machines(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read().filter(|e| e.kind == BuildingKind::Assembler) {
+        let recipe = Some(Recipe::ManaCrystal);
+
+        commands.entity(event.entity).insert((
+            Machine::new(recipe),
+            input_inventory(recipe),
+            Interactable::new("Configure Assembler"),
+            ManaConsumer { demand: 0.0 },
+            CorruptionEmitter { per_second: 0.0 },
+        ));
+    }
+}
+
+fn machine_input(
+    keys: Res<Input<KeyCode>>,
+    aim: Res<AimPoint>,
+    grid: Res<BuildingGrid>,
+    research: Res<Research>,
+    machine_query: Query<&Machine>,
+    mut configure_events: EventWriter<ConfigureMachine>,
+) {
+    let Some(entity) = aim.0.and_then(|a| grid.get(GridPos::from_world(a))) else {
+        return;
+    };
+    let Ok(machine) = machine_query.get(entity) else {
+        return;
+    };
+
+    let mut config = machine.config();
+
+    if keys.just_pressed(KeyCode::F) {
+        config.recipe = cycle_recipe(&research, config.recipe, true);
+    }
+    if keys.just_pressed(KeyCode::L) {
+        config.limit = cycle_limit(config.limit);
+    }
+    if keys.just_pressed(KeyCode::O) {
+        config.enabled = !config.enabled;
+    }
+
+    if config != machine.config() {
+        configure_events.send(ConfigureMachine { entity, config });
+    }
+}
+
+fn configure_machines(
+    mut machine_query: Query<(&mut Machine, &mut Inventory), Without<Player>>,
+    mut player_query: Query<(&mut Inventory, &Transform), With<Player>>,
+    mut configure_events: EventReader<ConfigureMachine>,
+    mut drop_events: EventWriter<DropItem>,
+) {
+    for event in configure_events.read() {
+        let Ok((mut machine, mut inventory)) = machine_query.get_mut(event.entity) else {
+            continue;
+        };
+
+        if machine.recipe != event.config.recipe {
+            // hand back whatever was loaded for the old recipe, the rest goes on the ground
+            let (mut player_inventory, player_transform) = player_query.get_single_mut().expect("not one player");
+            let mut refund: Vec<ItemStack> = inventory.slots().iter().filter_map(|s| s.stack).collect();
+            if let (Some(recipe), true) = (machine.recipe, machine.output > 0) {
+                refund.push(ItemStack::new(recipe.output().item, machine.output));
+            }
+
+            for stack in refund {
+                let leftover = player_inventory.insert(stack);
+                if leftover > 0 {
+                    drop_events.send(DropItem {
+                        stack: ItemStack::new(stack.item, leftover),
+                        pos: player_transform.translation,
+                        magnetic: false,
+                    });
+                }
+            }
+
+            *inventory = input_inventory(event.config.recipe);
+            machine.recipe = event.config.recipe;
+            machine.working = false;
+            machine.progress = 0.0;
+            machine.output = 0;
+        }
+
+        machine.limit = event.config.limit;
+        machine.enabled = event.config.enabled;
+    }
+}
+
+fn run_machines(
+    time: Res<Time>,
+    grid: Res<ManaGrid>,
+    mut machine_query: Query<(
+        Entity,
+        &mut Machine,
+        &mut Inventory,
+        &mut ManaConsumer,
+        Option<&mut CorruptionEmitter>,
+        Option<&Heat>,
+        Option<&ModuleSlots>,
+        Option<&CircuitCondition>,
+        Option<&Wear>,
+    ), Without<Dormant>>,
+    mut produced_events: EventWriter<ItemProduced>,
+    mut consumed_events: EventWriter<ItemConsumed>,
+    mut alert_events: EventWriter<RaiseAlert>,
+) {
+    for (entity, mut machine, mut inventory, mut consumer, emitter, heat, modules, condition, wear) in &mut machine_query {
+        let effects = module::effects(modules);
+
+        let Some(recipe) = machine.recipe else {
+            consumer.demand = 0.0;
+            continue;
+        };
+
+        // a switched off machine still finishes its current craft
+        let enabled = machine.enabled && condition.is_none_or(|c| c.allows());
+        // broken machines stand still until repaired
+        let wear_factor = wear.map_or(1.0, |w| w.speed_factor());
+        if wear_factor <= 0.0 || (!enabled && !machine.working) {
+            consumer.demand = 0.0;
+            if let Some(mut emitter) = emitter {
+                emitter.per_second = 0.0;
+            }
+            continue;
+        }
+
+        // start a new craft once the ingredients are there and the output isn't backed up
+        if !machine.working && machine.output < machine.output_limit() {
+            let has_inputs = recipe.inputs().iter().all(|(item, n)| inventory.count(*item) >= *n);
+
+            if has_inputs {
+                for (item, n) in recipe.inputs() {
+                    inventory.remove(*item, *n);
+                    consumed_events.send(ItemConsumed { item: *item, count: *n });
+                }
+                machine.working = true;
+                machine.progress = 0.0;
+            } else {
+                alert_events.send(RaiseAlert { entity, kind: AlertKind::MissingIngredient });
+            }
+        } else if !machine.working && machine.output >= recipe.output().item.max_stack() {
+            // hitting a limit the player set on purpose isn't worth an alert
+            alert_events.send(RaiseAlert { entity, kind: AlertKind::OutputFull });
+        }
+
+        let mana = recipe.mana() * effects.mana;
+        consumer.demand = if machine.working { mana } else { 0.0 };
+        if let Some(mut emitter) = emitter {
+            emitter.per_second = if machine.working { mana * 0.1 } else { 0.0 };
+        }
+
+        if !machine.working {
+            continue;
+        }
+
+        if grid.satisfaction <= 0.0 {
+            alert_events.send(RaiseAlert { entity, kind: AlertKind::NoPower });
+        }
+
+        // a starved grid, an overheating or a worn machine slows everything down
+        let speed = grid.satisfaction * heat.map_or(1.0, |h| h.speed_factor()) * wear_factor * effects.speed;
+        machine.progress += time.delta_seconds() * speed;
+
+        if machine.progress >= recipe.time() {
+            machine.output += recipe.output().count;
+            machine.working = false;
+            produced_events.send(ItemProduced {
+                item: recipe.output().item,
+                count: recipe.output().count,
+            });
+        }
+    }
+}
+
+fn output_to_belts(
+    grid: Res<BuildingGrid>,
+    mut machine_query: Query<(&mut Machine, &GridPos, &Facing)>,
+    mut belt_query: Query<(&mut Belt, &Facing), Without<Machine>>,
+) {
+    for (mut machine, pos, facing) in &mut machine_query {
+        let (Some(recipe), true) = (machine.recipe, machine.output > 0) else {
+            continue;
+        };
+
+        // machines drop their products onto the belt in front of them
+        let Some(mut belt) = grid.get(pos.step(*facing))
+            .and_then(|e| belt_query.get_mut(e).ok())
+            .filter(|(_, f)| **f != facing.opposite())
+            .map(|(belt, _)| belt)
+        else {
+            continue;
+        };
+
+        if belt.has_room() {
+            belt.push(recipe.output().item);
+            machine.output -= 1;
+        }
+    }
+}
+
+fn setup_machine_ui(mut commands: Commands) {
+    commands.spawn((
+        MachineText,
+        TextBundle::from_section("", TextStyle {
+            font_size: 16.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(40.0),
+            bottom: Val::Px(20.0),
+            ..default()
+        }),
+    ));
+}
+
+fn update_machine_ui(
+    locale: Res<Locale>,
+    aim: Res<AimPoint>,
+    grid: Res<BuildingGrid>,
+    machine_query: Query<(&Machine, Option<&Wear>)>,
+    mut text_query: Query<&mut Text, With<MachineText>>,
+) {
+    let mut text = text_query.get_single_mut().expect("not one machine text");
+
+    // only shown while looking at a machine
+    let Some((machine, wear)) = aim.0
+        .and_then(|a| grid.get(GridPos::from_world(a)))
+        .and_then(|e| machine_query.get(e).ok())
+    else {
+        text.sections[0].value.clear();
+        return;
+    };
+
+    let mut content = locale.fill("{} (F) | limit {} (L) | {} (O)", &[
+        &locale.get(machine.recipe.map_or("No recipe", |r| r.name())),
+        &machine.output_limit(),
+        &locale.get(if machine.enabled { "on" } else { "off" }),
+    ]);
+    if let Some(wear) = wear {
+        content += " | ";
+        content += &locale.fill("condition {}% (H)", &[&format!("{:.0}", wear.durability * 100.0)]);
+    }
+
+    text.sections[0].value = content;
+}
@@ -0,0 +1,355 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::Building,
+    camera::{CursorUser, CursorUsers},
+    input::{Action, InputMap},
+    interaction::{interact, Interacted, INTERACT_RANGE},
+    inventory::Inventory,
+    item::ItemStack,
+    locale::Locale,
+    machine::{cycle_limit, cycle_recipe, ConfigureMachine, Machine},
+    power::{ManaConsumer, ManaGrid},
+    research::{Lab, Research},
+    state::GameplaySet,
+    tooltip::ItemTooltip,
+    Player,
+};
+
+// more than any recipe or lab takes
+const MAX_SLOTS: usize = 4;
+const SLOT_SIZE: f32 = 40.0;
+const BAR_WIDTH: f32 = 200.0;
+const SLOT_COLOR: Color = Color::rgba(0.2, 0.2, 0.2, 0.8);
+const BUTTON_COLOR: Color = Color::rgba(0.3, 0.3, 0.3, 0.9);
+const HOVERED_COLOR: Color = Color::rgba(0.4, 0.4, 0.5, 0.9);
+
+pub struct MachineUiPlugin;
+
+impl Plugin for MachineUiPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<OpenMachine>()
+            .add_systems(Startup, setup_machine_panel)
+            .add_systems(Update, (
+                toggle_machine_panel.after(interact),
+                machine_panel_input,
+                update_machine_panel,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+/// The machine whose panel is currently shown.
+#[derive(Resource, Default)]
+pub struct OpenMachine {
+    pub entity: Option<Entity>,
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+enum PanelButton {
+    PreviousRecipe,
+    NextRecipe,
+    Limit,
+    Power,
+}
+
+#[derive(Component)]
+struct MachinePanel;
+
+#[derive(Component)]
+struct PanelTitle;
+
+// only machines that craft have these
+#[derive(Component)]
+struct RecipeRow;
+
+#[derive(Component)]
+struct RecipeText;
+
+#[derive(Component)]
+struct ControlRow;
+
+#[derive(Component)]
+struct InputSlot(usize);
+
+#[derive(Component)]
+struct OutputSlot;
+
+#[derive(Component)]
+struct ProgressFill;
+
+#[derive(Component)]
+struct PowerFill;
+
+#[derive(Component)]
+struct PowerText;
+
+fn setup_machine_panel(mut commands: Commands) {
+    let text = |size: f32| TextBundle::from_section("", TextStyle {
+        font_size: size,
+        color: Color::WHITE,
+        ..default()
+    });
+    let row = || NodeBundle {
+        style: Style {
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(6.0),
+            ..default()
+        },
+        ..default()
+    };
+    let slot = || NodeBundle {
+        style: Style {
+            width: Val::Px(SLOT_SIZE),
+            height: Val::Px(SLOT_SIZE),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        background_color: SLOT_COLOR.into(),
+        ..default()
+    };
+    let button = |kind: PanelButton| (
+        kind,
+        ButtonBundle {
+            style: Style {
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                ..default()
+            },
+            background_color: BUTTON_COLOR.into(),
+            ..default()
+        },
+    );
+    let bar = |fill: Color| NodeBundle {
+        style: Style {
+            width: Val::Percent(0.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        background_color: fill.into(),
+        ..default()
+    };
+    let track = || NodeBundle {
+        style: Style {
+            width: Val::Px(BAR_WIDTH),
+            height: Val::Px(6.0),
+            ..default()
+        },
+        background_color: Color::DARK_GRAY.into(),
+        ..default()
+    };
+
+    commands.spawn((
+        MachinePanel,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(20.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((PanelTitle, text(20.0)));
+
+        parent.spawn((RecipeRow, row())).with_children(|row| {
+            row.spawn(button(PanelButton::PreviousRecipe)).with_children(|b| {
+                b.spawn(TextBundle::from_section("<", TextStyle { font_size: 16.0, ..default() }));
+            });
+            row.spawn((RecipeText, text(16.0)));
+            row.spawn(button(PanelButton::NextRecipe)).with_children(|b| {
+                b.spawn(TextBundle::from_section(">", TextStyle { font_size: 16.0, ..default() }));
+            });
+        });
+
+        // inputs, then an arrow to what comes out
+        parent.spawn(row()).with_children(|row| {
+            for i in 0..MAX_SLOTS {
+                row.spawn((InputSlot(i), ItemTooltip::default(), slot())).with_children(|s| {
+                    s.spawn(text(12.0));
+                });
+            }
+            row.spawn(TextBundle::from_section("->", TextStyle { font_size: 16.0, ..default() }));
+            row.spawn((OutputSlot, ItemTooltip::default(), slot())).with_children(|s| {
+                s.spawn(text(12.0));
+            });
+        });
+
+        parent.spawn(track()).with_children(|t| {
+            t.spawn((ProgressFill, bar(Color::WHITE)));
+        });
+        parent.spawn((PowerText, text(14.0)));
+        parent.spawn(track()).with_children(|t| {
+            t.spawn((PowerFill, bar(Color::CYAN)));
+        });
+
+        parent.spawn((ControlRow, row())).with_children(|row| {
+            for kind in [PanelButton::Limit, PanelButton::Power] {
+                row.spawn(button(kind)).with_children(|b| {
+                    b.spawn(text(14.0));
+                });
+            }
+        });
+    });
+}
+
+fn toggle_machine_panel(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut open: ResMut<OpenMachine>,
+    player_query: Query<&Transform, With<Player>>,
+    machine_query: Query<&Transform, Or<(With<Machine>, With<Lab>)>>,
+    mut cursor_users: ResMut<CursorUsers>,
+    mut interacted_events: EventReader<Interacted>,
+) {
+    let player = player_query.get_single().expect("not one player");
+    let was_open = open.entity.is_some();
+
+    // close the panel once the player walks away or the machine is gone
+    if let Some(entity) = open.entity {
+        let in_range = machine_query.get(entity)
+            .is_ok_and(|t| t.translation.distance(player.translation) <= INTERACT_RANGE);
+
+        if !in_range {
+            open.entity = None;
+        }
+    }
+
+    let used = interacted_events.read().map(|e| e.entity).find(|e| machine_query.contains(*e));
+    if input_map.just_pressed(Action::Interact, &keys) {
+        open.entity = if open.entity.is_some() { None } else { used };
+    }
+
+    // the buttons need a pointer
+    if open.entity.is_some() != was_open {
+        cursor_users.set(CursorUser::Machine, open.entity.is_some());
+    }
+}
+
+fn machine_panel_input(
+    open: Res<OpenMachine>,
+    research: Res<Research>,
+    machine_query: Query<&Machine>,
+    mut button_query: Query<(&PanelButton, &Interaction, &mut BackgroundColor), Changed<Interaction>>,
+    mut configure_events: EventWriter<ConfigureMachine>,
+) {
+    let Some((entity, machine)) = open.entity.and_then(|e| machine_query.get(e).ok().map(|m| (e, m))) else {
+        return;
+    };
+
+    let mut config = machine.config();
+    for (button, interaction, mut background) in &mut button_query {
+        match interaction {
+            Interaction::Pressed => match button {
+                PanelButton::PreviousRecipe => config.recipe = cycle_recipe(&research, config.recipe, false),
+                PanelButton::NextRecipe => config.recipe = cycle_recipe(&research, config.recipe, true),
+                PanelButton::Limit => config.limit = cycle_limit(config.limit),
+                PanelButton::Power => config.enabled = !config.enabled,
+            },
+            Interaction::Hovered => *background = HOVERED_COLOR.into(),
+            Interaction::None => *background = BUTTON_COLOR.into(),
+        }
+    }
+
+    if config != machine.config() {
+        configure_events.send(ConfigureMachine { entity, config });
+    }
+}
+
+fn update_machine_panel(
+    open: Res<OpenMachine>,
+    research: Res<Research>,
+    grid: Res<ManaGrid>,
+    locale: Res<Locale>,
+    machine_query: Query<(
+        Option<&Building>,
+        Option<&Machine>,
+        Option<&Lab>,
+        Option<&Inventory>,
+        Option<&ManaConsumer>,
+    )>,
+    mut panel_query: Query<&mut Visibility, With<MachinePanel>>,
+    mut section_query: Query<&mut Style, Or<(With<RecipeRow>, With<ControlRow>)>>,
+    mut fill_query: Query<(&mut Style, Has<ProgressFill>), (Or<(With<ProgressFill>, With<PowerFill>)>, Without<RecipeRow>, Without<ControlRow>)>,
+    mut slot_query: Query<
+        (Option<&InputSlot>, &mut ItemTooltip, &mut BackgroundColor, &mut Style, &Children),
+        (Or<(With<InputSlot>, With<OutputSlot>)>, Without<ProgressFill>, Without<PowerFill>, Without<RecipeRow>, Without<ControlRow>),
+    >,
+    button_query: Query<(&PanelButton, &Children)>,
+    mut text_query: Query<(&mut Text, Has<PanelTitle>, Has<RecipeText>, Has<PowerText>)>,
+) {
+    let mut visibility = panel_query.get_single_mut().expect("not one machine panel");
+    let Some((building, machine, lab, inventory, consumer)) = open.entity.and_then(|e| machine_query.get(e).ok()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Visible;
+
+    // recipe and on/off only make sense for machines that craft
+    for mut style in &mut section_query {
+        style.display = if machine.is_some() { Display::Flex } else { Display::None };
+    }
+
+    let progress = match (machine, lab) {
+        (Some(m), _) if m.working => m.recipe.map_or(0.0, |r| m.progress / r.time()),
+        (_, Some(l)) if l.working => research.current.map_or(0.0, |t| l.progress / t.unit_time()),
+        _ => 0.0,
+    };
+    for (mut style, is_progress) in &mut fill_query {
+        let fraction = if is_progress { progress } else { grid.satisfaction };
+        style.width = Val::Percent(fraction.clamp(0.0, 1.0) * 100.0);
+    }
+
+    let inputs = inventory.map_or(&[][..], |i| i.slots());
+    let output = machine.and_then(|m| {
+        m.recipe.filter(|_| m.output > 0).map(|r| ItemStack::new(r.output().item, m.output))
+    });
+    for (input, mut tooltip, mut background, mut style, children) in &mut slot_query {
+        let (stack, shown) = match input {
+            Some(InputSlot(i)) => (inputs.get(*i).and_then(|s| s.stack), *i < inputs.len()),
+            None => (output, machine.is_some()),
+        };
+        // empty filtered slots hint at what goes in them
+        let filter = input.and_then(|InputSlot(i)| inputs.get(*i)).and_then(|s| s.filter);
+
+        style.display = if shown { Display::Flex } else { Display::None };
+        tooltip.0 = stack;
+        *background = match (stack, filter) {
+            (Some(stack), _) => stack.item.color().with_a(0.8),
+            (None, Some(item)) => item.color().with_a(0.2),
+            (None, None) => SLOT_COLOR,
+        }.into();
+        if let Some((mut text, ..)) = children.first().and_then(|c| text_query.get_mut(*c).ok()) {
+            text.sections[0].value = stack.map_or(String::new(), |s| s.count.to_string());
+        }
+    }
+
+    for (button, children) in &button_query {
+        let Some((mut text, ..)) = children.first().and_then(|c| text_query.get_mut(*c).ok()) else {
+            continue;
+        };
+        match (button, machine) {
+            (PanelButton::Limit, Some(m)) => text.sections[0].value = locale.fill("Limit {}", &[&m.output_limit()]),
+            (PanelButton::Power, Some(m)) => text.sections[0].value = locale.get(if m.enabled { "On" } else { "Off" }).to_string(),
+            _ => {}
+        }
+    }
+
+    for (mut text, title, recipe, power) in &mut text_query {
+        if title {
+            text.sections[0].value = locale.get(building.map_or("Machine", |b| b.kind.name())).to_string();
+        } else if recipe {
+            text.sections[0].value = locale.get(machine.and_then(|m| m.recipe).map_or("No recipe", |r| r.name())).to_string();
+        } else if power {
+            let demand = consumer.map_or(0.0, |c| c.demand);
+            let satisfaction = format!("{:.0}", grid.satisfaction * 100.0);
+            text.sections[0].value = locale.fill("Power {}% ({} mana/s)", &[&satisfaction, &format!("{demand:.1}")]);
+        }
+    }
+}
@@ -0,0 +1,716 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    barrier::SpawnBarrier,
+    camera::Camera,
+    character::Stats,
+    cooldown::{Ability, Cooldowns},
+    circle::Inscription,
+    dilation::TimeDilation,
+    enemy::Hostile,
+    health::{Damage, DamageEvent, DamageType, Health},
+    hitbox::{resolve_hit, Hurtbox},
+    hotbar::{Hotbar, HotbarEntry},
+    locale::Locale,
+    overload::Flux,
+    progression::{SpellProgress, UpgradeTracks},
+    projectile::{LaunchProjectile, ProjectileKind},
+    stagger::Knockback,
+    state::GameplaySet,
+    status::StatusEffects,
+    summon::SummonMinion,
+    Player,
+};
+
+// how far a caster can drift before a movement sensitive channel breaks
+const CHANNEL_MOVE_TOLERANCE: f32 = 0.5;
+// radians between projectiles fanning out of one cast
+const PROJECTILE_SPREAD: f32 = 0.15;
+// how hard a nova shoves everything it catches
+const NOVA_KNOCKBACK: f32 = 0.8;
+
+pub struct MagicPlugin;
+
+impl Plugin for MagicPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(SpellBook::starter())
+            .add_event::<CastSpell>()
+            .add_event::<InterruptCast>()
+            .add_event::<SpellCastStarted>()
+            .add_event::<SpellCast>()
+            .add_event::<SpellInterrupted>()
+            .add_event::<ReleaseCast>()
+            .add_event::<ChannelEnded>()
+            .add_event::<SpellHit>()
+            .add_systems(Startup, setup_magic_ui)
+            .add_systems(Update, (
+                start_casts,
+                interrupt_casts,
+                release_casts,
+                advance_casts,
+                channel_spells,
+                apply_spell_effects,
+                update_magic_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SpellEffect {
+    // instant hit on whatever hostile is in the line of sight
+    Bolt { damage: Damage, range: f32 },
+    // hits every hostile around the caster
+    Nova { damage: Damage, radius: f32 },
+    // launched from the caster's hand and flies until it hits something,
+    // more than one fan out
+    Projectile { kind: ProjectileKind, count: u32 },
+    // channeled, damage is per second along the line of sight
+    Beam { damage: Damage, range: f32 },
+    // calls an ally that fights for the caster for `duration` seconds
+    Summon { health: f32, damage: Damage, duration: f32 },
+    // channeled, holds a body in front of the caster and hurls it on release
+    Telekinesis { range: f32, hold_distance: f32, throw_speed: f32, damage: Damage },
+    // a dome around the caster that soaks up enemy projectiles
+    Barrier { radius: f32, budget: f32, duration: f32 },
+    // teleports the caster along the aim, see blink.rs
+    Blink { distance: f32, keep_momentum: bool, invulnerability: f32 },
+    // slows the world down to `factor`, see dilation.rs
+    TimeDilation { factor: f32, duration: f32, mana_per_second: f32 },
+    // toggles flight, see levitation.rs
+    Levitate { mana_per_second: f32, climb_speed: f32 },
+    // channeled in the middle of a circle of pylons and candles, see ritual.rs
+    Ritual,
+    // the kind of circle is picked beforehand, see circle.rs
+    Inscribe { radius: f32 },
+    // cleanses the caster and counters enemy casts nearby, see dispel.rs
+    Dispel { radius: f32 },
+}
+
+/// Makes a spell keep going while the button is held.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Channel {
+    pub mana_per_second: f32,
+    pub broken_by_damage: bool,
+    pub broken_by_movement: bool,
+}
+
+/// Everything that makes up a spell, kept as plain data so spells can be
+/// defined and combined at runtime.
+#[derive(Clone, Debug)]
+pub struct Spell {
+    pub name: String,
+    pub cost: f32,
+    // seconds before the spell goes off
+    pub cast_time: f32,
+    pub cooldown: f32,
+    pub effect: SpellEffect,
+    pub channel: Option<Channel>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SpellId(pub usize);
+
+/// All known spell definitions.
+#[derive(Resource, Default)]
+pub struct SpellBook {
+    spells: Vec<Spell>,
+}
+
+impl SpellBook {
+    fn starter() -> Self {
+        let mut book = SpellBook::default();
+        book.add(Spell {
+            name: "Arcane Bolt".into(),
+            cost: 10.0,
+            cast_time: 0.4,
+            cooldown: 0.8,
+            effect: SpellEffect::Bolt { damage: Damage::new(20.0, DamageType::Arc), range: 20.0 },
+            channel: None,
+        });
+        book.add(Spell {
+            name: "Nova".into(),
+            cost: 35.0,
+            cast_time: 1.2,
+            cooldown: 6.0,
+            effect: SpellEffect::Nova { damage: Damage::new(30.0, DamageType::Frost), radius: 4.0 },
+            channel: None,
+        });
+        book.add(Spell {
+            name: "Fireball".into(),
+            cost: 25.0,
+            cast_time: 0.8,
+            cooldown: 2.0,
+            effect: SpellEffect::Projectile { kind: ProjectileKind::Fireball, count: 1 },
+            channel: None,
+        });
+        book.add(Spell {
+            name: "Mana Bolt".into(),
+            cost: 8.0,
+            cast_time: 0.2,
+            cooldown: 0.5,
+            effect: SpellEffect::Projectile { kind: ProjectileKind::ManaBolt, count: 1 },
+            channel: None,
+        });
+        book.add(Spell {
+            name: "Arcane Beam".into(),
+            cost: 5.0,
+            cast_time: 0.3,
+            cooldown: 1.0,
+            effect: SpellEffect::Beam { damage: Damage::new(30.0, DamageType::Fire), range: 15.0 },
+            channel: Some(Channel {
+                mana_per_second: 12.0,
+                broken_by_damage: true,
+                broken_by_movement: false,
+            }),
+        });
+        book.add(Spell {
+            name: "Summon Spirit".into(),
+            cost: 40.0,
+            cast_time: 1.5,
+            cooldown: 10.0,
+            effect: SpellEffect::Summon {
+                health: 40.0,
+                damage: Damage::new(6.0, DamageType::Arc),
+                duration: 30.0,
+            },
+            channel: None,
+        });
+        book.add(Spell {
+            name: "Telekinesis".into(),
+            cost: 10.0,
+            cast_time: 0.2,
+            cooldown: 2.0,
+            effect: SpellEffect::Telekinesis {
+                range: 12.0,
+                hold_distance: 3.0,
+                throw_speed: 18.0,
+                damage: Damage::new(20.0, DamageType::Physical),
+            },
+            channel: Some(Channel {
+                mana_per_second: 8.0,
+                broken_by_damage: true,
+                broken_by_movement: false,
+            }),
+        });
+        book.add(Spell {
+            name: "Barrier".into(),
+            cost: 30.0,
+            cast_time: 0.6,
+            cooldown: 15.0,
+            effect: SpellEffect::Barrier { radius: 3.0, budget: 60.0, duration: 12.0 },
+            channel: None,
+        });
+        book.add(Spell {
+            name: "Blink".into(),
+            cost: 20.0,
+            cast_time: 0.0,
+            cooldown: 4.0,
+            effect: SpellEffect::Blink { distance: 8.0, keep_momentum: true, invulnerability: 0.3 },
+            channel: None,
+        });
+        book.add(Spell {
+            name: "Time Dilation".into(),
+            cost: 20.0,
+            cast_time: 0.5,
+            cooldown: 20.0,
+            effect: SpellEffect::TimeDilation { factor: 0.4, duration: 8.0, mana_per_second: 6.0 },
+            channel: None,
+        });
+        book.add(Spell {
+            name: "Levitate".into(),
+            cost: 10.0,
+            cast_time: 0.3,
+            cooldown: 1.0,
+            effect: SpellEffect::Levitate { mana_per_second: 5.0, climb_speed: 3.0 },
+            channel: None,
+        });
+        book.add(Spell {
+            name: "Ritual".into(),
+            cost: 20.0,
+            cast_time: 1.0,
+            cooldown: 5.0,
+            effect: SpellEffect::Ritual,
+            channel: Some(Channel { mana_per_second: 4.0, broken_by_damage: true, broken_by_movement: true }),
+        });
+        book.add(Spell {
+            name: "Inscribe Circle".into(),
+            cost: 30.0,
+            cast_time: 2.0,
+            cooldown: 3.0,
+            effect: SpellEffect::Inscribe { radius: 2.5 },
+            channel: None,
+        });
+        book.add(Spell {
+            name: "Dispel".into(),
+            cost: 15.0,
+            // instant, so it can be timed against an enemy cast
+            cast_time: 0.0,
+            cooldown: 6.0,
+            effect: SpellEffect::Dispel { radius: 8.0 },
+            channel: None,
+        });
+        book
+    }
+
+    pub fn add(&mut self, spell: Spell) -> SpellId {
+        self.spells.push(spell);
+        SpellId(self.spells.len() - 1)
+    }
+
+    pub fn get(&self, id: SpellId) -> Option<&Spell> {
+        self.spells.get(id.0)
+    }
+
+    pub fn get_mut(&mut self, id: SpellId) -> Option<&mut Spell> {
+        self.spells.get_mut(id.0)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = SpellId> {
+        (0..self.spells.len()).map(SpellId)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Casting {
+    pub spell: SpellId,
+    pub elapsed: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Channeling {
+    pub spell: SpellId,
+    pub elapsed: f32,
+    // where the caster stood and how healthy it was when the channel started
+    origin: Vec3,
+    health: Option<f32>,
+}
+
+#[derive(Component, Default)]
+pub struct Caster {
+    pub casting: Option<Casting>,
+    pub channeling: Option<Channeling>,
+}
+
+/// Asks `caster` to start casting `spell`.
+#[derive(Event)]
+pub struct CastSpell {
+    pub caster: Entity,
+    pub spell: SpellId,
+}
+
+/// Cancels whatever `caster` is casting, e.g. when it gets hit.
+#[derive(Event)]
+pub struct InterruptCast {
+    pub caster: Entity,
+}
+
+#[derive(Event)]
+pub struct SpellCastStarted {
+    pub caster: Entity,
+    pub spell: SpellId,
+}
+
+/// Sent when a cast completes, the mana is already paid for.
+#[derive(Event)]
+pub struct SpellCast {
+    pub caster: Entity,
+    pub spell: SpellId,
+}
+
+#[derive(Event)]
+pub struct SpellInterrupted {
+    pub caster: Entity,
+    pub spell: SpellId,
+}
+
+/// The cast button was let go, which ends a channel.
+#[derive(Event)]
+pub struct ReleaseCast {
+    pub caster: Entity,
+}
+
+/// Sent when a channel stops on its own terms, i.e. released or out of mana.
+#[derive(Event)]
+pub struct ChannelEnded {
+    pub caster: Entity,
+    pub spell: SpellId,
+}
+
+/// A bolt or nova connected with something hostile, projectiles report
+/// their hits through `ProjectileImpact` instead.
+#[derive(Event)]
+pub struct SpellHit {
+    pub caster: Entity,
+    pub target: Entity,
+}
+
+#[derive(Component)]
+struct MagicText;
+
+fn start_casts(
+    book: Res<SpellBook>,
+    cooldowns: Res<Cooldowns>,
+    mut caster_query: Query<(&mut Caster, &Stats)>,
+    mut cast_events: EventReader<CastSpell>,
+    mut started_events: EventWriter<SpellCastStarted>,
+) {
+    for event in cast_events.read() {
+        let Ok((mut caster, stats)) = caster_query.get_mut(event.caster) else {
+            continue;
+        };
+        let Some(spell) = book.get(event.spell) else {
+            continue;
+        };
+
+        let ready = caster.casting.is_none()
+            && caster.channeling.is_none()
+            && cooldowns.is_ready(event.caster, Ability::Spell(event.spell))
+            && stats.mana >= stats.spell_cost(spell.cost);
+        if !ready {
+            continue;
+        }
+
+        caster.casting = Some(Casting { spell: event.spell, elapsed: 0.0 });
+        started_events.send(SpellCastStarted { caster: event.caster, spell: event.spell });
+    }
+}
+
+fn interrupt_casts(
+    mut caster_query: Query<&mut Caster>,
+    mut interrupt_events: EventReader<InterruptCast>,
+    mut interrupted_events: EventWriter<SpellInterrupted>,
+) {
+    for event in interrupt_events.read() {
+        let Ok(mut caster) = caster_query.get_mut(event.caster) else {
+            continue;
+        };
+
+        if let Some(casting) = caster.casting.take() {
+            interrupted_events.send(SpellInterrupted { caster: event.caster, spell: casting.spell });
+        }
+        if let Some(channeling) = caster.channeling.take() {
+            interrupted_events.send(SpellInterrupted { caster: event.caster, spell: channeling.spell });
+        }
+    }
+}
+
+fn release_casts(
+    book: Res<SpellBook>,
+    mut caster_query: Query<&mut Caster>,
+    mut release_events: EventReader<ReleaseCast>,
+    mut interrupted_events: EventWriter<SpellInterrupted>,
+    mut ended_events: EventWriter<ChannelEnded>,
+) {
+    for event in release_events.read() {
+        let Ok(mut caster) = caster_query.get_mut(event.caster) else {
+            continue;
+        };
+
+        if let Some(channeling) = caster.channeling.take() {
+            ended_events.send(ChannelEnded { caster: event.caster, spell: channeling.spell });
+        }
+
+        // letting go before a channel even started calls it off
+        let channel_windup = caster.casting
+            .and_then(|c| book.get(c.spell))
+            .is_some_and(|s| s.channel.is_some());
+        if channel_windup {
+            let casting = caster.casting.take().expect("casting");
+            interrupted_events.send(SpellInterrupted { caster: event.caster, spell: casting.spell });
+        }
+    }
+}
+
+fn advance_casts(
+    time: Res<Time>,
+    dilation: Res<TimeDilation>,
+    book: Res<SpellBook>,
+    mut cooldowns: ResMut<Cooldowns>,
+    mut caster_query: Query<(Entity, &mut Caster, &mut Stats, &Transform, Option<&Health>, Has<Player>)>,
+    mut cast_events: EventWriter<SpellCast>,
+    mut interrupted_events: EventWriter<SpellInterrupted>,
+) {
+    for (entity, mut caster, mut stats, transform, health, is_player) in &mut caster_query {
+        // the player keeps casting at full speed while time is dilated
+        let dt = if is_player { dilation.unscaled(&time) } else { time.delta_seconds() };
+
+        let Some(mut casting) = caster.casting else {
+            continue;
+        };
+        let Some(spell) = book.get(casting.spell) else {
+            caster.casting = None;
+            continue;
+        };
+
+        casting.elapsed += dt * stats.cast_speed();
+        if casting.elapsed < spell.cast_time {
+            caster.casting = Some(casting);
+            continue;
+        }
+
+        // mana is only paid once the spell actually goes off
+        caster.casting = None;
+        let cost = stats.spell_cost(spell.cost);
+        if stats.spend_mana(cost) {
+            cooldowns.start(entity, Ability::Spell(casting.spell), spell.cooldown);
+            cast_events.send(SpellCast { caster: entity, spell: casting.spell });
+
+            if spell.channel.is_some() {
+                caster.channeling = Some(Channeling {
+                    spell: casting.spell,
+                    elapsed: 0.0,
+                    origin: transform.translation,
+                    health: health.map(|h| h.current),
+                });
+            }
+        } else {
+            interrupted_events.send(SpellInterrupted { caster: entity, spell: casting.spell });
+        }
+    }
+}
+
+fn channel_spells(
+    time: Res<Time>,
+    dilation: Res<TimeDilation>,
+    book: Res<SpellBook>,
+    spatial_query: SpatialQuery,
+    camera_query: Query<&Transform, With<Camera>>,
+    mut caster_query: Query<
+        (Entity, &mut Caster, &mut Stats, &Transform, Option<&Health>, Has<Player>),
+        Without<Hostile>,
+    >,
+    hostile_query: Query<(), (With<Hostile>, With<Health>)>,
+    hurtbox_query: Query<&Hurtbox>,
+    mut interrupted_events: EventWriter<SpellInterrupted>,
+    mut ended_events: EventWriter<ChannelEnded>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut gizmos: Gizmos,
+) {
+    for (entity, mut caster, mut stats, transform, health, is_player) in &mut caster_query {
+        let dt = if is_player { dilation.unscaled(&time) } else { time.delta_seconds() };
+
+        let Some(mut channeling) = caster.channeling else {
+            continue;
+        };
+        let Some((spell, channel)) = book.get(channeling.spell).and_then(|s| Some((s, s.channel?))) else {
+            caster.channeling = None;
+            continue;
+        };
+
+        let moved = transform.translation.xz().distance(channeling.origin.xz()) > CHANNEL_MOVE_TOLERANCE;
+        let hurt = health.zip(channeling.health).is_some_and(|(h, before)| h.current < before);
+        if (channel.broken_by_movement && moved) || (channel.broken_by_damage && hurt) {
+            caster.channeling = None;
+            interrupted_events.send(SpellInterrupted { caster: entity, spell: channeling.spell });
+            continue;
+        }
+
+        let upkeep = stats.spell_cost(channel.mana_per_second * dt);
+        if !stats.spend_mana(upkeep) {
+            caster.channeling = None;
+            ended_events.send(ChannelEnded { caster: entity, spell: channeling.spell });
+            continue;
+        }
+
+        channeling.elapsed += dt;
+        channeling.health = health.map(|h| h.current);
+        caster.channeling = Some(channeling);
+
+        if let SpellEffect::Beam { damage, range } = spell.effect {
+            let direction = camera_query.get_single().map_or(transform.forward(), |c| c.forward());
+            let hit = spatial_query.cast_ray(
+                transform.translation,
+                direction,
+                range,
+                true,
+                SpatialQueryFilter::new().without_entities([entity]),
+            );
+
+            let length = hit.map_or(range, |h| h.time_of_impact);
+            gizmos.line(transform.translation, transform.translation + direction * length, Color::ORANGE_RED);
+
+            let target = hit.map(|h| resolve_hit(&hurtbox_query, h.entity));
+            if let Some((target, multiplier)) = target.filter(|(t, _)| hostile_query.contains(*t)) {
+                damage_events.send(DamageEvent::new(
+                    target,
+                    Damage::new(damage.amount * multiplier * dt, damage.kind),
+                    Some(entity),
+                ).periodic());
+            }
+        }
+    }
+}
+
+fn apply_spell_effects(
+    book: Res<SpellBook>,
+    spatial_query: SpatialQuery,
+    caster_query: Query<(&Transform, Option<&Stats>)>,
+    camera_query: Query<&Transform, With<Camera>>,
+    hostile_query: Query<(Entity, &Transform), (With<Hostile>, With<Health>)>,
+    hurtbox_query: Query<&Hurtbox>,
+    mut cast_events: EventReader<SpellCast>,
+    mut launch_events: EventWriter<LaunchProjectile>,
+    mut summon_events: EventWriter<SummonMinion>,
+    mut barrier_events: EventWriter<SpawnBarrier>,
+    mut hit_events: EventWriter<SpellHit>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut knockback_events: EventWriter<Knockback>,
+) {
+    for event in cast_events.read() {
+        let (Some(spell), Ok((caster, stats))) = (book.get(event.spell), caster_query.get(event.caster)) else {
+            continue;
+        };
+
+        match spell.effect {
+            SpellEffect::Bolt { damage, range } => {
+                // aim where the camera looks
+                let direction = camera_query.get_single().map_or(caster.forward(), |c| c.forward());
+                let hit = spatial_query.cast_ray(
+                    caster.translation,
+                    direction,
+                    range,
+                    true,
+                    SpatialQueryFilter::new().without_entities([event.caster]),
+                );
+
+                let Some(hit) = hit else {
+                    continue;
+                };
+                // weak spots take more
+                let (target, multiplier) = resolve_hit(&hurtbox_query, hit.entity);
+                if hostile_query.contains(target) {
+                    let damage = Damage::new(damage.amount * multiplier, damage.kind);
+                    damage_events.send(DamageEvent::new(target, damage, Some(event.caster)));
+                    hit_events.send(SpellHit { caster: event.caster, target });
+                }
+            }
+            SpellEffect::Nova { damage, radius } => {
+                for (entity, transform) in &hostile_query {
+                    if transform.translation.distance(caster.translation) <= radius {
+                        damage_events.send(DamageEvent::new(entity, damage, Some(event.caster)));
+                        knockback_events.send(Knockback {
+                            target: entity,
+                            direction: transform.translation - caster.translation,
+                            force: NOVA_KNOCKBACK,
+                        });
+                        hit_events.send(SpellHit { caster: event.caster, target: entity });
+                    }
+                }
+            }
+            SpellEffect::Projectile { kind, count } => {
+                let count = count + stats.map_or(0, |s| s.extra_projectiles());
+                let camera = camera_query.get_single().unwrap_or(caster);
+                let forward = camera.forward();
+                // roughly where the right hand is
+                let origin = caster.translation + Vec3::Y * 0.5 + camera.right() * 0.4 + forward * 0.8;
+                for i in 0..count {
+                    let angle = (i as f32 - (count - 1) as f32 / 2.0) * PROJECTILE_SPREAD;
+                    let direction = Quat::from_rotation_y(angle) * forward;
+                    launch_events.send(LaunchProjectile { owner: event.caster, kind, origin, direction });
+                }
+            }
+            // handled every frame while channeling
+            SpellEffect::Beam { .. } => {}
+            // see telekinesis.rs, blink.rs, dilation.rs, levitation.rs, ritual.rs and circle.rs
+            SpellEffect::Telekinesis { .. }
+            | SpellEffect::Blink { .. }
+            | SpellEffect::TimeDilation { .. }
+            | SpellEffect::Levitate { .. }
+            | SpellEffect::Ritual
+            | SpellEffect::Inscribe { .. }
+            | SpellEffect::Dispel { .. } => {}
+            SpellEffect::Barrier { radius, budget, duration } => {
+                barrier_events.send(SpawnBarrier {
+                    pos: caster.translation,
+                    radius,
+                    budget,
+                    duration,
+                });
+            }
+            SpellEffect::Summon { health, damage, duration } => {
+                let mut forward = caster.forward();
+                forward.y = 0.0;
+                let pos = caster.translation + forward.normalize_or_zero() * 1.5;
+                summon_events.send(SummonMinion { owner: event.caster, pos, health, damage, duration });
+            }
+        }
+    }
+}
+
+fn setup_magic_ui(mut commands: Commands) {
+    commands.spawn((
+        MagicText,
+        TextBundle::from_section("", TextStyle {
+            font_size: 16.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(40.0),
+            bottom: Val::Px(68.0),
+            ..default()
+        }),
+    ));
+}
+
+fn update_magic_ui(
+    locale: Res<Locale>,
+    book: Res<SpellBook>,
+    dilation: Res<TimeDilation>,
+    inscription: Res<Inscription>,
+    tracks: Res<UpgradeTracks>,
+    player_query: Query<
+        (&Caster, &Stats, &Hotbar, &SpellProgress, &Flux, Option<&StatusEffects>),
+        With<Player>,
+    >,
+    mut text_query: Query<&mut Text, With<MagicText>>,
+) {
+    let (caster, stats, hotbar, progress, flux, effects) = player_query.get_single().expect("not one player");
+    let mut text = text_query.get_single_mut().expect("not one magic text");
+
+    // mana, stamina and the hotbar all have their own place in the hud
+    let mut lines = vec![locale.fill("Circle {}", &[&locale.get(inscription.kind().name())])];
+
+    // how far along the selected spell is and what it gets next
+    if let Some(HotbarEntry::Spell(id)) = hotbar.selected_entry() {
+        let mastery = progress.get(id);
+        let mut line = locale.fill("Level {}", &[&mastery.level]);
+        if let Some(next) = tracks.get(id).get(mastery.level as usize) {
+            let xp = [format!("{:.0}", mastery.xp), format!("{:.0}", mastery.xp_needed())];
+            line += &locale.fill(", next {} ({}/{})", &[&next.description(&locale), &xp[0], &xp[1]]);
+        }
+        lines.push(line);
+    }
+
+    if let Some((casting, spell)) = caster.casting.and_then(|c| book.get(c.spell).map(|s| (c, s))) {
+        let percent = format!("{:.0}", casting.elapsed / spell.cast_time * 100.0);
+        lines.push(locale.fill("Casting {} {}%", &[&locale.get(&spell.name), &percent]));
+    }
+    if let Some((channeling, spell)) = caster.channeling.and_then(|c| book.get(c.spell).map(|s| (c, s))) {
+        let seconds = format!("{:.1}", channeling.elapsed);
+        lines.push(locale.fill("Channeling {} {}s", &[&locale.get(&spell.name), &seconds]));
+    }
+    if flux.value > 0.0 {
+        let threshold = Flux::threshold(stats);
+        let (value, threshold_text) = (format!("{:.0}", flux.value), format!("{:.0}", threshold));
+        lines.push(if flux.value > threshold {
+            let risk = format!("{:.0}", flux.risk(threshold) * 100.0);
+            locale.fill("Flux {}/{} ({}% backlash)", &[&value, &threshold_text, &risk])
+        } else {
+            locale.fill("Flux {}/{}", &[&value, &threshold_text])
+        });
+    }
+    if dilation.is_active() {
+        lines.push(locale.fill("Time slowed to {}%", &[&format!("{:.0}", dilation.factor * 100.0)]));
+    }
+    for status in effects.into_iter().flat_map(|e| &e.active) {
+        let stacks = if status.stacks > 1 { format!(" x{}", status.stacks) } else { String::new() };
+        let name = format!("{}{}", locale.get(status.kind.name()), stacks);
+        lines.push(locale.fill("{} {}s", &[&name, &format!("{:.0}", status.remaining)]));
+    }
+
+    text.sections[0].value = lines.join("\n");
+}
@@ -1,7 +1,201 @@
+mod ai;
+mod alert;
+mod ambience;
+mod audio;
+mod barrier;
+mod belt;
+mod blink;
+mod blueprint;
+mod brewing;
+mod building;
 mod camera;
+mod character;
+mod circle;
+mod circuit;
+mod controls;
+mod cooldown;
+mod corruption;
+mod crafting;
+mod crosshair;
+mod damage_numbers;
+mod daynight;
+mod debug;
+mod dialogue;
+mod death;
+mod deconstruct;
+mod dilation;
+mod dispel;
+mod dodge;
+mod drone;
+mod enchanting;
+mod enemy;
+mod environment;
+mod equipment;
+mod faction;
+mod golem;
+mod ground;
+mod health;
+mod heat;
+mod hitbox;
+mod hotbar;
+mod hud;
+mod implement;
+mod indicator;
+mod input;
+mod interaction;
+mod inventory;
+mod inventory_ui;
+mod item;
+mod layer;
+mod levitation;
+mod loading;
+mod locale;
+mod loot;
+mod machine;
+mod machine_ui;
+mod magic;
+mod map;
+mod melee;
+mod minecart;
+mod minimap;
+mod module;
+mod nav;
+mod overload;
+mod perception;
+mod pickup;
+mod power;
+mod progression;
+mod projectile;
+mod quest;
+mod radial;
+mod raid;
+mod rail;
+mod recipe;
+mod research;
+mod ritual;
+mod runecraft;
+mod scanner;
+mod settings;
+mod sfx;
+mod shaman;
+mod splitter;
+mod stagger;
+mod state;
+mod stats;
+mod status;
+mod storage;
+mod streaming;
+mod summon;
+mod tech_tree;
+mod telekinesis;
+mod threat;
+mod toast;
+mod tooltip;
+mod turret;
+mod ui_sound;
+mod underground;
+mod waystone;
+mod wear;
+mod wildlife;
 
-use bevy::{prelude::*, window::WindowMode, winit::WinitSettings};
+use ai::AiPlugin;
+use alert::AlertPlugin;
+use ambience::AmbiencePlugin;
+use audio::AudioPlugin;
+use barrier::BarrierPlugin;
+use belt::BeltPlugin;
+use bevy::{audio::SpatialScale, prelude::*, window::WindowMode, winit::WinitSettings};
+use bevy_xpbd_3d::prelude::*;
+use blink::BlinkPlugin;
+use blueprint::BlueprintPlugin;
+use brewing::BrewingPlugin;
+use building::BuildingPlugin;
 use camera::{CameraPlugin, Camera};
+use character::{CharacterPlugin, Stats};
+use circle::CirclePlugin;
+use circuit::CircuitPlugin;
+use controls::{ControlsPlugin, SurfaceMaterial};
+use cooldown::CooldownPlugin;
+use corruption::{CorruptionPlugin, Vegetation};
+use crafting::{CraftingPlugin, CraftQueue};
+use crosshair::CrosshairPlugin;
+use damage_numbers::DamageNumberPlugin;
+use daynight::DayNightPlugin;
+use debug::DebugPlugin;
+use dialogue::DialoguePlugin;
+use death::DeathPlugin;
+use deconstruct::DeconstructPlugin;
+use dilation::TimeDilationPlugin;
+use dispel::DispelPlugin;
+use dodge::DodgePlugin;
+use drone::DronePlugin;
+use enchanting::EnchantingPlugin;
+use enemy::EnemyPlugin;
+use environment::EnvironmentPlugin;
+use equipment::{Equipment, EquipmentPlugin};
+use faction::FactionPlugin;
+use golem::GolemPlugin;
+use ground::GroundItemPlugin;
+use health::{Health, HealthPlugin, HitImmunity};
+use heat::HeatPlugin;
+use hitbox::HitboxPlugin;
+use hotbar::{Hotbar, HotbarPlugin};
+use hud::HudPlugin;
+use indicator::IndicatorPlugin;
+use input::KeybindPlugin;
+use interaction::InteractionPlugin;
+use inventory::Inventory;
+use inventory_ui::InventoryUiPlugin;
+use item::{Item, ItemStack};
+use levitation::LevitationPlugin;
+use loading::LoadingPlugin;
+use locale::LocalePlugin;
+use loot::LootPlugin;
+use machine::MachinePlugin;
+use machine_ui::MachineUiPlugin;
+use magic::{Caster, MagicPlugin, SpellId};
+use map::MapPlugin;
+use melee::{Combo, MeleePlugin};
+use minecart::MinecartPlugin;
+use minimap::MinimapPlugin;
+use module::ModulePlugin;
+use nav::NavPlugin;
+use overload::{Flux, OverloadPlugin};
+use perception::PerceptionPlugin;
+use pickup::PickupPlugin;
+use power::PowerPlugin;
+use progression::{ProgressionPlugin, SpellProgress};
+use projectile::ProjectilePlugin;
+use quest::QuestPlugin;
+use radial::RadialPlugin;
+use raid::RaidPlugin;
+use rail::RailPlugin;
+use research::ResearchPlugin;
+use ritual::RitualPlugin;
+use runecraft::RunecraftPlugin;
+use scanner::ScannerPlugin;
+use settings::SettingsPlugin;
+use sfx::{SoundPlugin, SPATIAL_SCALE};
+use shaman::ShamanPlugin;
+use splitter::SplitterPlugin;
+use stagger::StaggerPlugin;
+use state::{GameState, StatePlugin};
+use stats::StatsPlugin;
+use status::StatusPlugin;
+use storage::StoragePlugin;
+use streaming::StreamingPlugin;
+use summon::SummonPlugin;
+use tech_tree::TechTreePlugin;
+use telekinesis::TelekinesisPlugin;
+use threat::ThreatPlugin;
+use toast::ToastPlugin;
+use tooltip::TooltipPlugin;
+use turret::TurretPlugin;
+use ui_sound::UiSoundPlugin;
+use underground::UndergroundPlugin;
+use waystone::WaystonePlugin;
+use wear::WearPlugin;
+use wildlife::WildlifePlugin;
 
 fn main() {
     App::new()
@@ -14,11 +208,121 @@ fn main() {
                     ..default()
                 }),
                 ..default()
+            }).set(bevy::audio::AudioPlugin {
+                spatial_scale: SpatialScale::new(SPATIAL_SCALE),
+                ..default()
             }),
-            CameraPlugin
+            PhysicsPlugins::default(),
+            CameraPlugin,
+            ControlsPlugin,
+            HealthPlugin,
+            EnemyPlugin,
+            StatsPlugin,
+            AlertPlugin,
+            CraftingPlugin,
+            ModulePlugin,
+            GroundItemPlugin,
+            CircuitPlugin,
+            DeconstructPlugin,
+            DronePlugin,
+            ScannerPlugin,
+        ))
+        .add_plugins((
+            BuildingPlugin,
+            StoragePlugin,
+            BeltPlugin,
+            SplitterPlugin,
+            UndergroundPlugin,
+            BlueprintPlugin,
+            PowerPlugin,
+            DayNightPlugin,
+            MachinePlugin,
+            CorruptionPlugin,
+            HeatPlugin,
+            RailPlugin,
+            MinecartPlugin,
+            TurretPlugin,
+            ResearchPlugin,
+        ))
+        .add_plugins((
+            WearPlugin,
+            StreamingPlugin,
+            EnvironmentPlugin,
+            MagicPlugin,
+            HotbarPlugin,
+            ProjectilePlugin,
+            CharacterPlugin,
+            PickupPlugin,
+            RunecraftPlugin,
+            SummonPlugin,
+            TelekinesisPlugin,
+            BarrierPlugin,
+            BlinkPlugin,
+            TimeDilationPlugin,
+            LevitationPlugin,
+        ))
+        .add_plugins((
+            EquipmentPlugin,
+            EnchantingPlugin,
+            RitualPlugin,
+            CirclePlugin,
+            StatusPlugin,
+            BrewingPlugin,
+            CooldownPlugin,
+            ShamanPlugin,
+            DispelPlugin,
+            ProgressionPlugin,
+            OverloadPlugin,
+            GolemPlugin,
+            AiPlugin,
+            NavPlugin,
+            PerceptionPlugin,
+        ))
+        .add_plugins((
+            RaidPlugin,
+            ThreatPlugin,
+            LootPlugin,
+            StaggerPlugin,
+            FactionPlugin,
+            WildlifePlugin,
+            DamageNumberPlugin,
+            DeathPlugin,
+            HitboxPlugin,
+            MeleePlugin,
+            DodgePlugin,
+            IndicatorPlugin,
+            StatePlugin,
+            SettingsPlugin,
+            HudPlugin,
+        ))
+        .add_plugins((
+            InventoryUiPlugin,
+            CrosshairPlugin,
+            MinimapPlugin,
+            InteractionPlugin,
+            TooltipPlugin,
+            DebugPlugin,
+            LoadingPlugin,
+            QuestPlugin,
+            MachineUiPlugin,
+            ToastPlugin,
+            KeybindPlugin,
+            LocalePlugin,
+            MapPlugin,
+            WaystonePlugin,
+            TechTreePlugin,
         ))
-        .add_systems(Startup, (setup_camera, setup_light, setup_scene))
-        .add_systems(Update, player_movement)
+        .add_plugins((
+            DialoguePlugin,
+            RadialPlugin,
+            AudioPlugin,
+            SoundPlugin,
+            AmbiencePlugin,
+            UiSoundPlugin,
+        ))
+        .add_systems(Startup, (setup_camera, setup_light))
+        // the world only exists once a game is started from the menu
+        .add_systems(OnEnter(GameState::Loading), setup_scene)
         .run();
 }
 
@@ -35,14 +339,53 @@ fn setup_scene(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    commands.spawn(PbrBundle {
-        mesh: meshes.add(Mesh::from(shape::Plane {
-            size: 15.0,
-            subdivisions: 30,
-        })),
-        material: materials.add(Color::GREEN.into()),
-        ..default()
-    });
+    commands.spawn((
+        Vegetation { healthy: Color::GREEN },
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Plane {
+                size: 15.0,
+                subdivisions: 30,
+            })),
+            material: materials.add(Color::GREEN.into()),
+            ..default()
+        },
+        RigidBody::Static,
+        Collider::cuboid(15.0, 0.1, 15.0),
+        SurfaceMaterial::Grass,
+    ));
+
+    // scatter some bushes in a spiral around the spawn
+    let bush = meshes.add(Mesh::from(shape::UVSphere {
+        radius: 0.3,
+        sectors: 8,
+        stacks: 6,
+    }));
+    for i in 0..30 {
+        let angle = i as f32 * 2.4;
+        let radius = 2.0 + (i % 6) as f32;
+        let healthy = Color::rgb_u8(40, 120, 40);
+
+        commands.spawn((
+            Vegetation { healthy },
+            PbrBundle {
+                mesh: bush.clone(),
+                material: materials.add(healthy.into()),
+                transform: Transform::from_xyz(angle.cos() * radius, 0.2, angle.sin() * radius),
+                ..default()
+            },
+        ));
+    }
+
+    // enough raw materials to hand craft the first few parts
+    // and put down a small base
+    let mut inventory = Inventory::new(24);
+    inventory.insert(ItemStack::new(Item::IronOre, 20));
+    inventory.insert(ItemStack::new(Item::CopperOre, 10));
+    inventory.insert(ItemStack::new(Item::Stone, 40));
+    inventory.insert(ItemStack::new(Item::Wood, 30));
+    inventory.insert(ItemStack::new(Item::IronPlate, 100));
+    inventory.insert(ItemStack::new(Item::CopperPlate, 50));
+    inventory.insert(ItemStack::new(Item::Gear, 30));
 
     commands.spawn((
         Player,
@@ -54,53 +397,29 @@ fn setup_scene(
                 segments: 5,
             })),
             material: materials.add(Color::rgb_u8(124, 144, 255).into()),
-            transform: Transform::from_xyz(0.0, 0.5, 0.0),
+            transform: Transform::from_xyz(0.0, 1.0, 0.0),
             ..default()
         },
         Speed { value: 2.5 },
+        Equipment::default(),
+        inventory,
+        CraftQueue::default(),
+        // half a second of grace after each hit from the same attacker
+        (Health::new(100.0), HitImmunity::new(0.5)),
+        Stats::new(100.0),
+        (Caster::default(), Combo::default()),
+        SpellProgress::default(),
+        Flux::default(),
+        Hotbar::from_spells(&[
+            SpellId(0), SpellId(1), SpellId(2), SpellId(3),
+            SpellId(4), SpellId(5), SpellId(6), SpellId(7), SpellId(8),
+        ]),
+        RigidBody::Dynamic,
+        Collider::capsule(1.0, 0.5),
+        LockedAxes::ROTATION_LOCKED,
     ));
 }
 
-fn player_movement(
-    time: Res<Time>,
-    mut player_query: Query<(&mut Transform, &Speed), With<Player>>,
-    camera_query: Query<&Transform, (With<Camera>, Without<Player>)>,
-    keys: Res<Input<KeyCode>>,
-) {
-    use KeyCode as K;
-
-    let (mut player_transform, player_speed) = player_query
-        .get_single_mut()
-        .expect("no or more than one player");
-
-    let Ok(camera) = camera_query.get_single() else {
-        return Err("couldn't fetch camera").unwrap();
-    };
-
-    let mut direction = Vec3::ZERO;
-
-    if keys.pressed(K::W) {
-        direction += camera.forward();
-    }
-
-    if keys.pressed(K::A) {
-        direction += camera.left();
-    }
-
-    if keys.pressed(K::S) {
-        direction += camera.back();
-    }
-
-    if keys.pressed(K::D) {
-        direction += camera.right();
-    }
-
-    direction.y = 0.0;
-
-    let movement = direction.normalize_or_zero() * player_speed.value * time.delta_seconds();
-    player_transform.translation += movement;
-}
-
 fn setup_camera(mut commands: Commands) {
     commands.spawn((
         Camera3dBundle::default(),
@@ -0,0 +1,365 @@
+use bevy::{
+    input::mouse::MouseWheel,
+    prelude::*,
+    utils::HashSet,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    building::Building,
+    camera::{CursorUser, CursorUsers},
+    corruption::{lerp_color, CorruptionMap, WITHER_LEVEL},
+    faction::Trader,
+    input::{Action, InputMap},
+    locale::Localized,
+    minimap::Discovered,
+    quest::QuestLog,
+    state::GameplaySet,
+    waystone::{FastTravel, Waystone, WAYSTONE_COLOR},
+    Player,
+};
+
+// terrain cells across and down the screen
+const COLUMNS: usize = 48;
+const ROWS: usize = 27;
+// world units along each side of an explored cell
+const EXPLORE_CELL: f32 = 8.0;
+const EXPLORE_RANGE: f32 = 24.0;
+const MAX_ICONS: usize = 256;
+// world units per pixel
+const MIN_SCALE: f32 = 0.05;
+const MAX_SCALE: f32 = 2.0;
+// how far the cursor can move while pressed and still count as a click
+const CLICK_SLOP: f32 = 4.0;
+// pixels from an icon that still count as clicking it
+const PICK_RADIUS: f32 = 10.0;
+const UNEXPLORED: Color = Color::rgb(0.05, 0.05, 0.07);
+const PIN_COLOR: Color = Color::FUCHSIA;
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<WorldMap>()
+            .add_systems(Startup, setup_map)
+            .add_systems(Update, (
+                explore,
+                toggle_map,
+                map_input,
+                draw_map_terrain,
+                draw_map_icons,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+/// The full screen map, north up, showing only what the player has been near.
+#[derive(Resource)]
+pub struct WorldMap {
+    pub open: bool,
+    // world x and z in the middle of the screen
+    center: Vec2,
+    scale: f32,
+    explored: HashSet<IVec2>,
+    pins: Vec<Vec2>,
+    // cursor travel since the button went down, to tell drags from clicks
+    dragged: f32,
+}
+
+impl Default for WorldMap {
+    fn default() -> Self {
+        WorldMap {
+            open: false,
+            center: Vec2::ZERO,
+            scale: 0.25,
+            explored: HashSet::default(),
+            pins: Vec::new(),
+            dragged: 0.0,
+        }
+    }
+}
+
+impl WorldMap {
+    fn to_screen(&self, pos: Vec2, size: Vec2) -> Vec2 {
+        (pos - self.center) / self.scale + size / 2.0
+    }
+
+    fn to_world(&self, at: Vec2, size: Vec2) -> Vec2 {
+        (at - size / 2.0) * self.scale + self.center
+    }
+}
+
+#[derive(Component)]
+struct MapScreen;
+
+#[derive(Component)]
+struct MapCell(usize, usize);
+
+#[derive(Component)]
+struct MapIcon;
+
+fn flat(pos: Vec3) -> Vec2 {
+    Vec2::new(pos.x, pos.z)
+}
+
+fn cell_of(pos: Vec2) -> IVec2 {
+    (pos / EXPLORE_CELL).floor().as_ivec2()
+}
+
+fn setup_map(mut commands: Commands) {
+    let text = |value: &str, size: f32| TextBundle::from_section(value, TextStyle {
+        font_size: size,
+        color: Color::WHITE,
+        ..default()
+    });
+    let hint = "Drag to pan, scroll to zoom, right click to pin, click a waystone to travel";
+
+    commands.spawn((
+        MapScreen,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            background_color: UNEXPLORED.into(),
+            visibility: Visibility::Hidden,
+            // over the hud, under tooltips and toasts
+            z_index: ZIndex::Global(5),
+            ..default()
+        },
+    )).with_children(|parent| {
+        for x in 0..COLUMNS {
+            for y in 0..ROWS {
+                parent.spawn((
+                    MapCell(x, y),
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Percent(x as f32 * 100.0 / COLUMNS as f32),
+                            top: Val::Percent(y as f32 * 100.0 / ROWS as f32),
+                            width: Val::Percent(100.0 / COLUMNS as f32),
+                            height: Val::Percent(100.0 / ROWS as f32),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ));
+            }
+        }
+        for _ in 0..MAX_ICONS {
+            parent.spawn((
+                MapIcon,
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+            ));
+        }
+
+        parent.spawn((Localized("Map"), text("Map", 24.0).with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            top: Val::Px(20.0),
+            ..default()
+        })));
+        parent.spawn((Localized(hint), text(hint, 16.0).with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            bottom: Val::Px(20.0),
+            ..default()
+        })));
+    });
+}
+
+fn explore(
+    mut map: ResMut<WorldMap>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let player = flat(player_query.get_single().expect("not one player").translation);
+    let here = cell_of(player);
+    let reach = (EXPLORE_RANGE / EXPLORE_CELL).ceil() as i32;
+
+    for x in -reach..=reach {
+        for y in -reach..=reach {
+            let cell = here + IVec2::new(x, y);
+            let middle = (cell.as_vec2() + 0.5) * EXPLORE_CELL;
+            if middle.distance(player) <= EXPLORE_RANGE && !map.explored.contains(&cell) {
+                map.explored.insert(cell);
+            }
+        }
+    }
+}
+
+fn toggle_map(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut map: ResMut<WorldMap>,
+    player_query: Query<&Transform, With<Player>>,
+    mut cursor_users: ResMut<CursorUsers>,
+) {
+    if !input_map.just_pressed(Action::Map, &keys) {
+        return;
+    }
+
+    map.open = !map.open;
+    // opens on the player rather than wherever it was left
+    if map.open {
+        map.center = flat(player_query.get_single().expect("not one player").translation);
+    }
+    cursor_users.set(CursorUser::Map, map.open);
+}
+
+fn map_input(
+    mouse: Res<Input<MouseButton>>,
+    mut map: ResMut<WorldMap>,
+    waystone_query: Query<(Entity, &Waystone, &Transform)>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut cursor_users: ResMut<CursorUsers>,
+    mut scroll_events: EventReader<MouseWheel>,
+    mut travel_events: EventWriter<FastTravel>,
+    mut last_cursor: Local<Option<Vec2>>,
+) {
+    let scroll = scroll_events.read().fold(0.0, |sum, e| sum + e.y);
+    let window = window_query.get_single().expect("not one window");
+    let size = Vec2::new(window.width(), window.height());
+    let cursor = window.cursor_position();
+    let last = std::mem::replace(&mut *last_cursor, cursor);
+    let (true, Some(cursor)) = (map.open, cursor) else {
+        return;
+    };
+
+    // zoom around the cursor, so whatever is under it stays there
+    if scroll != 0.0 {
+        let anchor = map.to_world(cursor, size);
+        map.scale = (map.scale * 0.8_f32.powf(scroll)).clamp(MIN_SCALE, MAX_SCALE);
+        let drift = anchor - map.to_world(cursor, size);
+        map.center += drift;
+    }
+
+    if mouse.just_pressed(MouseButton::Left) {
+        map.dragged = 0.0;
+    } else if let (true, Some(last)) = (mouse.pressed(MouseButton::Left), last) {
+        let delta = cursor - last;
+        let scale = map.scale;
+        map.center -= delta * scale;
+        map.dragged += delta.length();
+    }
+
+    // clicking an attuned waystone rather than dragging past it travels there
+    if mouse.just_released(MouseButton::Left) && map.dragged <= CLICK_SLOP {
+        let clicked = waystone_query.iter()
+            .filter(|(_, waystone, _)| waystone.attuned)
+            .map(|(e, _, t)| (e, map.to_screen(flat(t.translation), size).distance(cursor)))
+            .filter(|(_, d)| *d <= PICK_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((to, _)) = clicked {
+            travel_events.send(FastTravel { to });
+            map.open = false;
+            cursor_users.set(CursorUser::Map, false);
+            return;
+        }
+    }
+
+    // right clicking drops a pin, or picks one back up
+    if mouse.just_pressed(MouseButton::Right) {
+        let picked = map.pins.iter().position(|p| map.to_screen(*p, size).distance(cursor) <= PICK_RADIUS);
+        if let Some(i) = picked {
+            map.pins.remove(i);
+        } else {
+            let pin = map.to_world(cursor, size);
+            map.pins.push(pin);
+        }
+    }
+}
+
+fn draw_map_terrain(
+    map: Res<WorldMap>,
+    corruption: Res<CorruptionMap>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut screen_query: Query<&mut Visibility, With<MapScreen>>,
+    mut cell_query: Query<(&MapCell, &mut BackgroundColor)>,
+) {
+    let mut visibility = screen_query.get_single_mut().expect("not one map screen");
+    *visibility = if map.open { Visibility::Visible } else { Visibility::Hidden };
+    if !map.open {
+        return;
+    }
+
+    let window = window_query.get_single().expect("not one window");
+    let size = Vec2::new(window.width(), window.height());
+    let cell = size / Vec2::new(COLUMNS as f32, ROWS as f32);
+
+    for (MapCell(x, y), mut background) in &mut cell_query {
+        let pos = map.to_world((Vec2::new(*x as f32, *y as f32) + 0.5) * cell, size);
+        *background = if map.explored.contains(&cell_of(pos)) {
+            let withered = (corruption.get(Vec3::new(pos.x, 0.0, pos.y)) / WITHER_LEVEL).min(1.0);
+            lerp_color(Color::rgb_u8(40, 90, 40), Color::rgb_u8(90, 60, 100), withered)
+        } else {
+            UNEXPLORED
+        }.into();
+    }
+}
+
+fn draw_map_icons(
+    map: Res<WorldMap>,
+    quest_log: Res<QuestLog>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    player_query: Query<&Transform, With<Player>>,
+    building_query: Query<(&Transform, &Building)>,
+    structure_query: Query<(&Transform, Has<Trader>), With<Discovered>>,
+    waystone_query: Query<(&Transform, &Waystone)>,
+    mut icon_query: Query<(&mut Style, &mut BackgroundColor, &mut Visibility), With<MapIcon>>,
+) {
+    if !map.open {
+        return;
+    }
+
+    let window = window_query.get_single().expect("not one window");
+    let size = Vec2::new(window.width(), window.height());
+    let player = player_query.get_single().expect("not one player").translation;
+
+    // whatever matters most comes last and is kept if there are too many
+    let mut icons: Vec<(Vec2, Color, f32)> = Vec::new();
+    icons.extend(building_query.iter().map(|(t, b)| (flat(t.translation), b.kind.color(), 4.0)));
+    icons.extend(structure_query.iter().map(|(t, trader)| {
+        (flat(t.translation), if trader { Color::GOLD } else { Color::rgb_u8(140, 100, 50) }, 8.0)
+    }));
+    icons.extend(waystone_query.iter()
+        .filter(|(_, w)| w.attuned)
+        .map(|(t, _)| (flat(t.translation), WAYSTONE_COLOR, 12.0)));
+    icons.extend(map.pins.iter().map(|p| (*p, PIN_COLOR, 10.0)));
+    if let Some(marker) = quest_log.marker {
+        icons.push((flat(marker), Color::GREEN, 10.0));
+    }
+    icons.push((flat(player), Color::WHITE, 8.0));
+
+    let mut visible: Vec<(Vec2, Color, f32)> = icons.into_iter()
+        .rev()
+        .map(|(pos, color, icon_size)| (map.to_screen(pos, size), color, icon_size))
+        .filter(|(at, ..)| at.cmpge(Vec2::ZERO).all() && at.cmple(size).all())
+        .take(MAX_ICONS)
+        .collect();
+    // later icons in the pool are drawn on top of earlier ones
+    visible.reverse();
+    let mut shown = visible.into_iter();
+
+    for (mut style, mut background, mut visibility) in &mut icon_query {
+        let Some((at, color, icon_size)) = shown.next() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        style.left = Val::Px(at.x - icon_size / 2.0);
+        style.top = Val::Px(at.y - icon_size / 2.0);
+        style.width = Val::Px(icon_size);
+        style.height = Val::Px(icon_size);
+        *background = color.into();
+        *visibility = Visibility::Inherited;
+    }
+}
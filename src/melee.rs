@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    camera::Camera,
+    health::{Damage, DamageType},
+    hitbox::MeleeSwing,
+    state::GameplaySet,
+    Player,
+};
+
+// seconds after a swipe's recovery in which the next one still continues the chain
+const COMBO_WINDOW: f32 = 0.6;
+// the staff swings about waist high
+const SWING_DROP: f32 = 0.5;
+
+pub struct MeleePlugin;
+
+impl Plugin for MeleePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (melee_input, lunge).chain().in_set(GameplaySet));
+    }
+}
+
+/// One swipe in the staff combo, kept as plain data like the spells.
+#[derive(Clone, Copy, Debug)]
+pub struct Swipe {
+    pub damage: f32,
+    // degrees
+    pub arc: f32,
+    pub range: f32,
+    // how hard it carries the player forward, and for how long
+    pub lunge: f32,
+    pub lunge_time: f32,
+    // seconds before anything else can be done
+    pub recovery: f32,
+    // the last this many seconds of the recovery can be cut short by the
+    // next swipe or a dodge
+    pub cancel: f32,
+}
+
+pub const COMBO: [Swipe; 3] = [
+    Swipe { damage: 8.0, arc: 100.0, range: 1.8, lunge: 3.0, lunge_time: 0.1, recovery: 0.45, cancel: 0.2 },
+    Swipe { damage: 10.0, arc: 120.0, range: 1.8, lunge: 3.0, lunge_time: 0.1, recovery: 0.5, cancel: 0.2 },
+    // the finisher reaches further but leaves the player open for longer
+    Swipe { damage: 16.0, arc: 40.0, range: 2.4, lunge: 6.0, lunge_time: 0.15, recovery: 0.8, cancel: 0.3 },
+];
+
+/// Where the player is in the staff combo.
+#[derive(Component, Default)]
+pub struct Combo {
+    // the swipe that comes next
+    next: usize,
+    // seconds until the current swipe is over
+    recovery: f32,
+    // seconds left to continue the chain after that
+    window: f32,
+    // pressed again too early, goes off as soon as the cancel window opens
+    queued: bool,
+}
+
+impl Combo {
+    pub fn is_attacking(&self) -> bool {
+        self.recovery > 0.0
+    }
+
+    /// Whether whatever's going on can be cut short right now.
+    pub fn cancellable(&self) -> bool {
+        let current = COMBO[(self.next + COMBO.len() - 1) % COMBO.len()];
+        self.recovery <= current.cancel
+    }
+
+    pub fn cancel(&mut self) {
+        self.recovery = 0.0;
+        self.window = 0.0;
+        self.queued = false;
+        self.next = 0;
+    }
+}
+
+/// Carried forward by a swipe, movement input is ignored meanwhile.
+#[derive(Component)]
+pub struct Lunging {
+    velocity: Vec3,
+    remaining: f32,
+}
+
+fn melee_input(
+    mut commands: Commands,
+    time: Res<Time>,
+    mouse: Res<Input<MouseButton>>,
+    mut player_query: Query<(Entity, &mut Combo, &Transform), With<Player>>,
+    camera_query: Query<&Transform, (With<Camera>, Without<Player>)>,
+    mut swing_events: EventWriter<MeleeSwing>,
+) {
+    let (player, mut combo, transform) = player_query.get_single_mut().expect("not one player");
+    let dt = time.delta_seconds();
+
+    if combo.recovery > 0.0 {
+        combo.recovery = (combo.recovery - dt).max(0.0);
+    } else {
+        combo.window = (combo.window - dt).max(0.0);
+    }
+
+    if mouse.just_pressed(MouseButton::Middle) {
+        if combo.is_attacking() && !combo.cancellable() {
+            combo.queued = true;
+            return;
+        }
+    } else if !(combo.queued && combo.cancellable()) {
+        return;
+    }
+
+    // starting over once the chain has gone cold
+    if !combo.is_attacking() && combo.window <= 0.0 {
+        combo.next = 0;
+    }
+    let swipe = COMBO[combo.next];
+    combo.next = (combo.next + 1) % COMBO.len();
+    combo.recovery = swipe.recovery;
+    combo.window = COMBO_WINDOW;
+    combo.queued = false;
+
+    let forward = camera_query.get_single().map_or(transform.forward(), |c| c.forward());
+    let forward = Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero();
+    swing_events.send(MeleeSwing {
+        attacker: player,
+        origin: transform.translation - Vec3::Y * SWING_DROP,
+        facing: forward,
+        arc: swipe.arc,
+        range: swipe.range,
+        damage: Damage::new(swipe.damage, DamageType::Physical),
+    });
+    commands.entity(player).insert(Lunging { velocity: forward * swipe.lunge, remaining: swipe.lunge_time });
+}
+
+fn lunge(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut lunging_query: Query<(Entity, &mut Lunging, &mut LinearVelocity)>,
+) {
+    for (entity, mut lunging, mut velocity) in &mut lunging_query {
+        lunging.remaining -= time.delta_seconds();
+        if lunging.remaining <= 0.0 {
+            commands.entity(entity).remove::<Lunging>();
+            continue;
+        }
+        velocity.x = lunging.velocity.x;
+        velocity.z = lunging.velocity.z;
+    }
+}
@@ -0,0 +1,248 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    Player,
+    building::{AimPoint, BuildingGrid, Facing, GridPos},
+    controls::SurfaceMaterial,
+    input::{Action, InputMap},
+    interaction::{interact, Interactable, Interacted},
+    rail::{Rail, Station},
+    state::GameplaySet,
+};
+
+// tiles per second
+pub const MAX_CART_SPEED: f32 = 8.0;
+const ACCELERATION: f32 = 4.0;
+const BRAKING: f32 = 10.0;
+const FRICTION: f32 = 0.5;
+
+pub struct MinecartPlugin;
+
+impl Plugin for MinecartPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            spawn_minecarts,
+            board_minecarts.after(interact),
+            drive_minecarts,
+            move_minecarts,
+            carry_riders,
+        ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Component)]
+pub struct Minecart {
+    // the tile the cart is leaving
+    pub from: GridPos,
+    pub heading: Facing,
+    // 0.0 at the center of `from`, 1.0 at the center of the next tile
+    pub offset: f32,
+    pub speed: f32,
+    // which way to go at the next junction, straight if `None`
+    pub turn: Option<Facing>,
+}
+
+impl Minecart {
+    pub fn translation(&self) -> Vec3 {
+        self.from.to_world() + self.heading.direction() * self.offset + Vec3::Y * 0.3
+    }
+}
+
+// added to the player while sitting in a cart
+#[derive(Component)]
+pub struct Riding {
+    pub cart: Entity,
+}
+
+fn spawn_minecarts(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    keys: Res<Input<KeyCode>>,
+    aim: Res<AimPoint>,
+    grid: Res<BuildingGrid>,
+    rail_query: Query<(&Facing, Has<Station>), With<Rail>>,
+) {
+    if !keys.just_pressed(KeyCode::T) {
+        return;
+    }
+
+    let Some(pos) = aim.0.map(GridPos::from_world) else {
+        return;
+    };
+
+    // stations spawn trains instead
+    let Some((facing, false)) = grid.get(pos).and_then(|e| rail_query.get(e).ok()) else {
+        return;
+    };
+
+    let cart = Minecart {
+        from: pos,
+        heading: *facing,
+        offset: 0.0,
+        speed: 0.0,
+        turn: None,
+    };
+
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(0.8, 0.5, 1.0))),
+            material: materials.add(Color::rgb_u8(100, 80, 60).into()),
+            transform: Transform::from_translation(cart.translation())
+                .with_rotation(cart.heading.rotation()),
+            ..default()
+        },
+        // moved along the rails by us, but still shoves loose objects around
+        RigidBody::Kinematic,
+        Collider::cuboid(0.8, 0.5, 1.0),
+        SurfaceMaterial::Metal,
+        Interactable::new("Ride Minecart"),
+        cart,
+    ));
+}
+
+fn board_minecarts(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    player_query: Query<(Entity, &Transform, Option<&Riding>), With<Player>>,
+    cart_query: Query<&Transform, With<Minecart>>,
+    mut interacted_events: EventReader<Interacted>,
+) {
+    let used = interacted_events.read().map(|e| e.entity).find(|e| cart_query.contains(*e));
+    if !input_map.just_pressed(Action::Interact, &keys) {
+        return;
+    }
+
+    let (player, transform, riding) = player_query.get_single().expect("not one player");
+
+    if let Some(riding) = riding {
+        // hop off to the side of the cart
+        let side = cart_query.get(riding.cart)
+            .map(|t| t.translation + t.right() * 1.2)
+            .unwrap_or(transform.translation);
+
+        commands.entity(player)
+            .remove::<Riding>()
+            .insert((RigidBody::Dynamic, Position(side + Vec3::Y)));
+        return;
+    }
+
+    if let Some(cart) = used {
+        commands.entity(player).insert((
+            Riding { cart },
+            RigidBody::Kinematic,
+            LinearVelocity::ZERO,
+        ));
+    }
+}
+
+fn drive_minecarts(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    rider_query: Query<&Riding, With<Player>>,
+    mut cart_query: Query<&mut Minecart>,
+) {
+    let dt = time.delta_seconds();
+
+    // carts slowly roll out unless someone keeps accelerating
+    for mut cart in &mut cart_query {
+        cart.speed = (cart.speed - FRICTION * dt).max(0.0);
+    }
+
+    let Ok(riding) = rider_query.get_single() else {
+        return;
+    };
+    let Ok(mut cart) = cart_query.get_mut(riding.cart) else {
+        return;
+    };
+
+    if input_map.pressed(Action::MoveForward, &keys) {
+        cart.speed = (cart.speed + ACCELERATION * dt).min(MAX_CART_SPEED);
+    }
+
+    if input_map.pressed(Action::MoveBack, &keys) {
+        cart.speed = (cart.speed - BRAKING * dt).max(0.0);
+    }
+
+    // a stopped cart can be pushed the other way
+    if input_map.just_pressed(Action::MoveBack, &keys) && cart.speed == 0.0 && cart.offset > 0.0 {
+        cart.from = cart.from.step(cart.heading);
+        cart.heading = cart.heading.opposite();
+        cart.offset = 1.0 - cart.offset;
+    } else if input_map.just_pressed(Action::MoveBack, &keys) && cart.speed == 0.0 {
+        cart.heading = cart.heading.opposite();
+    }
+
+    if input_map.just_pressed(Action::MoveLeft, &keys) {
+        cart.turn = Some(cart.heading.counter_clockwise());
+    }
+    if input_map.just_pressed(Action::MoveRight, &keys) {
+        cart.turn = Some(cart.heading.clockwise());
+    }
+}
+
+fn move_minecarts(
+    time: Res<Time>,
+    grid: Res<BuildingGrid>,
+    rail_query: Query<(), With<Rail>>,
+    mut cart_query: Query<(&mut Minecart, &mut Transform)>,
+) {
+    let is_rail = |pos: GridPos| grid.get(pos).is_some_and(|e| rail_query.contains(e));
+
+    for (mut cart, mut transform) in &mut cart_query {
+        // never leave the rails, stop at the tile center instead
+        if cart.offset == 0.0 && !is_rail(cart.from.step(cart.heading)) {
+            cart.speed = 0.0;
+        }
+
+        cart.offset += cart.speed * time.delta_seconds();
+
+        while cart.offset >= 1.0 {
+            cart.from = cart.from.step(cart.heading);
+            cart.offset -= 1.0;
+
+            // prefer the requested turn, then straight, then whatever is left
+            let heading = cart.heading;
+            let options = [
+                cart.turn,
+                Some(heading),
+                Some(heading.clockwise()),
+                Some(heading.counter_clockwise()),
+            ];
+            let next = options.into_iter()
+                .flatten()
+                .find(|h| is_rail(cart.from.step(*h)));
+
+            let Some(next) = next else {
+                // end of the line
+                cart.offset = 0.0;
+                cart.speed = 0.0;
+                break;
+            };
+
+            if next != heading {
+                cart.turn = None;
+            }
+            cart.heading = next;
+        }
+
+        transform.translation = cart.translation();
+        transform.rotation = cart.heading.rotation();
+    }
+}
+
+fn carry_riders(
+    mut player_query: Query<(&Riding, &mut Transform), With<Player>>,
+    cart_query: Query<&Transform, (With<Minecart>, Without<Player>)>,
+) {
+    let Ok((riding, mut transform)) = player_query.get_single_mut() else {
+        return;
+    };
+
+    if let Ok(cart) = cart_query.get(riding.cart) {
+        transform.translation = cart.translation + Vec3::Y * 1.0;
+    }
+}
@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::Building,
+    camera::Camera,
+    corruption::{lerp_color, CorruptionMap, WITHER_LEVEL},
+    enemy::Hostile,
+    faction::Trader,
+    health::Health,
+    hud::HudElement,
+    input::{Action, InputMap},
+    loot::Cache,
+    raid::RaidDirector,
+    settings::Settings,
+    state::GameplaySet,
+    Player,
+};
+
+const MAP_SIZE: f32 = 160.0;
+// terrain cells along each side
+const CELLS: usize = 12;
+const MAX_MARKERS: usize = 64;
+const MARKER_SIZE: f32 = 5.0;
+// world units from the middle to the edge, cycled with Z
+const ZOOM_LEVELS: [f32; 3] = [8.0, 16.0, 32.0];
+// how close the player has to get for a cache or trader to show up
+const DISCOVER_RANGE: f32 = 10.0;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Minimap>()
+            .add_systems(Startup, setup_minimap)
+            .add_systems(Update, (
+                minimap_input,
+                discover_structures,
+                draw_terrain,
+                draw_markers,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Minimap {
+    zoom: usize,
+}
+
+impl Minimap {
+    fn radius(&self) -> f32 {
+        ZOOM_LEVELS[self.zoom]
+    }
+}
+
+/// Found out in the world, it stays on the map from then on.
+#[derive(Component)]
+pub struct Discovered;
+
+#[derive(Component)]
+struct TerrainCell(usize, usize);
+
+#[derive(Component)]
+struct Marker;
+
+fn setup_minimap(mut commands: Commands) {
+    let cell = MAP_SIZE / CELLS as f32;
+
+    commands.spawn((
+        HudElement::Minimap,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Percent(30.0),
+                width: Val::Px(MAP_SIZE),
+                height: Val::Px(MAP_SIZE),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            background_color: Color::BLACK.into(),
+            ..default()
+        },
+    )).with_children(|parent| {
+        for x in 0..CELLS {
+            for y in 0..CELLS {
+                parent.spawn((
+                    TerrainCell(x, y),
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(x as f32 * cell),
+                            top: Val::Px(y as f32 * cell),
+                            width: Val::Px(cell),
+                            height: Val::Px(cell),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ));
+            }
+        }
+        for _ in 0..MAX_MARKERS {
+            parent.spawn((
+                Marker,
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(MARKER_SIZE),
+                        height: Val::Px(MARKER_SIZE),
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+            ));
+        }
+    });
+}
+
+fn minimap_input(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut minimap: ResMut<Minimap>,
+) {
+    if input_map.just_pressed(Action::MinimapZoom, &keys) {
+        minimap.zoom = (minimap.zoom + 1) % ZOOM_LEVELS.len();
+    }
+}
+
+fn discover_structures(
+    mut commands: Commands,
+    structure_query: Query<(Entity, &Transform), (Or<(With<Cache>, With<Trader>)>, Without<Discovered>)>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let player = player_query.get_single().expect("not one player");
+
+    for (entity, transform) in &structure_query {
+        if transform.translation.distance(player.translation) <= DISCOVER_RANGE {
+            commands.entity(entity).insert(Discovered);
+        }
+    }
+}
+
+// where on the map `pos` ends up, relative to its top left corner
+fn to_map(pos: Vec3, center: Vec3, rotation: Quat, radius: f32) -> Vec2 {
+    let offset = rotation * (pos - center);
+    let scale = MAP_SIZE / 2.0 / radius;
+    Vec2::new(offset.x, offset.z) * scale + Vec2::splat(MAP_SIZE / 2.0)
+}
+
+// the world turned so the camera looks up the map, or north up
+fn map_rotation(settings: &Settings, camera: &Transform) -> Quat {
+    if !settings.minimap_rotates {
+        return Quat::IDENTITY;
+    }
+    let forward = camera.forward();
+    let yaw = (-forward.x).atan2(-forward.z);
+    Quat::from_rotation_y(-yaw)
+}
+
+fn draw_terrain(
+    minimap: Res<Minimap>,
+    settings: Res<Settings>,
+    corruption: Res<CorruptionMap>,
+    player_query: Query<&Transform, With<Player>>,
+    camera_query: Query<&Transform, (With<Camera>, Without<Player>)>,
+    mut cell_query: Query<(&TerrainCell, &mut BackgroundColor)>,
+) {
+    let player = player_query.get_single().expect("not one player");
+    let camera = camera_query.get_single().expect("not one camera");
+    let inverse = map_rotation(&settings, camera).inverse();
+    let radius = minimap.radius();
+    let cell = radius * 2.0 / CELLS as f32;
+
+    for (TerrainCell(x, y), mut background) in &mut cell_query {
+        // back from the middle of the cell on the map to the world
+        let on_map = Vec3::new(*x as f32 + 0.5, 0.0, *y as f32 + 0.5) * cell - Vec3::new(radius, 0.0, radius);
+        let pos = player.translation + inverse * on_map;
+        let withered = (corruption.get(pos) / WITHER_LEVEL).min(1.0);
+        *background = lerp_color(Color::rgb_u8(40, 90, 40), Color::rgb_u8(90, 60, 100), withered).into();
+    }
+}
+
+fn draw_markers(
+    minimap: Res<Minimap>,
+    settings: Res<Settings>,
+    director: Res<RaidDirector>,
+    player_query: Query<&Transform, With<Player>>,
+    camera_query: Query<&Transform, (With<Camera>, Without<Player>)>,
+    building_query: Query<(&Transform, &Building)>,
+    structure_query: Query<(&Transform, Has<Trader>), With<Discovered>>,
+    enemy_query: Query<&Transform, (With<Hostile>, With<Health>)>,
+    mut marker_query: Query<(&mut Style, &mut BackgroundColor, &mut Visibility), With<Marker>>,
+) {
+    let player = player_query.get_single().expect("not one player").translation;
+    let camera = camera_query.get_single().expect("not one camera");
+    let rotation = map_rotation(&settings, camera);
+    let radius = minimap.radius();
+
+    // whatever matters most comes last, and if some of them stuck to the
+    // edge then even when it's out of range
+    let mut markers: Vec<(Vec3, Color, bool)> = Vec::new();
+    markers.extend(building_query.iter().map(|(t, b)| (t.translation, b.kind.color(), false)));
+    markers.extend(structure_query.iter().map(|(t, trader)| {
+        (t.translation, if trader { Color::GOLD } else { Color::rgb_u8(140, 100, 50) }, false)
+    }));
+    markers.extend(enemy_query.iter().map(|t| (t.translation, Color::RED, false)));
+    if let Some(edge) = director.edge {
+        markers.push((edge.closest_to(player), Color::ORANGE, true));
+    }
+    markers.push((player, Color::WHITE, false));
+
+    let mut visible: Vec<(Vec2, Color)> = markers.into_iter()
+        .rev()
+        .filter_map(|(pos, color, pinned)| {
+            let at = to_map(pos, player, rotation, radius);
+            let clamped = at.clamp(Vec2::ZERO, Vec2::splat(MAP_SIZE));
+            (pinned || at == clamped).then_some((clamped, color))
+        })
+        .take(MAX_MARKERS)
+        .collect();
+    // later markers in the pool are drawn on top of earlier ones
+    visible.reverse();
+    let mut shown = visible.into_iter();
+
+    for (mut style, mut background, mut visibility) in &mut marker_query {
+        let Some((at, color)) = shown.next() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        style.left = Val::Px(at.x - MARKER_SIZE / 2.0);
+        style.top = Val::Px(at.y - MARKER_SIZE / 2.0);
+        *background = color.into();
+        *visibility = Visibility::Inherited;
+    }
+}
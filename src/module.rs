@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::{AimPoint, Building, BuildingGrid, BuildingKind, BuildingPlaced, GridPos},
+    inventory::Inventory,
+    item::{Item, ItemStack},
+    state::GameplaySet,
+    Player,
+};
+
+const SPEED_BONUS: f32 = 0.25;
+// speed modules make machines hungrier
+const SPEED_MANA_COST: f32 = 0.4;
+const EFFICIENCY_BONUS: f32 = 0.3;
+const RANGE_BONUS: f32 = 0.2;
+// efficiency can't take the mana cost below this
+const MIN_MANA_FACTOR: f32 = 0.2;
+// only this many of the same kind count towards the bonus
+const MAX_SAME_KIND: usize = 2;
+
+pub struct ModulePlugin;
+
+impl Plugin for ModulePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            init_module_slots,
+            module_input,
+            update_module_indicators,
+        ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModuleKind {
+    Speed,
+    Efficiency,
+    Range,
+}
+
+impl ModuleKind {
+    pub const ALL: [ModuleKind; 3] = [ModuleKind::Speed, ModuleKind::Efficiency, ModuleKind::Range];
+
+    pub fn item(&self) -> Item {
+        match self {
+            ModuleKind::Speed => Item::SpeedModule,
+            ModuleKind::Efficiency => Item::EfficiencyModule,
+            ModuleKind::Range => Item::RangeModule,
+        }
+    }
+}
+
+/// Combined multipliers of all modules in a machine.
+#[derive(Clone, Copy, Debug)]
+pub struct ModuleEffects {
+    pub speed: f32,
+    pub mana: f32,
+    pub range: f32,
+}
+
+impl Default for ModuleEffects {
+    fn default() -> Self {
+        ModuleEffects { speed: 1.0, mana: 1.0, range: 1.0 }
+    }
+}
+
+#[derive(Component)]
+pub struct ModuleSlots {
+    pub slots: Vec<Option<ModuleKind>>,
+}
+
+impl ModuleSlots {
+    pub fn new(size: usize) -> Self {
+        ModuleSlots { slots: vec![None; size] }
+    }
+
+    pub fn count(&self, kind: ModuleKind) -> usize {
+        self.slots.iter().filter(|s| **s == Some(kind)).count()
+    }
+
+    pub fn effects(&self) -> ModuleEffects {
+        // bonuses add up, but past a couple of the same kind they stop stacking
+        let speed = self.count(ModuleKind::Speed).min(MAX_SAME_KIND) as f32;
+        let efficiency = self.count(ModuleKind::Efficiency).min(MAX_SAME_KIND) as f32;
+        let range = self.count(ModuleKind::Range).min(MAX_SAME_KIND) as f32;
+
+        ModuleEffects {
+            speed: 1.0 + speed * SPEED_BONUS,
+            mana: (1.0 + speed * SPEED_MANA_COST - efficiency * EFFICIENCY_BONUS).max(MIN_MANA_FACTOR),
+            range: 1.0 + range * RANGE_BONUS,
+        }
+    }
+}
+
+/// Shortcut for systems that treat machines without slots as unmodified.
+pub fn effects(slots: Option<&ModuleSlots>) -> ModuleEffects {
+    slots.map_or(ModuleEffects::default(), |s| s.effects())
+}
+
+#[derive(Component)]
+struct ModuleIndicator;
+
+fn init_module_slots(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read() {
+        let size = match event.kind {
+            BuildingKind::Assembler
+            | BuildingKind::Lab
+            | BuildingKind::BeamTurret
+            | BuildingKind::BoltTurret => 2,
+            _ => continue,
+        };
+
+        commands.entity(event.entity).insert(ModuleSlots::new(size));
+    }
+}
+
+fn module_input(
+    keys: Res<Input<KeyCode>>,
+    aim: Res<AimPoint>,
+    grid: Res<BuildingGrid>,
+    mut slots_query: Query<&mut ModuleSlots>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+) {
+    let Some(mut slots) = aim.0
+        .and_then(|a| grid.get(GridPos::from_world(a)))
+        .and_then(|e| slots_query.get_mut(e).ok())
+    else {
+        return;
+    };
+
+    let mut inventory = player_query.get_single_mut().expect("not one player");
+
+    // insert the first module the player carries into a free slot
+    if keys.just_pressed(KeyCode::Insert) {
+        let module = ModuleKind::ALL.into_iter().find(|m| inventory.count(m.item()) > 0);
+        let free = slots.slots.iter().position(|s| s.is_none());
+
+        if let (Some(module), Some(free)) = (module, free) {
+            inventory.remove(module.item(), 1);
+            slots.slots[free] = Some(module);
+        }
+    }
+
+    // take the last module back out
+    if keys.just_pressed(KeyCode::Delete) {
+        let Some(last) = slots.slots.iter().rposition(|s| s.is_some()) else {
+            return;
+        };
+        let module = slots.slots[last].expect("slot is filled");
+
+        if inventory.can_insert(ItemStack::new(module.item(), 1)) {
+            inventory.insert(ItemStack::new(module.item(), 1));
+            slots.slots[last] = None;
+        }
+    }
+}
+
+fn update_module_indicators(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    slots_query: Query<(Entity, &ModuleSlots, &Building, Option<&Children>), Changed<ModuleSlots>>,
+    indicator_query: Query<(), With<ModuleIndicator>>,
+) {
+    // rebuild a row of small gems on top of the machine, one per module
+    for (entity, slots, building, children) in &slots_query {
+        for child in children.into_iter().flatten() {
+            if indicator_query.contains(*child) {
+                commands.entity(*child).despawn_recursive();
+            }
+        }
+
+        let top = building.kind.size().y / 2.0 + 0.06;
+        let spacing = 0.2;
+        let start = -(slots.slots.len() as f32 - 1.0) * spacing / 2.0;
+
+        for (i, slot) in slots.slots.iter().enumerate() {
+            let Some(module) = slot else {
+                continue;
+            };
+
+            let indicator = commands.spawn((
+                ModuleIndicator,
+                PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Cube { size: 0.12 })),
+                    material: materials.add(StandardMaterial {
+                        base_color: module.item().color(),
+                        emissive: module.item().color(),
+                        ..default()
+                    }),
+                    transform: Transform::from_xyz(start + i as f32 * spacing, top, 0.0),
+                    ..default()
+                },
+            )).id();
+
+            commands.entity(entity).add_child(indicator);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    building::{BuildingGrid, GridPos},
+    stagger::Staggered,
+    state::GameplaySet,
+    telekinesis::Held,
+};
+
+// tiles looked at before giving up and walking straight at the goal
+const MAX_SEARCH: usize = 4096;
+// close enough to a tile to head for the next one
+const WAYPOINT_REACHED: f32 = 0.3;
+// agents closer than this push each other apart
+const AVOID_RADIUS: f32 = 1.0;
+const AVOID_STRENGTH: f32 = 0.8;
+
+const NEIGHBOURS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+pub struct NavPlugin;
+
+impl Plugin for NavPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (invalidate_paths, plan_paths, follow_paths).chain().in_set(GameplaySet));
+    }
+}
+
+/// Walks towards `goal`, around buildings and water.
+#[derive(Component, Default)]
+pub struct NavAgent {
+    pub goal: Option<Vec3>,
+    pub speed: f32,
+    // tiles left to walk, the next one last
+    path: Vec<GridPos>,
+    // the tile the current path leads to
+    planned_for: Option<GridPos>,
+}
+
+impl NavAgent {
+    fn clear(&mut self) {
+        self.path.clear();
+        self.planned_for = None;
+    }
+}
+
+#[derive(PartialEq)]
+struct Node {
+    // cost so far plus the estimate to the goal
+    score: f32,
+    pos: IVec2,
+}
+
+impl Eq for Node {}
+
+impl Ord for Node {
+    // the lowest score comes out of the heap first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.score.total_cmp(&self.score)
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A* over the building grid. The goal itself may be blocked, e.g. a
+/// building that's about to be bashed. The path comes back goal first,
+/// without `from`.
+pub fn find_path(grid: &BuildingGrid, from: GridPos, to: GridPos) -> Option<Vec<GridPos>> {
+    let walkable = |pos: IVec2| pos == to.0 || grid.is_free(GridPos(pos));
+    let estimate = |pos: IVec2| pos.as_vec2().distance(to.0.as_vec2());
+
+    let mut open = BinaryHeap::from([Node { score: estimate(from.0), pos: from.0 }]);
+    let mut cost: HashMap<IVec2, f32> = HashMap::default();
+    let mut came_from: HashMap<IVec2, IVec2> = HashMap::default();
+    cost.insert(from.0, 0.0);
+
+    while let Some(Node { pos, .. }) = open.pop() {
+        if pos == to.0 {
+            let mut path = vec![to];
+            let mut step = pos;
+            while let Some(&previous) = came_from.get(&step) {
+                if previous != from.0 {
+                    path.push(GridPos(previous));
+                }
+                step = previous;
+            }
+            return Some(path);
+        }
+        if came_from.len() > MAX_SEARCH {
+            return None;
+        }
+
+        for delta in NEIGHBOURS {
+            let next = pos + delta;
+            // no squeezing diagonally between two blocked tiles
+            let cuts_corner = delta.x != 0 && delta.y != 0
+                && (!walkable(pos + IVec2::new(delta.x, 0)) || !walkable(pos + IVec2::new(0, delta.y)));
+            if !walkable(next) || cuts_corner {
+                continue;
+            }
+
+            let next_cost = cost[&pos] + delta.as_vec2().length();
+            if cost.get(&next).is_some_and(|c| *c <= next_cost) {
+                continue;
+            }
+            cost.insert(next, next_cost);
+            came_from.insert(next, pos);
+            open.push(Node { score: next_cost + estimate(next), pos: next });
+        }
+    }
+    None
+}
+
+// something got built in the way
+fn invalidate_paths(grid: Res<BuildingGrid>, mut agent_query: Query<&mut NavAgent>) {
+    if !grid.is_changed() {
+        return;
+    }
+
+    for mut agent in &mut agent_query {
+        let blocked = agent.path.iter().any(|p| Some(*p) != agent.planned_for && !grid.is_free(*p));
+        if blocked {
+            agent.clear();
+        }
+    }
+}
+
+fn plan_paths(grid: Res<BuildingGrid>, mut agent_query: Query<(&mut NavAgent, &Transform)>) {
+    for (mut agent, transform) in &mut agent_query {
+        let Some(goal) = agent.goal.map(GridPos::from_world) else {
+            agent.clear();
+            continue;
+        };
+        if agent.planned_for == Some(goal) {
+            continue;
+        }
+
+        // no path just means walking straight at it
+        agent.path = find_path(&grid, GridPos::from_world(transform.translation), goal).unwrap_or_default();
+        agent.planned_for = Some(goal);
+    }
+}
+
+// held and staggered agents get flung around, so hands off their velocity
+fn follow_paths(
+    mut agent_query: Query<(Entity, &mut NavAgent, &Transform, &mut LinearVelocity), (Without<Held>, Without<Staggered>)>,
+) {
+    let positions: Vec<_> = agent_query.iter().map(|(e, _, t, _)| (e, t.translation)).collect();
+
+    for (entity, mut agent, transform, mut velocity) in &mut agent_query {
+        let pos = transform.translation;
+
+        while agent.path.last().is_some_and(|p| p.to_world().xz().distance(pos.xz()) <= WAYPOINT_REACHED) {
+            agent.path.pop();
+        }
+        // the last stretch goes to the exact spot
+        let waypoint = agent.path.last().map(|p| p.to_world()).or(agent.goal);
+
+        let mut direction = waypoint.map_or(Vec3::ZERO, |w| w - pos);
+        direction.y = 0.0;
+        let mut direction = direction.normalize_or_zero();
+
+        // keep out of each other's way
+        if direction != Vec3::ZERO {
+            for (other, other_pos) in &positions {
+                let mut away = pos - *other_pos;
+                away.y = 0.0;
+                let distance = away.length();
+                if *other != entity && distance < AVOID_RADIUS {
+                    direction += away.normalize_or_zero() * AVOID_STRENGTH * (1.0 - distance / AVOID_RADIUS);
+                }
+            }
+            direction = direction.normalize_or_zero();
+        }
+
+        velocity.x = direction.x * agent.speed;
+        velocity.z = direction.z * agent.speed;
+    }
+}
@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    character::{Stat, Stats},
+    dilation::TimeDilation,
+    health::{Damage, DamageEvent, DamageType},
+    magic::{SpellBook, SpellCast},
+    state::GameplaySet,
+    status::{ApplyStatus, Status, StatusKind},
+    Player,
+};
+
+// flux a caster can hold before casting gets risky
+const BASE_THRESHOLD: f32 = 60.0;
+// per second
+const FLUX_DECAY: f32 = 12.0;
+// fraction of the spell's cost that hits the caster as arc damage
+const BACKLASH_DAMAGE: f32 = 0.5;
+const SCATTER_DISTANCE: f32 = 4.0;
+
+pub struct OverloadPlugin;
+
+impl Plugin for OverloadPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<Backlash>()
+            .add_systems(Update, (build_flux, decay_flux).chain().in_set(GameplaySet));
+    }
+}
+
+/// What a backfiring spell does on top of hurting its caster.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WildMagic {
+    // half the mana goes up in smoke
+    ManaBurn,
+    // the spell curls back into a hex
+    Misfire,
+    // sometimes it's a lucky one
+    Surge,
+    // throws the caster somewhere close by
+    Scatter,
+}
+
+impl WildMagic {
+    pub const ALL: [WildMagic; 4] = [WildMagic::ManaBurn, WildMagic::Misfire, WildMagic::Surge, WildMagic::Scatter];
+
+    // what the player is told happened
+    pub fn message(&self) -> &'static str {
+        match self {
+            WildMagic::ManaBurn => "The spell backfired and burned your mana",
+            WildMagic::Misfire => "The spell backfired and hexed you",
+            WildMagic::Surge => "The spell backfired, but you feel quicker",
+            WildMagic::Scatter => "The spell backfired and threw you aside",
+        }
+    }
+}
+
+/// Recently cast mana that hasn't settled yet. Too much of it and spells
+/// start to backfire.
+#[derive(Component, Default)]
+pub struct Flux {
+    pub value: f32,
+}
+
+impl Flux {
+    pub fn threshold(stats: &Stats) -> f32 {
+        BASE_THRESHOLD + stats.modifier(Stat::Stability)
+    }
+
+    /// The chance that the next cast backfires.
+    pub fn risk(&self, threshold: f32) -> f32 {
+        ((self.value - threshold) / threshold).clamp(0.0, 0.9)
+    }
+}
+
+#[derive(Event)]
+pub struct Backlash {
+    pub caster: Entity,
+    pub effect: WildMagic,
+}
+
+fn build_flux(
+    book: Res<SpellBook>,
+    mut caster_query: Query<(&mut Flux, &mut Stats, &mut Transform)>,
+    mut cast_events: EventReader<SpellCast>,
+    mut status_events: EventWriter<ApplyStatus>,
+    mut backlash_events: EventWriter<Backlash>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for event in cast_events.read() {
+        let (Some(spell), Ok((mut flux, mut stats, mut transform))) =
+            (book.get(event.spell), caster_query.get_mut(event.caster))
+        else {
+            continue;
+        };
+
+        // the risk comes from what was built up before this cast
+        let cost = stats.spell_cost(spell.cost);
+        let risk = flux.risk(Flux::threshold(&stats));
+        flux.value += cost;
+        if !rng.gen_bool(risk as f64) {
+            continue;
+        }
+
+        damage_events.send(DamageEvent::new(
+            event.caster,
+            Damage::new(cost * BACKLASH_DAMAGE, DamageType::Arc),
+            Some(event.caster),
+        ));
+
+        let effect = WildMagic::ALL[rng.gen_range(0..WildMagic::ALL.len())];
+        match effect {
+            WildMagic::ManaBurn => {
+                let burned = stats.mana * 0.5;
+                stats.spend_mana(burned);
+            }
+            WildMagic::Misfire => {
+                status_events.send(ApplyStatus {
+                    target: event.caster,
+                    status: Status::new(StatusKind::Hexed, 6.0, 3.0),
+                });
+            }
+            WildMagic::Surge => {
+                status_events.send(ApplyStatus {
+                    target: event.caster,
+                    status: Status::new(StatusKind::Haste, 5.0, 0.4),
+                });
+            }
+            WildMagic::Scatter => {
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                transform.translation += Vec3::new(angle.cos(), 0.0, angle.sin()) * SCATTER_DISTANCE;
+            }
+        }
+        backlash_events.send(Backlash { caster: event.caster, effect });
+    }
+}
+
+fn decay_flux(
+    time: Res<Time>,
+    dilation: Res<TimeDilation>,
+    mut flux_query: Query<(&mut Flux, Has<Player>)>,
+) {
+    for (mut flux, is_player) in &mut flux_query {
+        // settles at the same pace the player casts at
+        let dt = if is_player { dilation.unscaled(&time) } else { time.delta_seconds() };
+        flux.value = (flux.value - FLUX_DECAY * dt).max(0.0);
+    }
+}
@@ -0,0 +1,260 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    ai::{Archetypes, Brain, Prey},
+    building::Building,
+    controls::Footstep,
+    faction::{Factions, Member, Stance},
+    health::DamageDealt,
+    layer::Layer,
+    machine::Machine,
+    projectile::ProjectileImpact,
+    state::GameplaySet,
+    threat::ThreatTable,
+    Player,
+};
+
+// awareness gained per second with the prey in plain sight up close
+const SIGHT_GAIN: f32 = 2.0;
+// awareness lost per second with nothing to go on
+const AWARENESS_DECAY: f32 = 0.15;
+// what a noise right next to the listener is worth
+const NOISE_GAIN: f32 = 0.5;
+const SUSPICIOUS: f32 = 0.3;
+const ALERT: f32 = 1.0;
+// seconds between noises
+const MACHINE_NOISE_INTERVAL: f32 = 2.0;
+// how far each kind of noise carries
+const FOOTSTEP_RADIUS: f32 = 4.0;
+const MACHINE_NOISE_RADIUS: f32 = 6.0;
+const EXPLOSION_RADIUS: f32 = 15.0;
+const EYE_HEIGHT: f32 = 0.4;
+
+pub struct PerceptionPlugin;
+
+impl Plugin for PerceptionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<Noise>()
+            .init_resource::<NoiseTimers>()
+            .add_systems(Update, (
+                emit_footsteps,
+                emit_machine_noise,
+                emit_explosions,
+                see,
+                hear,
+                notice_attackers,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Alertness {
+    Unaware,
+    // heard or glimpsed something, goes to have a look
+    Suspicious,
+    Alert,
+}
+
+#[derive(Component, Default)]
+pub struct Awareness {
+    // zero hasn't noticed a thing, one knows exactly what's going on
+    pub level: f32,
+    // the prey while it's in sight
+    pub target: Option<Entity>,
+    // where something was last seen or heard
+    pub last_known: Option<Vec3>,
+    // which way it's looking, the way it last walked
+    facing: Vec3,
+}
+
+impl Awareness {
+    pub fn alertness(&self) -> Alertness {
+        if self.level >= ALERT {
+            Alertness::Alert
+        } else if self.level >= SUSPICIOUS {
+            Alertness::Suspicious
+        } else {
+            Alertness::Unaware
+        }
+    }
+
+    pub fn notice(&mut self, amount: f32, position: Vec3) {
+        self.level = (self.level + amount).min(ALERT);
+        self.last_known = Some(position);
+    }
+}
+
+/// Something loud enough for enemies within `radius` to hear.
+#[derive(Event)]
+pub struct Noise {
+    pub position: Vec3,
+    pub radius: f32,
+}
+
+#[derive(Resource, Default)]
+struct NoiseTimers {
+    machines: f32,
+}
+
+fn emit_footsteps(
+    mut footstep_events: EventReader<Footstep>,
+    mut noise_events: EventWriter<Noise>,
+) {
+    for event in footstep_events.read() {
+        noise_events.send(Noise { position: event.at, radius: FOOTSTEP_RADIUS });
+    }
+}
+
+fn emit_machine_noise(
+    time: Res<Time>,
+    mut timers: ResMut<NoiseTimers>,
+    machine_query: Query<(&Machine, &Transform)>,
+    mut noise_events: EventWriter<Noise>,
+) {
+    timers.machines -= time.delta_seconds();
+    if timers.machines > 0.0 {
+        return;
+    }
+    timers.machines = MACHINE_NOISE_INTERVAL;
+
+    for (machine, transform) in &machine_query {
+        if machine.working {
+            noise_events.send(Noise { position: transform.translation, radius: MACHINE_NOISE_RADIUS });
+        }
+    }
+}
+
+fn emit_explosions(
+    mut impact_events: EventReader<ProjectileImpact>,
+    mut noise_events: EventWriter<Noise>,
+) {
+    for event in impact_events.read() {
+        if event.kind.splash() > 0.0 {
+            noise_events.send(Noise { position: event.position, radius: EXPLOSION_RADIUS });
+        }
+    }
+}
+
+fn see(
+    time: Res<Time>,
+    archetypes: Res<Archetypes>,
+    factions: Res<Factions>,
+    spatial_query: SpatialQuery,
+    mut watcher_query: Query<(
+        Entity,
+        &Brain,
+        &mut Awareness,
+        &Transform,
+        &LinearVelocity,
+        Option<&ThreatTable>,
+        Option<&Member>,
+    )>,
+    building_query: Query<(Entity, &Transform), With<Building>>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    threat_query: Query<(Entity, &Transform)>,
+) {
+    let player = player_query.get_single().expect("not one player");
+    let dt = time.delta_seconds();
+
+    for (entity, brain, mut awareness, transform, velocity, threat, member) in &mut watcher_query {
+        let behavior = archetypes.get(brain.archetype);
+        let eye = transform.translation + Vec3::Y * EYE_HEIGHT;
+        if velocity.xz().length() > 0.1 {
+            awareness.facing = Vec3::new(velocity.x, 0.0, velocity.z).normalize();
+        }
+
+        // whoever made it angriest comes before its usual prey
+        let threat = threat.and_then(|t| threat_query.get(t.top()?).ok());
+        let candidate = threat.or_else(|| match behavior.prey {
+            Prey::Buildings => building_query.iter()
+                .min_by(|a, b| a.1.translation.distance(eye).total_cmp(&b.1.translation.distance(eye))),
+            // factions that have warmed to the player leave them be unless provoked
+            Prey::Player => member.is_none_or(|m| factions.attitude(m.0) == Stance::Hostile).then_some(player),
+        });
+
+        let seen = candidate.and_then(|(target, target_transform)| {
+            let to_target = target_transform.translation - eye;
+            let distance = to_target.length();
+            let direction = to_target.normalize_or_zero();
+            let in_cone = awareness.facing == Vec3::ZERO
+                || awareness.facing.angle_between(Vec3::new(direction.x, 0.0, direction.z))
+                    <= behavior.fov.to_radians() / 2.0;
+            if distance > behavior.sight_range || !in_cone {
+                return None;
+            }
+
+            // whatever the ray hits first is what's actually in view, for
+            // constructs any building will do
+            let hit = spatial_query.cast_ray(
+                eye,
+                direction,
+                distance,
+                true,
+                // hurtboxes don't get in the way of anything
+                SpatialQueryFilter::new()
+                    .without_entities([entity])
+                    .with_masks([Layer::World, Layer::Enemy, Layer::Barrier]),
+            );
+            match hit {
+                None => Some((target, target_transform.translation, distance)),
+                Some(hit) if hit.entity == target => Some((target, target_transform.translation, distance)),
+                Some(hit) if behavior.prey == Prey::Buildings => building_query.get(hit.entity)
+                    .ok()
+                    .map(|(e, t)| (e, t.translation, hit.time_of_impact)),
+                Some(_) => None,
+            }
+        });
+
+        match seen {
+            Some((target, position, distance)) => {
+                // far away things take a while to make out
+                let gain = SIGHT_GAIN * (1.0 - 0.5 * distance / behavior.sight_range) * dt;
+                awareness.notice(gain, position);
+                if awareness.alertness() == Alertness::Alert {
+                    awareness.target = Some(target);
+                }
+            }
+            None => {
+                awareness.target = None;
+                awareness.level = (awareness.level - AWARENESS_DECAY * dt).max(0.0);
+                if awareness.alertness() == Alertness::Unaware {
+                    awareness.last_known = None;
+                }
+            }
+        }
+    }
+}
+
+fn hear(
+    archetypes: Res<Archetypes>,
+    mut listener_query: Query<(&Brain, &mut Awareness, &Transform)>,
+    mut noise_events: EventReader<Noise>,
+) {
+    for event in noise_events.read() {
+        for (brain, mut awareness, transform) in &mut listener_query {
+            let radius = event.radius * archetypes.get(brain.archetype).hearing;
+            let distance = transform.translation.distance(event.position);
+            if distance < radius {
+                awareness.notice(NOISE_GAIN * (1.0 - distance / radius), event.position);
+            }
+        }
+    }
+}
+
+// getting hurt gets anyone's attention
+fn notice_attackers(
+    mut awareness_query: Query<&mut Awareness>,
+    source_query: Query<&Transform>,
+    mut dealt_events: EventReader<DamageDealt>,
+) {
+    for event in dealt_events.read() {
+        let (Ok(mut awareness), Some(Ok(source))) =
+            (awareness_query.get_mut(event.target), event.source.map(|s| source_query.get(s)))
+        else {
+            continue;
+        };
+        awareness.notice(ALERT, source.translation);
+    }
+}
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{character::Stats, state::GameplaySet, Player};
+
+const MAX_SHARDS: usize = 5;
+// seconds between new shards showing up
+const SHARD_INTERVAL: f32 = 30.0;
+// shards appear within this distance of the origin
+const SHARD_EXTENT: f32 = 20.0;
+const SHARD_MANA: f32 = 25.0;
+const PICKUP_RADIUS: f32 = 1.0;
+const SHARD_HEIGHT: f32 = 0.6;
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(ShardTimer(Timer::from_seconds(SHARD_INTERVAL, TimerMode::Repeating)))
+            .add_systems(Startup, setup_shard_assets)
+            .add_systems(Update, (spawn_shards, collect_shards, bob_shards).chain().in_set(GameplaySet));
+    }
+}
+
+/// A bit of raw mana lying around that refills the player on touch.
+#[derive(Component)]
+pub struct ManaShard {
+    pub mana: f32,
+}
+
+#[derive(Resource)]
+struct ShardTimer(Timer);
+
+#[derive(Resource)]
+struct ShardAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn setup_shard_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(ShardAssets {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 0.25 })),
+        material: materials.add(StandardMaterial {
+            base_color: Color::CYAN,
+            emissive: Color::CYAN,
+            ..default()
+        }),
+    });
+}
+
+fn spawn_shards(
+    mut commands: Commands,
+    time: Res<Time>,
+    assets: Res<ShardAssets>,
+    mut timer: ResMut<ShardTimer>,
+    shard_query: Query<(), With<ManaShard>>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() || shard_query.iter().count() >= MAX_SHARDS {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    let pos = Vec3::new(
+        rng.gen_range(-SHARD_EXTENT..SHARD_EXTENT),
+        SHARD_HEIGHT,
+        rng.gen_range(-SHARD_EXTENT..SHARD_EXTENT),
+    );
+
+    commands.spawn((
+        ManaShard { mana: SHARD_MANA },
+        PbrBundle {
+            mesh: assets.mesh.clone(),
+            material: assets.material.clone(),
+            transform: Transform::from_translation(pos),
+            ..default()
+        },
+    ));
+}
+
+fn collect_shards(
+    mut commands: Commands,
+    shard_query: Query<(Entity, &ManaShard, &Transform)>,
+    mut player_query: Query<(&mut Stats, &Transform), With<Player>>,
+) {
+    let (mut stats, player) = player_query.get_single_mut().expect("not one player");
+
+    for (entity, shard, transform) in &shard_query {
+        // leave it lying around for later if mana is already full
+        let close = transform.translation.xz().distance(player.translation.xz()) <= PICKUP_RADIUS;
+        if close && stats.mana < stats.max_mana {
+            stats.restore_mana(shard.mana);
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn bob_shards(
+    time: Res<Time>,
+    mut shard_query: Query<&mut Transform, With<ManaShard>>,
+) {
+    for mut transform in &mut shard_query {
+        transform.rotate_y(time.delta_seconds());
+        transform.translation.y = SHARD_HEIGHT + 0.1 * (time.elapsed_seconds() * 2.0 + transform.translation.x).sin();
+    }
+}
@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::{BuildingKind, BuildingPlaced},
+    daynight::TimeOfDay,
+    environment::Wind,
+    locale::Locale,
+    state::GameplaySet,
+};
+
+pub struct PowerPlugin;
+
+impl Plugin for PowerPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<ManaGrid>()
+            .add_systems(Startup, setup_grid_ui)
+            .add_systems(Update, (
+                init_power_buildings,
+                update_solar_output,
+                update_wind_output,
+                balance_grid,
+                update_grid_ui,
+                draw_battery_charge,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+// all numbers are mana per second unless noted otherwise
+
+#[derive(Component)]
+pub struct ManaProducer {
+    pub output: f32,
+}
+
+#[derive(Component)]
+pub struct ManaConsumer {
+    pub demand: f32,
+}
+
+#[derive(Component)]
+pub struct ManaBattery {
+    // in mana
+    pub charge: f32,
+    pub capacity: f32,
+    pub max_rate: f32,
+}
+
+impl ManaBattery {
+    pub fn new(capacity: f32, max_rate: f32) -> Self {
+        ManaBattery { charge: 0.0, capacity, max_rate }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.charge / self.capacity
+    }
+}
+
+// output follows the sun, see `TimeOfDay::sunlight`
+#[derive(Component)]
+pub struct SolarCollector {
+    pub peak_output: f32,
+}
+
+// output follows the wind, see `Wind::strength`
+#[derive(Component)]
+pub struct WindTurbine {
+    pub peak_output: f32,
+}
+
+// every building is connected to one shared grid for now
+#[derive(Resource, Default)]
+pub struct ManaGrid {
+    pub production: f32,
+    pub demand: f32,
+    // positive while charging, negative while discharging
+    pub battery_flow: f32,
+    pub stored: f32,
+    pub capacity: f32,
+    // how much of the demand could be met, 0.0 to 1.0
+    pub satisfaction: f32,
+}
+
+fn init_power_buildings(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read() {
+        match event.kind {
+            BuildingKind::ManaWell => {
+                commands.entity(event.entity).insert(ManaProducer { output: 10.0 });
+            }
+            BuildingKind::ManaBattery => {
+                commands.entity(event.entity).insert(ManaBattery::new(500.0, 20.0));
+            }
+            BuildingKind::SolarCollector => {
+                commands.entity(event.entity).insert((
+                    SolarCollector { peak_output: 6.0 },
+                    ManaProducer { output: 0.0 },
+                ));
+            }
+            // the river never stops, so wheels are small but steady
+            BuildingKind::WaterWheel => {
+                commands.entity(event.entity).insert(ManaProducer { output: 3.0 });
+            }
+            BuildingKind::WindTurbine => {
+                commands.entity(event.entity).insert((
+                    WindTurbine { peak_output: 8.0 },
+                    ManaProducer { output: 0.0 },
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update_solar_output(
+    time_of_day: Res<TimeOfDay>,
+    mut solar_query: Query<(&SolarCollector, &mut ManaProducer)>,
+) {
+    for (collector, mut producer) in &mut solar_query {
+        producer.output = collector.peak_output * time_of_day.sunlight();
+    }
+}
+
+fn update_wind_output(
+    wind: Res<Wind>,
+    mut turbine_query: Query<(&WindTurbine, &mut ManaProducer)>,
+) {
+    for (turbine, mut producer) in &mut turbine_query {
+        producer.output = turbine.peak_output * wind.strength;
+    }
+}
+
+fn balance_grid(
+    time: Res<Time>,
+    mut grid: ResMut<ManaGrid>,
+    producer_query: Query<&ManaProducer>,
+    consumer_query: Query<&ManaConsumer>,
+    mut battery_query: Query<&mut ManaBattery>,
+) {
+    let dt = time.delta_seconds();
+    let production: f32 = producer_query.iter().map(|p| p.output).sum();
+    let demand: f32 = consumer_query.iter().map(|c| c.demand).sum();
+
+    // surplus charges the batteries, a deficit drains them
+    let mut balance = production - demand;
+    let mut flow = 0.0;
+
+    if dt > 0.0 {
+        for mut battery in &mut battery_query {
+            let rate = if balance > 0.0 {
+                let room = (battery.capacity - battery.charge) / dt;
+                balance.min(battery.max_rate).min(room)
+            } else {
+                let available = battery.charge / dt;
+                balance.max(-battery.max_rate).max(-available)
+            };
+
+            battery.charge = (battery.charge + rate * dt).clamp(0.0, battery.capacity);
+            balance -= rate;
+            flow += rate;
+        }
+    }
+
+    grid.production = production;
+    grid.demand = demand;
+    grid.battery_flow = flow;
+    grid.satisfaction = if demand > 0.0 {
+        ((production - flow) / demand).clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+
+    grid.stored = battery_query.iter().map(|b| b.charge).sum();
+    grid.capacity = battery_query.iter().map(|b| b.capacity).sum();
+}
+
+fn draw_battery_charge(
+    battery_query: Query<(&ManaBattery, &Transform)>,
+    mut gizmos: Gizmos,
+) {
+    // a bar above each battery that fills up as it charges
+    for (battery, transform) in &battery_query {
+        let start = transform.translation + Vec3::new(-0.4, 0.8, 0.0);
+        let end = start + Vec3::X * 0.8 * battery.fraction();
+        gizmos.line(start, start + Vec3::X * 0.8, Color::DARK_GRAY);
+        gizmos.line(start, end, Color::CYAN);
+    }
+}
+
+#[derive(Component)]
+struct GridText;
+
+fn setup_grid_ui(mut commands: Commands) {
+    commands.spawn((
+        GridText,
+        TextBundle::from_section("", TextStyle {
+            font_size: 16.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            bottom: Val::Px(20.0),
+            ..default()
+        }),
+    ));
+}
+
+fn update_grid_ui(
+    locale: Res<Locale>,
+    grid: Res<ManaGrid>,
+    wind: Res<Wind>,
+    mut text_query: Query<&mut Text, With<GridText>>,
+) {
+    let mut text = text_query.get_single_mut().expect("not one grid text");
+
+    let whole = |x: f32| format!("{:.0}", x);
+    let mana = locale.fill("Mana {}/s produced, {}/s used ({}%)", &[
+        &whole(grid.production),
+        &whole(grid.demand),
+        &whole(grid.satisfaction * 100.0),
+    ]);
+    let batteries = locale.fill("Batteries {}/{} ({}/s) | wind {}%", &[
+        &whole(grid.stored),
+        &whole(grid.capacity),
+        &format!("{:+.0}", grid.battery_flow),
+        &whole(wind.strength * 100.0),
+    ]);
+    text.sections[0].value = format!("{}\n{}", mana, batteries);
+}
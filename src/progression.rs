@@ -0,0 +1,215 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    locale::Locale,
+    magic::{SpellBook, SpellCast, SpellEffect, SpellId},
+    state::GameplaySet,
+    Player,
+};
+
+// experience, i.e. mana spent on the spell, for the first level, every
+// level after that takes this much more again
+const XP_PER_LEVEL: f32 = 60.0;
+
+pub struct ProgressionPlugin;
+
+impl Plugin for ProgressionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(UpgradeTracks::starter())
+            .add_event::<SpellLevelUp>()
+            .add_systems(Update, (gain_experience, apply_upgrades).chain().in_set(GameplaySet));
+    }
+}
+
+/// One step along an upgrade track, kept as plain data like the spells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Upgrade {
+    // fraction taken off the mana cost, channels included
+    Cost(f32),
+    // fraction taken off the cooldown
+    Cooldown(f32),
+    // fraction added to the damage
+    Damage(f32),
+    // fraction added to the area of novas, barriers and circles
+    Radius(f32),
+    // fraction added to the reach of bolts and beams
+    Range(f32),
+    ExtraProjectiles(u32),
+}
+
+impl Upgrade {
+    pub fn description(&self, locale: &Locale) -> String {
+        let percent = |x: f32| format!("{:.0}", x * 100.0);
+        match *self {
+            Upgrade::Cost(x) => locale.fill("{}% cheaper", &[&percent(x)]),
+            Upgrade::Cooldown(x) => locale.fill("{}% shorter cooldown", &[&percent(x)]),
+            Upgrade::Damage(x) => locale.fill("{}% more damage", &[&percent(x)]),
+            Upgrade::Radius(x) => locale.fill("{}% bigger radius", &[&percent(x)]),
+            Upgrade::Range(x) => locale.fill("{}% longer range", &[&percent(x)]),
+            Upgrade::ExtraProjectiles(n) => locale.fill("{} more projectiles", &[&n]),
+        }
+    }
+
+    fn apply(&self, book: &mut SpellBook, id: SpellId) {
+        let Some(spell) = book.get_mut(id) else {
+            return;
+        };
+
+        match *self {
+            Upgrade::Cost(x) => {
+                spell.cost *= 1.0 - x;
+                if let Some(channel) = spell.channel.as_mut() {
+                    channel.mana_per_second *= 1.0 - x;
+                }
+            }
+            Upgrade::Cooldown(x) => spell.cooldown *= 1.0 - x,
+            Upgrade::Damage(x) => match &mut spell.effect {
+                SpellEffect::Bolt { damage, .. }
+                | SpellEffect::Nova { damage, .. }
+                | SpellEffect::Beam { damage, .. }
+                | SpellEffect::Summon { damage, .. }
+                | SpellEffect::Telekinesis { damage, .. } => damage.amount *= 1.0 + x,
+                _ => {}
+            },
+            Upgrade::Radius(x) => match &mut spell.effect {
+                SpellEffect::Nova { radius, .. }
+                | SpellEffect::Barrier { radius, .. }
+                | SpellEffect::Inscribe { radius }
+                | SpellEffect::Dispel { radius } => *radius *= 1.0 + x,
+                _ => {}
+            },
+            Upgrade::Range(x) => match &mut spell.effect {
+                SpellEffect::Bolt { range, .. }
+                | SpellEffect::Beam { range, .. }
+                | SpellEffect::Telekinesis { range, .. } => *range *= 1.0 + x,
+                SpellEffect::Blink { distance, .. } => *distance *= 1.0 + x,
+                _ => {}
+            },
+            Upgrade::ExtraProjectiles(n) => {
+                if let SpellEffect::Projectile { count, .. } = &mut spell.effect {
+                    *count += n;
+                }
+            }
+        }
+    }
+}
+
+/// What each spell gets better at, level by level. Spells without a track
+/// don't level up.
+#[derive(Resource, Default)]
+pub struct UpgradeTracks {
+    tracks: HashMap<SpellId, Vec<Upgrade>>,
+}
+
+impl UpgradeTracks {
+    fn starter() -> Self {
+        let mut tracks = UpgradeTracks::default();
+        // arcane bolt
+        tracks.add(SpellId(0), &[Upgrade::Cost(0.2), Upgrade::Damage(0.25), Upgrade::Range(0.3)]);
+        // nova
+        tracks.add(SpellId(1), &[Upgrade::Radius(0.25), Upgrade::Cost(0.2), Upgrade::Radius(0.25)]);
+        // fireball
+        tracks.add(SpellId(2), &[Upgrade::Damage(0.2), Upgrade::ExtraProjectiles(1), Upgrade::Cost(0.2)]);
+        // mana bolt
+        tracks.add(SpellId(3), &[Upgrade::ExtraProjectiles(1), Upgrade::Cost(0.25), Upgrade::ExtraProjectiles(1)]);
+        // arcane beam
+        tracks.add(SpellId(4), &[Upgrade::Cost(0.25), Upgrade::Range(0.3), Upgrade::Damage(0.25)]);
+        // barrier
+        tracks.add(SpellId(7), &[Upgrade::Radius(0.3), Upgrade::Cooldown(0.25)]);
+        // blink
+        tracks.add(SpellId(8), &[Upgrade::Cooldown(0.25), Upgrade::Range(0.25)]);
+        // dispel
+        tracks.add(SpellId(13), &[Upgrade::Radius(0.25), Upgrade::Cooldown(0.3)]);
+        tracks
+    }
+
+    pub fn add(&mut self, spell: SpellId, track: &[Upgrade]) {
+        self.tracks.insert(spell, track.to_vec());
+    }
+
+    pub fn get(&self, spell: SpellId) -> &[Upgrade] {
+        self.tracks.get(&spell).map_or(&[], |t| t.as_slice())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Mastery {
+    pub level: u32,
+    // towards the next level
+    pub xp: f32,
+    // how many levels worth of upgrades are in the spell book
+    applied: u32,
+}
+
+impl Mastery {
+    pub fn xp_needed(&self) -> f32 {
+        XP_PER_LEVEL * (self.level + 1) as f32
+    }
+}
+
+/// How far the caster got with each spell.
+#[derive(Component, Clone, Default)]
+pub struct SpellProgress {
+    spells: HashMap<SpellId, Mastery>,
+}
+
+impl SpellProgress {
+    pub fn get(&self, spell: SpellId) -> Mastery {
+        self.spells.get(&spell).copied().unwrap_or_default()
+    }
+}
+
+#[derive(Event)]
+pub struct SpellLevelUp {
+    pub caster: Entity,
+    pub spell: SpellId,
+    pub level: u32,
+}
+
+fn gain_experience(
+    book: Res<SpellBook>,
+    tracks: Res<UpgradeTracks>,
+    mut progress_query: Query<&mut SpellProgress>,
+    mut cast_events: EventReader<SpellCast>,
+    mut level_events: EventWriter<SpellLevelUp>,
+) {
+    for event in cast_events.read() {
+        let (Some(spell), Ok(mut progress)) = (book.get(event.spell), progress_query.get_mut(event.caster)) else {
+            continue;
+        };
+        let max_level = tracks.get(event.spell).len() as u32;
+        let mastery = progress.spells.entry(event.spell).or_default();
+        if mastery.level >= max_level {
+            continue;
+        }
+
+        // upgrades make spells cheaper, so count at least a little for each cast
+        mastery.xp += spell.cost.max(1.0);
+        if mastery.xp < mastery.xp_needed() {
+            continue;
+        }
+
+        mastery.xp -= mastery.xp_needed();
+        mastery.level += 1;
+        level_events.send(SpellLevelUp { caster: event.caster, spell: event.spell, level: mastery.level });
+    }
+}
+
+// the spell book is shared, so only the player's progress changes it
+fn apply_upgrades(
+    mut book: ResMut<SpellBook>,
+    tracks: Res<UpgradeTracks>,
+    mut progress_query: Query<&mut SpellProgress, (With<Player>, Changed<SpellProgress>)>,
+) {
+    let Ok(mut progress) = progress_query.get_single_mut() else {
+        return;
+    };
+
+    for (id, mastery) in &mut progress.spells {
+        for upgrade in tracks.get(*id).iter().take(mastery.level as usize).skip(mastery.applied as usize) {
+            upgrade.apply(&mut book, *id);
+        }
+        mastery.applied = mastery.level;
+    }
+}
@@ -0,0 +1,346 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    barrier::Barrier,
+    enemy::Hostile,
+    health::{Damage, DamageEvent, DamageType, Health},
+    hitbox::{resolve_hit, Hurtbox},
+    layer::Layer,
+    stagger::Knockback,
+    state::GameplaySet,
+};
+
+// seconds before a projectile that hit nothing fizzles out
+const LIFETIME: f32 = 3.0;
+// seconds an impact stays visible
+const IMPACT_TIME: f32 = 0.3;
+
+pub struct ProjectilePlugin;
+
+impl Plugin for ProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<LaunchProjectile>()
+            .add_event::<ProjectileImpact>()
+            .add_event::<ProjectileBlocked>()
+            .add_systems(Startup, setup_projectile_assets)
+            .add_systems(Update, (
+                launch_projectiles,
+                expire_projectiles,
+                detect_hits,
+                apply_impacts,
+                draw_impacts,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ProjectileKind {
+    Fireball,
+    ManaBolt,
+    // what slingers throw
+    Shard,
+}
+
+impl ProjectileKind {
+    pub const ALL: [ProjectileKind; 3] = [ProjectileKind::Fireball, ProjectileKind::ManaBolt, ProjectileKind::Shard];
+
+    pub fn speed(self) -> f32 {
+        match self {
+            ProjectileKind::Fireball => 14.0,
+            ProjectileKind::ManaBolt => 25.0,
+            ProjectileKind::Shard => 16.0,
+        }
+    }
+
+    pub fn damage(self) -> Damage {
+        match self {
+            ProjectileKind::Fireball => Damage::new(25.0, DamageType::Fire),
+            ProjectileKind::ManaBolt => Damage::new(12.0, DamageType::Arc),
+            ProjectileKind::Shard => Damage::new(10.0, DamageType::Physical),
+        }
+    }
+
+    /// Everything on the other side this close to the impact gets the full
+    /// damage, zero means only whatever was hit directly.
+    pub fn splash(self) -> f32 {
+        match self {
+            ProjectileKind::Fireball => 2.5,
+            ProjectileKind::ManaBolt | ProjectileKind::Shard => 0.0,
+        }
+    }
+
+    // how hard it shoves enemies it hits or splashes
+    pub fn knockback(self) -> f32 {
+        match self {
+            ProjectileKind::Fireball => 0.6,
+            ProjectileKind::ManaBolt | ProjectileKind::Shard => 0.0,
+        }
+    }
+
+    // fireballs are heavy enough to drop a little
+    pub fn gravity(self) -> f32 {
+        match self {
+            ProjectileKind::Fireball => 0.3,
+            ProjectileKind::ManaBolt | ProjectileKind::Shard => 0.0,
+        }
+    }
+
+    pub fn radius(self) -> f32 {
+        match self {
+            ProjectileKind::Fireball => 0.3,
+            ProjectileKind::ManaBolt => 0.15,
+            ProjectileKind::Shard => 0.12,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            ProjectileKind::Fireball => Color::rgb(1.0, 0.45, 0.1),
+            ProjectileKind::ManaBolt => Color::rgb(0.4, 0.5, 1.0),
+            ProjectileKind::Shard => Color::rgb(0.6, 0.3, 0.7),
+        }
+    }
+}
+
+/// Which way to fire from `origin` so a projectile flying at `speed` meets
+/// a target moving at `velocity`. Aims straight at it if it can't be caught.
+pub fn lead(origin: Vec3, target: Vec3, velocity: Vec3, speed: f32) -> Vec3 {
+    let offset = target - origin;
+    // solves |offset + velocity * t| = speed * t for the earliest t
+    let a = velocity.length_squared() - speed * speed;
+    let b = 2.0 * offset.dot(velocity);
+    let c = offset.length_squared();
+    let discriminant = b * b - 4.0 * a * c;
+
+    let time = if a.abs() < f32::EPSILON {
+        -c / b
+    } else if discriminant >= 0.0 {
+        let root = discriminant.sqrt();
+        [(-b - root) / (2.0 * a), (-b + root) / (2.0 * a)]
+            .into_iter()
+            .filter(|t| *t > 0.0)
+            .fold(f32::NAN, f32::min)
+    } else {
+        f32::NAN
+    };
+
+    if time.is_finite() && time > 0.0 {
+        (offset + velocity * time).normalize_or_zero()
+    } else {
+        offset.normalize_or_zero()
+    }
+}
+
+#[derive(Event)]
+pub struct LaunchProjectile {
+    pub owner: Entity,
+    pub kind: ProjectileKind,
+    pub origin: Vec3,
+    pub direction: Vec3,
+}
+
+/// Sent when a projectile hits something, `target` is `None` if it was
+/// just the ground, a wall or something on its own side.
+#[derive(Event)]
+pub struct ProjectileImpact {
+    pub owner: Entity,
+    pub kind: ProjectileKind,
+    pub position: Vec3,
+    pub target: Option<Entity>,
+    // how much more the target takes for where it got hit
+    pub multiplier: f32,
+    // fired by an enemy, so it hurts everyone else
+    pub hostile: bool,
+}
+
+/// Sent instead of an impact when a barrier caught the projectile.
+#[derive(Event)]
+pub struct ProjectileBlocked {
+    pub kind: ProjectileKind,
+    pub barrier: Entity,
+}
+
+#[derive(Component)]
+pub struct Projectile {
+    pub owner: Entity,
+    pub kind: ProjectileKind,
+    pub hostile: bool,
+    ttl: f32,
+}
+
+#[derive(Component)]
+struct Impact {
+    kind: ProjectileKind,
+    ttl: f32,
+}
+
+#[derive(Resource)]
+struct ProjectileAssets {
+    meshes: HashMap<ProjectileKind, Handle<Mesh>>,
+    materials: HashMap<ProjectileKind, Handle<StandardMaterial>>,
+}
+
+fn setup_projectile_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(ProjectileAssets {
+        meshes: ProjectileKind::ALL.iter()
+            .map(|kind| (*kind, meshes.add(Mesh::from(shape::UVSphere { radius: kind.radius(), ..default() }))))
+            .collect(),
+        materials: ProjectileKind::ALL.iter()
+            .map(|kind| (*kind, materials.add(StandardMaterial {
+                base_color: kind.color(),
+                emissive: kind.color(),
+                ..default()
+            })))
+            .collect(),
+    });
+}
+
+fn launch_projectiles(
+    mut commands: Commands,
+    assets: Res<ProjectileAssets>,
+    hostile_query: Query<(), With<Hostile>>,
+    mut launch_events: EventReader<LaunchProjectile>,
+) {
+    for event in launch_events.read() {
+        let kind = event.kind;
+        // the collision layers already keep each side from shooting itself
+        let hostile = hostile_query.contains(event.owner);
+        let layers = if hostile { Layer::enemy_projectile() } else { Layer::player_projectile() };
+
+        commands.spawn((
+            Projectile { owner: event.owner, kind, hostile, ttl: LIFETIME },
+            PbrBundle {
+                mesh: assets.meshes[&kind].clone(),
+                material: assets.materials[&kind].clone(),
+                transform: Transform::from_translation(event.origin),
+                ..default()
+            },
+            RigidBody::Dynamic,
+            Collider::ball(kind.radius()),
+            // only used to find out what it flies into
+            Sensor,
+            layers,
+            GravityScale(kind.gravity()),
+            LinearVelocity(event.direction.normalize_or_zero() * kind.speed()),
+        ));
+    }
+}
+
+fn expire_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut projectile_query: Query<(Entity, &mut Projectile)>,
+) {
+    for (entity, mut projectile) in &mut projectile_query {
+        projectile.ttl -= time.delta_seconds();
+        if projectile.ttl <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn detect_hits(
+    mut commands: Commands,
+    projectile_query: Query<(&Projectile, &Transform)>,
+    target_query: Query<Has<Hostile>, With<Health>>,
+    hurtbox_query: Query<&Hurtbox>,
+    barrier_query: Query<(), With<Barrier>>,
+    mut collision_events: EventReader<CollisionStarted>,
+    mut impact_events: EventWriter<ProjectileImpact>,
+    mut blocked_events: EventWriter<ProjectileBlocked>,
+) {
+    let mut hit = Vec::new();
+
+    for CollisionStarted(a, b) in collision_events.read() {
+        for (entity, other) in [(*a, *b), (*b, *a)] {
+            let Ok((projectile, transform)) = projectile_query.get(entity) else {
+                continue;
+            };
+            // projectiles pass through their caster and each other
+            if other == projectile.owner || projectile_query.contains(other) {
+                continue;
+            }
+            // a projectile can touch several things in the same frame
+            if hit.contains(&entity) {
+                continue;
+            }
+
+            hit.push(entity);
+            if barrier_query.contains(other) {
+                blocked_events.send(ProjectileBlocked { kind: projectile.kind, barrier: other });
+            } else {
+                let (target, multiplier) = resolve_hit(&hurtbox_query, other);
+                impact_events.send(ProjectileImpact {
+                    owner: projectile.owner,
+                    kind: projectile.kind,
+                    position: transform.translation,
+                    target: target_query.get(target).is_ok_and(|h| h != projectile.hostile).then_some(target),
+                    multiplier,
+                    hostile: projectile.hostile,
+                });
+            }
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn apply_impacts(
+    mut commands: Commands,
+    target_query: Query<(Entity, &Transform, Has<Hostile>), With<Health>>,
+    mut impact_events: EventReader<ProjectileImpact>,
+    mut damage_events: EventWriter<DamageEvent>,
+    mut knockback_events: EventWriter<Knockback>,
+) {
+    for event in impact_events.read() {
+        let kind = event.kind;
+
+        for (entity, transform, is_hostile) in &target_query {
+            let direct = event.target == Some(entity);
+            let splashed = transform.translation.distance(event.position) <= kind.splash();
+            // no friendly fire, not even from the splash
+            if (direct || splashed) && is_hostile != event.hostile {
+                let damage = kind.damage();
+                let multiplier = if direct { event.multiplier } else { 1.0 };
+                let damage = Damage::new(damage.amount * multiplier, damage.kind);
+                damage_events.send(DamageEvent::new(entity, damage, Some(event.owner)));
+                if kind.knockback() > 0.0 {
+                    knockback_events.send(Knockback {
+                        target: entity,
+                        direction: transform.translation - event.position,
+                        force: kind.knockback(),
+                    });
+                }
+            }
+        }
+
+        commands.spawn((
+            Impact { kind, ttl: IMPACT_TIME },
+            TransformBundle::from_transform(Transform::from_translation(event.position)),
+        ));
+    }
+}
+
+fn draw_impacts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut impact_query: Query<(Entity, &mut Impact, &Transform)>,
+    mut gizmos: Gizmos,
+) {
+    for (entity, mut impact, transform) in &mut impact_query {
+        // a ring that grows out to the splash radius
+        let progress = 1.0 - impact.ttl / IMPACT_TIME;
+        let radius = impact.kind.splash().max(0.5) * progress;
+        gizmos.circle(transform.translation, Vec3::Y, radius, impact.kind.color());
+
+        impact.ttl -= time.delta_seconds();
+        if impact.ttl <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,415 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    building::{BuildingKind, BuildingPlaced},
+    camera::{CursorUser, CursorUsers},
+    enemy::Hostile,
+    health::Died,
+    input::{Action, InputMap},
+    item::Item,
+    locale::{Locale, Localized},
+    loot::Cache,
+    research::TechnologyResearched,
+    state::GameplaySet,
+    stats::ItemProduced,
+    toast::{Notify, Priority},
+    Player,
+};
+
+const ROW_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.0);
+const TRACKED_COLOR: Color = Color::rgba(0.3, 0.3, 0.1, 0.8);
+const COMPLETED_TEXT: Color = Color::GRAY;
+
+pub struct QuestPlugin;
+
+impl Plugin for QuestPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<QuestLog>()
+            .add_systems(Startup, setup_journal)
+            .add_systems(Update, (
+                track_progress,
+                place_marker,
+                toggle_journal,
+                journal_input,
+                update_journal,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Quest {
+    Foothold,
+    Smelting,
+    TreasureHunt,
+    Defender,
+    Scholar,
+    Distillation,
+}
+
+impl Quest {
+    pub const ALL: [Quest; 6] = [
+        Quest::Foothold,
+        Quest::Smelting,
+        Quest::TreasureHunt,
+        Quest::Defender,
+        Quest::Scholar,
+        Quest::Distillation,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Quest::Foothold => "Foothold",
+            Quest::Smelting => "Smelting",
+            Quest::TreasureHunt => "Treasure Hunt",
+            Quest::Defender => "Defender",
+            Quest::Scholar => "Scholar",
+            Quest::Distillation => "Distillation",
+        }
+    }
+
+    pub fn objectives(&self) -> &'static [Objective] {
+        match self {
+            Quest::Foothold => &[
+                Objective::Build(BuildingKind::Chest, 1),
+                Objective::Build(BuildingKind::ManaWell, 1),
+            ],
+            Quest::Smelting => &[
+                Objective::Produce(Item::IronPlate, 20),
+                Objective::Produce(Item::Gear, 10),
+            ],
+            Quest::TreasureHunt => &[Objective::OpenCaches(3)],
+            Quest::Defender => &[Objective::Kill(10)],
+            Quest::Scholar => &[
+                Objective::Build(BuildingKind::Lab, 1),
+                Objective::Research(2),
+            ],
+            Quest::Distillation => &[Objective::Produce(Item::Essence, 5)],
+        }
+    }
+
+    // the quest that has to be done before this one shows up
+    pub fn requires(&self) -> Option<Quest> {
+        match self {
+            Quest::Smelting => Some(Quest::Foothold),
+            Quest::Scholar => Some(Quest::Smelting),
+            Quest::Distillation => Some(Quest::Scholar),
+            _ => None,
+        }
+    }
+
+    // handed out in conversation rather than showing up on their own
+    pub fn offered(&self) -> bool {
+        matches!(self, Quest::TreasureHunt | Quest::Defender)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+    Build(BuildingKind, u32),
+    Produce(Item, u32),
+    OpenCaches(u32),
+    Kill(u32),
+    Research(u32),
+}
+
+impl Objective {
+    pub fn goal(&self) -> u32 {
+        match self {
+            Objective::Build(_, n)
+            | Objective::Produce(_, n)
+            | Objective::OpenCaches(n)
+            | Objective::Kill(n)
+            | Objective::Research(n) => *n,
+        }
+    }
+
+    pub fn describe(&self, locale: &Locale) -> String {
+        match self {
+            Objective::Build(kind, _) => locale.fill("Build a {}", &[&locale.get(kind.name())]),
+            Objective::Produce(item, _) => locale.fill("Produce {}", &[&locale.get(item.name())]),
+            Objective::OpenCaches(_) => locale.get("Open caches").to_string(),
+            Objective::Kill(_) => locale.get("Defeat enemies").to_string(),
+            Objective::Research(_) => locale.get("Research technologies").to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuestState {
+    Locked,
+    Active,
+    Completed,
+}
+
+#[derive(Resource)]
+pub struct QuestLog {
+    // per objective, only for quests that got anywhere
+    progress: HashMap<Quest, Vec<u32>>,
+    completed: Vec<Quest>,
+    // offered quests the player took on
+    started: Vec<Quest>,
+    pub tracked: Option<Quest>,
+    /// Where the tracked quest wants the player to go, if anywhere.
+    pub marker: Option<Vec3>,
+    pub open: bool,
+}
+
+impl Default for QuestLog {
+    fn default() -> Self {
+        QuestLog {
+            progress: HashMap::default(),
+            completed: Vec::new(),
+            started: Vec::new(),
+            tracked: Some(Quest::Foothold),
+            marker: None,
+            open: false,
+        }
+    }
+}
+
+impl QuestLog {
+    pub fn state(&self, quest: Quest) -> QuestState {
+        if self.completed.contains(&quest) {
+            QuestState::Completed
+        } else if quest.offered() && !self.started.contains(&quest) {
+            QuestState::Locked
+        } else if quest.requires().is_none_or(|q| self.completed.contains(&q)) {
+            QuestState::Active
+        } else {
+            QuestState::Locked
+        }
+    }
+
+    /// Takes on an offered quest, tracking it if nothing else is.
+    pub fn start(&mut self, quest: Quest) {
+        if !self.started.contains(&quest) {
+            self.started.push(quest);
+        }
+        if self.tracked.is_none() {
+            self.tracked = Some(quest);
+        }
+    }
+
+    pub fn progress(&self, quest: Quest, objective: usize) -> u32 {
+        self.progress.get(&quest).map_or(0, |p| p[objective])
+    }
+
+    // the first objective of the quest that isn't done yet
+    pub fn current_objective(&self, quest: Quest) -> Option<Objective> {
+        quest.objectives().iter().enumerate()
+            .find(|(i, o)| self.progress(quest, *i) < o.goal())
+            .map(|(_, o)| *o)
+    }
+
+    /// Counts `amount` towards every active objective `applies` accepts.
+    fn advance(&mut self, amount: u32, applies: impl Fn(&Objective) -> bool) {
+        let active: Vec<Quest> = Quest::ALL.into_iter()
+            .filter(|q| self.state(*q) == QuestState::Active)
+            .collect();
+
+        for quest in active {
+            let objectives = quest.objectives();
+            let progress = self.progress.entry(quest).or_insert_with(|| vec![0; objectives.len()]);
+
+            for (count, objective) in progress.iter_mut().zip(objectives).filter(|(_, o)| applies(o)) {
+                *count = (*count + amount).min(objective.goal());
+            }
+
+            if progress.iter().zip(objectives).all(|(count, o)| *count >= o.goal()) {
+                self.completed.push(quest);
+            }
+        }
+
+        // keep something tracked once the tracked quest is done
+        if self.tracked.is_none_or(|q| self.state(q) == QuestState::Completed) {
+            self.tracked = Quest::ALL.into_iter().find(|q| self.state(*q) == QuestState::Active);
+        }
+    }
+}
+
+#[derive(Component)]
+struct Journal;
+
+#[derive(Component)]
+struct QuestRow(Quest);
+
+fn track_progress(
+    mut log: ResMut<QuestLog>,
+    locale: Res<Locale>,
+    hostile_query: Query<(), With<Hostile>>,
+    player_query: Query<(), With<Player>>,
+    mut placed_events: EventReader<BuildingPlaced>,
+    mut produced_events: EventReader<ItemProduced>,
+    mut died_events: EventReader<Died>,
+    mut researched_events: EventReader<TechnologyResearched>,
+    mut opened_caches: RemovedComponents<Cache>,
+    mut notify_events: EventWriter<Notify>,
+) {
+    let completed = log.completed.len();
+
+    for event in placed_events.read() {
+        log.advance(1, |o| matches!(o, Objective::Build(kind, _) if *kind == event.kind));
+    }
+    for event in produced_events.read() {
+        log.advance(event.count, |o| matches!(o, Objective::Produce(item, _) if *item == event.item));
+    }
+    for event in died_events.read() {
+        if hostile_query.contains(event.entity) && event.killer.is_some_and(|k| player_query.contains(k)) {
+            log.advance(1, |o| matches!(o, Objective::Kill(_)));
+        }
+    }
+    for _ in researched_events.read() {
+        log.advance(1, |o| matches!(o, Objective::Research(_)));
+    }
+    // caches only ever go away by being opened
+    for _ in opened_caches.read() {
+        log.advance(1, |o| matches!(o, Objective::OpenCaches(_)));
+    }
+
+    for quest in &log.completed[completed..] {
+        let message = locale.fill("Quest complete: {}", &[&locale.get(quest.name())]);
+        notify_events.send(Notify::new(message, Priority::Normal));
+    }
+}
+
+fn place_marker(
+    mut log: ResMut<QuestLog>,
+    player_query: Query<&Transform, With<Player>>,
+    cache_query: Query<&Transform, With<Cache>>,
+    hostile_query: Query<&Transform, With<Hostile>>,
+) {
+    let player = player_query.get_single().expect("not one player").translation;
+    let nearest = |positions: Vec<Vec3>| positions.into_iter()
+        .min_by(|a, b| a.distance(player).total_cmp(&b.distance(player)));
+
+    let marker = match log.tracked.and_then(|q| log.current_objective(q)) {
+        Some(Objective::OpenCaches(_)) => nearest(cache_query.iter().map(|t| t.translation).collect()),
+        Some(Objective::Kill(_)) => nearest(hostile_query.iter().map(|t| t.translation).collect()),
+        // nowhere in particular to go for the rest
+        _ => None,
+    };
+    log.marker = marker;
+}
+
+fn setup_journal(mut commands: Commands) {
+    commands.spawn((
+        Journal,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(420.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+            ..default()
+        }).with_children(|list| {
+            list.spawn((
+                Localized("Journal"),
+                TextBundle::from_section("Journal", TextStyle {
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                    ..default()
+                }),
+            ));
+            for quest in Quest::ALL {
+                list.spawn((
+                    QuestRow(quest),
+                    ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(6.0)),
+                            ..default()
+                        },
+                        background_color: ROW_COLOR.into(),
+                        ..default()
+                    },
+                )).with_children(|row| {
+                    row.spawn(TextBundle::from_sections([
+                        TextSection::new("", TextStyle { font_size: 18.0, color: Color::WHITE, ..default() }),
+                        TextSection::new("", TextStyle { font_size: 14.0, color: Color::WHITE, ..default() }),
+                    ]));
+                });
+            }
+        });
+    });
+}
+
+fn toggle_journal(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut log: ResMut<QuestLog>,
+    mut cursor_users: ResMut<CursorUsers>,
+) {
+    if !input_map.just_pressed(Action::Journal, &keys) {
+        return;
+    }
+
+    log.open = !log.open;
+    cursor_users.set(CursorUser::Journal, log.open);
+}
+
+// clicking an active quest tracks it, clicking it again stops tracking
+fn journal_input(
+    mut log: ResMut<QuestLog>,
+    row_query: Query<(&QuestRow, &Interaction), Changed<Interaction>>,
+) {
+    if !log.open {
+        return;
+    }
+
+    for (QuestRow(quest), interaction) in &row_query {
+        if *interaction != Interaction::Pressed || log.state(*quest) != QuestState::Active {
+            continue;
+        }
+        log.tracked = if log.tracked == Some(*quest) { None } else { Some(*quest) };
+    }
+}
+
+fn update_journal(
+    log: Res<QuestLog>,
+    locale: Res<Locale>,
+    mut journal_query: Query<&mut Visibility, With<Journal>>,
+    mut row_query: Query<(&QuestRow, &mut Style, &mut BackgroundColor, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    let mut visibility = journal_query.get_single_mut().expect("not one journal");
+    *visibility = if log.open { Visibility::Visible } else { Visibility::Hidden };
+    if !log.open {
+        return;
+    }
+
+    for (QuestRow(quest), mut style, mut background, children) in &mut row_query {
+        let state = log.state(*quest);
+        style.display = if state == QuestState::Locked { Display::None } else { Display::Flex };
+        *background = if log.tracked == Some(*quest) { TRACKED_COLOR } else { ROW_COLOR }.into();
+
+        let Some(mut text) = children.first().and_then(|c| text_query.get_mut(*c).ok()) else {
+            continue;
+        };
+        let color = if state == QuestState::Completed { COMPLETED_TEXT } else { Color::WHITE };
+        let tracked = if log.tracked == Some(*quest) { format!(" ({})", locale.get("tracked")) } else { String::new() };
+        text.sections[0].value = format!("{}{}\n", locale.get(quest.name()), tracked);
+        text.sections[0].style.color = color;
+
+        text.sections[1].value = quest.objectives().iter().enumerate()
+            .map(|(i, o)| format!("  {} {}/{}", o.describe(&locale), log.progress(*quest, i), o.goal()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        text.sections[1].style.color = color;
+    }
+}
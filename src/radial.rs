@@ -0,0 +1,261 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    building::{BuildMode, BuildingKind},
+    camera::{CursorUser, CursorUsers},
+    dilation::TimeDilation,
+    hotbar::{Hotbar, HotbarEntry},
+    input::{Action, InputMap},
+    locale::Locale,
+    magic::{SpellBook, SpellId},
+    research::Research,
+    state::GameplaySet,
+    Player,
+};
+
+// pixels from the middle of the screen to the middle of each entry
+const RADIUS: f32 = 160.0;
+// closer in than this nothing is picked, so letting go there cancels
+const DEAD_ZONE: f32 = 40.0;
+const STICK_DEAD_ZONE: f32 = 0.3;
+const MAX_ENTRIES: usize = 12;
+const ENTRY_SIZE: Vec2 = Vec2::new(120.0, 36.0);
+// the world keeps going, just slower, while choosing
+const TIME_FACTOR: f32 = 0.3;
+const ENTRY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.7);
+const HOVERED_COLOR: Color = Color::rgba(0.9, 0.8, 0.3, 0.9);
+
+pub struct RadialPlugin;
+
+impl Plugin for RadialPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RadialMenu>()
+            .add_systems(Startup, setup_radial)
+            .add_systems(Update, (
+                open_radial,
+                point_radial,
+                confirm_radial,
+                update_radial_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Choice {
+    Spell(SpellId),
+    Building(usize),
+}
+
+/// Held open to pick a spell for the selected hotbar slot, or what to build
+/// while in build mode.
+#[derive(Resource, Default)]
+pub struct RadialMenu {
+    pub open: bool,
+    choices: Vec<Choice>,
+    hovered: Option<usize>,
+}
+
+#[derive(Component)]
+struct RadialUi;
+
+#[derive(Component)]
+struct RadialEntry(usize);
+
+// clockwise from the top, the first entry straight up
+fn entry_offset(i: usize, count: usize) -> Vec2 {
+    let angle = i as f32 / count as f32 * TAU;
+    Vec2::new(angle.sin(), -angle.cos()) * RADIUS
+}
+
+fn setup_radial(mut commands: Commands) {
+    commands.spawn((
+        RadialUi,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(5),
+            ..default()
+        },
+    )).with_children(|parent| {
+        for i in 0..MAX_ENTRIES {
+            parent.spawn((
+                RadialEntry(i),
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent(50.0),
+                        top: Val::Percent(50.0),
+                        width: Val::Px(ENTRY_SIZE.x),
+                        height: Val::Px(ENTRY_SIZE.y),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    background_color: ENTRY_COLOR.into(),
+                    ..default()
+                },
+            )).with_children(|entry| {
+                entry.spawn(TextBundle::from_section("", TextStyle {
+                    font_size: 16.0,
+                    color: Color::WHITE,
+                    ..default()
+                }));
+            });
+        }
+    });
+}
+
+fn open_radial(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    book: Res<SpellBook>,
+    research: Res<Research>,
+    build_mode: Res<BuildMode>,
+    mut menu: ResMut<RadialMenu>,
+    mut dilation: ResMut<TimeDilation>,
+    mut cursor_users: ResMut<CursorUsers>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if menu.open || !input_map.just_pressed(Action::QuickSelect, &keys) {
+        return;
+    }
+
+    menu.choices = if build_mode.active {
+        BuildingKind::ALL.iter().enumerate()
+            .filter(|(_, kind)| research.building_unlocked(**kind))
+            .map(|(i, _)| Choice::Building(i))
+            .take(MAX_ENTRIES)
+            .collect()
+    } else {
+        book.ids().map(Choice::Spell).take(MAX_ENTRIES).collect()
+    };
+    if menu.choices.is_empty() {
+        return;
+    }
+
+    menu.open = true;
+    menu.hovered = None;
+    dilation.menu_factor = Some(TIME_FACTOR);
+
+    // the cursor starts in the middle, where nothing is picked
+    cursor_users.set(CursorUser::Radial, true);
+    let mut window = window_query.get_single_mut().expect("not one window");
+    let center = Vec2::new(window.width(), window.height()) / 2.0;
+    window.set_cursor_position(Some(center));
+}
+
+fn point_radial(
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut menu: ResMut<RadialMenu>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    if !menu.open {
+        return;
+    }
+
+    let window = window_query.get_single().expect("not one window");
+    let center = Vec2::new(window.width(), window.height()) / 2.0;
+    let mouse = window.cursor_position().map(|c| c - center).filter(|p| p.length() >= DEAD_ZONE);
+
+    // a stick pushed past its dead zone wins over wherever the mouse is
+    let stick = gamepads.iter()
+        .map(|gamepad| Vec2::new(
+            axes.get(GamepadAxis::new(gamepad, GamepadAxisType::RightStickX)).unwrap_or(0.0),
+            // stick y points up, screen y down
+            -axes.get(GamepadAxis::new(gamepad, GamepadAxisType::RightStickY)).unwrap_or(0.0),
+        ))
+        .find(|s| s.length() >= STICK_DEAD_ZONE);
+
+    let Some(pointer) = stick.or(mouse) else {
+        menu.hovered = None;
+        return;
+    };
+
+    // the entry whose wedge the pointer is in, wedges centered on their entries
+    let count = menu.choices.len();
+    let angle = pointer.x.atan2(-pointer.y).rem_euclid(TAU);
+    let wedge = TAU / count as f32;
+    menu.hovered = Some(((angle + wedge / 2.0) / wedge) as usize % count);
+}
+
+fn confirm_radial(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut menu: ResMut<RadialMenu>,
+    mut build_mode: ResMut<BuildMode>,
+    mut dilation: ResMut<TimeDilation>,
+    mut hotbar_query: Query<&mut Hotbar, With<Player>>,
+    mut cursor_users: ResMut<CursorUsers>,
+) {
+    if !menu.open || !input_map.just_released(Action::QuickSelect, &keys) {
+        return;
+    }
+
+    match menu.hovered.map(|i| menu.choices[i]) {
+        Some(Choice::Spell(spell)) => {
+            let mut hotbar = hotbar_query.get_single_mut().expect("not one player");
+            let slot = hotbar.selected;
+            hotbar.equip(slot, HotbarEntry::Spell(spell));
+        }
+        Some(Choice::Building(i)) => build_mode.selected = i,
+        // let go in the middle, nothing changes
+        None => {}
+    }
+
+    menu.open = false;
+    dilation.menu_factor = None;
+    cursor_users.set(CursorUser::Radial, false);
+}
+
+fn update_radial_ui(
+    menu: Res<RadialMenu>,
+    book: Res<SpellBook>,
+    locale: Res<Locale>,
+    mut ui_query: Query<&mut Visibility, With<RadialUi>>,
+    mut entry_query: Query<(&RadialEntry, &mut Style, &mut BackgroundColor, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    let mut visibility = ui_query.get_single_mut().expect("not one radial menu");
+    *visibility = if menu.open { Visibility::Visible } else { Visibility::Hidden };
+    if !menu.open {
+        return;
+    }
+
+    let count = menu.choices.len();
+    for (RadialEntry(i), mut style, mut background, children) in &mut entry_query {
+        let Some(choice) = menu.choices.get(*i) else {
+            style.display = Display::None;
+            continue;
+        };
+        style.display = Display::Flex;
+
+        // left and top sit on the middle of the screen, the margin moves it out from there
+        let offset = entry_offset(*i, count) - ENTRY_SIZE / 2.0;
+        style.margin = UiRect {
+            left: Val::Px(offset.x),
+            top: Val::Px(offset.y),
+            ..default()
+        };
+        *background = if menu.hovered == Some(*i) { HOVERED_COLOR } else { ENTRY_COLOR }.into();
+
+        let name = match choice {
+            Choice::Spell(spell) => book.get(*spell).map_or("", |s| s.name.as_str()),
+            Choice::Building(kind) => locale.get(BuildingKind::ALL[*kind].name()),
+        };
+        if let Some(mut text) = children.first().and_then(|c| text_query.get_mut(*c).ok()) {
+            text.sections[0].value = name.to_string();
+        }
+    }
+}
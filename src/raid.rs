@@ -0,0 +1,215 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    building::Building,
+    corruption::CorruptionMap,
+    enemy::{spawn_construct, spawn_slinger, WORLD_EXTENT},
+    locale::Locale,
+    perception::Awareness,
+    research::Research,
+    state::GameplaySet,
+};
+
+// seconds between raids
+const RAID_INTERVAL: f32 = 300.0;
+// seconds of warning before the raid arrives
+const WARNING_TIME: f32 = 30.0;
+const BASE_RAIDERS: u32 = 3;
+// every this much corruption across the map brings another raider
+const CORRUPTION_PER_RAIDER: f32 = 200.0;
+// and so does every few technologies
+const TECHNOLOGIES_PER_RAIDER: u32 = 2;
+// every third raider stays back and shoots
+const SLINGER_EVERY: u32 = 3;
+// how far apart along the edge the raiders show up
+const SPREAD: f32 = 2.0;
+
+pub struct RaidPlugin;
+
+impl Plugin for RaidPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RaidDirector>()
+            .add_event::<RaidWarning>()
+            .add_event::<RaidStarted>()
+            .add_systems(Startup, setup_raid_ui)
+            .add_systems(Update, (direct_raids, update_raid_ui).chain().in_set(GameplaySet));
+    }
+}
+
+/// The side of the map a raid comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edge {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Edge {
+    pub const ALL: [Edge; 4] = [Edge::North, Edge::East, Edge::South, Edge::West];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Edge::North => "north",
+            Edge::East => "east",
+            Edge::South => "south",
+            Edge::West => "west",
+        }
+    }
+
+    // `along` runs from one corner of the edge to the other
+    fn point(&self, along: f32) -> Vec3 {
+        let along = along.clamp(-WORLD_EXTENT, WORLD_EXTENT);
+        match self {
+            Edge::North => Vec3::new(along, 0.5, -WORLD_EXTENT),
+            Edge::East => Vec3::new(WORLD_EXTENT, 0.5, along),
+            Edge::South => Vec3::new(along, 0.5, WORLD_EXTENT),
+            Edge::West => Vec3::new(-WORLD_EXTENT, 0.5, along),
+        }
+    }
+
+    /// The spot on this edge closest to `pos`.
+    pub fn closest_to(&self, pos: Vec3) -> Vec3 {
+        match self {
+            Edge::North | Edge::South => self.point(pos.x),
+            Edge::East | Edge::West => self.point(pos.z),
+        }
+    }
+}
+
+/// Sends waves at the factory, bigger the dirtier and more advanced it gets.
+#[derive(Resource)]
+pub struct RaidDirector {
+    // seconds until the next raid
+    pub countdown: f32,
+    // raids so far
+    pub wave: u32,
+    // picked once the warning goes out
+    pub edge: Option<Edge>,
+}
+
+impl Default for RaidDirector {
+    fn default() -> Self {
+        RaidDirector { countdown: RAID_INTERVAL, wave: 0, edge: None }
+    }
+}
+
+impl RaidDirector {
+    pub fn raid_size(&self, corruption: &CorruptionMap, research: &Research) -> u32 {
+        BASE_RAIDERS
+            + self.wave
+            + (corruption.total() / CORRUPTION_PER_RAIDER) as u32
+            + research.unlocked.len() as u32 / TECHNOLOGIES_PER_RAIDER
+    }
+}
+
+#[derive(Event)]
+pub struct RaidWarning {
+    pub edge: Edge,
+    pub size: u32,
+}
+
+#[derive(Event)]
+pub struct RaidStarted {
+    pub edge: Edge,
+    pub raiders: Vec<Entity>,
+}
+
+#[derive(Component)]
+struct RaidText;
+
+fn direct_raids(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    corruption: Res<CorruptionMap>,
+    research: Res<Research>,
+    mut director: ResMut<RaidDirector>,
+    building_query: Query<&Transform, With<Building>>,
+    mut warning_events: EventWriter<RaidWarning>,
+    mut started_events: EventWriter<RaidStarted>,
+) {
+    // nothing to raid yet
+    if building_query.is_empty() {
+        return;
+    }
+
+    let mut rng = rand::thread_rng();
+    director.countdown -= time.delta_seconds();
+
+    if director.edge.is_none() && director.countdown <= WARNING_TIME {
+        let edge = Edge::ALL[rng.gen_range(0..Edge::ALL.len())];
+        director.edge = Some(edge);
+        warning_events.send(RaidWarning { edge, size: director.raid_size(&corruption, &research) });
+    }
+    if director.countdown > 0.0 {
+        return;
+    }
+
+    let edge = director.edge.take().expect("raid without a warning");
+    let size = director.raid_size(&corruption, &research);
+    let center = rng.gen_range(-WORLD_EXTENT..WORLD_EXTENT);
+
+    let mut raiders = Vec::new();
+    for i in 0..size {
+        let pos = edge.point(center + rng.gen_range(-SPREAD..SPREAD));
+        let raider = if (i + 1) % SLINGER_EVERY == 0 {
+            spawn_slinger(&mut commands, &mut meshes, &mut materials, pos)
+        } else {
+            spawn_construct(&mut commands, &mut meshes, &mut materials, pos)
+        };
+
+        // raiders know where the factory is, even the ones after the player
+        let factory = building_query.iter()
+            .map(|t| t.translation)
+            .min_by(|a, b| a.distance(pos).total_cmp(&b.distance(pos)))
+            .expect("raid without buildings");
+        let mut awareness = Awareness::default();
+        awareness.notice(1.0, factory);
+        commands.entity(raider).insert(awareness);
+        raiders.push(raider);
+    }
+
+    director.wave += 1;
+    director.countdown = RAID_INTERVAL;
+    started_events.send(RaidStarted { edge, raiders });
+}
+
+fn setup_raid_ui(mut commands: Commands) {
+    commands.spawn((
+        RaidText,
+        TextBundle::from_section("", TextStyle {
+            font_size: 18.0,
+            color: Color::RED,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(40.0),
+            top: Val::Px(48.0),
+            ..default()
+        }),
+    ));
+}
+
+fn update_raid_ui(
+    locale: Res<Locale>,
+    director: Res<RaidDirector>,
+    corruption: Res<CorruptionMap>,
+    research: Res<Research>,
+    mut text_query: Query<&mut Text, With<RaidText>>,
+) {
+    let mut text = text_query.get_single_mut().expect("not one raid text");
+
+    text.sections[0].value = match director.edge {
+        Some(edge) => locale.fill("Raid of {} incoming from the {} in {}s", &[
+            &director.raid_size(&corruption, &research),
+            &locale.get(edge.name()),
+            &format!("{:.0}", director.countdown.max(0.0)),
+        ]),
+        None => String::new(),
+    };
+}
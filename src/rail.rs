@@ -0,0 +1,458 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::{HashMap, HashSet}};
+
+use crate::{
+    building::{AimPoint, BuildingGrid, BuildingKind, BuildingPlaced, Facing, GridPos},
+    circuit::CircuitCondition,
+    inventory::Inventory,
+    item::ItemStack,
+    power::{ManaConsumer, ManaGrid},
+    state::GameplaySet,
+};
+
+// tiles per second at full power
+pub const TRAIN_SPEED: f32 = 4.0;
+pub const WAGON_SLOTS: usize = 8;
+// mana per second while moving
+const TRAIN_DEMAND: f32 = 5.0;
+// how long a train placed with shift waits at each station
+const TIMED_STOP: f32 = 5.0;
+// seconds without any cargo moving before a train gives up on filling or emptying
+const STATION_TIMEOUT: f32 = 10.0;
+
+pub struct RailPlugin;
+
+impl Plugin for RailPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RailReservations>()
+            .add_systems(Update, (
+                init_rails,
+                station_input,
+                run_train_stops,
+                drive_trains,
+                update_train_transforms,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+// anything a train can drive over: plain rails, stations and signals
+#[derive(Component)]
+pub struct Rail;
+
+// splits the track into blocks only one train may be in at a time
+#[derive(Component)]
+pub struct RailSignal;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StationMode {
+    Load,
+    Unload,
+}
+
+#[derive(Component)]
+pub struct Station {
+    pub name: String,
+    pub mode: StationMode,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum WaitCondition {
+    Time(f32),
+    Full,
+    Empty,
+    // until the station's circuit condition holds
+    Circuit,
+}
+
+#[derive(Clone, Debug)]
+pub struct ScheduleEntry {
+    pub station: Entity,
+    pub wait: WaitCondition,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrainState {
+    // at a station, for how long
+    Stopped(f32),
+    Moving,
+    // waiting at a signal for the block ahead to clear
+    Blocked,
+}
+
+#[derive(Component)]
+pub struct Train {
+    pub schedule: Vec<ScheduleEntry>,
+    pub current: usize,
+    pub state: TrainState,
+    pub wagons: Vec<Entity>,
+    // the tile the locomotive is leaving first, then the rest of the route
+    path: Vec<GridPos>,
+    // fraction of the way from `path[0]` to `path[1]`
+    progress: f32,
+    // seconds since cargo last moved at the current stop
+    idle: f32,
+    // tiles the locomotive came from, most recent first
+    trail: VecDeque<GridPos>,
+}
+
+#[derive(Component)]
+pub struct Wagon;
+
+// which train has claimed which rail tile
+#[derive(Resource, Default)]
+pub struct RailReservations(HashMap<IVec2, Entity>);
+
+impl RailReservations {
+    fn try_reserve(&mut self, tiles: &[GridPos], train: Entity) -> bool {
+        if tiles.iter().any(|t| self.0.get(&t.0).is_some_and(|e| *e != train)) {
+            return false;
+        }
+
+        for tile in tiles {
+            self.0.insert(tile.0, train);
+        }
+        true
+    }
+
+    fn release(&mut self, tile: GridPos, train: Entity) {
+        if self.0.get(&tile.0) == Some(&train) {
+            self.0.remove(&tile.0);
+        }
+    }
+}
+
+fn init_rails(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+    mut station_count: Local<u32>,
+) {
+    for event in placed_events.read() {
+        let mut entity = commands.entity(event.entity);
+
+        match event.kind {
+            BuildingKind::Rail => {
+                entity.insert(Rail);
+            }
+            BuildingKind::RailSignal => {
+                entity.insert((Rail, RailSignal));
+            }
+            BuildingKind::TrainStation => {
+                *station_count += 1;
+                entity.insert((
+                    Rail,
+                    Station {
+                        name: format!("Station {}", *station_count),
+                        mode: StationMode::Unload,
+                    },
+                    Inventory::new(16),
+                ));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Shortest route over connected rail tiles, including both ends.
+pub fn find_route(
+    from: GridPos,
+    to: GridPos,
+    grid: &BuildingGrid,
+    rail_query: &Query<(), With<Rail>>,
+) -> Option<Vec<GridPos>> {
+    let is_rail = |pos: GridPos| grid.get(pos).is_some_and(|e| rail_query.contains(e));
+
+    let mut came_from = HashMap::default();
+    let mut visited = HashSet::default();
+    let mut queue = VecDeque::from([from]);
+    visited.insert(from);
+
+    while let Some(pos) = queue.pop_front() {
+        if pos == to {
+            let mut route = vec![to];
+            while let Some(prev) = came_from.get(route.last().unwrap()) {
+                route.push(*prev);
+            }
+            route.reverse();
+            return Some(route);
+        }
+
+        for facing in [Facing::North, Facing::East, Facing::South, Facing::West] {
+            let next = pos.step(facing);
+            if is_rail(next) && visited.insert(next) {
+                came_from.insert(next, pos);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+fn station_input(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    keys: Res<Input<KeyCode>>,
+    aim: Res<AimPoint>,
+    grid: Res<BuildingGrid>,
+    mut reservations: ResMut<RailReservations>,
+    mut station_query: Query<(Entity, &mut Station, &GridPos, Option<&CircuitCondition>)>,
+) {
+    let Some(entity) = aim.0.and_then(|a| grid.get(GridPos::from_world(a))) else {
+        return;
+    };
+    let Ok((_, mut station, pos, _)) = station_query.get_mut(entity) else {
+        return;
+    };
+    let pos = *pos;
+
+    if keys.just_pressed(KeyCode::F) {
+        station.mode = match station.mode {
+            StationMode::Load => StationMode::Unload,
+            StationMode::Unload => StationMode::Load,
+        };
+    }
+
+    // put a new train on the aimed station, running between all stations
+    if !keys.just_pressed(KeyCode::T) || reservations.0.contains_key(&pos.0) {
+        return;
+    }
+
+    let mut stations: Vec<_> = station_query.iter()
+        .map(|(e, s, _, c)| (e, s.name.clone(), s.mode, c.is_some_and(|c| c.signal.is_some())))
+        .collect();
+    stations.sort_by(|a, b| a.1.cmp(&b.1));
+
+    // stations with a circuit condition decide themselves when trains leave
+    let timed = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let schedule: Vec<_> = stations.into_iter()
+        .map(|(station, _, mode, circuit)| ScheduleEntry {
+            station,
+            wait: match mode {
+                _ if circuit => WaitCondition::Circuit,
+                _ if timed => WaitCondition::Time(TIMED_STOP),
+                StationMode::Load => WaitCondition::Full,
+                StationMode::Unload => WaitCondition::Empty,
+            },
+        })
+        .collect();
+    let current = schedule.iter().position(|s| s.station == entity).unwrap_or(0);
+
+    let wagon_mesh = meshes.add(Mesh::from(shape::Box::new(0.8, 0.6, 0.9)));
+    let wagon_material = materials.add(Color::rgb_u8(90, 70, 50).into());
+    let wagons = (0..2)
+        .map(|_| commands.spawn((
+            Wagon,
+            Inventory::new(WAGON_SLOTS),
+            PbrBundle {
+                mesh: wagon_mesh.clone(),
+                material: wagon_material.clone(),
+                transform: Transform::from_translation(pos.to_world()),
+                ..default()
+            },
+        )).id())
+        .collect();
+
+    let train = commands.spawn((
+        Train {
+            schedule,
+            current,
+            state: TrainState::Stopped(0.0),
+            wagons,
+            path: vec![pos],
+            progress: 0.0,
+            idle: 0.0,
+            trail: VecDeque::new(),
+        },
+        ManaConsumer { demand: 0.0 },
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(0.8, 0.8, 0.9))),
+            material: materials.add(Color::rgb_u8(170, 40, 40).into()),
+            transform: Transform::from_translation(pos.to_world()),
+            ..default()
+        },
+    )).id();
+
+    reservations.0.insert(pos.0, train);
+}
+
+fn run_train_stops(
+    time: Res<Time>,
+    grid: Res<BuildingGrid>,
+    rail_query: Query<(), With<Rail>>,
+    station_query: Query<(&Station, &GridPos)>,
+    condition_query: Query<&CircuitCondition>,
+    mut train_query: Query<&mut Train>,
+    mut inventory_query: Query<&mut Inventory>,
+) {
+    for mut train in &mut train_query {
+        let TrainState::Stopped(elapsed) = train.state else {
+            continue;
+        };
+        let elapsed = elapsed + time.delta_seconds();
+        train.state = TrainState::Stopped(elapsed);
+        train.idle += time.delta_seconds();
+
+        let Some(entry) = train.schedule.get(train.current).cloned() else {
+            continue;
+        };
+
+        // only do business at the station we were heading to
+        let here = train.path[0];
+        let at_station = station_query.get(entry.station).is_ok_and(|(_, pos)| *pos == here);
+        if at_station {
+            let mode = station_query.get(entry.station).map(|(s, _)| s.mode).unwrap();
+            let mut moved = false;
+            for wagon in &train.wagons {
+                let (from, to) = match mode {
+                    StationMode::Load => (entry.station, *wagon),
+                    StationMode::Unload => (*wagon, entry.station),
+                };
+                moved |= transfer(&mut inventory_query, from, to);
+            }
+            if moved {
+                train.idle = 0.0;
+            }
+        }
+
+        // don't wait forever on a station that isn't supplied or emptied
+        let stuck = train.idle >= STATION_TIMEOUT;
+        let wagons = || train.wagons.iter().filter_map(|w| inventory_query.get(*w).ok());
+        let done = !at_station || match entry.wait {
+            WaitCondition::Time(seconds) => elapsed >= seconds,
+            WaitCondition::Full => stuck || wagons().all(|i| i.is_full()),
+            WaitCondition::Empty => stuck || wagons().all(|i| i.is_empty()),
+            WaitCondition::Circuit => condition_query.get(entry.station).is_ok_and(|c| c.satisfied),
+        };
+
+        if !done {
+            continue;
+        }
+
+        // head for the next station that can be reached
+        for _ in 0..train.schedule.len() {
+            train.current = (train.current + 1) % train.schedule.len();
+
+            let target = station_query.get(train.schedule[train.current].station).map(|(_, p)| *p);
+            if let Some(route) = target.ok().and_then(|t| find_route(here, t, &grid, &rail_query)) {
+                if route.len() > 1 {
+                    train.path = route;
+                    train.progress = 0.0;
+                    train.state = TrainState::Blocked;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// returns whether anything was moved
+fn transfer(inventory_query: &mut Query<&mut Inventory>, from: Entity, to: Entity) -> bool {
+    let Ok([mut from, mut to]) = inventory_query.get_many_mut([from, to]) else {
+        return false;
+    };
+
+    let mut moved = false;
+    while let Some(stack) = from.extract(None, u32::MAX) {
+        let left = to.insert(stack);
+        moved |= left < stack.count;
+        if left > 0 {
+            from.insert(ItemStack::new(stack.item, left));
+            break;
+        }
+    }
+    moved
+}
+
+fn drive_trains(
+    time: Res<Time>,
+    grid: Res<BuildingGrid>,
+    mana: Res<ManaGrid>,
+    signal_query: Query<(), With<RailSignal>>,
+    mut reservations: ResMut<RailReservations>,
+    mut train_query: Query<(Entity, &mut Train, &mut ManaConsumer)>,
+) {
+    for (entity, mut train, mut consumer) in &mut train_query {
+        if matches!(train.state, TrainState::Stopped(_)) {
+            consumer.demand = 0.0;
+            continue;
+        }
+
+        // claim the track up to the next signal before moving into it
+        if train.state == TrainState::Blocked {
+            let block_end = train.path.iter().skip(1)
+                .position(|t| grid.get(*t).is_some_and(|e| signal_query.contains(e)))
+                .map_or(train.path.len(), |i| i + 2);
+
+            let block = train.path[1..block_end].to_vec();
+            if !reservations.try_reserve(&block, entity) {
+                consumer.demand = 0.0;
+                continue;
+            }
+            train.state = TrainState::Moving;
+        }
+
+        consumer.demand = TRAIN_DEMAND;
+        train.progress += TRAIN_SPEED * mana.satisfaction * time.delta_seconds();
+
+        while train.progress >= 1.0 && train.path.len() > 1 {
+            train.progress -= 1.0;
+            let left = train.path.remove(0);
+            train.trail.push_front(left);
+
+            // free the track once the last wagon has left it
+            while train.trail.len() > train.wagons.len() {
+                let tile = train.trail.pop_back().unwrap();
+                reservations.release(tile, entity);
+            }
+
+            let entered_signal = grid.get(train.path[0]).is_some_and(|e| signal_query.contains(e));
+            if train.path.len() == 1 {
+                train.progress = 0.0;
+                train.state = TrainState::Stopped(0.0);
+                train.idle = 0.0;
+            } else if entered_signal {
+                train.state = TrainState::Blocked;
+                break;
+            }
+        }
+    }
+}
+
+fn update_train_transforms(
+    train_query: Query<(Entity, &Train)>,
+    mut transform_query: Query<&mut Transform>,
+) {
+    for (entity, train) in &train_query {
+        // the tiles from the front of the train to its back
+        let points: Vec<Vec3> = train.path.get(1).into_iter()
+            .chain(train.path.first())
+            .chain(train.trail.iter())
+            .map(|t| t.to_world() + Vec3::Y * 0.4)
+            .collect();
+        let moving = train.path.len() > 1;
+
+        let cars = std::iter::once(entity).chain(train.wagons.iter().copied());
+        for (i, car) in cars.enumerate() {
+            let Ok(mut transform) = transform_query.get_mut(car) else {
+                continue;
+            };
+
+            let (ahead, behind) = if moving {
+                (points.get(i), points.get(i + 1))
+            } else {
+                (points.get(i), points.get(i))
+            };
+            let (Some(ahead), Some(behind)) = (ahead, behind.or(ahead)) else {
+                continue;
+            };
+
+            transform.translation = behind.lerp(*ahead, train.progress);
+            if ahead != behind {
+                transform.look_at(*ahead, Vec3::Y);
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::item::{Item, ItemStack};
+
+/// How recipes are grouped on the crafting screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category {
+    Materials,
+    Machines,
+    Magic,
+    Equipment,
+}
+
+impl Category {
+    pub const ALL: [Category; 4] = [
+        Category::Materials,
+        Category::Machines,
+        Category::Magic,
+        Category::Equipment,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::Materials => "Materials",
+            Category::Machines => "Machines",
+            Category::Magic => "Magic",
+            Category::Equipment => "Equipment",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Recipe {
+    ManaCrystal,
+    IronPlate,
+    CopperPlate,
+    Gear,
+    SpeedModule,
+    EfficiencyModule,
+    RangeModule,
+    RepairKit,
+    BoltRune,
+    NovaRune,
+    BeamRune,
+    FireRune,
+    FrostRune,
+    ArcRune,
+    PowerRune,
+    HasteRune,
+    Essence,
+    Staff,
+    Wand,
+    Robe,
+    Amulet,
+}
+
+impl Recipe {
+    pub const ALL: [Recipe; 21] = [
+        Recipe::ManaCrystal,
+        Recipe::IronPlate,
+        Recipe::CopperPlate,
+        Recipe::Gear,
+        Recipe::SpeedModule,
+        Recipe::EfficiencyModule,
+        Recipe::RangeModule,
+        Recipe::RepairKit,
+        Recipe::BoltRune,
+        Recipe::NovaRune,
+        Recipe::BeamRune,
+        Recipe::FireRune,
+        Recipe::FrostRune,
+        Recipe::ArcRune,
+        Recipe::PowerRune,
+        Recipe::HasteRune,
+        Recipe::Essence,
+        Recipe::Staff,
+        Recipe::Wand,
+        Recipe::Robe,
+        Recipe::Amulet,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        self.output().item.name()
+    }
+
+    pub fn inputs(&self) -> &'static [(Item, u32)] {
+        match self {
+            // condensed straight out of the grid
+            Recipe::ManaCrystal => &[],
+            Recipe::IronPlate => &[(Item::IronOre, 1)],
+            Recipe::CopperPlate => &[(Item::CopperOre, 1)],
+            Recipe::Gear => &[(Item::IronPlate, 2)],
+            Recipe::SpeedModule => &[(Item::Gear, 2), (Item::ManaCrystal, 2)],
+            Recipe::EfficiencyModule => &[(Item::CopperPlate, 3), (Item::ManaCrystal, 2)],
+            Recipe::RangeModule => &[(Item::IronPlate, 3), (Item::ManaCrystal, 2)],
+            Recipe::RepairKit => &[(Item::IronPlate, 2), (Item::Gear, 2)],
+            Recipe::BoltRune | Recipe::NovaRune | Recipe::BeamRune => &[(Item::Stone, 2), (Item::ManaCrystal, 2)],
+            Recipe::FireRune => &[(Item::Wood, 2), (Item::ManaCrystal, 2)],
+            Recipe::FrostRune => &[(Item::Stone, 2), (Item::ManaCrystal, 3)],
+            Recipe::ArcRune => &[(Item::CopperPlate, 2), (Item::ManaCrystal, 2)],
+            Recipe::PowerRune | Recipe::HasteRune => &[(Item::Gear, 2), (Item::ManaCrystal, 3)],
+            Recipe::Essence => &[(Item::ManaCrystal, 3)],
+            Recipe::Staff => &[(Item::Wood, 4), (Item::ManaCrystal, 1)],
+            Recipe::Wand => &[(Item::Wood, 1), (Item::CopperPlate, 1), (Item::ManaCrystal, 2)],
+            Recipe::Robe => &[(Item::Wood, 2), (Item::CopperPlate, 4)],
+            Recipe::Amulet => &[(Item::CopperPlate, 2), (Item::ManaCrystal, 2)],
+        }
+    }
+
+    pub fn output(&self) -> ItemStack {
+        match self {
+            Recipe::ManaCrystal => ItemStack::new(Item::ManaCrystal, 1),
+            Recipe::IronPlate => ItemStack::new(Item::IronPlate, 1),
+            Recipe::CopperPlate => ItemStack::new(Item::CopperPlate, 1),
+            Recipe::Gear => ItemStack::new(Item::Gear, 1),
+            Recipe::SpeedModule => ItemStack::new(Item::SpeedModule, 1),
+            Recipe::EfficiencyModule => ItemStack::new(Item::EfficiencyModule, 1),
+            Recipe::RangeModule => ItemStack::new(Item::RangeModule, 1),
+            Recipe::RepairKit => ItemStack::new(Item::RepairKit, 1),
+            Recipe::BoltRune => ItemStack::new(Item::BoltRune, 1),
+            Recipe::NovaRune => ItemStack::new(Item::NovaRune, 1),
+            Recipe::BeamRune => ItemStack::new(Item::BeamRune, 1),
+            Recipe::FireRune => ItemStack::new(Item::FireRune, 1),
+            Recipe::FrostRune => ItemStack::new(Item::FrostRune, 1),
+            Recipe::ArcRune => ItemStack::new(Item::ArcRune, 1),
+            Recipe::PowerRune => ItemStack::new(Item::PowerRune, 1),
+            Recipe::HasteRune => ItemStack::new(Item::HasteRune, 1),
+            Recipe::Essence => ItemStack::new(Item::Essence, 1),
+            Recipe::Staff => ItemStack::new(Item::Staff, 1),
+            Recipe::Wand => ItemStack::new(Item::Wand, 1),
+            Recipe::Robe => ItemStack::new(Item::Robe, 1),
+            Recipe::Amulet => ItemStack::new(Item::Amulet, 1),
+        }
+    }
+
+    pub fn category(&self) -> Category {
+        match self {
+            Recipe::ManaCrystal | Recipe::IronPlate | Recipe::CopperPlate | Recipe::Gear => Category::Materials,
+            Recipe::SpeedModule | Recipe::EfficiencyModule | Recipe::RangeModule | Recipe::RepairKit => Category::Machines,
+            Recipe::Staff | Recipe::Wand | Recipe::Robe | Recipe::Amulet => Category::Equipment,
+            _ => Category::Magic,
+        }
+    }
+
+    // crystals can only be condensed out of the grid, and runes and
+    // essence need an assembler to distill them
+    pub fn handcraftable(&self) -> bool {
+        !matches!(self, Recipe::ManaCrystal | Recipe::Essence) && !self.output().item.is_rune()
+    }
+
+    // seconds per craft at full speed
+    pub fn time(&self) -> f32 {
+        match self {
+            Recipe::ManaCrystal => 4.0,
+            Recipe::IronPlate | Recipe::CopperPlate => 2.0,
+            Recipe::Gear => 1.5,
+            Recipe::SpeedModule | Recipe::EfficiencyModule | Recipe::RangeModule => 10.0,
+            Recipe::RepairKit => 3.0,
+            _ => 6.0,
+        }
+    }
+
+    // mana per second while crafting
+    pub fn mana(&self) -> f32 {
+        match self {
+            Recipe::ManaCrystal => 8.0,
+            _ => 3.0,
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{
+    building::{BuildingKind, BuildingPlaced},
+    interaction::Interactable,
+    inventory::Inventory,
+    item::Item,
+    module::{self, ModuleSlots},
+    power::{ManaConsumer, ManaGrid},
+    recipe::Recipe,
+    state::GameplaySet,
+    stats::ItemConsumed,
+};
+
+// mana per second while a lab is working
+const LAB_DEMAND: f32 = 4.0;
+
+pub struct ResearchPlugin;
+
+impl Plugin for ResearchPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Research>()
+            .add_event::<TechnologyResearched>()
+            .add_systems(Update, (
+                init_labs,
+                run_labs,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Technology {
+    Logistics,
+    ManaStorage,
+    Cooling,
+    Purification,
+    Metallurgy,
+    Railways,
+    Defense,
+    Modules,
+    Circuits,
+    Runecraft,
+    Enchanting,
+    Alchemy,
+}
+
+impl Technology {
+    pub const ALL: [Technology; 12] = [
+        Technology::Logistics,
+        Technology::ManaStorage,
+        Technology::Cooling,
+        Technology::Purification,
+        Technology::Metallurgy,
+        Technology::Railways,
+        Technology::Defense,
+        Technology::Modules,
+        Technology::Circuits,
+        Technology::Runecraft,
+        Technology::Enchanting,
+        Technology::Alchemy,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technology::Logistics => "Logistics",
+            Technology::ManaStorage => "Mana Storage",
+            Technology::Cooling => "Cooling",
+            Technology::Purification => "Purification",
+            Technology::Metallurgy => "Metallurgy",
+            Technology::Railways => "Railways",
+            Technology::Defense => "Defense",
+            Technology::Modules => "Modules",
+            Technology::Circuits => "Circuits",
+            Technology::Runecraft => "Runecraft",
+            Technology::Enchanting => "Enchanting",
+            Technology::Alchemy => "Alchemy",
+        }
+    }
+
+    pub fn prerequisites(&self) -> &'static [Technology] {
+        match self {
+            Technology::Purification => &[Technology::ManaStorage],
+            Technology::Railways => &[Technology::Logistics, Technology::Metallurgy],
+            Technology::Defense => &[Technology::ManaStorage, Technology::Metallurgy],
+            Technology::Modules => &[Technology::Metallurgy],
+            Technology::Circuits => &[Technology::Logistics],
+            Technology::Runecraft => &[Technology::ManaStorage],
+            Technology::Enchanting => &[Technology::Runecraft],
+            Technology::Alchemy => &[Technology::ManaStorage],
+            _ => &[],
+        }
+    }
+
+    // a lab consumes these for every unit of research
+    pub fn unit_cost(&self) -> &'static [(Item, u32)] {
+        match self {
+            Technology::Railways
+            | Technology::Defense
+            | Technology::Modules => &[(Item::ManaCrystal, 1), (Item::Gear, 1)],
+            _ => &[(Item::ManaCrystal, 1)],
+        }
+    }
+
+    pub fn units(&self) -> u32 {
+        match self {
+            Technology::Cooling => 5,
+            Technology::Railways => 20,
+            Technology::Defense => 15,
+            _ => 10,
+        }
+    }
+
+    // seconds per unit in a single lab
+    pub fn unit_time(&self) -> f32 {
+        match self {
+            Technology::Railways | Technology::Defense | Technology::Modules => 8.0,
+            _ => 5.0,
+        }
+    }
+
+    pub fn buildings(&self) -> &'static [BuildingKind] {
+        match self {
+            Technology::Logistics => &[
+                BuildingKind::Splitter,
+                BuildingKind::UndergroundEntrance,
+                BuildingKind::UndergroundExit,
+                BuildingKind::Collector,
+                BuildingKind::DroneHub,
+            ],
+            Technology::ManaStorage => &[BuildingKind::ManaBattery, BuildingKind::SolarCollector],
+            Technology::Cooling => &[BuildingKind::HeatSink, BuildingKind::WaterPipe],
+            Technology::Purification => &[BuildingKind::Purifier],
+            Technology::Metallurgy => &[],
+            Technology::Railways => &[
+                BuildingKind::Rail,
+                BuildingKind::RailSignal,
+                BuildingKind::TrainStation,
+            ],
+            Technology::Defense => &[BuildingKind::BeamTurret, BuildingKind::BoltTurret, BuildingKind::Decoy],
+            Technology::Modules => &[],
+            Technology::Circuits => &[
+                BuildingKind::Wire,
+                BuildingKind::ConstantCombinator,
+                BuildingKind::ArithmeticCombinator,
+                BuildingKind::DeciderCombinator,
+                BuildingKind::Lamp,
+            ],
+            Technology::Runecraft => &[BuildingKind::Pylon, BuildingKind::Candle, BuildingKind::GolemForge],
+            Technology::Enchanting => &[BuildingKind::EnchantingTable],
+            Technology::Alchemy => &[BuildingKind::BrewingStand],
+        }
+    }
+
+    pub fn recipes(&self) -> &'static [Recipe] {
+        match self {
+            Technology::Metallurgy => &[Recipe::Gear],
+            Technology::Modules => &[
+                Recipe::SpeedModule,
+                Recipe::EfficiencyModule,
+                Recipe::RangeModule,
+            ],
+            Technology::Runecraft => &[
+                Recipe::BoltRune,
+                Recipe::NovaRune,
+                Recipe::BeamRune,
+                Recipe::FireRune,
+                Recipe::FrostRune,
+                Recipe::ArcRune,
+                Recipe::PowerRune,
+                Recipe::HasteRune,
+            ],
+            Technology::Enchanting => &[
+                Recipe::Essence,
+                Recipe::Staff,
+                Recipe::Wand,
+                Recipe::Robe,
+                Recipe::Amulet,
+            ],
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Research {
+    pub unlocked: HashSet<Technology>,
+    pub current: Option<Technology>,
+    // finished units of the current technology
+    pub progress: u32,
+}
+
+impl Research {
+    pub fn is_available(&self, tech: Technology) -> bool {
+        !self.unlocked.contains(&tech)
+            && tech.prerequisites().iter().all(|p| self.unlocked.contains(p))
+    }
+
+    // anything no technology unlocks is available from the start
+    pub fn building_unlocked(&self, kind: BuildingKind) -> bool {
+        Technology::ALL.iter()
+            .filter(|t| t.buildings().contains(&kind))
+            .all(|t| self.unlocked.contains(t))
+    }
+
+    pub fn recipe_unlocked(&self, recipe: Recipe) -> bool {
+        Technology::ALL.iter()
+            .filter(|t| t.recipes().contains(&recipe))
+            .all(|t| self.unlocked.contains(t))
+    }
+}
+
+#[derive(Event)]
+pub struct TechnologyResearched(pub Technology);
+
+#[derive(Component)]
+pub struct Lab {
+    pub working: bool,
+    // seconds spent on the current unit
+    pub progress: f32,
+}
+
+fn init_labs(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read().filter(|e| e.kind == BuildingKind::Lab) {
+        let mut inventory = Inventory::new(2);
+        inventory.set_filter(0, Some(Item::ManaCrystal));
+        inventory.set_filter(1, Some(Item::Gear));
+
+        commands.entity(event.entity).insert((
+            Lab { working: false, progress: 0.0 },
+            inventory,
+            Interactable::new("Inspect Lab"),
+            ManaConsumer { demand: 0.0 },
+        ));
+    }
+}
+
+fn run_labs(
+    time: Res<Time>,
+    grid: Res<ManaGrid>,
+    mut research: ResMut<Research>,
+    mut lab_query: Query<(&mut Lab, &mut Inventory, &mut ManaConsumer, Option<&ModuleSlots>)>,
+    mut researched_events: EventWriter<TechnologyResearched>,
+    mut consumed_events: EventWriter<ItemConsumed>,
+) {
+    let Some(tech) = research.current else {
+        for (_, _, mut consumer, _) in &mut lab_query {
+            consumer.demand = 0.0;
+        }
+        return;
+    };
+
+    for (mut lab, mut inventory, mut consumer, modules) in &mut lab_query {
+        let effects = module::effects(modules);
+
+        if !lab.working {
+            let has_cost = tech.unit_cost().iter().all(|(item, n)| inventory.count(*item) >= *n);
+
+            if has_cost {
+                for (item, n) in tech.unit_cost() {
+                    inventory.remove(*item, *n);
+                    consumed_events.send(ItemConsumed { item: *item, count: *n });
+                }
+                lab.working = true;
+                lab.progress = 0.0;
+            }
+        }
+
+        consumer.demand = if lab.working { LAB_DEMAND * effects.mana } else { 0.0 };
+        if !lab.working {
+            continue;
+        }
+
+        lab.progress += time.delta_seconds() * grid.satisfaction * effects.speed;
+        if lab.progress >= tech.unit_time() {
+            lab.working = false;
+            research.progress += 1;
+        }
+    }
+
+    if research.progress >= tech.units() {
+        research.unlocked.insert(tech);
+        research.current = None;
+        research.progress = 0;
+        researched_events.send(TechnologyResearched(tech));
+    }
+}
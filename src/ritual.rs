@@ -0,0 +1,281 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::{Building, BuildingGrid, BuildingKind, GridPos},
+    circle::{CircleKind, MagicCircle},
+    dilation::TimeDilation,
+    enemy::spawn_construct,
+    environment::Wind,
+    health::Health,
+    inventory::Inventory,
+    item::{Item, ItemStack},
+    magic::{Caster, InterruptCast, ReleaseCast, SpellBook, SpellCast, SpellEffect},
+    stagger::Poise,
+    state::GameplaySet,
+    storage::Chest,
+    Player,
+};
+
+// chests this close to the center get transmuted
+const TRANSMUTE_RADIUS: f32 = 6.0;
+const COLOSSUS_HEALTH: f32 = 400.0;
+const COLOSSUS_POISE: f32 = 0.9;
+// where the colossus shows up relative to the center
+const COLOSSUS_OFFSET: Vec3 = Vec3::new(0.0, 1.5, -4.0);
+
+// the patterns around the caster's tile
+const SUMMONING: &[(IVec2, BuildingKind)] = &[
+    (IVec2::new(2, 0), BuildingKind::Pylon),
+    (IVec2::new(-2, 0), BuildingKind::Pylon),
+    (IVec2::new(0, 2), BuildingKind::Pylon),
+    (IVec2::new(0, -2), BuildingKind::Pylon),
+    (IVec2::new(2, 2), BuildingKind::Candle),
+    (IVec2::new(-2, 2), BuildingKind::Candle),
+    (IVec2::new(2, -2), BuildingKind::Candle),
+    (IVec2::new(-2, -2), BuildingKind::Candle),
+];
+const TRANSMUTATION: &[(IVec2, BuildingKind)] = &[
+    (IVec2::new(1, 0), BuildingKind::Candle),
+    (IVec2::new(-1, 0), BuildingKind::Candle),
+    (IVec2::new(0, 1), BuildingKind::Candle),
+    (IVec2::new(0, -1), BuildingKind::Candle),
+    (IVec2::new(1, 1), BuildingKind::Pylon),
+    (IVec2::new(-1, -1), BuildingKind::Pylon),
+];
+const TEMPEST: &[(IVec2, BuildingKind)] = &[
+    (IVec2::new(2, 0), BuildingKind::Pylon),
+    (IVec2::new(-2, 0), BuildingKind::Pylon),
+    (IVec2::new(0, 2), BuildingKind::Pylon),
+    (IVec2::new(0, -2), BuildingKind::Pylon),
+];
+
+pub struct RitualPlugin;
+
+impl Plugin for RitualPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (begin_rituals, advance_rituals).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Ritual {
+    Summoning,
+    Transmutation,
+    Tempest,
+}
+
+impl Ritual {
+    // checked in this order, the summoning circle contains the tempest one
+    pub const ALL: [Ritual; 3] = [Ritual::Summoning, Ritual::Transmutation, Ritual::Tempest];
+
+    /// What has to stand where, relative to the tile the caster is on.
+    pub fn pattern(&self) -> &'static [(IVec2, BuildingKind)] {
+        match self {
+            Ritual::Summoning => SUMMONING,
+            Ritual::Transmutation => TRANSMUTATION,
+            Ritual::Tempest => TEMPEST,
+        }
+    }
+
+    // seconds of channeling it takes
+    pub fn channel_time(&self) -> f32 {
+        match self {
+            Ritual::Summoning => 12.0,
+            Ritual::Transmutation => 8.0,
+            Ritual::Tempest => 5.0,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Ritual::Summoning => Color::CRIMSON,
+            Ritual::Transmutation => Color::GOLD,
+            Ritual::Tempest => Color::AQUAMARINE,
+        }
+    }
+
+    pub fn matches(&self, center: GridPos, grid: &BuildingGrid, building_query: &Query<&Building>) -> bool {
+        // the caster has to be able to stand in the middle
+        grid.get(center).is_none()
+            && self.fits(center, |pos| grid.get(pos).and_then(|e| building_query.get(e).ok()).map(|b| b.kind))
+    }
+
+    // whether what's built around `center` is laid out the way this ritual needs
+    fn fits(&self, center: GridPos, kind_at: impl Fn(GridPos) -> Option<BuildingKind>) -> bool {
+        self.pattern().iter().all(|(offset, kind)| kind_at(GridPos(center.0 + *offset)) == Some(*kind))
+    }
+}
+
+// what a transmutation turns each item into
+fn transmuted(item: Item) -> Option<Item> {
+    match item {
+        Item::Stone => Some(Item::IronOre),
+        Item::IronOre => Some(Item::CopperOre),
+        Item::CopperOre => Some(Item::ManaCrystal),
+        _ => None,
+    }
+}
+
+#[derive(Component)]
+struct Performing {
+    ritual: Ritual,
+    center: GridPos,
+    elapsed: f32,
+}
+
+fn begin_rituals(
+    mut commands: Commands,
+    book: Res<SpellBook>,
+    grid: Res<BuildingGrid>,
+    building_query: Query<&Building>,
+    caster_query: Query<&Transform>,
+    circle_query: Query<(&MagicCircle, &Transform)>,
+    mut cast_events: EventReader<SpellCast>,
+    mut interrupt_events: EventWriter<InterruptCast>,
+) {
+    for event in cast_events.read() {
+        if !matches!(book.get(event.spell).map(|s| s.effect), Some(SpellEffect::Ritual)) {
+            continue;
+        }
+        let Ok(caster) = caster_query.get(event.caster) else {
+            continue;
+        };
+
+        // an anchor circle lets the caster stand anywhere inside it
+        let anchor = circle_query.iter()
+            .find(|(c, t)| c.kind == CircleKind::Anchor && c.contains(t.translation, caster.translation));
+        let center = GridPos::from_world(anchor.map_or(caster.translation, |(_, t)| t.translation));
+        let ritual = Ritual::ALL.into_iter().find(|r| r.matches(center, &grid, &building_query));
+
+        // channeling outside of a circle does nothing
+        let Some(ritual) = ritual else {
+            interrupt_events.send(InterruptCast { caster: event.caster });
+            continue;
+        };
+
+        commands.entity(event.caster).insert(Performing { ritual, center, elapsed: 0.0 });
+    }
+}
+
+fn advance_rituals(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    dilation: Res<TimeDilation>,
+    grid: Res<BuildingGrid>,
+    mut wind: ResMut<Wind>,
+    building_query: Query<&Building>,
+    mut performer_query: Query<(Entity, &mut Performing, &Caster, Has<Player>)>,
+    mut chest_query: Query<(&Transform, &mut Inventory), With<Chest>>,
+    mut release_events: EventWriter<ReleaseCast>,
+    mut interrupt_events: EventWriter<InterruptCast>,
+    mut gizmos: Gizmos,
+) {
+    for (entity, mut performing, caster, is_player) in &mut performer_query {
+        // the channel broke or was let go early
+        if caster.channeling.is_none() {
+            commands.entity(entity).remove::<Performing>();
+            continue;
+        }
+
+        let ritual = performing.ritual;
+        let center = performing.center;
+
+        // someone took a piece away mid-ritual
+        if !ritual.matches(center, &grid, &building_query) {
+            commands.entity(entity).remove::<Performing>();
+            interrupt_events.send(InterruptCast { caster: entity });
+            continue;
+        }
+
+        performing.elapsed += if is_player { dilation.unscaled(&time) } else { time.delta_seconds() };
+        let progress = (performing.elapsed / ritual.channel_time()).min(1.0);
+
+        // lines from the center out to every piece, reaching further the longer it goes
+        let middle = center.to_world() + Vec3::Y * 0.1;
+        for (offset, _) in ritual.pattern() {
+            let piece = GridPos(center.0 + *offset).to_world() + Vec3::Y * 0.1;
+            gizmos.line(middle, middle.lerp(piece, progress), ritual.color());
+        }
+        gizmos.circle(middle, Vec3::Y, 0.5 + progress, ritual.color());
+
+        if progress < 1.0 {
+            continue;
+        }
+
+        match ritual {
+            Ritual::Tempest => wind.storm(),
+            Ritual::Summoning => {
+                let colossus = spawn_construct(&mut commands, &mut meshes, &mut materials, middle + COLOSSUS_OFFSET);
+                commands.entity(colossus).insert((
+                    Health::new(COLOSSUS_HEALTH),
+                    // far too big to be pushed around
+                    Poise(COLOSSUS_POISE),
+                    Transform::from_translation(middle + COLOSSUS_OFFSET).with_scale(Vec3::splat(2.0)),
+                ));
+            }
+            Ritual::Transmutation => {
+                for (transform, mut inventory) in &mut chest_query {
+                    if transform.translation.distance(middle) > TRANSMUTE_RADIUS {
+                        continue;
+                    }
+
+                    // count everything up front so nothing gets transmuted twice,
+                    // whatever doesn't fit afterwards is lost to the ritual
+                    let counts: Vec<(Item, Item, u32)> = Item::ALL.into_iter()
+                        .filter_map(|item| Some((item, transmuted(item)?, inventory.count(item))))
+                        .filter(|(_, _, n)| *n > 0)
+                        .collect();
+                    for (from, to, n) in counts {
+                        inventory.remove(from, n);
+                        inventory.insert(ItemStack::new(to, n));
+                    }
+                }
+            }
+        }
+
+        commands.entity(entity).remove::<Performing>();
+        release_events.send(ReleaseCast { caster: entity });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashMap;
+
+    use super::*;
+
+    fn laid_out(center: IVec2, pattern: &[(IVec2, BuildingKind)]) -> HashMap<GridPos, BuildingKind> {
+        pattern.iter().map(|(offset, kind)| (GridPos(center + *offset), *kind)).collect()
+    }
+
+    #[test]
+    fn pylons_and_candles_make_a_summoning_circle() {
+        let buildings = laid_out(IVec2::ZERO, SUMMONING);
+        let kind_at = |pos| buildings.get(&pos).copied();
+
+        assert!(Ritual::Summoning.fits(GridPos(IVec2::ZERO), kind_at));
+        // the summoning circle has the tempest's pylons in it as well
+        assert!(Ritual::Tempest.fits(GridPos(IVec2::ZERO), kind_at));
+        assert!(!Ritual::Transmutation.fits(GridPos(IVec2::ZERO), kind_at));
+    }
+
+    #[test]
+    fn every_piece_has_to_be_there() {
+        let mut buildings = laid_out(IVec2::ZERO, SUMMONING);
+        buildings.remove(&GridPos(IVec2::new(2, 2)));
+
+        assert!(!Ritual::Summoning.fits(GridPos(IVec2::ZERO), |pos| buildings.get(&pos).copied()));
+    }
+
+    #[test]
+    fn the_circle_can_be_anywhere() {
+        let center = IVec2::new(7, -3);
+        let buildings = laid_out(center, TRANSMUTATION);
+        let kind_at = |pos| buildings.get(&pos).copied();
+
+        assert!(Ritual::Transmutation.fits(GridPos(center), kind_at));
+        assert!(!Ritual::Transmutation.fits(GridPos(IVec2::ZERO), kind_at));
+    }
+}
@@ -0,0 +1,359 @@
+use std::fmt::Write;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    health::{Damage, DamageType},
+    hotbar::{Hotbar, HotbarEntry},
+    inventory::Inventory,
+    item::Item,
+    magic::{Channel, Spell, SpellBook, SpellEffect, SpellId},
+    state::GameplaySet,
+    Player,
+};
+
+pub struct RunecraftPlugin;
+
+impl Plugin for RunecraftPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<RunePanel>()
+            .init_resource::<InscribedSpells>()
+            .add_systems(Startup, setup_rune_ui)
+            .add_systems(Update, (rune_input, update_rune_ui).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Shape {
+    Bolt,
+    Nova,
+    Beam,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 3] = [Shape::Bolt, Shape::Nova, Shape::Beam];
+
+    pub fn rune(self) -> Item {
+        match self {
+            Shape::Bolt => Item::BoltRune,
+            Shape::Nova => Item::NovaRune,
+            Shape::Beam => Item::BeamRune,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Shape::Bolt => "Bolt",
+            Shape::Nova => "Nova",
+            Shape::Beam => "Beam",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Element {
+    Fire,
+    Frost,
+    Arc,
+}
+
+impl Element {
+    pub const ALL: [Element; 3] = [Element::Fire, Element::Frost, Element::Arc];
+
+    pub fn rune(self) -> Item {
+        match self {
+            Element::Fire => Item::FireRune,
+            Element::Frost => Item::FrostRune,
+            Element::Arc => Item::ArcRune,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Element::Fire => "Fire",
+            Element::Frost => "Frost",
+            Element::Arc => "Arc",
+        }
+    }
+
+    pub fn damage_type(self) -> DamageType {
+        match self {
+            Element::Fire => DamageType::Fire,
+            Element::Frost => DamageType::Frost,
+            Element::Arc => DamageType::Arc,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Modifier {
+    // hits harder for more mana
+    Power,
+    // quicker to cast and recover, a little weaker
+    Haste,
+}
+
+impl Modifier {
+    pub const ALL: [Modifier; 2] = [Modifier::Power, Modifier::Haste];
+
+    pub fn rune(self) -> Item {
+        match self {
+            Modifier::Power => Item::PowerRune,
+            Modifier::Haste => Item::HasteRune,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Modifier::Power => "Empowered",
+            Modifier::Haste => "Hasted",
+        }
+    }
+}
+
+/// A spell put together from runes, everything else about it is derived.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RuneSpell {
+    pub shape: Shape,
+    pub element: Element,
+    pub modifier: Option<Modifier>,
+}
+
+impl RuneSpell {
+    pub fn runes(&self) -> Vec<Item> {
+        let mut runes = vec![self.shape.rune(), self.element.rune()];
+        runes.extend(self.modifier.map(|m| m.rune()));
+        runes
+    }
+
+    pub fn name(&self) -> String {
+        let base = format!("{} {}", self.element.name(), self.shape.name());
+        match self.modifier {
+            Some(modifier) => format!("{} {}", modifier.name(), base),
+            None => base,
+        }
+    }
+
+    pub fn spell(&self) -> Spell {
+        // cost, cast time, cooldown, damage
+        let (mut cost, mut cast_time, mut cooldown, mut damage) = match self.shape {
+            Shape::Bolt => (10.0, 0.4, 0.8, 20.0),
+            Shape::Nova => (30.0, 1.0, 5.0, 25.0),
+            // damage per second while channeling
+            Shape::Beam => (5.0, 0.3, 1.0, 25.0),
+        };
+
+        match self.modifier {
+            Some(Modifier::Power) => {
+                cost *= 1.5;
+                damage *= 1.5;
+            }
+            Some(Modifier::Haste) => {
+                cast_time *= 0.5;
+                cooldown *= 0.7;
+                damage *= 0.85;
+            }
+            None => {}
+        }
+
+        let damage = Damage::new(damage, self.element.damage_type());
+        let (effect, channel) = match self.shape {
+            Shape::Bolt => (SpellEffect::Bolt { damage, range: 20.0 }, None),
+            Shape::Nova => (SpellEffect::Nova { damage, radius: 4.0 }, None),
+            Shape::Beam => (SpellEffect::Beam { damage, range: 15.0 }, Some(Channel {
+                mana_per_second: cost * 2.4,
+                broken_by_damage: true,
+                broken_by_movement: false,
+            })),
+        };
+
+        Spell { name: self.name(), cost, cast_time, cooldown, effect, channel }
+    }
+}
+
+/// Rune spells that were already inscribed, so the same combination isn't
+/// added to the spell book twice.
+#[derive(Resource, Default)]
+pub struct InscribedSpells {
+    pub known: HashMap<RuneSpell, SpellId>,
+}
+
+#[derive(Resource)]
+struct RunePanel {
+    open: bool,
+    // 0 shape, 1 element, 2 modifier
+    row: usize,
+    shape: usize,
+    element: usize,
+    // one past the last modifier means none
+    modifier: usize,
+}
+
+impl Default for RunePanel {
+    fn default() -> Self {
+        RunePanel {
+            open: false,
+            row: 0,
+            shape: 0,
+            element: 0,
+            modifier: Modifier::ALL.len(),
+        }
+    }
+}
+
+impl RunePanel {
+    fn rune_spell(&self) -> RuneSpell {
+        RuneSpell {
+            shape: Shape::ALL[self.shape],
+            element: Element::ALL[self.element],
+            modifier: Modifier::ALL.get(self.modifier).copied(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct RuneUi;
+
+#[derive(Component)]
+struct RuneText;
+
+fn rune_input(
+    keys: Res<Input<KeyCode>>,
+    mut panel: ResMut<RunePanel>,
+    mut book: ResMut<SpellBook>,
+    mut inscribed: ResMut<InscribedSpells>,
+    mut player_query: Query<(&mut Inventory, &mut Hotbar), With<Player>>,
+) {
+    if keys.just_pressed(KeyCode::J) {
+        panel.open = !panel.open;
+    }
+
+    if !panel.open {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Down) {
+        panel.row = (panel.row + 1) % 3;
+    }
+    if keys.just_pressed(KeyCode::Up) {
+        panel.row = (panel.row + 2) % 3;
+    }
+
+    let step = match (keys.just_pressed(KeyCode::Left), keys.just_pressed(KeyCode::Right)) {
+        (true, false) => -1,
+        (false, true) => 1,
+        _ => 0,
+    };
+    if step != 0 {
+        let (value, count) = match panel.row {
+            0 => (&mut panel.shape, Shape::ALL.len()),
+            1 => (&mut panel.element, Element::ALL.len()),
+            _ => (&mut panel.modifier, Modifier::ALL.len() + 1),
+        };
+        *value = (*value as i32 + step).rem_euclid(count as i32) as usize;
+    }
+
+    if !keys.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    let (mut inventory, mut hotbar) = player_query.get_single_mut().expect("not one player");
+    let rune_spell = panel.rune_spell();
+
+    // known combinations are just equipped again, new ones use up the runes
+    let id = match inscribed.known.get(&rune_spell) {
+        Some(id) => *id,
+        None => {
+            let runes = rune_spell.runes();
+            if !runes.iter().all(|r| inventory.count(*r) >= 1) {
+                return;
+            }
+            for rune in runes {
+                inventory.remove(rune, 1);
+            }
+
+            let id = book.add(rune_spell.spell());
+            inscribed.known.insert(rune_spell, id);
+            id
+        }
+    };
+
+    let slot = hotbar.slots.iter().position(|s| s.is_none()).unwrap_or(hotbar.selected);
+    hotbar.equip(slot, HotbarEntry::Spell(id));
+}
+
+fn setup_rune_ui(mut commands: Commands) {
+    commands.spawn((
+        RuneUi,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(40.0),
+                top: Val::Percent(30.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            RuneText,
+            TextBundle::from_section("", TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            }),
+        ));
+    });
+}
+
+fn update_rune_ui(
+    panel: Res<RunePanel>,
+    inscribed: Res<InscribedSpells>,
+    player_query: Query<&Inventory, With<Player>>,
+    mut ui_query: Query<&mut Visibility, With<RuneUi>>,
+    mut text_query: Query<&mut Text, With<RuneText>>,
+) {
+    let mut visibility = ui_query.get_single_mut().expect("not one rune ui");
+    if !panel.open {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Visible;
+
+    let inventory = player_query.get_single().expect("not one player");
+    let rune_spell = panel.rune_spell();
+
+    let rows = [
+        ("Shape", rune_spell.shape.name(), Some(rune_spell.shape.rune())),
+        ("Element", rune_spell.element.name(), Some(rune_spell.element.rune())),
+        ("Modifier", rune_spell.modifier.map_or("None", |m| m.name()), rune_spell.modifier.map(|m| m.rune())),
+    ];
+
+    let mut content = String::from("Runecraft (Enter to inscribe)");
+    for (i, (label, name, rune)) in rows.iter().enumerate() {
+        let cursor = if i == panel.row { ">" } else { " " };
+        let _ = write!(content, "\n{} {}: < {} >", cursor, label, name);
+        if let Some(rune) = rune {
+            let _ = write!(content, " ({} owned)", inventory.count(*rune));
+        }
+    }
+
+    let spell = rune_spell.spell();
+    let _ = write!(
+        content,
+        "\n\n{}: {:.0} mana, {:.1}s cast, {:.1}s cooldown",
+        spell.name, spell.cost, spell.cast_time, spell.cooldown,
+    );
+    if let Some(channel) = spell.channel {
+        let _ = write!(content, ", {:.0} mana/s", channel.mana_per_second);
+    }
+    if inscribed.known.contains_key(&rune_spell) {
+        content += "\nalready known, Enter equips it";
+    }
+
+    text_query.get_single_mut().expect("not one rune text").sections[0].value = content;
+}
@@ -0,0 +1,250 @@
+use bevy::{prelude::*, utils::HashSet};
+use rand::Rng;
+
+use crate::{item::Item, locale::Locale, power::ManaBattery, state::GameplaySet, Player};
+
+const VEIN_COUNT: usize = 12;
+// veins are scattered this far from the origin in every direction
+const VEIN_SPREAD: f32 = 30.0;
+const MAX_RICHNESS: u32 = 2000;
+const EXPLORE_CHUNK_SIZE: f32 = 4.0;
+// how far the player can see to explore the map
+const SIGHT_RADIUS: f32 = 8.0;
+const SCAN_RADIUS: f32 = 20.0;
+const SCAN_INTERVAL: f32 = 3.0;
+// mana drawn from batteries per pulse
+const SCAN_COST: f32 = 15.0;
+
+pub struct ScannerPlugin;
+
+impl Plugin for ScannerPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Explored>()
+            .init_resource::<Scanner>()
+            .add_systems(Startup, (generate_ore_veins, setup_scanner_ui))
+            .add_systems(Update, (
+                explore_map,
+                scanner_input,
+                scan_pulse,
+                draw_ore_veins,
+                update_scanner_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct OreVein {
+    pub item: Item,
+    pub center: Vec2,
+    pub radius: f32,
+    // ore left in the vein
+    pub richness: u32,
+    pub discovered: bool,
+}
+
+#[derive(Resource, Default)]
+pub struct OreVeins(pub Vec<OreVein>);
+
+/// Map chunks the player has been close enough to see.
+#[derive(Resource, Default)]
+pub struct Explored {
+    chunks: HashSet<IVec2>,
+}
+
+impl Explored {
+    pub fn chunk_of(pos: Vec2) -> IVec2 {
+        (pos / EXPLORE_CHUNK_SIZE).floor().as_ivec2()
+    }
+
+    pub fn contains(&self, pos: Vec2) -> bool {
+        self.chunks.contains(&Self::chunk_of(pos))
+    }
+}
+
+#[derive(Resource)]
+pub struct Scanner {
+    pub active: bool,
+    pulse: Timer,
+    // the last pulse couldn't be paid for
+    starved: bool,
+}
+
+impl Default for Scanner {
+    fn default() -> Self {
+        Scanner {
+            active: false,
+            pulse: Timer::from_seconds(SCAN_INTERVAL, TimerMode::Repeating),
+            starved: false,
+        }
+    }
+}
+
+#[derive(Component)]
+struct ScannerText;
+
+fn generate_ore_veins(mut commands: Commands) {
+    let mut rng = rand::thread_rng();
+    let ores = [Item::IronOre, Item::CopperOre, Item::Stone];
+
+    let veins = (0..VEIN_COUNT)
+        .map(|i| OreVein {
+            item: ores[i % ores.len()],
+            center: Vec2::new(
+                rng.gen_range(-VEIN_SPREAD..VEIN_SPREAD),
+                rng.gen_range(-VEIN_SPREAD..VEIN_SPREAD),
+            ),
+            radius: rng.gen_range(1.5..3.5),
+            richness: rng.gen_range(200..MAX_RICHNESS),
+            discovered: false,
+        })
+        .collect();
+
+    commands.insert_resource(OreVeins(veins));
+}
+
+fn explore_map(
+    mut explored: ResMut<Explored>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let player = player_query.get_single().expect("not one player").translation.xz();
+
+    // mark every chunk whose center is in sight
+    let reach = (SIGHT_RADIUS / EXPLORE_CHUNK_SIZE).ceil() as i32;
+    let here = Explored::chunk_of(player);
+    for x in -reach..=reach {
+        for y in -reach..=reach {
+            let chunk = here + IVec2::new(x, y);
+            let center = (chunk.as_vec2() + 0.5) * EXPLORE_CHUNK_SIZE;
+            if center.distance(player) <= SIGHT_RADIUS {
+                explored.chunks.insert(chunk);
+            }
+        }
+    }
+}
+
+fn scanner_input(
+    keys: Res<Input<KeyCode>>,
+    mut scanner: ResMut<Scanner>,
+) {
+    if keys.just_pressed(KeyCode::V) {
+        scanner.active = !scanner.active;
+        scanner.starved = false;
+
+        // pulse right away when switched on
+        let duration = scanner.pulse.duration();
+        scanner.pulse.set_elapsed(duration);
+    }
+}
+
+fn scan_pulse(
+    time: Res<Time>,
+    explored: Res<Explored>,
+    mut scanner: ResMut<Scanner>,
+    mut veins: ResMut<OreVeins>,
+    mut battery_query: Query<&mut ManaBattery>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if !scanner.active {
+        return;
+    }
+
+    if !scanner.pulse.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    // the pulse is paid for out of the batteries
+    let stored: f32 = battery_query.iter().map(|b| b.charge).sum();
+    scanner.starved = stored < SCAN_COST;
+    if scanner.starved {
+        return;
+    }
+
+    let mut remaining = SCAN_COST;
+    for mut battery in &mut battery_query {
+        let drawn = battery.charge.min(remaining);
+        battery.charge -= drawn;
+        remaining -= drawn;
+    }
+
+    // only ground the player has seen can be scanned
+    let player = player_query.get_single().expect("not one player").translation.xz();
+    for vein in &mut veins.0 {
+        if vein.center.distance(player) <= SCAN_RADIUS && explored.contains(vein.center) {
+            vein.discovered = true;
+        }
+    }
+}
+
+fn draw_ore_veins(
+    scanner: Res<Scanner>,
+    veins: Res<OreVeins>,
+    mut gizmos: Gizmos,
+) {
+    if !scanner.active {
+        return;
+    }
+
+    for vein in veins.0.iter().filter(|v| v.discovered) {
+        let center = Vec3::new(vein.center.x, 0.05, vein.center.y);
+        let color = vein.item.color();
+
+        // the inner ring shrinks as the vein is used up
+        let fill = vein.richness as f32 / MAX_RICHNESS as f32;
+        gizmos.circle(center, Vec3::Y, vein.radius, color);
+        gizmos.circle(center, Vec3::Y, vein.radius * fill.max(0.1), color);
+    }
+}
+
+fn setup_scanner_ui(mut commands: Commands) {
+    commands.spawn((
+        ScannerText,
+        TextBundle::from_section("", TextStyle {
+            font_size: 16.0,
+            color: Color::WHITE,
+            ..default()
+        })
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(20.0),
+            top: Val::Px(20.0),
+            ..default()
+        }),
+    ));
+}
+
+fn update_scanner_ui(
+    locale: Res<Locale>,
+    scanner: Res<Scanner>,
+    veins: Res<OreVeins>,
+    player_query: Query<&Transform, With<Player>>,
+    mut text_query: Query<&mut Text, With<ScannerText>>,
+) {
+    let mut text = text_query.get_single_mut().expect("not one scanner text");
+
+    if !scanner.active {
+        text.sections[0].value.clear();
+        return;
+    }
+
+    let player = player_query.get_single().expect("not one player").translation.xz();
+    let closest = veins.0.iter()
+        .filter(|v| v.discovered)
+        .min_by(|a, b| a.center.distance(player).total_cmp(&b.center.distance(player)));
+
+    let mut content = locale.fill("Scanner | {} veins found", &[&veins.0.iter().filter(|v| v.discovered).count()]);
+    if let Some(vein) = closest {
+        content += "\n";
+        content += &locale.fill("closest: {} ({}) {}m", &[
+            &locale.get(vein.item.name()),
+            &vein.richness,
+            &format!("{:.0}", vein.center.distance(player)),
+        ]);
+    }
+    if scanner.starved {
+        content += "\n";
+        content += locale.get("not enough stored mana");
+    }
+
+    text.sections[0].value = content;
+}
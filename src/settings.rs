@@ -0,0 +1,484 @@
+use bevy::{
+    prelude::*,
+    ui::FocusPolicy,
+    window::{PresentMode, PrimaryWindow},
+};
+
+use crate::{
+    camera::Camera,
+    input::ControlsMenu,
+    locale::{Language, Locale, Localized},
+    state::GameState,
+};
+
+const BUTTON_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.7);
+const FOG_COLOR: Color = Color::rgba(0.35, 0.48, 0.66, 1.0);
+const FOG_START: f32 = 15.0;
+const FOG_END: f32 = 50.0;
+
+const STANDARD: Palette = Palette {
+    health: Color::rgb(0.78, 0.16, 0.16),
+    mana: Color::rgb(0.24, 0.39, 0.9),
+    stamina: Color::rgb(0.35, 0.75, 0.27),
+    good: Color::rgb(0.6, 1.0, 0.6),
+    bad: Color::rgb(1.0, 0.5, 0.4),
+};
+// told apart by brightness as much as hue, so red against green never matters
+const COLORBLIND: Palette = Palette {
+    health: Color::rgb(0.84, 0.37, 0.0),
+    mana: Color::rgb(0.0, 0.45, 0.7),
+    stamina: Color::rgb(0.94, 0.89, 0.26),
+    good: Color::rgb(0.34, 0.71, 0.91),
+    bad: Color::rgb(0.9, 0.62, 0.0),
+};
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Settings>()
+            .init_resource::<SettingsMenu>()
+            // settings only open on top of a menu
+            .add_systems(OnExit(GameState::MainMenu), close_settings)
+            .add_systems(OnExit(GameState::Paused), close_settings)
+            .add_systems(Update, (
+                show_settings_menu,
+                settings_input,
+                update_settings_ui,
+                apply_settings,
+            ).chain());
+    }
+}
+
+/// Everything the player can change about the game, applied as soon as
+/// it changes.
+#[derive(Resource)]
+pub struct Settings {
+    pub fog: bool,
+    pub shadows: bool,
+    pub vsync: bool,
+    // all from zero to one
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+    pub ui_volume: f32,
+    pub mouse_sensitivity: f32,
+    pub invert_y: bool,
+    // degrees, vertical
+    pub fov: f32,
+    // alt frees the cursor until pressed again rather than while held
+    pub toggle_cursor: bool,
+    pub crosshair: bool,
+    // spreads out while moving or casting
+    pub dynamic_crosshair: bool,
+    // camera up rather than north up
+    pub minimap_rotates: bool,
+    pub language: Language,
+    // how big the whole interface is drawn
+    pub ui_scale: f32,
+    pub hud_opacity: f32,
+    pub colorblind: bool,
+    pub show_bars: bool,
+    pub show_hotbar: bool,
+    pub show_minimap: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            fog: true,
+            shadows: true,
+            vsync: true,
+            master_volume: 0.8,
+            music_volume: 0.6,
+            effects_volume: 0.8,
+            ui_volume: 0.8,
+            mouse_sensitivity: 0.5,
+            invert_y: false,
+            fov: 45.0,
+            toggle_cursor: false,
+            crosshair: true,
+            dynamic_crosshair: true,
+            minimap_rotates: false,
+            language: Language::English,
+            ui_scale: 1.0,
+            hud_opacity: 1.0,
+            colorblind: false,
+            show_bars: true,
+            show_hotbar: true,
+            show_minimap: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn palette(&self) -> &'static Palette {
+        if self.colorblind { &COLORBLIND } else { &STANDARD }
+    }
+}
+
+/// Colors that carry meaning, swapped as a set.
+pub struct Palette {
+    pub health: Color,
+    pub mana: Color,
+    pub stamina: Color,
+    // enough of something, or not
+    pub good: Color,
+    pub bad: Color,
+}
+
+#[derive(Resource, Default)]
+pub struct SettingsMenu {
+    pub open: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Setting {
+    Fog,
+    Shadows,
+    Vsync,
+    MasterVolume,
+    MusicVolume,
+    EffectsVolume,
+    UiVolume,
+    Sensitivity,
+    InvertY,
+    Fov,
+    ToggleCursor,
+    Crosshair,
+    DynamicCrosshair,
+    MinimapRotates,
+    Language,
+    UiScale,
+    HudOpacity,
+    Colorblind,
+    Bars,
+    Hotbar,
+    Minimap,
+}
+
+impl Setting {
+    const ALL: [Setting; 21] = [
+        Setting::Fog,
+        Setting::Shadows,
+        Setting::Vsync,
+        Setting::MasterVolume,
+        Setting::MusicVolume,
+        Setting::EffectsVolume,
+        Setting::UiVolume,
+        Setting::Sensitivity,
+        Setting::InvertY,
+        Setting::Fov,
+        Setting::ToggleCursor,
+        Setting::Crosshair,
+        Setting::DynamicCrosshair,
+        Setting::MinimapRotates,
+        Setting::Language,
+        Setting::UiScale,
+        Setting::HudOpacity,
+        Setting::Colorblind,
+        Setting::Bars,
+        Setting::Hotbar,
+        Setting::Minimap,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Setting::Fog => "Fog",
+            Setting::Shadows => "Shadows",
+            Setting::Vsync => "VSync",
+            Setting::MasterVolume => "Master volume",
+            Setting::MusicVolume => "Music volume",
+            Setting::EffectsVolume => "Effects volume",
+            Setting::UiVolume => "Interface volume",
+            Setting::Sensitivity => "Mouse sensitivity",
+            Setting::InvertY => "Invert mouse",
+            Setting::Fov => "Field of view",
+            Setting::ToggleCursor => "Alt toggles cursor",
+            Setting::Crosshair => "Crosshair",
+            Setting::DynamicCrosshair => "Dynamic crosshair",
+            Setting::MinimapRotates => "Rotating minimap",
+            Setting::Language => "Language",
+            Setting::UiScale => "UI scale",
+            Setting::HudOpacity => "HUD opacity",
+            Setting::Colorblind => "Colorblind palette",
+            Setting::Bars => "Status bars",
+            Setting::Hotbar => "Hotbar",
+            Setting::Minimap => "Minimap",
+        }
+    }
+
+    fn section(&self) -> &'static str {
+        match self {
+            Setting::Fog | Setting::Shadows | Setting::Vsync => "Graphics",
+            Setting::MasterVolume | Setting::MusicVolume | Setting::EffectsVolume
+                | Setting::UiVolume => "Audio",
+            Setting::Sensitivity | Setting::InvertY | Setting::Fov => "Camera",
+            Setting::ToggleCursor => "Controls",
+            Setting::Crosshair | Setting::DynamicCrosshair | Setting::MinimapRotates
+                | Setting::Language => "Interface",
+            Setting::UiScale | Setting::HudOpacity | Setting::Colorblind
+                | Setting::Bars | Setting::Hotbar | Setting::Minimap => "HUD",
+        }
+    }
+
+    fn value(&self, settings: &Settings) -> String {
+        let on_off = |b: bool| if b { "On" } else { "Off" }.to_string();
+        match self {
+            Setting::Fog => on_off(settings.fog),
+            Setting::Shadows => on_off(settings.shadows),
+            Setting::Vsync => on_off(settings.vsync),
+            Setting::MasterVolume => format!("{:.0}%", settings.master_volume * 100.0),
+            Setting::MusicVolume => format!("{:.0}%", settings.music_volume * 100.0),
+            Setting::EffectsVolume => format!("{:.0}%", settings.effects_volume * 100.0),
+            Setting::UiVolume => format!("{:.0}%", settings.ui_volume * 100.0),
+            Setting::Sensitivity => format!("{:.1}", settings.mouse_sensitivity),
+            Setting::InvertY => on_off(settings.invert_y),
+            Setting::Fov => format!("{:.0}", settings.fov),
+            Setting::ToggleCursor => on_off(settings.toggle_cursor),
+            Setting::Crosshair => on_off(settings.crosshair),
+            Setting::DynamicCrosshair => on_off(settings.dynamic_crosshair),
+            Setting::MinimapRotates => on_off(settings.minimap_rotates),
+            Setting::Language => settings.language.name().to_string(),
+            Setting::UiScale => format!("{:.0}%", settings.ui_scale * 100.0),
+            Setting::HudOpacity => format!("{:.0}%", settings.hud_opacity * 100.0),
+            Setting::Colorblind => on_off(settings.colorblind),
+            Setting::Bars => on_off(settings.show_bars),
+            Setting::Hotbar => on_off(settings.show_hotbar),
+            Setting::Minimap => on_off(settings.show_minimap),
+        }
+    }
+
+    // one step up or down, switches just flip
+    fn adjust(&self, settings: &mut Settings, step: f32) {
+        let volume = |v: &mut f32| *v = (*v + step * 0.1).clamp(0.0, 1.0);
+        match self {
+            Setting::Fog => settings.fog = !settings.fog,
+            Setting::Shadows => settings.shadows = !settings.shadows,
+            Setting::Vsync => settings.vsync = !settings.vsync,
+            Setting::MasterVolume => volume(&mut settings.master_volume),
+            Setting::MusicVolume => volume(&mut settings.music_volume),
+            Setting::EffectsVolume => volume(&mut settings.effects_volume),
+            Setting::UiVolume => volume(&mut settings.ui_volume),
+            Setting::Sensitivity => {
+                settings.mouse_sensitivity = (settings.mouse_sensitivity + step * 0.1).clamp(0.1, 2.0);
+            }
+            Setting::InvertY => settings.invert_y = !settings.invert_y,
+            Setting::Fov => settings.fov = (settings.fov + step * 5.0).clamp(30.0, 110.0),
+            Setting::ToggleCursor => settings.toggle_cursor = !settings.toggle_cursor,
+            Setting::Crosshair => settings.crosshair = !settings.crosshair,
+            Setting::DynamicCrosshair => settings.dynamic_crosshair = !settings.dynamic_crosshair,
+            Setting::MinimapRotates => settings.minimap_rotates = !settings.minimap_rotates,
+            Setting::Language => {
+                let all = Language::ALL;
+                let i = all.iter().position(|l| *l == settings.language).unwrap_or(0) as isize;
+                settings.language = all[(i + step as isize).rem_euclid(all.len() as isize) as usize];
+            }
+            Setting::UiScale => settings.ui_scale = (settings.ui_scale + step * 0.1).clamp(0.5, 2.0),
+            Setting::HudOpacity => settings.hud_opacity = (settings.hud_opacity + step * 0.1).clamp(0.2, 1.0),
+            Setting::Colorblind => settings.colorblind = !settings.colorblind,
+            Setting::Bars => settings.show_bars = !settings.show_bars,
+            Setting::Hotbar => settings.show_hotbar = !settings.show_hotbar,
+            Setting::Minimap => settings.show_minimap = !settings.show_minimap,
+        }
+    }
+}
+
+#[derive(Component)]
+struct SettingsPanel;
+
+#[derive(Component)]
+struct SettingButton {
+    setting: Setting,
+    step: f32,
+}
+
+#[derive(Component)]
+struct SettingValue(Setting);
+
+#[derive(Component)]
+struct BackButton;
+
+#[derive(Component)]
+struct ControlsButton;
+
+fn close_settings(mut menu: ResMut<SettingsMenu>) {
+    menu.open = false;
+}
+
+fn show_settings_menu(
+    mut commands: Commands,
+    menu: Res<SettingsMenu>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    panel_query: Query<Entity, With<SettingsPanel>>,
+) {
+    if !menu.is_changed() {
+        return;
+    }
+    for panel in &panel_query {
+        commands.entity(panel).despawn_recursive();
+    }
+    if !menu.open {
+        return;
+    }
+
+    let text = |value: &str, size: f32| TextBundle::from_section(value, TextStyle {
+        font_size: size,
+        color: Color::WHITE,
+        ..default()
+    });
+    let button = |width: f32| ButtonBundle {
+        style: Style {
+            width: Val::Px(width),
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        background_color: BUTTON_COLOR.into(),
+        ..default()
+    };
+
+    commands.spawn((
+        SettingsPanel,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                // more settings than fit on a small screen wrap into another column
+                flex_wrap: FlexWrap::Wrap,
+                align_items: AlignItems::Center,
+                align_content: AlignContent::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(4.0),
+                column_gap: Val::Px(32.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+            // over the menu it was opened from, which can't be clicked through it
+            z_index: ZIndex::Global(11),
+            focus_policy: FocusPolicy::Block,
+            ..default()
+        },
+    )).with_children(|parent| {
+        let mut section = "";
+        for setting in Setting::ALL {
+            if setting.section() != section {
+                section = setting.section();
+                parent.spawn((Localized(section), text(section, 22.0).with_style(Style {
+                    margin: UiRect::top(Val::Px(8.0)),
+                    ..default()
+                })));
+            }
+
+            parent.spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(8.0),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            }).with_children(|row| {
+                row.spawn((Localized(setting.name()), text(setting.name(), 18.0).with_style(Style {
+                    width: Val::Px(200.0),
+                    ..default()
+                })));
+                row.spawn((SettingButton { setting, step: -1.0 }, button(32.0)))
+                    .with_children(|b| { b.spawn(text("<", 18.0)); });
+                row.spawn((SettingValue(setting), text(locale.get(&setting.value(&settings)), 18.0).with_style(Style {
+                    width: Val::Px(64.0),
+                    ..default()
+                })));
+                row.spawn((SettingButton { setting, step: 1.0 }, button(32.0)))
+                    .with_children(|b| { b.spawn(text(">", 18.0)); });
+            });
+        }
+
+        // a little apart from the settings above it
+        let mut controls = button(200.0);
+        controls.style.margin = UiRect::top(Val::Px(12.0));
+        parent.spawn((ControlsButton, controls)).with_children(|b| { b.spawn((Localized("Controls"), text("Controls", 24.0))); });
+        parent.spawn((BackButton, button(200.0)))
+            .with_children(|b| { b.spawn((Localized("Back"), text("Back", 24.0))); });
+    });
+}
+
+fn settings_input(
+    mut menu: ResMut<SettingsMenu>,
+    mut controls_menu: ResMut<ControlsMenu>,
+    mut settings: ResMut<Settings>,
+    button_query: Query<(&SettingButton, &Interaction), Changed<Interaction>>,
+    back_query: Query<&Interaction, (With<BackButton>, Changed<Interaction>)>,
+    controls_query: Query<&Interaction, (With<ControlsButton>, Changed<Interaction>)>,
+) {
+    for (button, interaction) in &button_query {
+        if *interaction == Interaction::Pressed {
+            button.setting.adjust(&mut settings, button.step);
+        }
+    }
+    if back_query.iter().any(|i| *i == Interaction::Pressed) {
+        menu.open = false;
+    }
+    if controls_query.iter().any(|i| *i == Interaction::Pressed) {
+        controls_menu.open = true;
+    }
+}
+
+fn update_settings_ui(
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    mut value_query: Query<(&SettingValue, &mut Text)>,
+) {
+    if !settings.is_changed() && !locale.is_changed() {
+        return;
+    }
+
+    for (SettingValue(setting), mut text) in &mut value_query {
+        // numbers pass through untouched, on and off get translated
+        text.sections[0].value = locale.get(&setting.value(&settings)).to_string();
+    }
+}
+
+fn apply_settings(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut ui_scale: ResMut<UiScale>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut camera_query: Query<(Entity, &mut Camera, &mut Projection)>,
+    mut point_light_query: Query<&mut PointLight>,
+    mut directional_light_query: Query<&mut DirectionalLight>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let mut window = window_query.get_single_mut().expect("not one window");
+    window.present_mode = if settings.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync };
+    ui_scale.0 = settings.ui_scale as f64;
+
+    for mut light in &mut point_light_query {
+        light.shadows_enabled = settings.shadows;
+    }
+    for mut light in &mut directional_light_query {
+        light.shadows_enabled = settings.shadows;
+    }
+
+    let (entity, mut camera, mut projection) = camera_query.get_single_mut().expect("not one camera");
+    camera.mouse_sensitivity = settings.mouse_sensitivity;
+    camera.invert_y = settings.invert_y;
+    if let Projection::Perspective(perspective) = projection.as_mut() {
+        perspective.fov = settings.fov.to_radians();
+    }
+    if settings.fog {
+        commands.entity(entity).insert(FogSettings {
+            color: FOG_COLOR,
+            falloff: FogFalloff::Linear { start: FOG_START, end: FOG_END },
+            ..default()
+        });
+    } else {
+        commands.entity(entity).remove::<FogSettings>();
+    }
+}
@@ -0,0 +1,444 @@
+use bevy::{
+    audio::{SpatialAudioSink, SpatialListener, Volume},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_xpbd_3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    audio::{Bus, Mixer},
+    camera::Camera,
+    controls::{Footstep, Landed, SurfaceMaterial},
+    enemy::Hostile,
+    health::Died,
+    hitbox::MeleeSwing,
+    loading::Preload,
+    machine::Machine,
+    magic::{SpellBook, SpellCast, SpellCastStarted, SpellHit},
+    perception::{Alertness, Awareness},
+    projectile::ProjectileImpact,
+    state::GameplaySet,
+    Player,
+};
+
+// distance between the listener's ears, in world units
+const EAR_GAP: f32 = 0.3;
+// sounds fade out linearly to nothing at this distance
+const MAX_DISTANCE: f32 = 40.0;
+// hums stop a little further out than they start, so walking along the
+// edge doesn't keep respawning them
+const HUM_MARGIN: f32 = 5.0;
+/// Shrinks the world for the audio backend, which would otherwise fall off
+/// with the square of the distance. Everything stays within a unit of the
+/// listener, so the backend only pans, and `falloff` does the fading.
+pub const SPATIAL_SCALE: f32 = 1.0 / MAX_DISTANCE;
+// landings slower than this are just another footstep
+const THUD_SPEED: f32 = 4.0;
+// and this fast or faster thud at full volume
+const HARD_LANDING_SPEED: f32 = 15.0;
+// bumps gentler than this make no sound
+const IMPACT_SPEED: f32 = 2.0;
+const LOUD_IMPACT_SPEED: f32 = 12.0;
+// seconds before the same body can clatter again
+const IMPACT_COOLDOWN: f32 = 0.3;
+// impacts heard per second at most, however many things are falling over
+const IMPACTS_PER_SECOND: f32 = 6.0;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<PlaySound>()
+            .add_systems(Startup, preload_sounds)
+            .add_systems(Update, attach_listener)
+            .add_systems(Update, (
+                sounds_from_spells,
+                sounds_from_combat,
+                sounds_from_footsteps,
+                sounds_from_impacts,
+                hum_machines,
+                play_sounds,
+                fade_emitters,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sound {
+    CastStart,
+    Cast,
+    Impact,
+    EnemyAlert,
+    Swing,
+    Death,
+    MachineHum,
+    Footstep(SurfaceMaterial),
+    Landing(SurfaceMaterial),
+    Collision(SurfaceMaterial),
+}
+
+fn surface_name(surface: SurfaceMaterial) -> &'static str {
+    match surface {
+        SurfaceMaterial::Grass => "grass",
+        SurfaceMaterial::Metal => "metal",
+        SurfaceMaterial::Crystal => "crystal",
+    }
+}
+
+impl Sound {
+    fn all() -> Vec<Sound> {
+        let mut sounds = vec![
+            Sound::CastStart,
+            Sound::Cast,
+            Sound::Impact,
+            Sound::EnemyAlert,
+            Sound::Swing,
+            Sound::Death,
+            Sound::MachineHum,
+        ];
+        for surface in SurfaceMaterial::ALL {
+            sounds.extend([Sound::Footstep(surface), Sound::Landing(surface), Sound::Collision(surface)]);
+        }
+        sounds
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Sound::CastStart => "cast_start".into(),
+            Sound::Cast => "cast".into(),
+            Sound::Impact => "impact".into(),
+            Sound::EnemyAlert => "enemy_alert".into(),
+            Sound::Swing => "swing".into(),
+            Sound::Death => "death".into(),
+            Sound::MachineHum => "machine_hum".into(),
+            Sound::Footstep(surface) => format!("footstep_{}", surface_name(*surface)),
+            Sound::Landing(surface) => format!("landing_{}", surface_name(*surface)),
+            Sound::Collision(surface) => format!("collision_{}", surface_name(*surface)),
+        }
+    }
+
+    // recordings to pick from at random, numbered from 1
+    fn variations(&self) -> u32 {
+        match self {
+            Sound::Footstep(_) => 4,
+            Sound::Landing(_) => 2,
+            Sound::Collision(_) => 3,
+            _ => 1,
+        }
+    }
+
+    // how far the playback speed may wander either way, so repeats don't drone
+    fn pitch_spread(&self) -> f32 {
+        match self {
+            Sound::Footstep(_) => 0.1,
+            Sound::Landing(_) => 0.05,
+            Sound::Collision(_) => 0.15,
+            _ => 0.0,
+        }
+    }
+
+    // every recording of it
+    fn paths(&self) -> Vec<String> {
+        match self.variations() {
+            1 => vec![format!("sounds/{}.ogg", self.name())],
+            n => (1..=n).map(|i| format!("sounds/{}_{}.ogg", self.name(), i)).collect(),
+        }
+    }
+
+    // one of the recordings at random
+    fn path(&self) -> String {
+        let mut paths = self.paths();
+        paths.swap_remove(rand::thread_rng().gen_range(0..paths.len()))
+    }
+
+    // how loud it is right next to the listener
+    fn volume(&self) -> f32 {
+        match self {
+            Sound::CastStart => 0.5,
+            Sound::MachineHum => 0.4,
+            Sound::Death => 0.9,
+            Sound::Footstep(_) => 0.3,
+            Sound::Landing(_) => 0.8,
+            _ => 0.7,
+        }
+    }
+}
+
+/// Plays `sound` once, out in the world at `at`, `loudness` times as loud
+/// as it normally is.
+#[derive(Event)]
+pub struct PlaySound {
+    pub sound: Sound,
+    pub at: Vec3,
+    pub loudness: f32,
+}
+
+/// A sound playing somewhere in the world, kept at the right volume as the
+/// listener moves.
+#[derive(Component)]
+pub struct SoundEmitter {
+    pub sound: Sound,
+    pub loudness: f32,
+}
+
+// the looping hum of a working machine, a child of it
+#[derive(Component)]
+struct Humming(Entity);
+
+fn falloff(distance: f32) -> f32 {
+    (1.0 - distance / MAX_DISTANCE).clamp(0.0, 1.0)
+}
+
+fn emitter_volume(emitter: &SoundEmitter, at: Vec3, listener: Vec3, mixer: &Mixer) -> f32 {
+    emitter.sound.volume() * emitter.loudness * falloff(at.distance(listener)) * mixer.volume(Bus::Effects)
+}
+
+// the camera rig hears on the player's behalf
+fn preload_sounds(asset_server: Res<AssetServer>, mut preload: ResMut<Preload>) {
+    for path in Sound::all().iter().flat_map(|s| s.paths()) {
+        preload.add(asset_server.load::<AudioSource>(path));
+    }
+}
+
+fn attach_listener(
+    mut commands: Commands,
+    camera_query: Query<Entity, Added<Camera>>,
+) {
+    for camera in &camera_query {
+        commands.entity(camera).insert(SpatialListener::new(EAR_GAP));
+    }
+}
+
+fn sounds_from_spells(
+    book: Res<SpellBook>,
+    transform_query: Query<&GlobalTransform>,
+    mut started_events: EventReader<SpellCastStarted>,
+    mut cast_events: EventReader<SpellCast>,
+    mut hit_events: EventReader<SpellHit>,
+    mut impact_events: EventReader<ProjectileImpact>,
+    mut sound_events: EventWriter<PlaySound>,
+) {
+    let at = |entity: Entity| transform_query.get(entity).ok().map(|t| t.translation());
+
+    // instant spells go straight to the cast sound
+    for event in started_events.read().filter(|e| book.get(e.spell).is_some_and(|s| s.cast_time > 0.0)) {
+        if let Some(at) = at(event.caster) {
+            sound_events.send(PlaySound { sound: Sound::CastStart, at, loudness: 1.0 });
+        }
+    }
+    for event in cast_events.read() {
+        if let Some(at) = at(event.caster) {
+            sound_events.send(PlaySound { sound: Sound::Cast, at, loudness: 1.0 });
+        }
+    }
+    for event in hit_events.read() {
+        if let Some(at) = at(event.target) {
+            sound_events.send(PlaySound { sound: Sound::Impact, at, loudness: 1.0 });
+        }
+    }
+    for event in impact_events.read() {
+        sound_events.send(PlaySound { sound: Sound::Impact, at: event.position, loudness: 1.0 });
+    }
+}
+
+fn sounds_from_combat(
+    enemy_query: Query<(Entity, &GlobalTransform, &Awareness), With<Hostile>>,
+    hostile_query: Query<&GlobalTransform, With<Hostile>>,
+    mut swing_events: EventReader<MeleeSwing>,
+    mut died_events: EventReader<Died>,
+    mut sound_events: EventWriter<PlaySound>,
+    mut alerted: Local<HashSet<Entity>>,
+) {
+    // once as it notices the player, not every frame it stays alert
+    for (entity, transform, awareness) in &enemy_query {
+        if awareness.alertness() != Alertness::Alert {
+            alerted.remove(&entity);
+        } else if alerted.insert(entity) {
+            sound_events.send(PlaySound { sound: Sound::EnemyAlert, at: transform.translation(), loudness: 1.0 });
+        }
+    }
+    alerted.retain(|e| enemy_query.contains(*e));
+
+    for event in swing_events.read() {
+        sound_events.send(PlaySound { sound: Sound::Swing, at: event.origin, loudness: 1.0 });
+    }
+    for event in died_events.read() {
+        if let Ok(transform) = hostile_query.get(event.entity) {
+            sound_events.send(PlaySound { sound: Sound::Death, at: transform.translation(), loudness: 1.0 });
+        }
+    }
+}
+
+fn sounds_from_footsteps(
+    mut footstep_events: EventReader<Footstep>,
+    mut landed_events: EventReader<Landed>,
+    mut sound_events: EventWriter<PlaySound>,
+) {
+    for event in footstep_events.read() {
+        sound_events.send(PlaySound { sound: Sound::Footstep(event.surface), at: event.at, loudness: 1.0 });
+    }
+    for event in landed_events.read() {
+        let (sound, loudness) = if event.fall_speed < THUD_SPEED {
+            (Sound::Footstep(event.surface), 1.0)
+        } else {
+            (Sound::Landing(event.surface), (event.fall_speed / HARD_LANDING_SPEED).min(1.0))
+        };
+        sound_events.send(PlaySound { sound, at: event.at, loudness });
+    }
+}
+
+#[derive(Default)]
+struct ImpactLimiter {
+    // what everything was doing before this frame's collisions stopped it
+    velocities: HashMap<Entity, Vec3>,
+    // elapsed seconds each body last made a sound
+    last_heard: HashMap<Entity, f32>,
+    // impacts that may still be heard, refilled over time
+    budget: f32,
+}
+
+fn sounds_from_impacts(
+    time: Res<Time>,
+    body_query: Query<(Entity, &LinearVelocity)>,
+    transform_query: Query<&GlobalTransform>,
+    surface_query: Query<&SurfaceMaterial>,
+    // the player's own bumps are footsteps and landings
+    ignored_query: Query<(), Or<(With<Player>, With<Sensor>)>>,
+    mut collision_events: EventReader<CollisionStarted>,
+    mut sound_events: EventWriter<PlaySound>,
+    mut limiter: Local<ImpactLimiter>,
+) {
+    let now = time.elapsed_seconds();
+    limiter.budget = (limiter.budget + time.delta_seconds() * IMPACTS_PER_SECOND).min(IMPACTS_PER_SECOND);
+
+    let mut impacts: Vec<_> = collision_events.read()
+        .filter(|CollisionStarted(a, b)| !ignored_query.contains(*a) && !ignored_query.contains(*b))
+        .filter_map(|CollisionStarted(a, b)| {
+            let velocity = |e: &Entity| limiter.velocities.get(e).copied().unwrap_or(Vec3::ZERO);
+            let speed = velocity(a).distance(velocity(b));
+            (speed >= IMPACT_SPEED).then_some((*a, *b, speed))
+        })
+        .collect();
+    // when there's more than the budget allows, the hardest hits are heard
+    impacts.sort_by(|x, y| y.2.total_cmp(&x.2));
+
+    for (a, b, speed) in impacts {
+        if limiter.budget < 1.0 {
+            break;
+        }
+        let cooling = |e: Entity| limiter.last_heard.get(&e).is_some_and(|t| now - t < IMPACT_COOLDOWN);
+        if cooling(a) || cooling(b) {
+            continue;
+        }
+        // the moving one is where the sound comes from
+        let moving = |e: Entity| limiter.velocities.get(&e).map_or(0.0, |v| v.length());
+        let source = if moving(a) >= moving(b) { a } else { b };
+        let Ok(transform) = transform_query.get(source) else {
+            continue;
+        };
+
+        let surface = |e: Entity| surface_query.get(e).copied().unwrap_or_default();
+        let loudness = ((speed - IMPACT_SPEED) / (LOUD_IMPACT_SPEED - IMPACT_SPEED)).clamp(0.1, 1.0);
+        sound_events.send(PlaySound {
+            sound: Sound::Collision(surface(a).max(surface(b))),
+            at: transform.translation(),
+            loudness,
+        });
+
+        limiter.budget -= 1.0;
+        limiter.last_heard.insert(a, now);
+        limiter.last_heard.insert(b, now);
+    }
+
+    limiter.last_heard.retain(|_, t| now - *t < IMPACT_COOLDOWN);
+    limiter.velocities = body_query.iter().map(|(e, v)| (e, v.0)).collect();
+}
+
+// only machines close enough to hear get a looping sink
+fn hum_machines(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    listener_query: Query<&GlobalTransform, With<SpatialListener>>,
+    machine_query: Query<(Entity, &Machine, &GlobalTransform, Option<&Humming>)>,
+) {
+    let Ok(listener) = listener_query.get_single() else {
+        return;
+    };
+
+    for (entity, machine, transform, humming) in &machine_query {
+        let distance = transform.translation().distance(listener.translation());
+        let range = if humming.is_some() { MAX_DISTANCE + HUM_MARGIN } else { MAX_DISTANCE };
+        match (machine.working && machine.enabled && distance <= range, humming) {
+            (true, None) => {
+                let sound = Sound::MachineHum;
+                let hum = commands.spawn((
+                    SoundEmitter { sound, loudness: 1.0 },
+                    AudioBundle {
+                        source: asset_server.load(sound.path()),
+                        // faded in by fade_emitters once it's playing
+                        settings: PlaybackSettings::LOOP.with_spatial(true).with_volume(Volume::new_relative(0.0)),
+                    },
+                    TransformBundle::default(),
+                )).id();
+                commands.entity(entity).add_child(hum).insert(Humming(hum));
+            }
+            (false, Some(Humming(hum))) => {
+                commands.entity(*hum).despawn_recursive();
+                commands.entity(entity).remove::<Humming>();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn play_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mixer: Res<Mixer>,
+    listener_query: Query<&GlobalTransform, With<SpatialListener>>,
+    mut sound_events: EventReader<PlaySound>,
+) {
+    let Ok(listener) = listener_query.get_single() else {
+        sound_events.clear();
+        return;
+    };
+
+    for event in sound_events.read() {
+        let emitter = SoundEmitter { sound: event.sound, loudness: event.loudness };
+        let volume = emitter_volume(&emitter, event.at, listener.translation(), &mixer);
+        // too far off to hear, no point playing it
+        if volume <= 0.0 {
+            continue;
+        }
+
+        let spread = event.sound.pitch_spread();
+        let speed = 1.0 + rand::thread_rng().gen_range(-spread..=spread);
+        commands.spawn((
+            emitter,
+            AudioBundle {
+                source: asset_server.load(event.sound.path()),
+                settings: PlaybackSettings::DESPAWN
+                    .with_spatial(true)
+                    .with_volume(Volume::new_relative(volume))
+                    .with_speed(speed),
+            },
+            TransformBundle::from_transform(Transform::from_translation(event.at)),
+        ));
+    }
+}
+
+fn fade_emitters(
+    mixer: Res<Mixer>,
+    listener_query: Query<&GlobalTransform, With<SpatialListener>>,
+    emitter_query: Query<(&SoundEmitter, &GlobalTransform, &SpatialAudioSink)>,
+) {
+    let Ok(listener) = listener_query.get_single() else {
+        return;
+    };
+
+    for (emitter, transform, sink) in &emitter_query {
+        sink.set_volume(emitter_volume(emitter, transform.translation(), listener.translation(), &mixer));
+    }
+}
@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    ai::{Archetype, Brain},
+    cooldown::{Ability, Cooldowns},
+    death::DeathEffect,
+    enemy::Hostile,
+    faction::{Faction, Member},
+    health::{Health, Resistances},
+    layer::Layer,
+    loot::{Loot, LootTableId},
+    nav::NavAgent,
+    perception::Awareness,
+    stagger::{Poise, Staggered},
+    state::GameplaySet,
+    status::{ApplyStatus, Status, StatusKind},
+    wildlife::Wildlife,
+    Player,
+};
+
+const MEND_RADIUS: f32 = 5.0;
+const MEND_HEALING: f32 = 25.0;
+// allies below this fraction of their health are worth mending
+const MEND_THRESHOLD: f32 = 0.7;
+const HEX_RANGE: f32 = 9.0;
+
+pub struct ShamanPlugin;
+
+impl Plugin for ShamanPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<InterruptEnemyCast>()
+            .add_systems(Update, (
+                begin_enemy_casts,
+                interrupt_enemy_casts,
+                advance_enemy_casts,
+                draw_enemy_casts,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EnemySpell {
+    // heals and fortifies nearby hostiles
+    Mend,
+    // smothers the player's mana regeneration
+    Hex,
+}
+
+impl EnemySpell {
+    // seconds of wind-up, which is the window for countering it
+    pub fn cast_time(&self) -> f32 {
+        match self {
+            EnemySpell::Mend => 2.5,
+            EnemySpell::Hex => 1.5,
+        }
+    }
+
+    pub fn cooldown(&self) -> f32 {
+        match self {
+            EnemySpell::Mend => 8.0,
+            EnemySpell::Hex => 6.0,
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            EnemySpell::Mend => Color::LIME_GREEN,
+            EnemySpell::Hex => Color::VIOLET,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Shaman;
+
+/// An enemy spell being wound up, out in the open so it can be countered.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct EnemyCasting {
+    pub spell: EnemySpell,
+    pub elapsed: f32,
+}
+
+impl EnemyCasting {
+    pub fn progress(&self) -> f32 {
+        (self.elapsed / self.spell.cast_time()).min(1.0)
+    }
+}
+
+/// Breaks off an enemy cast, the spell still goes on cooldown.
+#[derive(Event)]
+pub struct InterruptEnemyCast {
+    pub caster: Entity,
+}
+
+pub fn spawn_shaman(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    pos: Vec3,
+) -> Entity {
+    commands.spawn((
+        Hostile,
+        Shaman,
+        Brain::new(Archetype::Shaman, pos),
+        Member(Faction::Guild),
+        Loot(LootTableId::Shaman),
+        DeathEffect::Dissolve,
+        NavAgent::default(),
+        Awareness::default(),
+        Health::new(35.0),
+        // frail, but wrapped in its own magic
+        Resistances { physical: -0.2, arc: 0.3, ..default() },
+        Poise(0.1),
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Capsule { radius: 0.3, depth: 0.6, ..default() })),
+            material: materials.add(Color::rgb_u8(60, 20, 90).into()),
+            transform: Transform::from_translation(pos),
+            ..default()
+        },
+        // bevy only takes bundles of up to 15, so the physics go together
+        (RigidBody::Dynamic, Collider::capsule(0.6, 0.3), LockedAxes::ROTATION_LOCKED, Layer::enemy()),
+    )).id()
+}
+
+fn begin_enemy_casts(
+    mut commands: Commands,
+    cooldowns: Res<Cooldowns>,
+    shaman_query: Query<(Entity, &Transform), (With<Shaman>, Without<EnemyCasting>, Without<Staggered>)>,
+    hostile_query: Query<(&Transform, &Health), (With<Hostile>, Without<Wildlife>)>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let player = player_query.get_single().expect("not one player");
+
+    for (entity, transform) in &shaman_query {
+        let pos = transform.translation;
+        let ready = |spell| cooldowns.is_ready(entity, Ability::EnemySpell(spell));
+
+        // patching up the others comes first
+        let wounded = hostile_query.iter()
+            .any(|(t, h)| t.translation.distance(pos) <= MEND_RADIUS && h.fraction() < MEND_THRESHOLD);
+        let spell = if wounded && ready(EnemySpell::Mend) {
+            EnemySpell::Mend
+        } else if player.translation.distance(pos) <= HEX_RANGE && ready(EnemySpell::Hex) {
+            EnemySpell::Hex
+        } else {
+            continue;
+        };
+
+        commands.entity(entity).insert(EnemyCasting { spell, elapsed: 0.0 });
+    }
+}
+
+fn interrupt_enemy_casts(
+    mut commands: Commands,
+    mut cooldowns: ResMut<Cooldowns>,
+    casting_query: Query<&EnemyCasting>,
+    mut interrupt_events: EventReader<InterruptEnemyCast>,
+) {
+    for event in interrupt_events.read() {
+        let Ok(casting) = casting_query.get(event.caster) else {
+            continue;
+        };
+
+        cooldowns.start(event.caster, Ability::EnemySpell(casting.spell), casting.spell.cooldown());
+        commands.entity(event.caster).remove::<EnemyCasting>();
+    }
+}
+
+fn advance_enemy_casts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cooldowns: ResMut<Cooldowns>,
+    mut casting_query: Query<(Entity, &mut EnemyCasting, &Transform)>,
+    mut hostile_query: Query<(Entity, &Transform, &mut Health), (With<Hostile>, Without<Wildlife>)>,
+    player_query: Query<Entity, With<Player>>,
+    mut status_events: EventWriter<ApplyStatus>,
+) {
+    for (entity, mut casting, transform) in &mut casting_query {
+        casting.elapsed += time.delta_seconds();
+        if casting.elapsed < casting.spell.cast_time() {
+            continue;
+        }
+
+        match casting.spell {
+            EnemySpell::Mend => {
+                for (target, t, mut health) in &mut hostile_query {
+                    if t.translation.distance(transform.translation) > MEND_RADIUS {
+                        continue;
+                    }
+                    health.heal(MEND_HEALING);
+                    status_events.send(ApplyStatus {
+                        target,
+                        status: Status::new(StatusKind::Fortified, 10.0, 0.3),
+                    });
+                }
+            }
+            EnemySpell::Hex => {
+                let player = player_query.get_single().expect("not one player");
+                status_events.send(ApplyStatus {
+                    target: player,
+                    status: Status::new(StatusKind::Hexed, 8.0, 4.0),
+                });
+            }
+        }
+
+        cooldowns.start(entity, Ability::EnemySpell(casting.spell), casting.spell.cooldown());
+        commands.entity(entity).remove::<EnemyCasting>();
+    }
+}
+
+fn draw_enemy_casts(
+    casting_query: Query<(&EnemyCasting, &Transform)>,
+    mut gizmos: Gizmos,
+) {
+    // a ring closing in overhead, the cast goes off once it's gone
+    for (casting, transform) in &casting_query {
+        let center = transform.translation + Vec3::Y * 0.8;
+        gizmos.circle(center, Vec3::Y, 0.2 + (1.0 - casting.progress()), casting.spell.color());
+    }
+}
@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::{AimPoint, BuildingGrid, BuildingKind, Facing, GridPos},
+    item::Item,
+    state::GameplaySet,
+};
+
+pub struct SplitterPlugin;
+
+impl Plugin for SplitterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (configure_splitters, draw_splitter_settings).in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Side {
+    #[default]
+    Left,
+    Right,
+}
+
+impl Side {
+    pub const BOTH: [Side; 2] = [Side::Left, Side::Right];
+
+    pub fn index(self) -> usize {
+        match self {
+            Side::Left => 0,
+            Side::Right => 1,
+        }
+    }
+
+    pub fn other(self) -> Self {
+        match self {
+            Side::Left => Side::Right,
+            Side::Right => Side::Left,
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub struct Splitter {
+    // a filtered output only takes its item, every other item
+    // goes to the unfiltered side
+    pub filters: [Option<Item>; 2],
+    pub input_priority: Option<Side>,
+    pub output_priority: Option<Side>,
+    next_output: Side,
+}
+
+impl Splitter {
+    pub fn tile(&self, pos: GridPos, facing: Facing, side: Side) -> GridPos {
+        BuildingKind::Splitter.tiles(pos, facing)[side.index()]
+    }
+
+    /// Output sides to try for `item`, best first.
+    pub fn output_order(&self, item: Item) -> Vec<Side> {
+        if let Some(side) = Side::BOTH.into_iter().find(|s| self.filters[s.index()] == Some(item)) {
+            return vec![side];
+        }
+
+        let first = self.output_priority.unwrap_or(self.next_output);
+        [first, first.other()].into_iter()
+            .filter(|s| self.filters[s.index()].is_none())
+            .collect()
+    }
+
+    pub fn routed(&mut self, side: Side) {
+        // alternate between outputs to balance them
+        self.next_output = side.other();
+    }
+}
+
+fn cycle_filter(filter: Option<Item>) -> Option<Item> {
+    match filter {
+        None => Some(Item::ALL[0]),
+        Some(item) => {
+            let i = Item::ALL.iter().position(|i| *i == item).expect("unknown item");
+            Item::ALL.get(i + 1).copied()
+        }
+    }
+}
+
+fn toggle(current: Option<Side>, side: Side) -> Option<Side> {
+    (current != Some(side)).then_some(side)
+}
+
+fn configure_splitters(
+    keys: Res<Input<KeyCode>>,
+    aim: Res<AimPoint>,
+    grid: Res<BuildingGrid>,
+    mut splitter_query: Query<(&mut Splitter, &GridPos, &Facing)>,
+) {
+    let Some(aim) = aim.0 else {
+        return;
+    };
+
+    let aimed = GridPos::from_world(aim);
+    let Some(entity) = grid.get(aimed) else {
+        return;
+    };
+
+    let Ok((mut splitter, pos, facing)) = splitter_query.get_mut(entity) else {
+        return;
+    };
+
+    let side = if splitter.tile(*pos, *facing, Side::Left) == aimed {
+        Side::Left
+    } else {
+        Side::Right
+    };
+
+    let shift = keys.pressed(KeyCode::ShiftLeft);
+
+    if keys.just_pressed(KeyCode::F) {
+        splitter.filters[side.index()] = cycle_filter(splitter.filters[side.index()]);
+    }
+
+    if keys.just_pressed(KeyCode::P) {
+        if shift {
+            splitter.input_priority = toggle(splitter.input_priority, side);
+        } else {
+            splitter.output_priority = toggle(splitter.output_priority, side);
+        }
+    }
+}
+
+fn draw_splitter_settings(
+    splitter_query: Query<(&Splitter, &GridPos, &Facing)>,
+    mut gizmos: Gizmos,
+) {
+    for (splitter, pos, facing) in &splitter_query {
+        for side in Side::BOTH {
+            let center = splitter.tile(*pos, *facing, side).to_world() + Vec3::Y * 0.6;
+
+            if let Some(item) = splitter.filters[side.index()] {
+                gizmos.sphere(center, Quat::IDENTITY, 0.15, item.color());
+            }
+
+            if splitter.output_priority == Some(side) {
+                gizmos.line(center, center + facing.direction() * 0.5, Color::GREEN);
+            }
+
+            if splitter.input_priority == Some(side) {
+                gizmos.line(center, center - facing.direction() * 0.5, Color::BLUE);
+            }
+        }
+    }
+}
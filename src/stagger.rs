@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{ai::Brain, shaman::InterruptEnemyCast, state::GameplaySet};
+
+// seconds of hit-stun per unit of knockback that gets through
+const STUN_PER_FORCE: f32 = 1.0;
+const MAX_STUN: f32 = 1.5;
+// knockback throws things up a little so they don't just grind along the ground
+const LIFT: f32 = 0.3;
+
+pub struct StaggerPlugin;
+
+impl Plugin for StaggerPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<Knockback>()
+            .add_systems(Update, (apply_knockback, recover).chain().in_set(GameplaySet));
+    }
+}
+
+/// Shoves an enemy away and stuns it for a moment, from heavy spells.
+#[derive(Event)]
+pub struct Knockback {
+    pub target: Entity,
+    // only the horizontal part counts
+    pub direction: Vec3,
+    pub force: f32,
+}
+
+/// The fraction of every knockback shrugged off, 1.0 can't be moved at all.
+#[derive(Component, Clone, Copy)]
+pub struct Poise(pub f32);
+
+/// Reeling from a hit, the AI and pathing leave it alone until it's over.
+#[derive(Component)]
+pub struct Staggered {
+    pub remaining: f32,
+}
+
+fn apply_knockback(
+    mut commands: Commands,
+    target_query: Query<(Option<&Poise>, Option<&Staggered>), With<Brain>>,
+    mut knockback_events: EventReader<Knockback>,
+    mut interrupt_events: EventWriter<InterruptEnemyCast>,
+) {
+    for event in knockback_events.read() {
+        let Ok((poise, staggered)) = target_query.get(event.target) else {
+            continue;
+        };
+        let force = event.force * (1.0 - poise.map_or(0.0, |p| p.0).min(1.0));
+        if force <= 0.0 {
+            continue;
+        }
+
+        let direction = Vec3::new(event.direction.x, 0.0, event.direction.z).normalize_or_zero() + Vec3::Y * LIFT;
+        // a fresh hit never shortens a stun that's already running
+        let stun = (force * STUN_PER_FORCE).min(MAX_STUN).max(staggered.map_or(0.0, |s| s.remaining));
+        commands.entity(event.target).insert((
+            ExternalImpulse::new(direction * force),
+            Staggered { remaining: stun },
+        ));
+        interrupt_events.send(InterruptEnemyCast { caster: event.target });
+    }
+}
+
+fn recover(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut staggered_query: Query<(Entity, &mut Staggered)>,
+) {
+    for (entity, mut staggered) in &mut staggered_query {
+        staggered.remaining -= time.delta_seconds();
+        if staggered.remaining <= 0.0 {
+            commands.entity(entity).remove::<Staggered>();
+        }
+    }
+}
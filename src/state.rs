@@ -0,0 +1,224 @@
+use bevy::{app::AppExit, prelude::*, window::PrimaryWindow};
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{camera::set_cursor_free, locale::Localized, settings::SettingsMenu, Player};
+
+const BUTTON_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.7);
+const HOVERED_COLOR: Color = Color::rgba(0.2, 0.2, 0.3, 0.9);
+const DISABLED_TEXT: Color = Color::GRAY;
+
+pub struct StatePlugin;
+
+impl Plugin for StatePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_state::<GameState>()
+            .configure_sets(Update, GameplaySet.run_if(in_state(GameState::InGame)))
+            .add_systems(OnEnter(GameState::MainMenu), setup_main_menu)
+            .add_systems(OnExit(GameState::MainMenu), despawn_menus)
+            .add_systems(OnEnter(GameState::Paused), setup_pause_menu)
+            .add_systems(OnExit(GameState::Paused), despawn_menus)
+            // the world stands still whenever it isn't being played
+            .add_systems(OnEnter(GameState::InGame), resume_world)
+            .add_systems(OnExit(GameState::InGame), (pause_world, free_cursor))
+            .add_systems(Update, (
+                menu_input.run_if(in_state(GameState::MainMenu).or_else(in_state(GameState::Paused))),
+                toggle_pause.run_if(in_state(GameState::InGame).or_else(in_state(GameState::Paused))),
+            ));
+    }
+}
+
+#[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GameState {
+    #[default]
+    MainMenu,
+    // the world is being put together
+    Loading,
+    InGame,
+    Paused,
+}
+
+/// Everything that only runs while the game is actually being played.
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GameplaySet;
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+enum MenuButton {
+    NewGame,
+    Continue,
+    Settings,
+    Quit,
+    Resume,
+    QuitToMenu,
+}
+
+impl MenuButton {
+    const MAIN: [MenuButton; 4] = [
+        MenuButton::NewGame,
+        MenuButton::Continue,
+        MenuButton::Settings,
+        MenuButton::Quit,
+    ];
+    const PAUSE: [MenuButton; 3] = [
+        MenuButton::Resume,
+        MenuButton::Settings,
+        MenuButton::QuitToMenu,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            MenuButton::NewGame => "New Game",
+            MenuButton::Continue => "Continue",
+            MenuButton::Settings => "Settings",
+            MenuButton::Quit => "Quit",
+            MenuButton::Resume => "Resume",
+            MenuButton::QuitToMenu => "Quit to Menu",
+        }
+    }
+
+    // there's no saving, so only one world at a time
+    fn enabled(&self, in_progress: bool) -> bool {
+        match self {
+            MenuButton::NewGame => !in_progress,
+            MenuButton::Continue => in_progress,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Menu;
+
+fn setup_main_menu(
+    mut commands: Commands,
+    player_query: Query<(), With<Player>>,
+) {
+    spawn_menu(&mut commands, &MenuButton::MAIN, !player_query.is_empty());
+}
+
+fn setup_pause_menu(mut commands: Commands) {
+    spawn_menu(&mut commands, &MenuButton::PAUSE, true);
+}
+
+fn spawn_menu(commands: &mut Commands, buttons: &[MenuButton], in_progress: bool) {
+    commands.spawn((
+        Menu,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            // above the rest of the ui
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+    )).with_children(|parent| {
+        for &button in buttons {
+            let color = if button.enabled(in_progress) { Color::WHITE } else { DISABLED_TEXT };
+            parent.spawn((
+                button,
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(200.0),
+                        padding: UiRect::all(Val::Px(8.0)),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    background_color: BUTTON_COLOR.into(),
+                    ..default()
+                },
+            )).with_children(|row| {
+                row.spawn((
+                    Localized(button.name()),
+                    TextBundle::from_section(button.name(), TextStyle {
+                        font_size: 24.0,
+                        color,
+                        ..default()
+                    }),
+                ));
+            });
+        }
+    });
+}
+
+fn despawn_menus(
+    mut commands: Commands,
+    menu_query: Query<Entity, With<Menu>>,
+) {
+    for menu in &menu_query {
+        commands.entity(menu).despawn_recursive();
+    }
+}
+
+fn menu_input(
+    player_query: Query<(), With<Player>>,
+    mut button_query: Query<(&MenuButton, &Interaction, &mut BackgroundColor), Changed<Interaction>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut settings_menu: ResMut<SettingsMenu>,
+    mut exit_events: EventWriter<AppExit>,
+) {
+    let in_progress = !player_query.is_empty();
+
+    for (button, interaction, mut background) in &mut button_query {
+        if !button.enabled(in_progress) {
+            continue;
+        }
+
+        match interaction {
+            Interaction::Pressed => match button {
+                MenuButton::NewGame => next_state.set(GameState::Loading),
+                MenuButton::Continue | MenuButton::Resume => next_state.set(GameState::InGame),
+                MenuButton::Settings => settings_menu.open = true,
+                MenuButton::Quit => exit_events.send(AppExit),
+                MenuButton::QuitToMenu => next_state.set(GameState::MainMenu),
+            },
+            Interaction::Hovered => *background = HOVERED_COLOR.into(),
+            Interaction::None => *background = BUTTON_COLOR.into(),
+        }
+    }
+}
+
+pub fn toggle_pause(
+    keys: Res<Input<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keys.just_pressed(KeyCode::Escape) {
+        return;
+    }
+
+    match state.get() {
+        GameState::InGame => next_state.set(GameState::Paused),
+        GameState::Paused => next_state.set(GameState::InGame),
+        _ => {}
+    }
+}
+
+// virtual time drives everything else that ticks, fixed updates included
+fn pause_world(
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut physics_time: ResMut<Time<Physics>>,
+) {
+    virtual_time.pause();
+    physics_time.pause();
+}
+
+fn resume_world(
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut physics_time: ResMut<Time<Physics>>,
+) {
+    virtual_time.unpause();
+    physics_time.unpause();
+}
+
+// the menus need a pointer, grabbing it again is up to the camera
+fn free_cursor(mut window_query: Query<&mut Window, With<PrimaryWindow>>) {
+    set_cursor_free(&mut window_query.get_single_mut().expect("not one window"), true);
+}
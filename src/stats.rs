@@ -0,0 +1,308 @@
+use std::fmt::Write;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{item::Item, locale::Locale, power::ManaGrid, state::GameplaySet};
+
+// one sample per second, so a full buffer covers a minute
+pub const HISTORY_LEN: usize = 60;
+const GRAPH_HEIGHT: f32 = 80.0;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<ProductionStats>()
+            .init_resource::<StatsPanel>()
+            .add_event::<ItemProduced>()
+            .add_event::<ItemConsumed>()
+            .add_systems(Startup, setup_stats_ui)
+            .add_systems(Update, (
+                record_items,
+                record_mana,
+                stats_input,
+                update_stats_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Event)]
+pub struct ItemProduced {
+    pub item: Item,
+    pub count: u32,
+}
+
+#[derive(Event)]
+pub struct ItemConsumed {
+    pub item: Item,
+    pub count: u32,
+}
+
+/// Ring buffer of per-second totals.
+#[derive(Clone)]
+pub struct History {
+    samples: [f32; HISTORY_LEN],
+    head: usize,
+    // accumulates until the next sample is taken
+    current: f32,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History { samples: [0.0; HISTORY_LEN], head: 0, current: 0.0 }
+    }
+}
+
+impl History {
+    pub fn record(&mut self, amount: f32) {
+        self.current += amount;
+    }
+
+    fn roll(&mut self) {
+        self.samples[self.head] = self.current;
+        self.head = (self.head + 1) % HISTORY_LEN;
+        self.current = 0.0;
+    }
+
+    // oldest first
+    pub fn samples(&self) -> impl Iterator<Item = f32> + '_ {
+        (0..HISTORY_LEN).map(|i| self.samples[(self.head + i) % HISTORY_LEN])
+    }
+
+    pub fn per_minute(&self) -> f32 {
+        self.samples.iter().sum()
+    }
+
+    pub fn peak(&self) -> f32 {
+        self.samples.iter().copied().fold(0.0, f32::max)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Series {
+    ManaGenerated,
+    ManaUsed,
+    Produced(Item),
+    Consumed(Item),
+}
+
+impl Series {
+    pub fn all() -> Vec<Series> {
+        let mut all = vec![Series::ManaGenerated, Series::ManaUsed];
+        all.extend(Item::ALL.iter().map(|i| Series::Produced(*i)));
+        all.extend(Item::ALL.iter().map(|i| Series::Consumed(*i)));
+        all
+    }
+
+    pub fn name(&self, locale: &Locale) -> String {
+        match self {
+            Series::ManaGenerated => locale.get("Mana generated").to_string(),
+            Series::ManaUsed => locale.get("Mana used").to_string(),
+            Series::Produced(item) => locale.fill("{} produced", &[&locale.get(item.name())]),
+            Series::Consumed(item) => locale.fill("{} consumed", &[&locale.get(item.name())]),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct ProductionStats {
+    pub produced: HashMap<Item, History>,
+    pub consumed: HashMap<Item, History>,
+    pub mana_generated: History,
+    pub mana_used: History,
+    timer: Timer,
+}
+
+impl Default for ProductionStats {
+    fn default() -> Self {
+        ProductionStats {
+            produced: HashMap::default(),
+            consumed: HashMap::default(),
+            mana_generated: History::default(),
+            mana_used: History::default(),
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+        }
+    }
+}
+
+impl ProductionStats {
+    pub fn history(&self, series: Series) -> Option<&History> {
+        match series {
+            Series::ManaGenerated => Some(&self.mana_generated),
+            Series::ManaUsed => Some(&self.mana_used),
+            Series::Produced(item) => self.produced.get(&item),
+            Series::Consumed(item) => self.consumed.get(&item),
+        }
+    }
+
+    pub fn per_minute(&self, series: Series) -> f32 {
+        self.history(series).map_or(0.0, |h| h.per_minute())
+    }
+
+    fn roll(&mut self) {
+        self.produced.values_mut()
+            .chain(self.consumed.values_mut())
+            .for_each(History::roll);
+        self.mana_generated.roll();
+        self.mana_used.roll();
+    }
+}
+
+#[derive(Resource, Default)]
+struct StatsPanel {
+    open: bool,
+    selected: usize,
+}
+
+#[derive(Component)]
+struct StatsUi;
+
+#[derive(Component)]
+struct StatsText;
+
+#[derive(Component)]
+struct GraphBar(usize);
+
+fn record_items(
+    mut stats: ResMut<ProductionStats>,
+    mut produced_events: EventReader<ItemProduced>,
+    mut consumed_events: EventReader<ItemConsumed>,
+) {
+    for event in produced_events.read() {
+        stats.produced.entry(event.item).or_default().record(event.count as f32);
+    }
+    for event in consumed_events.read() {
+        stats.consumed.entry(event.item).or_default().record(event.count as f32);
+    }
+}
+
+fn record_mana(
+    time: Res<Time>,
+    grid: Res<ManaGrid>,
+    mut stats: ResMut<ProductionStats>,
+) {
+    let dt = time.delta_seconds();
+    stats.mana_generated.record(grid.production * dt);
+    stats.mana_used.record(grid.demand * grid.satisfaction * dt);
+
+    if stats.timer.tick(time.delta()).just_finished() {
+        stats.roll();
+    }
+}
+
+fn stats_input(
+    keys: Res<Input<KeyCode>>,
+    mut panel: ResMut<StatsPanel>,
+) {
+    if keys.just_pressed(KeyCode::G) {
+        panel.open = !panel.open;
+    }
+
+    if !panel.open {
+        return;
+    }
+
+    let count = Series::all().len();
+    if keys.just_pressed(KeyCode::Right) {
+        panel.selected = (panel.selected + 1) % count;
+    }
+    if keys.just_pressed(KeyCode::Left) {
+        panel.selected = (panel.selected + count - 1) % count;
+    }
+}
+
+fn setup_stats_ui(mut commands: Commands) {
+    commands.spawn((
+        StatsUi,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                bottom: Val::Px(20.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(10.0)),
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            StatsText,
+            TextBundle::from_section("", TextStyle {
+                font_size: 16.0,
+                color: Color::WHITE,
+                ..default()
+            }),
+        ));
+
+        // one bar per second of history, growing upwards
+        parent.spawn(NodeBundle {
+            style: Style {
+                height: Val::Px(GRAPH_HEIGHT),
+                align_items: AlignItems::FlexEnd,
+                column_gap: Val::Px(1.0),
+                ..default()
+            },
+            ..default()
+        }).with_children(|graph| {
+            for i in 0..HISTORY_LEN {
+                graph.spawn((
+                    GraphBar(i),
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(4.0),
+                            height: Val::Px(0.0),
+                            ..default()
+                        },
+                        background_color: Color::CYAN.into(),
+                        ..default()
+                    },
+                ));
+            }
+        });
+    });
+}
+
+fn update_stats_ui(
+    locale: Res<Locale>,
+    panel: Res<StatsPanel>,
+    stats: Res<ProductionStats>,
+    mut ui_query: Query<&mut Visibility, With<StatsUi>>,
+    mut text_query: Query<&mut Text, With<StatsText>>,
+    mut bar_query: Query<(&GraphBar, &mut Style)>,
+) {
+    let mut visibility = ui_query.get_single_mut().expect("not one stats ui");
+    if !panel.open {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Visible;
+
+    let series = Series::all()[panel.selected];
+    let per_minute = |x: f32| locale.fill("{}/min", &[&format!("{:.0}", x)]);
+    let mut content = format!("< {} > {}", series.name(&locale), per_minute(stats.per_minute(series)));
+
+    // everything that moved in the last minute
+    for item in Item::ALL {
+        let produced = stats.per_minute(Series::Produced(item));
+        let consumed = stats.per_minute(Series::Consumed(item));
+        if produced > 0.0 || consumed > 0.0 {
+            let (produced, consumed) = (format!("{:.0}", produced), format!("{:.0}", consumed));
+            let _ = write!(content, "\n{}", locale.fill("{}: +{} -{} /min", &[&locale.get(item.name()), &produced, &consumed]));
+        }
+    }
+
+    text_query.get_single_mut().expect("not one stats text").sections[0].value = content;
+
+    let samples: Vec<f32> = stats.history(series)
+        .map_or(vec![0.0; HISTORY_LEN], |h| h.samples().collect());
+    let peak = stats.history(series).map_or(0.0, |h| h.peak()).max(1.0);
+
+    for (bar, mut style) in &mut bar_query {
+        style.height = Val::Px(samples[bar.0] / peak * GRAPH_HEIGHT);
+    }
+}
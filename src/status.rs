@@ -0,0 +1,257 @@
+use bevy::prelude::*;
+
+use crate::{
+    character::{Modifier, ModifierSource, Stat, Stats},
+    health::{Damage, DamageEvent, DamageType, Health, Resistances},
+    state::GameplaySet,
+};
+
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<ApplyStatus>()
+            .add_event::<RemoveStatus>()
+            .add_systems(Update, (
+                apply_statuses,
+                remove_statuses,
+                tick_statuses,
+                apply_status_modifiers,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+/// What happens when an effect lands on something that already has it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stacking {
+    // keeps the longer and the stronger of the two
+    Refresh,
+    // adds up to `max` stacks, each one as strong as the first, and
+    // restarts the duration
+    Intensify { max: u32 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusKind {
+    // heals `strength` per second
+    Regeneration,
+    // `strength` is the fraction added to the speed
+    Haste,
+    // `strength` is a resistance against every damage type
+    Fortified,
+    // `strength` fire damage per second
+    Burning,
+    // `strength` mana regeneration taken away
+    Hexed,
+}
+
+impl StatusKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatusKind::Regeneration => "Regeneration",
+            StatusKind::Haste => "Haste",
+            StatusKind::Fortified => "Fortified",
+            StatusKind::Burning => "Burning",
+            StatusKind::Hexed => "Hexed",
+        }
+    }
+
+    /// Curses and the like, what a dispel takes off its caster.
+    pub fn is_harmful(&self) -> bool {
+        matches!(self, StatusKind::Burning | StatusKind::Hexed)
+    }
+
+    pub fn stacking(&self) -> Stacking {
+        match self {
+            StatusKind::Burning => Stacking::Intensify { max: 5 },
+            _ => Stacking::Refresh,
+        }
+    }
+
+    // seconds between ticks for effects that work in pulses
+    pub fn tick_interval(&self) -> Option<f32> {
+        match self {
+            StatusKind::Regeneration => Some(1.0),
+            StatusKind::Burning => Some(0.5),
+            StatusKind::Haste | StatusKind::Fortified | StatusKind::Hexed => None,
+        }
+    }
+
+    /// How the effect changes the stats of whatever it's on.
+    pub fn modifier(&self, strength: f32) -> Option<Modifier> {
+        match self {
+            StatusKind::Haste => Some(Modifier { stat: Stat::Speed, amount: strength }),
+            StatusKind::Fortified => Some(Modifier { stat: Stat::Resistance, amount: strength }),
+            StatusKind::Hexed => Some(Modifier { stat: Stat::ManaRegen, amount: -strength }),
+            StatusKind::Regeneration | StatusKind::Burning => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Status {
+    pub kind: StatusKind,
+    // seconds
+    pub remaining: f32,
+    pub strength: f32,
+    pub stacks: u32,
+    // seconds until the next pulse
+    next_tick: f32,
+}
+
+impl Status {
+    pub fn new(kind: StatusKind, duration: f32, strength: f32) -> Self {
+        Status {
+            kind,
+            remaining: duration,
+            strength,
+            stacks: 1,
+            next_tick: kind.tick_interval().unwrap_or(0.0),
+        }
+    }
+
+    /// The strength with all stacks counted in.
+    pub fn total_strength(&self) -> f32 {
+        self.strength * self.stacks as f32
+    }
+}
+
+#[derive(Component, Default)]
+pub struct StatusEffects {
+    pub active: Vec<Status>,
+}
+
+impl StatusEffects {
+    pub fn modifiers(&self) -> impl Iterator<Item = Modifier> + '_ {
+        self.active.iter().filter_map(|s| s.kind.modifier(s.total_strength()))
+    }
+
+    fn apply(&mut self, status: Status) {
+        let Some(existing) = self.active.iter_mut().find(|s| s.kind == status.kind) else {
+            self.active.push(status);
+            return;
+        };
+
+        match status.kind.stacking() {
+            Stacking::Refresh => {
+                existing.remaining = existing.remaining.max(status.remaining);
+                existing.strength = existing.strength.max(status.strength);
+            }
+            Stacking::Intensify { max } => {
+                existing.remaining = status.remaining;
+                existing.strength = existing.strength.max(status.strength);
+                existing.stacks = (existing.stacks + status.stacks).min(max);
+            }
+        }
+    }
+}
+
+#[derive(Event)]
+pub struct ApplyStatus {
+    pub target: Entity,
+    pub status: Status,
+}
+
+/// Ends an effect early, e.g. when it gets dispelled.
+#[derive(Event)]
+pub struct RemoveStatus {
+    pub target: Entity,
+    pub kind: StatusKind,
+}
+
+fn apply_statuses(
+    mut commands: Commands,
+    mut effects_query: Query<&mut StatusEffects>,
+    mut apply_events: EventReader<ApplyStatus>,
+) {
+    for event in apply_events.read() {
+        match effects_query.get_mut(event.target) {
+            Ok(mut effects) => effects.apply(event.status),
+            Err(_) => {
+                let Some(mut target) = commands.get_entity(event.target) else {
+                    continue;
+                };
+                let mut effects = StatusEffects::default();
+                effects.apply(event.status);
+                target.insert(effects);
+            }
+        }
+    }
+}
+
+fn remove_statuses(
+    mut effects_query: Query<&mut StatusEffects>,
+    mut remove_events: EventReader<RemoveStatus>,
+) {
+    for event in remove_events.read() {
+        if let Ok(mut effects) = effects_query.get_mut(event.target) {
+            effects.active.retain(|s| s.kind != event.kind);
+        }
+    }
+}
+
+fn tick_statuses(
+    time: Res<Time>,
+    mut effects_query: Query<(Entity, &mut StatusEffects, Option<&mut Health>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let dt = time.delta_seconds();
+
+    for (entity, mut effects, mut health) in &mut effects_query {
+        for status in &mut effects.active {
+            status.remaining -= dt;
+
+            let Some(interval) = status.kind.tick_interval() else {
+                continue;
+            };
+            status.next_tick -= dt;
+            if status.next_tick > 0.0 {
+                continue;
+            }
+            status.next_tick += interval;
+
+            // each pulse does a whole interval's worth at once
+            let amount = status.total_strength() * interval;
+            let Some(health) = health.as_mut() else {
+                continue;
+            };
+            match status.kind {
+                StatusKind::Regeneration => health.heal(amount),
+                StatusKind::Burning => {
+                    damage_events.send(DamageEvent::new(entity, Damage::new(amount, DamageType::Fire), None).periodic());
+                }
+                _ => {}
+            }
+        }
+        effects.active.retain(|s| s.remaining > 0.0);
+    }
+}
+
+fn apply_status_modifiers(
+    mut commands: Commands,
+    mut effects_query: Query<
+        (Entity, &StatusEffects, Option<&mut Stats>, Option<&mut Resistances>),
+        Changed<StatusEffects>,
+    >,
+) {
+    for (entity, effects, stats, resistances) in &mut effects_query {
+        if let Some(mut stats) = stats {
+            stats.set_modifiers(ModifierSource::Status, effects.modifiers());
+        }
+
+        // not everything with statuses has stats, so resistances are kept
+        // up to date directly
+        let bonus: f32 = effects.modifiers()
+            .filter(|m| m.stat == Stat::Resistance)
+            .map(|m| m.amount)
+            .sum();
+        match resistances {
+            Some(mut resistances) => resistances.bonus = bonus,
+            None if bonus != 0.0 => {
+                commands.entity(entity).insert(Resistances { bonus, ..default() });
+            }
+            None => {}
+        }
+    }
+}
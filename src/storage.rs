@@ -0,0 +1,144 @@
+use std::fmt::Write;
+
+use bevy::prelude::*;
+
+use crate::{
+    Player,
+    building::{BuildingKind, BuildingPlaced},
+    input::{Action, InputMap},
+    interaction::{interact, Interactable, Interacted, INTERACT_RANGE},
+    inventory::Inventory,
+    locale::Locale,
+    state::GameplaySet,
+};
+
+pub const CHEST_SLOTS: usize = 16;
+
+pub struct StoragePlugin;
+
+impl Plugin for StoragePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<OpenContainer>()
+            .add_systems(Startup, setup_container_ui)
+            .add_systems(Update, (
+                init_chests,
+                toggle_container.after(interact),
+                update_container_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Component)]
+pub struct Chest;
+
+// the container whose ui is currently shown
+#[derive(Resource, Default)]
+pub struct OpenContainer(pub Option<Entity>);
+
+#[derive(Component)]
+struct ContainerUi;
+
+#[derive(Component)]
+struct ContainerText;
+
+fn init_chests(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read().filter(|e| e.kind == BuildingKind::Chest) {
+        commands.entity(event.entity).insert((
+            Chest,
+            Inventory::new(CHEST_SLOTS),
+            Interactable::new("Open Chest"),
+        ));
+    }
+}
+
+fn toggle_container(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut open: ResMut<OpenContainer>,
+    player_query: Query<&Transform, With<Player>>,
+    chest_query: Query<(Entity, &Transform), With<Chest>>,
+    mut interacted_events: EventReader<Interacted>,
+) {
+    let player = player_query.get_single().expect("not one player");
+
+    // close the ui once the player walks away or the chest is gone
+    if let Some(entity) = open.0 {
+        let in_range = chest_query.get(entity)
+            .is_ok_and(|(_, t)| t.translation.distance(player.translation) <= INTERACT_RANGE);
+
+        if !in_range {
+            open.0 = None;
+        }
+    }
+
+    // drained every frame so a stale use can't reopen it later
+    let used = interacted_events.read().map(|e| e.entity).find(|e| chest_query.contains(*e));
+    if !input_map.just_pressed(Action::Interact, &keys) {
+        return;
+    }
+
+    if open.0.is_some() {
+        open.0 = None;
+        return;
+    }
+
+    open.0 = used;
+}
+
+fn setup_container_ui(mut commands: Commands) {
+    commands.spawn((
+        ContainerUi,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(20.0),
+                top: Val::Px(20.0),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((
+            ContainerText,
+            TextBundle::from_section("", TextStyle {
+                font_size: 18.0,
+                color: Color::WHITE,
+                ..default()
+            }),
+        ));
+    });
+}
+
+fn update_container_ui(
+    open: Res<OpenContainer>,
+    locale: Res<Locale>,
+    inventory_query: Query<&Inventory>,
+    mut ui_query: Query<&mut Visibility, With<ContainerUi>>,
+    mut text_query: Query<&mut Text, With<ContainerText>>,
+) {
+    let mut visibility = ui_query.get_single_mut().expect("not one container ui");
+    let Some(inventory) = open.0.and_then(|e| inventory_query.get(e).ok()) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    *visibility = Visibility::Visible;
+
+    let mut content = String::from(locale.get("Chest"));
+    for (i, slot) in inventory.slots().iter().enumerate() {
+        let _ = match (slot.stack, slot.filter) {
+            (Some(stack), _) => write!(content, "\n{:>2}: {} x{}", i + 1, locale.get(stack.item.name()), stack.count),
+            (None, Some(filter)) => write!(content, "\n{:>2}: ({})", i + 1, locale.get(filter.name())),
+            (None, None) => write!(content, "\n{:>2}: -", i + 1),
+        };
+    }
+
+    text_query.get_single_mut().expect("not one container text").sections[0].value = content;
+}
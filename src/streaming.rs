@@ -0,0 +1,149 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    building::{Building, GridPos},
+    heat::Heat,
+    inventory::Inventory,
+    machine::Machine,
+    module::{self, ModuleSlots},
+    power::ManaGrid,
+    state::GameplaySet,
+    stats::{ItemConsumed, ItemProduced},
+    wear::Wear,
+    Player,
+};
+
+// in tiles
+const CHUNK_SIZE: i32 = 16;
+// chunks further than this from the player unload
+const ACTIVE_DISTANCE: f32 = 48.0;
+// unloaded chunks catch up in steps of this many seconds
+const CATCH_UP_INTERVAL: f32 = 5.0;
+
+pub struct StreamingPlugin;
+
+impl Plugin for StreamingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<FactoryChunks>()
+            .add_systems(Update, stream_chunks.in_set(GameplaySet));
+    }
+}
+
+/// Marks buildings in an unloaded chunk, they're hidden and skipped by the
+/// per-frame simulation.
+#[derive(Component)]
+pub struct Dormant;
+
+#[derive(Default)]
+struct ChunkState {
+    dormant: bool,
+    // seconds that passed since the chunk last caught up
+    pending: f32,
+}
+
+#[derive(Resource, Default)]
+pub struct FactoryChunks {
+    chunks: HashMap<IVec2, ChunkState>,
+}
+
+impl FactoryChunks {
+    pub fn chunk_of(pos: GridPos) -> IVec2 {
+        pos.0.div_euclid(IVec2::splat(CHUNK_SIZE))
+    }
+
+    pub fn is_dormant(&self, chunk: IVec2) -> bool {
+        self.chunks.get(&chunk).is_some_and(|c| c.dormant)
+    }
+
+    pub fn chunk_count(&self, dormant: bool) -> usize {
+        self.chunks.values().filter(|c| c.dormant == dormant).count()
+    }
+}
+
+fn stream_chunks(
+    mut commands: Commands,
+    time: Res<Time>,
+    grid: Res<ManaGrid>,
+    mut chunks: ResMut<FactoryChunks>,
+    player_query: Query<&Transform, With<Player>>,
+    mut building_query: Query<(Entity, &GridPos, &mut Visibility, Has<Dormant>), With<Building>>,
+    mut machine_query: Query<
+        (&mut Machine, &mut Inventory, &GridPos, Option<&Heat>, Option<&Wear>, Option<&ModuleSlots>),
+        With<Dormant>,
+    >,
+    mut produced_events: EventWriter<ItemProduced>,
+    mut consumed_events: EventWriter<ItemConsumed>,
+) {
+    let player = player_query.get_single().expect("not one player").translation.xz();
+
+    let wants_dormant = |chunk: IVec2| {
+        let center = (chunk.as_vec2() + 0.5) * CHUNK_SIZE as f32;
+        center.distance(player) > ACTIVE_DISTANCE
+    };
+
+    // only chunks with buildings in them are tracked
+    let occupied: Vec<IVec2> = building_query.iter()
+        .map(|(_, pos, ..)| FactoryChunks::chunk_of(*pos))
+        .collect();
+    chunks.chunks.retain(|chunk, _| occupied.contains(chunk));
+
+    let mut catch_up: HashMap<IVec2, f32> = HashMap::default();
+    for chunk in occupied {
+        let state = chunks.chunks.entry(chunk).or_default();
+        let dormant = wants_dormant(chunk);
+
+        if state.dormant {
+            state.pending += time.delta_seconds();
+        }
+
+        // fast forward in steps while away and once more on the way back in
+        let waking = state.dormant && !dormant;
+        if waking || state.pending >= CATCH_UP_INTERVAL {
+            catch_up.insert(chunk, state.pending);
+            state.pending = 0.0;
+        }
+
+        state.dormant = dormant;
+    }
+
+    for (mut machine, mut inventory, pos, heat, wear, modules) in &mut machine_query {
+        let Some(seconds) = catch_up.get(&FactoryChunks::chunk_of(*pos)).copied() else {
+            continue;
+        };
+        let Some(recipe) = machine.recipe else {
+            continue;
+        };
+
+        // conditions are assumed to stay the way they were when the chunk unloaded
+        let speed = grid.satisfaction
+            * heat.map_or(1.0, |h| h.speed_factor())
+            * wear.map_or(1.0, |w| w.speed_factor())
+            * module::effects(modules).speed;
+
+        let crafts = machine.fast_forward(&mut inventory, seconds, speed);
+        if crafts == 0 {
+            continue;
+        }
+
+        for (item, n) in recipe.inputs() {
+            consumed_events.send(ItemConsumed { item: *item, count: n * crafts });
+        }
+        produced_events.send(ItemProduced { item: recipe.output().item, count: recipe.output().count * crafts });
+    }
+
+    for (entity, pos, mut visibility, has_dormant) in &mut building_query {
+        let dormant = chunks.is_dormant(FactoryChunks::chunk_of(*pos));
+        if dormant == has_dormant {
+            continue;
+        }
+
+        if dormant {
+            commands.entity(entity).insert(Dormant);
+            *visibility = Visibility::Hidden;
+        } else {
+            commands.entity(entity).remove::<Dormant>();
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
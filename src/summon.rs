@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    cooldown::{Ability, Cooldowns},
+    enemy::Hostile,
+    health::{Damage, DamageEvent, Health, Resistances},
+    state::GameplaySet,
+    telekinesis::Held,
+    wildlife::Wildlife,
+    Player,
+};
+
+// per caster, summoning more replaces the oldest
+pub const MAX_MINIONS: usize = 3;
+const MINION_SPEED: f32 = 3.0;
+// hostiles this close to a minion get attacked
+const AGGRO_RANGE: f32 = 8.0;
+// minions don't chase further than this from their owner
+const LEASH_RANGE: f32 = 12.0;
+const FOLLOW_DISTANCE: f32 = 2.0;
+const ATTACK_RANGE: f32 = 1.2;
+// seconds between hits
+const ATTACK_INTERVAL: f32 = 1.0;
+
+pub struct SummonPlugin;
+
+impl Plugin for SummonPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<SummonMinion>()
+            .add_event::<DismissMinions>()
+            .add_systems(Startup, setup_minion_assets)
+            .add_systems(Update, (
+                dismiss_input,
+                summon_minions,
+                expire_minions,
+                dismiss_minions,
+                move_minions,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Event)]
+pub struct SummonMinion {
+    pub owner: Entity,
+    pub pos: Vec3,
+    pub health: f32,
+    pub damage: Damage,
+    // seconds until it fades
+    pub duration: f32,
+}
+
+#[derive(Event)]
+pub struct DismissMinions {
+    pub owner: Entity,
+}
+
+/// A creature fighting for `owner`, it fades once `lifetime` runs out.
+#[derive(Component)]
+pub struct Minion {
+    pub owner: Entity,
+    pub damage: Damage,
+    pub lifetime: f32,
+}
+
+#[derive(Resource)]
+struct MinionAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+fn setup_minion_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(MinionAssets {
+        mesh: meshes.add(Mesh::from(shape::UVSphere { radius: 0.35, ..default() })),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.6, 0.8, 1.0, 0.8),
+            emissive: Color::rgb(0.2, 0.4, 0.8),
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        }),
+    });
+}
+
+fn dismiss_input(
+    keys: Res<Input<KeyCode>>,
+    player_query: Query<Entity, With<Player>>,
+    mut dismiss_events: EventWriter<DismissMinions>,
+) {
+    if keys.just_pressed(KeyCode::Y) {
+        let owner = player_query.get_single().expect("not one player");
+        dismiss_events.send(DismissMinions { owner });
+    }
+}
+
+fn summon_minions(
+    mut commands: Commands,
+    assets: Res<MinionAssets>,
+    minion_query: Query<(Entity, &Minion)>,
+    mut summon_events: EventReader<SummonMinion>,
+) {
+    for event in summon_events.read() {
+        // the one closest to fading makes room
+        let mut owned: Vec<(Entity, f32)> = minion_query.iter()
+            .filter(|(_, m)| m.owner == event.owner)
+            .map(|(e, m)| (e, m.lifetime))
+            .collect();
+        if owned.len() >= MAX_MINIONS {
+            owned.sort_by(|a, b| a.1.total_cmp(&b.1));
+            for (entity, _) in owned.iter().take(owned.len() + 1 - MAX_MINIONS) {
+                commands.entity(*entity).despawn_recursive();
+            }
+        }
+
+        commands.spawn((
+            Minion {
+                owner: event.owner,
+                damage: event.damage,
+                lifetime: event.duration,
+            },
+            Health::new(event.health),
+            Resistances::default(),
+            PbrBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_translation(event.pos),
+                ..default()
+            },
+            RigidBody::Dynamic,
+            Collider::ball(0.35),
+            LockedAxes::ROTATION_LOCKED,
+        ));
+    }
+}
+
+fn expire_minions(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut minion_query: Query<(Entity, &mut Minion)>,
+) {
+    for (entity, mut minion) in &mut minion_query {
+        minion.lifetime -= time.delta_seconds();
+        if minion.lifetime <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn dismiss_minions(
+    mut commands: Commands,
+    minion_query: Query<(Entity, &Minion)>,
+    mut dismiss_events: EventReader<DismissMinions>,
+) {
+    for event in dismiss_events.read() {
+        for (entity, _) in minion_query.iter().filter(|(_, m)| m.owner == event.owner) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn move_minions(
+    mut cooldowns: ResMut<Cooldowns>,
+    mut minion_query: Query<(Entity, &Minion, &Transform, &mut LinearVelocity), Without<Held>>,
+    owner_query: Query<&Transform, Without<Minion>>,
+    hostile_query: Query<(Entity, &Transform), (With<Hostile>, With<Health>, Without<Minion>, Without<Wildlife>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, minion, transform, mut velocity) in &mut minion_query {
+        // the owner is gone, nothing left to fight for
+        let Ok(owner) = owner_query.get(minion.owner) else {
+            velocity.x = 0.0;
+            velocity.z = 0.0;
+            continue;
+        };
+        let pos = transform.translation;
+
+        let target = hostile_query.iter()
+            .filter(|(_, t)| {
+                t.translation.distance(pos) <= AGGRO_RANGE
+                    && t.translation.distance(owner.translation) <= LEASH_RANGE
+            })
+            .min_by(|a, b| a.1.translation.distance(pos).total_cmp(&b.1.translation.distance(pos)))
+            .map(|(e, t)| (e, t.translation));
+
+        // fight whatever is close, otherwise stay near the owner
+        let goal = match target {
+            Some((target, target_pos)) if target_pos.distance(pos) <= ATTACK_RANGE => {
+                if cooldowns.is_ready(entity, Ability::MinionAttack) {
+                    cooldowns.start(entity, Ability::MinionAttack, ATTACK_INTERVAL);
+                    damage_events.send(DamageEvent::new(target, minion.damage, Some(entity)));
+                }
+                None
+            }
+            Some((_, target_pos)) => Some(target_pos),
+            None if owner.translation.distance(pos) > FOLLOW_DISTANCE => Some(owner.translation),
+            None => None,
+        };
+
+        let mut direction = goal.map_or(Vec3::ZERO, |g| g - pos);
+        direction.y = 0.0;
+        let direction = direction.normalize_or_zero();
+
+        velocity.x = direction.x * MINION_SPEED;
+        velocity.z = direction.z * MINION_SPEED;
+    }
+}
@@ -0,0 +1,384 @@
+use bevy::{
+    input::mouse::MouseWheel,
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::{
+    camera::{CursorUser, CursorUsers},
+    input::{Action, InputMap},
+    locale::{Locale, Localized},
+    research::{Research, Technology},
+    state::GameplaySet,
+    tooltip::TechTooltip,
+    ui_sound::{PlayUiSound, UiSound},
+};
+
+// graph units between columns and rows of nodes, nodes are laid out by how
+// many prerequisites deep they sit
+const COLUMN_SPACING: f32 = 260.0;
+const ROW_SPACING: f32 = 90.0;
+const NODE_SIZE: Vec2 = Vec2::new(180.0, 56.0);
+const LINE_WIDTH: f32 = 3.0;
+const MIN_ZOOM: f32 = 0.4;
+const MAX_ZOOM: f32 = 2.0;
+// how far the cursor can move while pressed and still count as a click
+const CLICK_SLOP: f32 = 4.0;
+const BACKGROUND: Color = Color::rgba(0.02, 0.02, 0.05, 0.95);
+const UNLOCKED_COLOR: Color = Color::rgb(0.2, 0.5, 0.25);
+const CURRENT_COLOR: Color = Color::rgb(0.6, 0.45, 0.1);
+const AVAILABLE_COLOR: Color = Color::rgb(0.15, 0.3, 0.55);
+const LOCKED_COLOR: Color = Color::rgb(0.2, 0.2, 0.22);
+const HOVER_TINT: f32 = 0.15;
+
+pub struct TechTreePlugin;
+
+impl Plugin for TechTreePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<TechTree>()
+            .add_systems(Startup, setup_tech_tree)
+            .add_systems(Update, (
+                toggle_tech_tree,
+                tech_tree_input,
+                layout_tech_tree,
+                update_tech_nodes,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+/// The research screen, every technology as a node wired to its prerequisites.
+#[derive(Resource)]
+pub struct TechTree {
+    pub open: bool,
+    // graph position in the middle of the screen
+    center: Vec2,
+    // pixels per graph unit
+    zoom: f32,
+    // cursor travel since the button went down, to tell drags from clicks
+    dragged: f32,
+}
+
+impl Default for TechTree {
+    fn default() -> Self {
+        TechTree {
+            open: false,
+            center: graph_center(),
+            zoom: 1.0,
+            dragged: 0.0,
+        }
+    }
+}
+
+impl TechTree {
+    fn to_screen(&self, pos: Vec2, size: Vec2) -> Vec2 {
+        (pos - self.center) * self.zoom + size / 2.0
+    }
+
+    fn to_graph(&self, at: Vec2, size: Vec2) -> Vec2 {
+        (at - size / 2.0) / self.zoom + self.center
+    }
+}
+
+#[derive(Component)]
+struct TechTreeScreen;
+
+#[derive(Component)]
+struct TechNode(Technology);
+
+#[derive(Component)]
+struct TechState;
+
+#[derive(Component)]
+struct PrerequisiteLine {
+    from: Technology,
+    to: Technology,
+}
+
+#[derive(Component)]
+struct ResearchStatus;
+
+fn depth(tech: Technology) -> usize {
+    tech.prerequisites().iter().map(|p| depth(*p) + 1).max().unwrap_or(0)
+}
+
+// middle of the node in graph units
+fn node_position(tech: Technology) -> Vec2 {
+    let column = depth(tech);
+    let row = Technology::ALL.iter()
+        .filter(|t| depth(**t) == column)
+        .position(|t| *t == tech)
+        .unwrap_or_default();
+    Vec2::new(column as f32 * COLUMN_SPACING, row as f32 * ROW_SPACING)
+}
+
+fn graph_center() -> Vec2 {
+    let (min, max) = Technology::ALL.iter()
+        .map(|t| node_position(*t))
+        .fold((Vec2::MAX, Vec2::MIN), |(min, max), p| (min.min(p), max.max(p)));
+    (min + max) / 2.0
+}
+
+fn node_color(tech: Technology, research: &Research) -> Color {
+    if research.unlocked.contains(&tech) {
+        UNLOCKED_COLOR
+    } else if research.current == Some(tech) {
+        CURRENT_COLOR
+    } else if research.is_available(tech) {
+        AVAILABLE_COLOR
+    } else {
+        LOCKED_COLOR
+    }
+}
+
+fn setup_tech_tree(mut commands: Commands) {
+    let text = |value: &str, size: f32| TextBundle::from_section(value, TextStyle {
+        font_size: size,
+        color: Color::WHITE,
+        ..default()
+    });
+    let hint = "Drag to pan, scroll to zoom, click a technology to research it";
+
+    commands.spawn((
+        TechTreeScreen,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            background_color: BACKGROUND.into(),
+            visibility: Visibility::Hidden,
+            // over the hud, under tooltips and toasts
+            z_index: ZIndex::Global(5),
+            ..default()
+        },
+    )).with_children(|parent| {
+        // lines first so the nodes are drawn over their ends
+        for to in Technology::ALL {
+            for from in to.prerequisites() {
+                parent.spawn((
+                    PrerequisiteLine { from: *from, to },
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ));
+            }
+        }
+
+        for tech in Technology::ALL {
+            parent.spawn((
+                TechNode(tech),
+                TechTooltip(tech),
+                Interaction::default(),
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    ..default()
+                },
+            )).with_children(|node| {
+                node.spawn((Localized(tech.name()), text(tech.name(), 18.0)));
+                node.spawn((TechState, text("", 14.0)));
+            });
+        }
+
+        parent.spawn((Localized("Research"), text("Research", 24.0).with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            top: Val::Px(20.0),
+            ..default()
+        })));
+        parent.spawn((ResearchStatus, text("", 16.0).with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            top: Val::Px(52.0),
+            ..default()
+        })));
+        parent.spawn((Localized(hint), text(hint, 16.0).with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(20.0),
+            bottom: Val::Px(20.0),
+            ..default()
+        })));
+    });
+}
+
+fn toggle_tech_tree(
+    keys: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut tree: ResMut<TechTree>,
+    mut cursor_users: ResMut<CursorUsers>,
+) {
+    if !input_map.just_pressed(Action::Research, &keys) {
+        return;
+    }
+
+    tree.open = !tree.open;
+    cursor_users.set(CursorUser::TechTree, tree.open);
+}
+
+fn tech_tree_input(
+    mouse: Res<Input<MouseButton>>,
+    mut tree: ResMut<TechTree>,
+    mut research: ResMut<Research>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    node_query: Query<(&TechNode, &Interaction)>,
+    mut scroll_events: EventReader<MouseWheel>,
+    mut sound_events: EventWriter<PlayUiSound>,
+    mut last_cursor: Local<Option<Vec2>>,
+) {
+    let scroll = scroll_events.read().fold(0.0, |sum, e| sum + e.y);
+    let window = window_query.get_single().expect("not one window");
+    let size = Vec2::new(window.width(), window.height());
+    let cursor = window.cursor_position();
+    let last = std::mem::replace(&mut *last_cursor, cursor);
+    let (true, Some(cursor)) = (tree.open, cursor) else {
+        return;
+    };
+
+    // zoom around the cursor, so whatever is under it stays there
+    if scroll != 0.0 {
+        let anchor = tree.to_graph(cursor, size);
+        tree.zoom = (tree.zoom * 1.25_f32.powf(scroll)).clamp(MIN_ZOOM, MAX_ZOOM);
+        let drift = anchor - tree.to_graph(cursor, size);
+        tree.center += drift;
+    }
+
+    if mouse.just_pressed(MouseButton::Left) {
+        tree.dragged = 0.0;
+    } else if let (true, Some(last)) = (mouse.pressed(MouseButton::Left), last) {
+        let delta = cursor - last;
+        let zoom = tree.zoom;
+        tree.center -= delta / zoom;
+        tree.dragged += delta.length();
+    }
+
+    // clicking rather than dragging across a node sets the labs to it
+    if !mouse.just_released(MouseButton::Left) || tree.dragged > CLICK_SLOP {
+        return;
+    }
+    let clicked = node_query.iter()
+        .find(|(_, interaction)| **interaction != Interaction::None)
+        .map(|(node, _)| node.0);
+    if let Some(tech) = clicked {
+        if research.is_available(tech) && research.current != Some(tech) {
+            research.current = Some(tech);
+            research.progress = 0;
+        } else if !research.is_available(tech) && !research.unlocked.contains(&tech) {
+            // still missing what it builds on
+            sound_events.send(PlayUiSound(UiSound::Error));
+        }
+    }
+}
+
+fn layout_tech_tree(
+    tree: Res<TechTree>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut screen_query: Query<&mut Visibility, With<TechTreeScreen>>,
+    mut node_query: Query<(&TechNode, &mut Style, &Children), Without<PrerequisiteLine>>,
+    mut line_query: Query<(&PrerequisiteLine, &mut Style, &mut Transform), Without<TechNode>>,
+    mut text_query: Query<&mut Text>,
+) {
+    let mut visibility = screen_query.get_single_mut().expect("not one tech tree screen");
+    *visibility = if tree.open { Visibility::Visible } else { Visibility::Hidden };
+    if !tree.open {
+        return;
+    }
+
+    let window = window_query.get_single().expect("not one window");
+    let size = Vec2::new(window.width(), window.height());
+
+    for (TechNode(tech), mut style, children) in &mut node_query {
+        let at = tree.to_screen(node_position(*tech), size);
+        let node = NODE_SIZE * tree.zoom;
+        style.left = Val::Px(at.x - node.x / 2.0);
+        style.top = Val::Px(at.y - node.y / 2.0);
+        style.width = Val::Px(node.x);
+        style.height = Val::Px(node.y);
+
+        // the name and the state under it
+        for (child, font_size) in children.iter().zip([18.0, 14.0]) {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].style.font_size = font_size * tree.zoom;
+            }
+        }
+    }
+
+    // a thin node stretched between the two and turned to face along the line
+    for (line, mut style, mut transform) in &mut line_query {
+        let from = tree.to_screen(node_position(line.from), size);
+        let to = tree.to_screen(node_position(line.to), size);
+        let middle = (from + to) / 2.0;
+        let length = from.distance(to);
+        style.left = Val::Px(middle.x - length / 2.0);
+        style.top = Val::Px(middle.y - LINE_WIDTH / 2.0);
+        style.width = Val::Px(length);
+        style.height = Val::Px(LINE_WIDTH);
+        let direction = to - from;
+        transform.rotation = Quat::from_rotation_z(direction.y.atan2(direction.x));
+    }
+}
+
+fn update_tech_nodes(
+    tree: Res<TechTree>,
+    research: Res<Research>,
+    locale: Res<Locale>,
+    mut node_query: Query<(&TechNode, &Interaction, &mut BackgroundColor, &Children)>,
+    mut line_query: Query<(&PrerequisiteLine, &mut BackgroundColor), Without<TechNode>>,
+    mut state_query: Query<&mut Text, (With<TechState>, Without<ResearchStatus>)>,
+    mut status_query: Query<&mut Text, (With<ResearchStatus>, Without<TechState>)>,
+) {
+    if !tree.open {
+        return;
+    }
+
+    for (TechNode(tech), interaction, mut background, children) in &mut node_query {
+        let mut color = node_color(*tech, &research);
+        if *interaction != Interaction::None {
+            color = Color::rgb(color.r() + HOVER_TINT, color.g() + HOVER_TINT, color.b() + HOVER_TINT);
+        }
+        *background = color.into();
+
+        let state = if research.unlocked.contains(tech) {
+            locale.get("done").to_string()
+        } else if research.current == Some(*tech) {
+            format!("{}/{}", research.progress, tech.units())
+        } else if research.is_available(*tech) {
+            locale.get("available").to_string()
+        } else {
+            locale.get("locked").to_string()
+        };
+        for child in children.iter() {
+            if let Ok(mut text) = state_query.get_mut(*child) {
+                text.sections[0].value = state.clone();
+            }
+        }
+    }
+
+    // lit once the prerequisite is done, so the open paths stand out
+    for (line, mut background) in &mut line_query {
+        *background = if research.unlocked.contains(&line.from) {
+            Color::rgb(0.7, 0.7, 0.7)
+        } else {
+            Color::rgb(0.3, 0.3, 0.3)
+        }.into();
+    }
+
+    let status = match research.current {
+        Some(tech) => locale.fill("Researching {} ({}/{})", &[&locale.get(tech.name()), &research.progress, &tech.units()]),
+        None => locale.get("Nothing being researched").to_string(),
+    };
+    status_query.get_single_mut().expect("not one research status").sections[0].value = status;
+}
@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    camera::Camera,
+    enemy::Hostile,
+    health::{Damage, DamageEvent, Health},
+    magic::{ChannelEnded, InterruptCast, SpellBook, SpellCast, SpellEffect, SpellInterrupted},
+    state::GameplaySet,
+    Player,
+};
+
+// gains of the controller that pulls held bodies towards the hold point
+const HOLD_STIFFNESS: f32 = 40.0;
+const HOLD_DAMPING: f32 = 12.0;
+// seconds a thrown body stays dangerous
+const THROWN_TIME: f32 = 3.0;
+
+pub struct TelekinesisPlugin;
+
+impl Plugin for TelekinesisPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            grab_bodies,
+            hold_bodies,
+            release_bodies,
+            thrown_hits,
+        ).chain().in_set(GameplaySet));
+    }
+}
+
+/// A body floating in front of `holder` while the spell is channeled.
+#[derive(Component)]
+pub struct Held {
+    pub holder: Entity,
+    hold_distance: f32,
+    throw_speed: f32,
+    damage: Damage,
+}
+
+/// A body that was hurled and hurts whatever it slams into.
+#[derive(Component)]
+pub struct Thrown {
+    pub thrower: Entity,
+    damage: Damage,
+    ttl: f32,
+}
+
+fn aim_direction(camera: Option<&Transform>, caster: &Transform) -> Vec3 {
+    camera.map_or(caster.forward(), |c| c.forward())
+}
+
+fn grab_bodies(
+    mut commands: Commands,
+    book: Res<SpellBook>,
+    spatial_query: SpatialQuery,
+    caster_query: Query<&Transform>,
+    camera_query: Query<&Transform, With<Camera>>,
+    body_query: Query<&RigidBody, (Without<Player>, Without<Held>)>,
+    mut cast_events: EventReader<SpellCast>,
+    mut interrupt_events: EventWriter<InterruptCast>,
+) {
+    for event in cast_events.read() {
+        let Some(SpellEffect::Telekinesis { range, hold_distance, throw_speed, damage }) =
+            book.get(event.spell).map(|s| s.effect)
+        else {
+            continue;
+        };
+        let Ok(caster) = caster_query.get(event.caster) else {
+            continue;
+        };
+
+        let hit = spatial_query.cast_ray(
+            caster.translation,
+            aim_direction(camera_query.get_single().ok(), caster),
+            range,
+            true,
+            SpatialQueryFilter::new().without_entities([event.caster]),
+        );
+        let grabbable = hit.filter(|h| body_query.get(h.entity).is_ok_and(|rb| rb.is_dynamic()));
+
+        // nothing to hold on to, don't keep draining mana
+        let Some(hit) = grabbable else {
+            interrupt_events.send(InterruptCast { caster: event.caster });
+            continue;
+        };
+
+        commands.entity(hit.entity)
+            .remove::<Thrown>()
+            .insert(Held { holder: event.caster, hold_distance, throw_speed, damage });
+    }
+}
+
+fn hold_bodies(
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    caster_query: Query<&Transform>,
+    camera_query: Query<&Transform, With<Camera>>,
+    mut held_query: Query<(&Held, &Transform, &mut LinearVelocity)>,
+) {
+    let dt = time.delta_seconds();
+
+    for (held, transform, mut velocity) in &mut held_query {
+        let Ok(holder) = caster_query.get(held.holder) else {
+            continue;
+        };
+
+        let direction = aim_direction(camera_query.get_single().ok(), holder);
+        let target = holder.translation + Vec3::Y + direction * held.hold_distance;
+        let error = target - transform.translation;
+
+        // a pd controller on the velocity, cancelling out gravity as well
+        let acceleration = HOLD_STIFFNESS * error - HOLD_DAMPING * velocity.0 - gravity.0;
+        velocity.0 += acceleration * dt;
+    }
+}
+
+fn release_bodies(
+    mut commands: Commands,
+    book: Res<SpellBook>,
+    caster_query: Query<&Transform>,
+    camera_query: Query<&Transform, With<Camera>>,
+    mut held_query: Query<(Entity, &Held, &mut LinearVelocity)>,
+    mut ended_events: EventReader<ChannelEnded>,
+    mut interrupted_events: EventReader<SpellInterrupted>,
+) {
+    let telekinesis = |spell| book.get(spell).is_some_and(|s| matches!(s.effect, SpellEffect::Telekinesis { .. }));
+
+    // letting go on purpose hurls it, being interrupted just drops it
+    for event in ended_events.read().filter(|e| telekinesis(e.spell)) {
+        for (entity, held, mut velocity) in held_query.iter_mut().filter(|(_, h, _)| h.holder == event.caster) {
+            if let Ok(holder) = caster_query.get(held.holder) {
+                velocity.0 = aim_direction(camera_query.get_single().ok(), holder) * held.throw_speed;
+            }
+
+            commands.entity(entity)
+                .remove::<Held>()
+                .insert(Thrown { thrower: held.holder, damage: held.damage, ttl: THROWN_TIME });
+        }
+    }
+
+    for event in interrupted_events.read().filter(|e| telekinesis(e.spell)) {
+        for (entity, ..) in held_query.iter().filter(|(_, h, _)| h.holder == event.caster) {
+            commands.entity(entity).remove::<Held>();
+        }
+    }
+}
+
+fn thrown_hits(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut thrown_query: Query<(Entity, &mut Thrown)>,
+    hostile_query: Query<(), (With<Hostile>, With<Health>)>,
+    mut collision_events: EventReader<CollisionStarted>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let mut landed = Vec::new();
+
+    for CollisionStarted(a, b) in collision_events.read() {
+        for (entity, other) in [(*a, *b), (*b, *a)] {
+            let Ok((_, thrown)) = thrown_query.get(entity) else {
+                continue;
+            };
+            if other == thrown.thrower || landed.contains(&entity) {
+                continue;
+            }
+            landed.push(entity);
+            let (damage, thrower) = (thrown.damage, thrown.thrower);
+
+            // hostiles get hurt by whatever is thrown at them, and a hostile
+            // that's thrown gets hurt by the landing
+            for target in [other, entity] {
+                if hostile_query.contains(target) {
+                    damage_events.send(DamageEvent::new(target, damage, Some(thrower)));
+                }
+            }
+
+            commands.entity(entity).remove::<Thrown>();
+        }
+    }
+
+    for (entity, mut thrown) in &mut thrown_query {
+        thrown.ttl -= time.delta_seconds();
+        if thrown.ttl <= 0.0 {
+            commands.entity(entity).remove::<Thrown>();
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+
+use crate::{
+    ai::Brain,
+    building::{BuildingKind, BuildingPlaced},
+    golem::Golem,
+    health::DamageDealt,
+    state::GameplaySet,
+    summon::Minion,
+    Player,
+};
+
+// threat per point of damage dealt
+const DAMAGE_THREAT: f32 = 1.0;
+// threat per second from standing right next to an enemy
+const PROXIMITY_THREAT: f32 = 4.0;
+const PROXIMITY_RANGE: f32 = 5.0;
+// fraction of the threat forgotten per second
+const THREAT_DECAY: f32 = 0.1;
+const DECOY_RADIUS: f32 = 7.0;
+const DECOY_THREAT: f32 = 12.0;
+
+pub struct ThreatPlugin;
+
+impl Plugin for ThreatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            init_threat_tables,
+            init_decoys,
+            threat_from_damage,
+            threat_from_proximity,
+            threat_from_taunts,
+            decay_threat,
+        ).chain().in_set(GameplaySet));
+    }
+}
+
+/// Who an enemy is angriest at. The one with the most threat is who it goes
+/// after, so anyone can pull it away by hurting it or getting in its face.
+#[derive(Component, Default)]
+pub struct ThreatTable {
+    entries: Vec<(Entity, f32)>,
+}
+
+impl ThreatTable {
+    pub fn add(&mut self, entity: Entity, threat: f32) {
+        match self.entries.iter_mut().find(|(e, _)| *e == entity) {
+            Some((_, t)) => *t += threat,
+            None => self.entries.push((entity, threat)),
+        }
+    }
+
+    pub fn forget(&mut self, entity: Entity) {
+        self.entries.retain(|(e, _)| *e != entity);
+    }
+
+    // ties are broken by entity so the pick doesn't flicker
+    pub fn top(&self) -> Option<Entity> {
+        self.entries.iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+            .map(|(e, _)| *e)
+    }
+}
+
+/// Draws the attention of every enemy within `radius`.
+#[derive(Component)]
+pub struct Taunt {
+    pub radius: f32,
+    // threat per second
+    pub threat: f32,
+}
+
+fn init_threat_tables(mut commands: Commands, brain_query: Query<Entity, Added<Brain>>) {
+    for entity in &brain_query {
+        commands.entity(entity).insert(ThreatTable::default());
+    }
+}
+
+fn init_decoys(mut commands: Commands, mut placed_events: EventReader<BuildingPlaced>) {
+    for event in placed_events.read().filter(|e| e.kind == BuildingKind::Decoy) {
+        commands.entity(event.entity).insert(Taunt { radius: DECOY_RADIUS, threat: DECOY_THREAT });
+    }
+}
+
+fn threat_from_damage(
+    mut table_query: Query<&mut ThreatTable>,
+    mut dealt_events: EventReader<DamageDealt>,
+) {
+    for event in dealt_events.read() {
+        let (Ok(mut table), Some(source)) = (table_query.get_mut(event.target), event.source) else {
+            continue;
+        };
+        // hurting itself doesn't make it angry at itself
+        if source != event.target {
+            table.add(source, event.amount * DAMAGE_THREAT);
+        }
+    }
+}
+
+fn threat_from_proximity(
+    time: Res<Time>,
+    mut table_query: Query<(&mut ThreatTable, &Transform)>,
+    nearby_query: Query<(Entity, &Transform), Or<(With<Player>, With<Golem>, With<Minion>)>>,
+) {
+    for (mut table, transform) in &mut table_query {
+        for (entity, nearby) in &nearby_query {
+            let distance = nearby.translation.distance(transform.translation);
+            if distance < PROXIMITY_RANGE {
+                table.add(entity, PROXIMITY_THREAT * (1.0 - distance / PROXIMITY_RANGE) * time.delta_seconds());
+            }
+        }
+    }
+}
+
+fn threat_from_taunts(
+    time: Res<Time>,
+    mut table_query: Query<(&mut ThreatTable, &Transform)>,
+    taunt_query: Query<(Entity, &Taunt, &Transform)>,
+) {
+    for (mut table, transform) in &mut table_query {
+        for (entity, taunt, taunter) in &taunt_query {
+            if taunter.translation.distance(transform.translation) <= taunt.radius {
+                table.add(entity, taunt.threat * time.delta_seconds());
+            }
+        }
+    }
+}
+
+fn decay_threat(
+    time: Res<Time>,
+    mut table_query: Query<&mut ThreatTable>,
+    alive_query: Query<(), With<Transform>>,
+) {
+    let keep = 1.0 - THREAT_DECAY * time.delta_seconds();
+
+    for mut table in &mut table_query {
+        // forget about anything that's gone
+        table.entries.retain_mut(|(entity, threat)| {
+            *threat *= keep;
+            *threat > 0.01 && alive_query.contains(*entity)
+        });
+    }
+}
@@ -0,0 +1,278 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera::Camera,
+    locale::Locale,
+    magic::SpellBook,
+    overload::Backlash,
+    progression::SpellLevelUp,
+    raid::{RaidStarted, RaidWarning},
+    research::TechnologyResearched,
+    state::GameplaySet,
+    Player,
+};
+
+const MAX_SHOWN: usize = 4;
+// seconds a repeated toast counts as the same one
+const MERGE_WINDOW: f32 = 2.0;
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Toasts>()
+            .add_event::<Notify>()
+            .add_systems(Startup, setup_toast_ui)
+            .add_systems(Update, (
+                notify_research,
+                notify_raids,
+                notify_level_ups,
+                notify_backlash,
+                collect_toasts,
+                focus_toasts,
+                update_toast_ui,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+}
+
+impl Priority {
+    // seconds on screen
+    fn duration(&self) -> f32 {
+        match self {
+            Priority::Low => 3.0,
+            Priority::Normal => 5.0,
+            Priority::High => 8.0,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Priority::Low => Color::GRAY,
+            Priority::Normal => Color::WHITE,
+            Priority::High => Color::ORANGE,
+        }
+    }
+}
+
+/// Puts up a toast, clicking it points the camera at `focus` if set.
+#[derive(Event)]
+pub struct Notify {
+    pub message: String,
+    pub priority: Priority,
+    pub focus: Option<Entity>,
+}
+
+impl Notify {
+    pub fn new(message: impl Into<String>, priority: Priority) -> Self {
+        Notify { message: message.into(), priority, focus: None }
+    }
+
+    pub fn focusing(mut self, entity: Entity) -> Self {
+        self.focus = Some(entity);
+        self
+    }
+}
+
+struct Toast {
+    message: String,
+    priority: Priority,
+    focus: Option<Entity>,
+    // how many times it came in, shown as "x3"
+    count: u32,
+    // seconds on screen, toasts waiting in the queue don't age
+    age: f32,
+}
+
+/// Shown toasts come first, the most important ones on top.
+#[derive(Resource, Default)]
+pub struct Toasts(Vec<Toast>);
+
+#[derive(Component)]
+struct ToastRow(usize);
+
+fn notify_research(
+    locale: Res<Locale>,
+    mut researched_events: EventReader<TechnologyResearched>,
+    mut notify_events: EventWriter<Notify>,
+) {
+    for TechnologyResearched(tech) in researched_events.read() {
+        let message = locale.fill("Researched {}", &[&locale.get(tech.name())]);
+        notify_events.send(Notify::new(message, Priority::Normal));
+        for recipe in tech.recipes() {
+            let message = locale.fill("Unlocked {}", &[&locale.get(recipe.name())]);
+            notify_events.send(Notify::new(message, Priority::Low));
+        }
+    }
+}
+
+fn notify_raids(
+    locale: Res<Locale>,
+    mut warning_events: EventReader<RaidWarning>,
+    mut started_events: EventReader<RaidStarted>,
+    mut notify_events: EventWriter<Notify>,
+) {
+    for event in warning_events.read() {
+        let message = locale.fill("Raid incoming from the {}: {} raiders", &[&locale.get(event.edge.name()), &event.size]);
+        notify_events.send(Notify::new(message, Priority::High));
+    }
+    for event in started_events.read() {
+        let message = locale.fill("The raid from the {} has arrived", &[&locale.get(event.edge.name())]);
+        let mut notify = Notify::new(message, Priority::High);
+        if let Some(raider) = event.raiders.first() {
+            notify = notify.focusing(*raider);
+        }
+        notify_events.send(notify);
+    }
+}
+
+fn notify_level_ups(
+    locale: Res<Locale>,
+    book: Res<SpellBook>,
+    player_query: Query<(), With<Player>>,
+    mut level_events: EventReader<SpellLevelUp>,
+    mut notify_events: EventWriter<Notify>,
+) {
+    for event in level_events.read() {
+        if !player_query.contains(event.caster) {
+            continue;
+        }
+        let Some(spell) = book.get(event.spell) else {
+            continue;
+        };
+        let message = locale.fill("{} reached level {}", &[&locale.get(&spell.name), &event.level]);
+        notify_events.send(Notify::new(message, Priority::Normal));
+    }
+}
+
+fn notify_backlash(
+    locale: Res<Locale>,
+    player_query: Query<(), With<Player>>,
+    mut backlash_events: EventReader<Backlash>,
+    mut notify_events: EventWriter<Notify>,
+) {
+    for event in backlash_events.read() {
+        if player_query.contains(event.caster) {
+            notify_events.send(Notify::new(locale.get(event.effect.message()), Priority::High));
+        }
+    }
+}
+
+fn collect_toasts(
+    time: Res<Time>,
+    mut toasts: ResMut<Toasts>,
+    mut notify_events: EventReader<Notify>,
+) {
+    for toast in toasts.0.iter_mut().take(MAX_SHOWN) {
+        toast.age += time.delta_seconds();
+    }
+    toasts.0.retain(|t| t.age < t.priority.duration());
+
+    for event in notify_events.read() {
+        // the same thing again shortly after stacks instead of piling up
+        let repeat = toasts.0.iter_mut()
+            .find(|t| t.message == event.message && t.age < MERGE_WINDOW);
+        if let Some(toast) = repeat {
+            toast.count += 1;
+            toast.age = 0.0;
+            continue;
+        }
+
+        toasts.0.push(Toast {
+            message: event.message.clone(),
+            priority: event.priority,
+            focus: event.focus,
+            count: 1,
+            age: 0.0,
+        });
+    }
+
+    // stable, so equally important toasts keep their order
+    toasts.0.sort_by_key(|t| std::cmp::Reverse(t.priority));
+}
+
+fn focus_toasts(
+    toasts: Res<Toasts>,
+    row_query: Query<(&ToastRow, &Interaction), Changed<Interaction>>,
+    mut cam_query: Query<&mut Camera>,
+) {
+    for (row, interaction) in &row_query {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if let Some(entity) = toasts.0.get(row.0).and_then(|t| t.focus) {
+            cam_query.get_single_mut().expect("not one camera").focus_on(entity);
+        }
+    }
+}
+
+fn setup_toast_ui(mut commands: Commands) {
+    commands.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            top: Val::Px(80.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        ..default()
+    }).with_children(|parent| {
+        for i in 0..MAX_SHOWN {
+            parent.spawn((
+                ToastRow(i),
+                ButtonBundle {
+                    style: Style {
+                        display: Display::None,
+                        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                    ..default()
+                },
+            )).with_children(|row| {
+                row.spawn(TextBundle::from_section("", TextStyle {
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                    ..default()
+                }));
+            });
+        }
+    });
+}
+
+fn update_toast_ui(
+    toasts: Res<Toasts>,
+    mut row_query: Query<(&ToastRow, &mut Style, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (ToastRow(i), mut style, children) in &mut row_query {
+        let Some(toast) = toasts.0.get(*i) else {
+            style.display = Display::None;
+            continue;
+        };
+        style.display = Display::Flex;
+
+        let Some(mut text) = children.first().and_then(|c| text_query.get_mut(*c).ok()) else {
+            continue;
+        };
+        let section = &mut text.sections[0];
+        section.value = if toast.count > 1 {
+            format!("{} x{}", toast.message, toast.count)
+        } else {
+            toast.message.clone()
+        };
+        // fade out over the last second
+        let remaining = toast.priority.duration() - toast.age;
+        section.style.color = toast.priority.color().with_a(remaining.clamp(0.0, 1.0));
+    }
+}
@@ -0,0 +1,216 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    brewing::Potion,
+    enchanting::MAX_ENCHANTMENTS,
+    implement::ImplementStats,
+    item::{Item, ItemStack},
+    locale::Locale,
+    recipe::Recipe,
+    research::{Research, Technology},
+    settings::{Palette, Settings},
+    state::GameplaySet,
+};
+
+// keeps the box from sitting right under the cursor
+const OFFSET: f32 = 16.0;
+const WIDTH: f32 = 260.0;
+const MUTED: Color = Color::rgb(0.7, 0.7, 0.7);
+const ENCHANTED: Color = Color::rgb(0.6, 0.5, 1.0);
+
+pub struct TooltipPlugin;
+
+impl Plugin for TooltipPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, setup_tooltip)
+            .add_systems(Update, show_tooltip.in_set(GameplaySet));
+    }
+}
+
+/// Describes the stack while the cursor is over this ui node.
+#[derive(Component, Default)]
+pub struct ItemTooltip(pub Option<ItemStack>);
+
+/// Describes the technology while the cursor is over this ui node.
+#[derive(Component)]
+pub struct TechTooltip(pub Technology);
+
+#[derive(Component)]
+struct Tooltip;
+
+#[derive(Component)]
+struct TooltipText;
+
+fn setup_tooltip(mut commands: Commands) {
+    commands.spawn((
+        Tooltip,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                max_width: Val::Px(WIDTH),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            visibility: Visibility::Hidden,
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+    )).with_children(|parent| {
+        parent.spawn((TooltipText, TextBundle::default()));
+    });
+}
+
+fn show_tooltip(
+    locale: Res<Locale>,
+    research: Res<Research>,
+    settings: Res<Settings>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    hover_query: Query<(&ItemTooltip, &Node, &GlobalTransform, &InheritedVisibility)>,
+    tech_query: Query<(&TechTooltip, &Node, &GlobalTransform, &InheritedVisibility)>,
+    mut tooltip_query: Query<(&mut Style, &mut Visibility), With<Tooltip>>,
+    mut text_query: Query<&mut Text, With<TooltipText>>,
+) {
+    let (mut style, mut visibility) = tooltip_query.get_single_mut().expect("not one tooltip");
+    let cursor = window_query.get_single().expect("not one window").cursor_position();
+
+    let hovered = cursor.and_then(|cursor| {
+        hover_query.iter()
+            .filter(|(.., visible)| visible.get())
+            .find(|(_, node, t, _)| node.logical_rect(t).contains(cursor))
+            .and_then(|(tooltip, ..)| tooltip.0)
+            .map(|stack| sections(&stack, &locale))
+    });
+    let hovered = hovered.or_else(|| cursor.and_then(|cursor| {
+        tech_query.iter()
+            .filter(|(.., visible)| visible.get())
+            .find(|(_, node, t, _)| node.logical_rect(t).contains(cursor))
+            .map(|(tooltip, ..)| tech_sections(tooltip.0, &research, settings.palette(), &locale))
+    }));
+    let (Some(sections), Some(cursor)) = (hovered, cursor) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    *visibility = Visibility::Visible;
+    style.left = Val::Px(cursor.x + OFFSET);
+    style.top = Val::Px(cursor.y + OFFSET);
+    text_query.get_single_mut().expect("not one tooltip text").sections = sections;
+}
+
+fn sections(stack: &ItemStack, locale: &Locale) -> Vec<TextSection> {
+    let line = |value: String, size: f32, color: Color| {
+        TextSection::new(value + "\n", TextStyle { font_size: size, color, ..default() })
+    };
+    let item = stack.item;
+    let implement = ImplementStats::of(stack);
+
+    let title = match implement {
+        Some(_) => format!("{} {}", locale.get(stack.rarity.name()), locale.get(item.name())),
+        None => locale.get(item.name()).to_string(),
+    };
+    let mut sections = vec![
+        line(title, 18.0, item.color()),
+        line(locale.get(item.description()).to_string(), 14.0, MUTED),
+    ];
+
+    // what it does
+    let mut stats = Vec::new();
+    if let Some(slot) = item.gear_slot() {
+        stats.push(locale.fill("{} gear", &[&locale.get(slot.name())]));
+    }
+    if let Some(implement) = implement {
+        if implement.cast_speed > 0.0 {
+            stats.push(locale.fill("+{}% cast speed", &[&format!("{:.0}", implement.cast_speed * 100.0)]));
+        }
+        if implement.mana_efficiency > 0.0 {
+            stats.push(locale.fill("-{}% mana cost", &[&format!("{:.0}", implement.mana_efficiency * 100.0)]));
+        }
+        if implement.extra_projectiles > 0 {
+            stats.push(locale.fill("+{} projectiles", &[&implement.extra_projectiles]));
+        }
+    }
+    if let Some(potion) = Potion::ALL.into_iter().find(|p| p.item() == item) {
+        let status = potion.status();
+        stats.push(locale.fill("{} for {}s", &[&locale.get(status.kind.name()), &format!("{:.0}", status.remaining)]));
+    }
+    sections.extend(stats.into_iter().map(|s| line(s, 14.0, Color::WHITE)));
+
+    if item.gear_slot().is_some() {
+        for enchantment in stack.enchantments() {
+            let value = format!("{}: {}", locale.get(enchantment.name()), locale.get(enchantment.description()));
+            sections.push(line(value, 14.0, ENCHANTED));
+        }
+        let free = MAX_ENCHANTMENTS - stack.enchantments().count();
+        if free > 0 {
+            sections.push(line(locale.fill("{} free sockets", &[&free]), 14.0, MUTED));
+        }
+    }
+
+    // where it comes from and where it goes
+    if let Some(recipe) = Recipe::ALL.into_iter().find(|r| r.output().item == item && !r.inputs().is_empty()) {
+        sections.push(line(locale.fill("Made from {}", &[&list_inputs(recipe.inputs(), locale)]), 14.0, MUTED));
+    }
+    if let Some(potion) = Potion::ALL.into_iter().find(|p| p.item() == item) {
+        sections.push(line(locale.fill("Brewed from {}", &[&list_inputs(potion.reagents(), locale)]), 14.0, MUTED));
+    }
+
+    let mut uses: Vec<&str> = Recipe::ALL.iter()
+        .filter(|r| r.inputs().iter().any(|(i, _)| *i == item))
+        .map(|r| locale.get(r.name()))
+        .chain(Potion::ALL.iter().filter(|p| p.reagents().iter().any(|(i, _)| *i == item)).map(|p| locale.get(p.name())))
+        .collect();
+    if item == Item::Essence {
+        uses.push(locale.get("Enchanting"));
+    }
+    if !uses.is_empty() {
+        sections.push(line(locale.fill("Used in {}", &[&uses.join(", ")]), 14.0, MUTED));
+    }
+
+    // no trailing newline after the last line
+    if let Some(last) = sections.last_mut() {
+        last.value.pop();
+    }
+    sections
+}
+
+fn tech_sections(tech: Technology, research: &Research, palette: &Palette, locale: &Locale) -> Vec<TextSection> {
+    let line = |value: String, size: f32, color: Color| {
+        TextSection::new(value + "\n", TextStyle { font_size: size, color, ..default() })
+    };
+
+    let mut sections = vec![
+        line(locale.get(tech.name()).to_string(), 18.0, Color::WHITE),
+        line(locale.fill("{} units of {} each", &[&tech.units(), &list_inputs(tech.unit_cost(), locale)]), 14.0, MUTED),
+        line(locale.fill("{}s per unit in one lab", &[&format!("{:.0}", tech.unit_time())]), 14.0, MUTED),
+    ];
+
+    // prerequisites still missing are what keep it locked
+    if !tech.prerequisites().is_empty() {
+        let names: Vec<&str> = tech.prerequisites().iter().map(|p| locale.get(p.name())).collect();
+        let met = tech.prerequisites().iter().all(|p| research.unlocked.contains(p));
+        let color = if met { MUTED } else { palette.bad };
+        sections.push(line(locale.fill("Requires {}", &[&names.join(", ")]), 14.0, color));
+    }
+
+    let unlocks: Vec<&str> = tech.buildings().iter()
+        .map(|b| locale.get(b.name()))
+        .chain(tech.recipes().iter().map(|r| locale.get(r.name())))
+        .collect();
+    if !unlocks.is_empty() {
+        sections.push(line(locale.fill("Unlocks {}", &[&unlocks.join(", ")]), 14.0, Color::WHITE));
+    }
+
+    if let Some(last) = sections.last_mut() {
+        last.value.pop();
+    }
+    sections
+}
+
+fn list_inputs(inputs: &[(Item, u32)], locale: &Locale) -> String {
+    inputs.iter()
+        .map(|(item, count)| format!("{count} {}", locale.get(item.name())))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
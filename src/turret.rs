@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+
+use crate::{
+    building::{AimPoint, BuildingGrid, BuildingKind, BuildingPlaced, GridPos},
+    cooldown::{Ability, Cooldowns},
+    enemy::Hostile,
+    health::{Damage, DamageEvent, DamageType, Health},
+    hitbox::{resolve_hit, Hurtbox},
+    module::{self, ModuleSlots},
+    power::{ManaConsumer, ManaGrid},
+    state::GameplaySet,
+    wildlife::Wildlife,
+};
+
+// mana per second just to stay armed
+const IDLE_DEMAND: f32 = 1.0;
+const BOLT_SPEED: f32 = 12.0;
+
+pub struct TurretPlugin;
+
+impl Plugin for TurretPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            init_turrets,
+            configure_turrets,
+            fire_turrets,
+            move_bolts,
+            draw_beams,
+        ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TargetPriority {
+    #[default]
+    Nearest,
+    Weakest,
+    Strongest,
+}
+
+impl TargetPriority {
+    pub fn next(self) -> Self {
+        match self {
+            TargetPriority::Nearest => TargetPriority::Weakest,
+            TargetPriority::Weakest => TargetPriority::Strongest,
+            TargetPriority::Strongest => TargetPriority::Nearest,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weapon {
+    // hits instantly
+    Beam,
+    // fires a slow homing bolt
+    Bolt,
+}
+
+impl Weapon {
+    pub fn damage_type(self) -> DamageType {
+        match self {
+            Weapon::Beam => DamageType::Arc,
+            Weapon::Bolt => DamageType::Fire,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Turret {
+    pub weapon: Weapon,
+    pub range: f32,
+    pub damage: f32,
+    // mana per second while firing
+    pub firing_demand: f32,
+    pub priority: TargetPriority,
+    // seconds between shots at full power
+    pub interval: f32,
+}
+
+#[derive(Component)]
+struct Bolt {
+    turret: Entity,
+    target: Entity,
+    damage: Damage,
+}
+
+#[derive(Component)]
+struct Beam {
+    from: Vec3,
+    to: Vec3,
+    ttl: f32,
+}
+
+fn init_turrets(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read() {
+        let turret = match event.kind {
+            BuildingKind::BeamTurret => Turret {
+                weapon: Weapon::Beam,
+                range: 8.0,
+                damage: 6.0,
+                firing_demand: 12.0,
+                priority: TargetPriority::default(),
+                interval: 0.4,
+            },
+            BuildingKind::BoltTurret => Turret {
+                weapon: Weapon::Bolt,
+                range: 12.0,
+                damage: 20.0,
+                firing_demand: 6.0,
+                priority: TargetPriority::default(),
+                interval: 1.5,
+            },
+            _ => continue,
+        };
+
+        commands.entity(event.entity).insert((turret, ManaConsumer { demand: IDLE_DEMAND }));
+    }
+}
+
+fn configure_turrets(
+    keys: Res<Input<KeyCode>>,
+    aim: Res<AimPoint>,
+    grid: Res<BuildingGrid>,
+    mut turret_query: Query<&mut Turret>,
+) {
+    if !keys.just_pressed(KeyCode::F) {
+        return;
+    }
+
+    let Some(entity) = aim.0.and_then(|a| grid.get(GridPos::from_world(a))) else {
+        return;
+    };
+
+    if let Ok(mut turret) = turret_query.get_mut(entity) {
+        turret.priority = turret.priority.next();
+    }
+}
+
+fn fire_turrets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    grid: Res<ManaGrid>,
+    mut cooldowns: ResMut<Cooldowns>,
+    spatial_query: SpatialQuery,
+    mut turret_query: Query<(Entity, &Turret, &mut ManaConsumer, &Transform, Option<&ModuleSlots>)>,
+    hostile_query: Query<(Entity, &Transform, &Health), (With<Hostile>, Without<Turret>, Without<Wildlife>)>,
+    hurtbox_query: Query<&Hurtbox>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, turret, mut consumer, transform, modules) in &mut turret_query {
+        let muzzle = transform.translation + Vec3::Y * 0.6;
+        let effects = module::effects(modules);
+        let range = turret.range * effects.range;
+
+        // only consider targets in range that the turret can actually see
+        let visible = hostile_query.iter()
+            .filter(|(_, t, ..)| t.translation.distance(muzzle) <= range)
+            .filter(|(target, t, ..)| {
+                let to_target = t.translation - muzzle;
+                spatial_query.cast_ray(
+                    muzzle,
+                    to_target.normalize_or_zero(),
+                    to_target.length(),
+                    true,
+                    SpatialQueryFilter::new().without_entities([entity]),
+                // hitting the target's own weak spot still counts as seeing it
+                ).is_none_or(|hit| resolve_hit(&hurtbox_query, hit.entity).0 == *target)
+            })
+            .map(|(e, t, h)| (e, t.translation, h.current));
+
+        let target = match turret.priority {
+            TargetPriority::Nearest => visible.min_by(|a, b| {
+                a.1.distance(muzzle).total_cmp(&b.1.distance(muzzle))
+            }),
+            TargetPriority::Weakest => visible.min_by(|a, b| a.2.total_cmp(&b.2)),
+            TargetPriority::Strongest => visible.max_by(|a, b| a.2.total_cmp(&b.2)),
+        };
+
+        let Some((target, target_pos, _)) = target else {
+            consumer.demand = IDLE_DEMAND * effects.mana;
+            continue;
+        };
+
+        // a weak grid makes the turret fire slower
+        consumer.demand = (IDLE_DEMAND + turret.firing_demand) * effects.mana;
+        cooldowns.set_rate(entity, Ability::TurretShot, grid.satisfaction * effects.speed);
+        if !cooldowns.is_ready(entity, Ability::TurretShot) {
+            continue;
+        }
+        cooldowns.start(entity, Ability::TurretShot, turret.interval);
+
+        match turret.weapon {
+            Weapon::Beam => {
+                damage_events.send(DamageEvent::new(
+                    target,
+                    Damage::new(turret.damage, turret.weapon.damage_type()),
+                    Some(entity),
+                ));
+                commands.spawn(Beam { from: muzzle, to: target_pos, ttl: 0.1 });
+            }
+            Weapon::Bolt => {
+                commands.spawn((
+                    Bolt {
+                        turret: entity,
+                        target,
+                        damage: Damage::new(turret.damage, turret.weapon.damage_type()),
+                    },
+                    PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::UVSphere {
+                            radius: 0.12,
+                            sectors: 8,
+                            stacks: 6,
+                        })),
+                        material: materials.add(StandardMaterial {
+                            emissive: Color::ORANGE_RED,
+                            ..default()
+                        }),
+                        transform: Transform::from_translation(muzzle),
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+}
+
+fn move_bolts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bolt_query: Query<(Entity, &Bolt, &mut Transform)>,
+    target_query: Query<&Transform, (With<Health>, Without<Bolt>)>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    for (entity, bolt, mut transform) in &mut bolt_query {
+        // the target died before the bolt arrived
+        let Ok(target) = target_query.get(bolt.target) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        let to_target = target.translation - transform.translation;
+        let step = BOLT_SPEED * time.delta_seconds();
+
+        if to_target.length() <= step {
+            damage_events.send(DamageEvent::new(bolt.target, bolt.damage, Some(bolt.turret)));
+            commands.entity(entity).despawn_recursive();
+        } else {
+            transform.translation += to_target.normalize() * step;
+        }
+    }
+}
+
+fn draw_beams(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut beam_query: Query<(Entity, &mut Beam)>,
+    mut gizmos: Gizmos,
+) {
+    for (entity, mut beam) in &mut beam_query {
+        gizmos.line(beam.from, beam.to, Color::CYAN);
+
+        beam.ttl -= time.delta_seconds();
+        if beam.ttl <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    audio::{Bus, Mixer},
+    cooldown::{Ability, CooldownExpired},
+    crafting::CraftingPanel,
+    dialogue::Dialogue,
+    hotbar::Hotbar,
+    input::ControlsMenu,
+    inventory_ui::InventoryScreen,
+    loading::Preload,
+    map::WorldMap,
+    quest::QuestLog,
+    radial::RadialMenu,
+    settings::SettingsMenu,
+    state::GameState,
+    tech_tree::TechTree,
+    Player,
+};
+
+pub struct UiSoundPlugin;
+
+impl Plugin for UiSoundPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<PlayUiSound>()
+            .add_systems(Startup, preload_ui_sounds)
+            // menus make sounds too, so none of this is gameplay only
+            .add_systems(Update, (
+                widget_sounds,
+                screen_sounds,
+                hotbar_sounds,
+                cooldown_sounds,
+                play_ui_sounds,
+            ).chain());
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UiSound {
+    Click,
+    Hover,
+    Open,
+    Close,
+    // a spell can be cast again
+    Ready,
+    // asked for something that can't be done
+    Error,
+}
+
+impl UiSound {
+    const ALL: [UiSound; 6] = [
+        UiSound::Click,
+        UiSound::Hover,
+        UiSound::Open,
+        UiSound::Close,
+        UiSound::Ready,
+        UiSound::Error,
+    ];
+
+    fn path(&self) -> &'static str {
+        match self {
+            UiSound::Click => "sounds/ui/click.ogg",
+            UiSound::Hover => "sounds/ui/hover.ogg",
+            UiSound::Open => "sounds/ui/open.ogg",
+            UiSound::Close => "sounds/ui/close.ogg",
+            UiSound::Ready => "sounds/ui/ready.ogg",
+            UiSound::Error => "sounds/ui/error.ogg",
+        }
+    }
+
+    fn volume(&self) -> f32 {
+        match self {
+            UiSound::Hover | UiSound::Ready => 0.3,
+            UiSound::Error => 0.8,
+            _ => 0.6,
+        }
+    }
+}
+
+/// Plays an interface sound, the same wherever it's asked for.
+#[derive(Event)]
+pub struct PlayUiSound(pub UiSound);
+
+// every button and clickable node, whichever screen it's on
+fn preload_ui_sounds(asset_server: Res<AssetServer>, mut preload: ResMut<Preload>) {
+    for sound in UiSound::ALL {
+        preload.add(asset_server.load::<AudioSource>(sound.path()));
+    }
+}
+
+fn widget_sounds(
+    interaction_query: Query<&Interaction, Changed<Interaction>>,
+    mut sound_events: EventWriter<PlayUiSound>,
+) {
+    for interaction in &interaction_query {
+        match interaction {
+            Interaction::Pressed => sound_events.send(PlayUiSound(UiSound::Click)),
+            Interaction::Hovered => sound_events.send(PlayUiSound(UiSound::Hover)),
+            Interaction::None => {}
+        }
+    }
+}
+
+fn screen_sounds(
+    state: Res<State<GameState>>,
+    screens: (
+        Res<InventoryScreen>,
+        Res<CraftingPanel>,
+        Res<WorldMap>,
+        Res<TechTree>,
+        Res<QuestLog>,
+        Res<Dialogue>,
+        Res<RadialMenu>,
+        Res<SettingsMenu>,
+        Res<ControlsMenu>,
+    ),
+    mut sound_events: EventWriter<PlayUiSound>,
+    mut was_open: Local<Vec<bool>>,
+    mut was_holding: Local<bool>,
+) {
+    let (inventory, crafting, map, tech_tree, quests, dialogue, radial, settings, controls) = screens;
+    let open = vec![
+        inventory.open,
+        crafting.open,
+        map.open,
+        tech_tree.open,
+        quests.open,
+        dialogue.is_open(),
+        radial.open,
+        settings.open,
+        controls.open,
+        *state.get() == GameState::Paused,
+    ];
+
+    // nothing to compare against the very first time
+    if was_open.len() == open.len() {
+        for (now, before) in open.iter().zip(was_open.iter()) {
+            match (now, before) {
+                (true, false) => sound_events.send(PlayUiSound(UiSound::Open)),
+                (false, true) => sound_events.send(PlayUiSound(UiSound::Close)),
+                _ => {}
+            }
+        }
+    }
+    *was_open = open;
+
+    // picking a stack up and putting it down again
+    if inventory.is_holding() != *was_holding {
+        *was_holding = inventory.is_holding();
+        sound_events.send(PlayUiSound(UiSound::Click));
+    }
+}
+
+fn hotbar_sounds(
+    hotbar_query: Query<&Hotbar, With<Player>>,
+    mut sound_events: EventWriter<PlayUiSound>,
+    mut selected: Local<Option<usize>>,
+) {
+    let Ok(hotbar) = hotbar_query.get_single() else {
+        *selected = None;
+        return;
+    };
+    if selected.is_some_and(|s| s != hotbar.selected) {
+        sound_events.send(PlayUiSound(UiSound::Click));
+    }
+    *selected = Some(hotbar.selected);
+}
+
+fn cooldown_sounds(
+    player_query: Query<(), With<Player>>,
+    mut expired_events: EventReader<CooldownExpired>,
+    mut sound_events: EventWriter<PlayUiSound>,
+) {
+    for event in expired_events.read() {
+        if matches!(event.ability, Ability::Spell(_)) && player_query.contains(event.entity) {
+            sound_events.send(PlayUiSound(UiSound::Ready));
+        }
+    }
+}
+
+fn play_ui_sounds(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mixer: Res<Mixer>,
+    mut sound_events: EventReader<PlayUiSound>,
+) {
+    for PlayUiSound(sound) in sound_events.read() {
+        commands.spawn(AudioBundle {
+            source: asset_server.load(sound.path()),
+            settings: PlaybackSettings::DESPAWN
+                .with_volume(Volume::new_relative(sound.volume() * mixer.volume(Bus::Ui))),
+        });
+    }
+}
@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+
+use crate::{
+    building::{
+        Building, BuildingGrid, BuildingKind, BuildingPlaced, BuildingRemoved, Facing, GridPos,
+    },
+    state::GameplaySet,
+};
+
+// max number of tiles between an entrance and its exit
+pub const MAX_UNDERGROUND_GAP: i32 = 4;
+
+pub struct UndergroundPlugin;
+
+impl Plugin for UndergroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            pair_underground_belts,
+            unpair_removed_belts,
+            draw_underground_links,
+        ).in_set(GameplaySet));
+    }
+}
+
+#[derive(Component)]
+pub struct UndergroundBelt {
+    pub exit: bool,
+    pub partner: Option<Entity>,
+}
+
+/// Finds the entrance an exit placed at `pos` would connect to.
+/// Another exit facing the same way in between blocks the tunnel.
+pub fn find_entrance(
+    pos: GridPos,
+    facing: Facing,
+    grid: &BuildingGrid,
+    building_query: &Query<(&Building, &Facing)>,
+) -> Option<Entity> {
+    (1..=MAX_UNDERGROUND_GAP + 1)
+        .map(|i| GridPos(pos.0 - facing.delta() * i))
+        .filter_map(|tile| grid.get(tile))
+        .filter_map(|e| building_query.get(e).ok().map(|(b, f)| (e, b.kind, *f)))
+        .find(|(_, kind, f)| *f == facing && matches!(
+            kind,
+            BuildingKind::UndergroundEntrance | BuildingKind::UndergroundExit
+        ))
+        .and_then(|(e, kind, _)| (kind == BuildingKind::UndergroundEntrance).then_some(e))
+}
+
+fn pair_underground_belts(
+    mut commands: Commands,
+    grid: Res<BuildingGrid>,
+    building_query: Query<(&Building, &Facing)>,
+    mut underground_query: Query<&mut UndergroundBelt>,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read() {
+        match event.kind {
+            BuildingKind::UndergroundEntrance => {
+                commands.entity(event.entity).insert(UndergroundBelt {
+                    exit: false,
+                    partner: None,
+                });
+            }
+            BuildingKind::UndergroundExit => {
+                let entrance = find_entrance(event.pos, event.facing, &grid, &building_query);
+
+                // an exit placed closer to the entrance takes over the tunnel
+                if let Some(mut entrance) = entrance.and_then(|e| underground_query.get_mut(e).ok()) {
+                    if let Some(old_exit) = entrance.partner.replace(event.entity) {
+                        commands.entity(old_exit).insert(UndergroundBelt {
+                            exit: true,
+                            partner: None,
+                        });
+                    }
+                }
+
+                commands.entity(event.entity).insert(UndergroundBelt {
+                    exit: true,
+                    partner: entrance,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn unpair_removed_belts(
+    mut underground_query: Query<&mut UndergroundBelt>,
+    mut removed_events: EventReader<BuildingRemoved>,
+) {
+    let removed: Vec<Entity> = removed_events.read().map(|e| e.entity).collect();
+    if removed.is_empty() {
+        return;
+    }
+
+    for mut underground in &mut underground_query {
+        if underground.partner.is_some_and(|p| removed.contains(&p)) {
+            underground.partner = None;
+        }
+    }
+}
+
+fn draw_underground_links(
+    underground_query: Query<(&UndergroundBelt, &GridPos)>,
+    pos_query: Query<&GridPos>,
+    mut gizmos: Gizmos,
+) {
+    for (underground, pos) in &underground_query {
+        let (false, Some(partner)) = (underground.exit, underground.partner) else {
+            continue;
+        };
+
+        if let Ok(exit_pos) = pos_query.get(partner) {
+            let offset = Vec3::Y * 0.05;
+            gizmos.line(pos.to_world() + offset, exit_pos.to_world() + offset, Color::ORANGE);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    interaction::{interact, Interactable, Interacted},
+    locale::Locale,
+    minecart::Riding,
+    state::GameplaySet,
+    toast::{Notify, Priority},
+    Player,
+};
+
+const WAYSTONE_COUNT: usize = 6;
+const WAYSTONE_MIN_DISTANCE: f32 = 25.0;
+const WAYSTONE_MAX_DISTANCE: f32 = 120.0;
+// where travellers land, next to the stone rather than inside it
+const ARRIVAL_OFFSET: Vec3 = Vec3::new(1.5, 1.0, 0.0);
+pub const WAYSTONE_COLOR: Color = Color::rgb(0.4, 0.8, 1.0);
+
+pub struct WaystonePlugin;
+
+impl Plugin for WaystonePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<FastTravel>()
+            .add_systems(Startup, spawn_waystones)
+            .add_systems(Update, (attune_waystones.after(interact), fast_travel).in_set(GameplaySet));
+    }
+}
+
+/// Scattered around the world, once attuned the player can travel to it.
+#[derive(Component, Default)]
+pub struct Waystone {
+    pub attuned: bool,
+}
+
+/// Moves the player to an attuned waystone.
+#[derive(Event)]
+pub struct FastTravel {
+    pub to: Entity,
+}
+
+fn spawn_waystones(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut rng = rand::thread_rng();
+    let mesh = meshes.add(Mesh::from(shape::Box::new(0.5, 2.0, 0.5)));
+    let material = materials.add(StandardMaterial {
+        base_color: WAYSTONE_COLOR,
+        emissive: WAYSTONE_COLOR * 0.5,
+        ..default()
+    });
+
+    for _ in 0..WAYSTONE_COUNT {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let distance = rng.gen_range(WAYSTONE_MIN_DISTANCE..WAYSTONE_MAX_DISTANCE);
+        commands.spawn((
+            Waystone::default(),
+            Interactable::new("Attune Waystone"),
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_xyz(angle.cos() * distance, 1.0, angle.sin() * distance),
+                ..default()
+            },
+        ));
+    }
+}
+
+fn attune_waystones(
+    mut commands: Commands,
+    locale: Res<Locale>,
+    mut waystone_query: Query<&mut Waystone>,
+    mut interacted_events: EventReader<Interacted>,
+    mut notify_events: EventWriter<Notify>,
+) {
+    for event in interacted_events.read() {
+        let Ok(mut waystone) = waystone_query.get_mut(event.entity) else {
+            continue;
+        };
+
+        waystone.attuned = true;
+        // there's nothing left to do with it up close
+        commands.entity(event.entity).remove::<Interactable>();
+        notify_events.send(Notify::new(locale.get("Waystone attuned"), Priority::Normal).focusing(event.entity));
+    }
+}
+
+fn fast_travel(
+    waystone_query: Query<(&Waystone, &Transform), Without<Player>>,
+    mut player_query: Query<(&mut Transform, &mut LinearVelocity), (With<Player>, Without<Riding>)>,
+    mut travel_events: EventReader<FastTravel>,
+) {
+    for event in travel_events.read() {
+        let Ok((waystone, target)) = waystone_query.get(event.to) else {
+            continue;
+        };
+        // can't leave a minecart behind
+        let Ok((mut transform, mut velocity)) = player_query.get_single_mut() else {
+            continue;
+        };
+        if !waystone.attuned {
+            continue;
+        }
+
+        transform.translation = target.translation + ARRIVAL_OFFSET;
+        velocity.0 = Vec3::ZERO;
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+
+use crate::{
+    alert::{AlertKind, RaiseAlert},
+    building::{AimPoint, BuildingGrid, BuildingKind, BuildingPlaced, GridPos},
+    inventory::Inventory,
+    item::Item,
+    machine::Machine,
+    module,
+    module::ModuleSlots,
+    state::GameplaySet,
+    Player,
+};
+
+// durability lost per second of crafting, a fresh machine lasts about ten minutes
+const WEAR_PER_SECOND: f32 = 1.0 / 600.0;
+// machines start slowing down below this
+pub const WORN_DURABILITY: f32 = 0.5;
+// maintenance drones come by below this
+pub const SERVICE_DURABILITY: f32 = 0.3;
+
+pub struct WearPlugin;
+
+impl Plugin for WearPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+            init_wear,
+            wear_machines,
+            repair_input,
+        ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Component)]
+pub struct Wear {
+    // 1.0 when new, broken at 0.0
+    pub durability: f32,
+}
+
+impl Default for Wear {
+    fn default() -> Self {
+        Wear { durability: 1.0 }
+    }
+}
+
+impl Wear {
+    pub fn is_broken(&self) -> bool {
+        self.durability <= 0.0
+    }
+
+    /// Multiplier for the machine's crafting speed.
+    pub fn speed_factor(&self) -> f32 {
+        if self.is_broken() {
+            return 0.0;
+        }
+
+        // down to half speed right before it breaks
+        0.5 + 0.5 * (self.durability / WORN_DURABILITY).min(1.0)
+    }
+
+    pub fn repair(&mut self) {
+        self.durability = 1.0;
+    }
+}
+
+fn init_wear(
+    mut commands: Commands,
+    mut placed_events: EventReader<BuildingPlaced>,
+) {
+    for event in placed_events.read().filter(|e| e.kind == BuildingKind::Assembler) {
+        commands.entity(event.entity).insert(Wear::default());
+    }
+}
+
+fn wear_machines(
+    time: Res<Time>,
+    mut machine_query: Query<(Entity, &Machine, &mut Wear, Option<&ModuleSlots>)>,
+    mut alert_events: EventWriter<RaiseAlert>,
+) {
+    for (entity, machine, mut wear, modules) in &mut machine_query {
+        if wear.is_broken() {
+            alert_events.send(RaiseAlert { entity, kind: AlertKind::Broken });
+            continue;
+        }
+
+        // running faster wears it out faster
+        if machine.working {
+            let speed = module::effects(modules).speed;
+            wear.durability = (wear.durability - WEAR_PER_SECOND * speed * time.delta_seconds()).max(0.0);
+        }
+    }
+}
+
+fn repair_input(
+    keys: Res<Input<KeyCode>>,
+    aim: Res<AimPoint>,
+    grid: Res<BuildingGrid>,
+    mut wear_query: Query<&mut Wear>,
+    mut player_query: Query<&mut Inventory, With<Player>>,
+) {
+    if !keys.just_pressed(KeyCode::H) {
+        return;
+    }
+
+    let Some(mut wear) = aim.0
+        .and_then(|a| grid.get(GridPos::from_world(a)))
+        .and_then(|e| wear_query.get_mut(e).ok())
+    else {
+        return;
+    };
+
+    let mut inventory = player_query.get_single_mut().expect("not one player");
+    if wear.durability < 1.0 && inventory.remove(Item::RepairKit, 1) {
+        wear.repair();
+    }
+}
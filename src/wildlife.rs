@@ -0,0 +1,282 @@
+use bevy::prelude::*;
+use bevy_xpbd_3d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    corruption::CorruptionMap,
+    death::LeavesCorpse,
+    enemy::{Hostile, WORLD_EXTENT},
+    health::{DamageDealt, Health},
+    layer::Layer,
+    loot::{Loot, LootTableId},
+    perception::Noise,
+    state::GameplaySet,
+    telekinesis::Held,
+    Player,
+};
+
+const MAX_WILDLIFE: usize = 6;
+// seconds between spawns while there's room for more
+const SPAWN_INTERVAL: f32 = 8.0;
+// spawned out of sight, but not too far to ever be seen
+const SPAWN_MIN_DISTANCE: f32 = 5.0;
+const SPAWN_MAX_DISTANCE: f32 = 9.0;
+// anything further from the player than this is gone
+const DESPAWN_DISTANCE: f32 = 14.0;
+// nothing lives on ground this corrupted, crawlers aside
+const CLEAN_LEVEL: f32 = 10.0;
+// seconds between picking a new direction to amble in
+const WANDER_TIME: f32 = 3.0;
+// seconds spent running once startled
+const FLEE_TIME: f32 = 2.5;
+// seconds a carcass lies around
+const CORPSE_TIME: f32 = 20.0;
+
+pub struct WildlifePlugin;
+
+impl Plugin for WildlifePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<WildlifeCountdown>()
+            .add_systems(Update, (
+                spawn_wildlife,
+                startle_by_noise,
+                startle_by_player,
+                startle_by_damage,
+                roam,
+                despawn_far_wildlife,
+            ).chain().in_set(GameplaySet));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Species {
+    Hare,
+    Stag,
+}
+
+impl Species {
+    pub const ALL: [Species; 2] = [Species::Hare, Species::Stag];
+
+    pub fn color(&self) -> Color {
+        match self {
+            Species::Hare => Color::rgb_u8(160, 140, 110),
+            Species::Stag => Color::rgb_u8(120, 80, 45),
+        }
+    }
+
+    fn size(&self) -> f32 {
+        match self {
+            Species::Hare => 0.2,
+            Species::Stag => 0.45,
+        }
+    }
+
+    fn health(&self) -> f32 {
+        match self {
+            Species::Hare => 8.0,
+            Species::Stag => 30.0,
+        }
+    }
+
+    fn walk_speed(&self) -> f32 {
+        match self {
+            Species::Hare => 0.8,
+            Species::Stag => 0.6,
+        }
+    }
+
+    fn flee_speed(&self) -> f32 {
+        match self {
+            Species::Hare => 4.5,
+            Species::Stag => 3.5,
+        }
+    }
+
+    // how close the player gets before it bolts
+    fn shyness(&self) -> f32 {
+        match self {
+            Species::Hare => 2.5,
+            Species::Stag => 4.0,
+        }
+    }
+
+    fn loot(&self) -> LootTableId {
+        match self {
+            Species::Hare => LootTableId::Hare,
+            Species::Stag => LootTableId::Stag,
+        }
+    }
+}
+
+/// A passive creature, it wanders about and runs from anything scary.
+#[derive(Component)]
+pub struct Wildlife {
+    pub species: Species,
+    heading: Vec3,
+    // seconds until it picks a new direction
+    wander: f32,
+    // seconds left running, and from where
+    fleeing: f32,
+    danger: Vec3,
+}
+
+impl Wildlife {
+    fn startle(&mut self, from: Vec3) {
+        self.fleeing = FLEE_TIME;
+        self.danger = from;
+    }
+}
+
+#[derive(Resource)]
+struct WildlifeCountdown(f32);
+
+impl Default for WildlifeCountdown {
+    fn default() -> Self {
+        WildlifeCountdown(SPAWN_INTERVAL)
+    }
+}
+
+fn spawn_wildlife(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+    corruption: Res<CorruptionMap>,
+    mut countdown: ResMut<WildlifeCountdown>,
+    wildlife_query: Query<(), With<Wildlife>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    countdown.0 -= time.delta_seconds();
+    if countdown.0 > 0.0 {
+        return;
+    }
+    countdown.0 = SPAWN_INTERVAL;
+    if wildlife_query.iter().count() >= MAX_WILDLIFE {
+        return;
+    }
+
+    let player = player_query.get_single().expect("not one player");
+    let mut rng = rand::thread_rng();
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    let distance = rng.gen_range(SPAWN_MIN_DISTANCE..SPAWN_MAX_DISTANCE);
+    let offset = Vec3::new(angle.cos(), 0.0, angle.sin()) * distance;
+    let pos = (player.translation + offset).clamp(Vec3::splat(-WORLD_EXTENT), Vec3::splat(WORLD_EXTENT));
+    // try again next time rather than hunting for a clean spot
+    if corruption.get(pos) >= CLEAN_LEVEL {
+        return;
+    }
+
+    let species = Species::ALL[rng.gen_range(0..Species::ALL.len())];
+    let size = species.size();
+    commands.spawn((
+        // spells should go for it, see the filters on turrets and friends
+        Hostile,
+        Wildlife { species, heading: Vec3::ZERO, wander: 0.0, fleeing: 0.0, danger: Vec3::ZERO },
+        Loot(species.loot()),
+        LeavesCorpse(CORPSE_TIME),
+        Health::new(species.health()),
+        PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::UVSphere { radius: size, sectors: 8, stacks: 6 })),
+            material: materials.add(species.color().into()),
+            transform: Transform::from_xyz(pos.x, size, pos.z),
+            ..default()
+        },
+        RigidBody::Dynamic,
+        Collider::ball(size),
+        LockedAxes::ROTATION_LOCKED,
+        Layer::enemy(),
+    ));
+}
+
+fn startle_by_noise(
+    mut wildlife_query: Query<(&mut Wildlife, &Transform)>,
+    mut noise_events: EventReader<Noise>,
+) {
+    for event in noise_events.read() {
+        for (mut wildlife, transform) in &mut wildlife_query {
+            if transform.translation.distance(event.position) < event.radius {
+                wildlife.startle(event.position);
+            }
+        }
+    }
+}
+
+fn startle_by_player(
+    mut wildlife_query: Query<(&mut Wildlife, &Transform)>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let player = player_query.get_single().expect("not one player");
+
+    for (mut wildlife, transform) in &mut wildlife_query {
+        if transform.translation.distance(player.translation) < wildlife.species.shyness() {
+            wildlife.startle(player.translation);
+        }
+    }
+}
+
+fn startle_by_damage(
+    mut wildlife_query: Query<(&mut Wildlife, &Transform)>,
+    source_query: Query<&Transform>,
+    mut dealt_events: EventReader<DamageDealt>,
+) {
+    for event in dealt_events.read() {
+        let Ok((mut wildlife, transform)) = wildlife_query.get_mut(event.target) else {
+            continue;
+        };
+        // without a source anything is better than standing still
+        let from = event.source
+            .and_then(|s| source_query.get(s).ok())
+            .map_or(transform.translation - wildlife.heading, |t| t.translation);
+        wildlife.startle(from);
+    }
+}
+
+// no pathing, just a heading that changes every so often
+fn roam(
+    time: Res<Time>,
+    mut wildlife_query: Query<(&mut Wildlife, &Transform, &mut LinearVelocity), Without<Held>>,
+) {
+    let mut rng = rand::thread_rng();
+    let dt = time.delta_seconds();
+
+    for (mut wildlife, transform, mut velocity) in &mut wildlife_query {
+        let pos = transform.translation;
+        let speed = if wildlife.fleeing > 0.0 {
+            wildlife.fleeing -= dt;
+            let away = pos - wildlife.danger;
+            wildlife.heading = Vec3::new(away.x, 0.0, away.z).normalize_or_zero();
+            wildlife.species.flee_speed()
+        } else {
+            wildlife.wander -= dt;
+            if wildlife.wander <= 0.0 {
+                wildlife.wander = WANDER_TIME;
+                // stand around about a third of the time
+                wildlife.heading = if rng.gen_bool(0.3) {
+                    Vec3::ZERO
+                } else {
+                    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                    Vec3::new(angle.cos(), 0.0, angle.sin())
+                };
+            }
+            wildlife.species.walk_speed()
+        };
+
+        velocity.x = wildlife.heading.x * speed;
+        velocity.z = wildlife.heading.z * speed;
+    }
+}
+
+fn despawn_far_wildlife(
+    mut commands: Commands,
+    wildlife_query: Query<(Entity, &Transform), With<Wildlife>>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let player = player_query.get_single().expect("not one player");
+
+    for (entity, transform) in &wildlife_query {
+        if transform.translation.distance(player.translation) > DESPAWN_DISTANCE {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}